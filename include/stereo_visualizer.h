@@ -0,0 +1,54 @@
+/* The stereo analysis of stereo-visualizer, built as a shared library by
+ * `cargo build --release --lib`. */
+#ifndef STEREO_VISUALIZER_H
+#define STEREO_VISUALIZER_H
+
+#include <stddef.h>
+#include <stdint.h>
+
+#ifdef __cplusplus
+extern "C" {
+#endif
+
+typedef struct sv_analyzer sv_analyzer;
+
+/* One FFT bin; bin i is centred on i * sample_rate / fft_size Hz. */
+typedef struct sv_source {
+    float dir;        /* -1 (left) to 1 (right) */
+    float amp;
+    float confidence; /* 0 to 1 */
+} sv_source;
+
+/* fft_size must be a power of two, e.g. 1024; returns NULL otherwise. */
+sv_analyzer *sv_init(uint32_t fft_size);
+
+/* Adds `frames` frames of interleaved stereo samples. */
+void sv_push_samples(sv_analyzer *analyzer, const float *samples, size_t frames);
+
+/* Analyses the newest window and writes up to `capacity` of its fft_size / 2
+ * bins to `out`. Returns the count written, or 0 when nothing new arrived
+ * since the last poll or less than one window has been pushed. */
+size_t sv_poll_frame(sv_analyzer *analyzer, sv_source *out, size_t capacity);
+
+void sv_free(sv_analyzer *analyzer);
+
+/* The bridge functions are only in libraries built with the network
+ * feature (`cargo build --release --features network`). */
+typedef struct sv_bridge sv_bridge;
+
+/* Streams audio to `stereo-visualizer --bridge ADDR` from a DAW plugin,
+ * reconnecting whenever the visualizer is restarted. addr is e.g.
+ * "127.0.0.1:7878"; returns NULL for a bad argument. */
+sv_bridge *sv_bridge_connect(const char *addr, uint32_t sample_rate, uint32_t channels);
+
+/* Queues `frames` frames of interleaved samples without blocking; audio is
+ * dropped while the visualizer is not there or falls behind. */
+void sv_bridge_send(sv_bridge *bridge, const float *samples, size_t frames);
+
+void sv_bridge_free(sv_bridge *bridge);
+
+#ifdef __cplusplus
+}
+#endif
+
+#endif
@@ -0,0 +1,60 @@
+use crate::DirectionalSource;
+use std::collections::VecDeque;
+
+const HISTORY: usize = 300;
+const PERCENTILE: f32 = 0.95;
+// Where the percentile lands on the display: an amp of 10 is a bright bar.
+const TARGET: f32 = 10.0;
+// Frames below this are silence and would otherwise pump the gain up.
+const FLOOR: f32 = 0.05;
+const MIN_GAIN: f32 = 0.1;
+const MAX_GAIN: f32 = 20.0;
+
+// Scales the picture (not the sound) so the loud end of the last few seconds
+// always reaches the same brightness.
+pub struct Agc {
+    pub enabled: bool,
+    levels: VecDeque<f32>,
+    gain: f32,
+}
+
+impl Agc {
+    pub fn new(enabled: bool) -> Self {
+        Agc {
+            enabled,
+            levels: VecDeque::with_capacity(HISTORY),
+            gain: 1.0,
+        }
+    }
+
+    pub fn update(&mut self, directions: &[DirectionalSource]) {
+        let mut amps: Vec<f32> = directions.iter().skip(1).map(|source| source.amp).collect();
+        if amps.is_empty() {
+            return;
+        }
+        let rank = ((amps.len() - 1) as f32 * PERCENTILE) as usize;
+        let (_, &mut level, _) = amps.select_nth_unstable_by(rank, f32::total_cmp);
+
+        if level > FLOOR {
+            if self.levels.len() == HISTORY {
+                self.levels.pop_front();
+            }
+            self.levels.push_back(level);
+        }
+        if self.levels.is_empty() {
+            return;
+        }
+
+        let level = self.levels.iter().sum::<f32>() / self.levels.len() as f32;
+        let target = (TARGET / level).clamp(MIN_GAIN, MAX_GAIN);
+        self.gain += (target - self.gain) * 0.05;
+    }
+
+    pub fn gain(&self) -> f32 {
+        if self.enabled {
+            self.gain
+        } else {
+            1.0
+        }
+    }
+}
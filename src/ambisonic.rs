@@ -0,0 +1,104 @@
+use crate::DirectionalSource;
+use rustfft::{num_complex::Complex, num_traits::Zero, FFTplanner, FFT};
+use std::sync::Arc;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Format {
+    AmbiX,
+    FuMa,
+}
+
+impl Format {
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name {
+            "ambix" => Some(Format::AmbiX),
+            "fuma" => Some(Format::FuMa),
+            _ => None,
+        }
+    }
+}
+
+pub struct Ambisonic {
+    w: Vec<f32>,
+    x: Vec<f32>,
+    y: Vec<f32>,
+    fft: Arc<dyn FFT<f32>>,
+    intensity: Vec<(f32, f32)>,
+}
+
+impl Ambisonic {
+    pub fn new(mut channels: Vec<Vec<f32>>, format: Format, fft_size: usize) -> Option<Self> {
+        if channels.len() != 4 {
+            return None;
+        }
+
+        // AmbiX is ACN order W, Y, Z, X with SN3D; FuMa is W, X, Y, Z with W at -3 dB.
+        let (w, x, y) = match format {
+            Format::AmbiX => {
+                let x = channels.swap_remove(3);
+                let y = channels.swap_remove(1);
+                (channels.swap_remove(0), x, y)
+            }
+            Format::FuMa => {
+                let y = channels.swap_remove(2);
+                let x = channels.swap_remove(1);
+                let w = channels
+                    .swap_remove(0)
+                    .into_iter()
+                    .map(|amp| amp * std::f32::consts::SQRT_2)
+                    .collect();
+                (w, x, y)
+            }
+        };
+
+        Some(Ambisonic {
+            w,
+            x,
+            y,
+            fft: FFTplanner::new(false).plan_fft(fft_size),
+            intensity: vec![(0.0, 0.0); fft_size / 2],
+        })
+    }
+
+    pub fn decode_stereo(&self) -> (Vec<f32>, Vec<f32>) {
+        self.w
+            .iter()
+            .zip(&self.y)
+            .map(|(&w, &y)| (0.5 * (w + y), 0.5 * (w - y)))
+            .unzip()
+    }
+
+    fn spectrum(&self, wave: &[f32], offset: usize, len: usize) -> Vec<Complex<f32>> {
+        let mut input: Vec<_> = wave[offset..offset + len]
+            .iter()
+            .map(|&amp| Complex::new(amp, 0.0))
+            .collect();
+        let mut output = vec![Complex::zero(); len];
+        self.fft.process(&mut input, &mut output);
+        output
+    }
+
+    pub fn apply(&mut self, offset: usize, directions: &mut [DirectionalSource]) {
+        let len = self.intensity.len() * 2;
+        if offset + len > self.w.len() {
+            return;
+        }
+
+        let w = self.spectrum(&self.w, offset, len);
+        let x = self.spectrum(&self.x, offset, len);
+        let y = self.spectrum(&self.y, offset, len);
+
+        for (idx, source) in directions.iter_mut().enumerate() {
+            let ix = (w[idx].conj() * x[idx]).re;
+            let iy = (w[idx].conj() * y[idx]).re;
+
+            let rev = &mut self.intensity[idx];
+            rev.0 += (ix - rev.0) * 0.9;
+            rev.1 += (iy - rev.1) * 0.9;
+
+            // Positive azimuth is to the left, which is the negative side of the stage.
+            let azimuth = rev.1.atan2(rev.0);
+            source.dir = -azimuth.sin();
+        }
+    }
+}
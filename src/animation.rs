@@ -0,0 +1,36 @@
+use crate::DirectionalSource;
+
+// About one analysis frame at 43 frames/sec: bars glide between frames at any
+// refresh rate without trailing visibly behind the music.
+const EASING_SECS: f32 = 0.025;
+
+// The bars as drawn: the analysed sources eased towards by the render delta,
+// so motion stays smooth when the display refreshes faster than the analysis.
+pub struct Animated {
+    shown: Vec<DirectionalSource>,
+}
+
+impl Animated {
+    pub fn new(len: usize) -> Self {
+        Animated {
+            shown: vec![DirectionalSource::new(); len],
+        }
+    }
+
+    pub fn update(&mut self, target: &[DirectionalSource], delta: f32) {
+        if self.shown.len() != target.len() {
+            self.shown = target.to_vec();
+            return;
+        }
+
+        let blend = 1.0 - (-delta / EASING_SECS).exp();
+        for (shown, target) in self.shown.iter_mut().zip(target) {
+            shown.amp += (target.amp - shown.amp) * blend;
+            shown.dir += (target.dir - shown.dir) * blend;
+        }
+    }
+
+    pub fn sources(&self) -> &[DirectionalSource] {
+        &self.shown
+    }
+}
@@ -0,0 +1,92 @@
+use serde::{Deserialize, Serialize};
+
+// The `[attract]` table in the config.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Settings {
+    // How long nothing has to be loaded before the demo starts.
+    pub idle_secs: f32,
+    // How long each look is shown before the next.
+    pub look_secs: f32,
+    // Preset slots to show off in turn; the modes in turn when empty.
+    pub presets: Vec<usize>,
+}
+
+impl Default for Settings {
+    fn default() -> Self {
+        Settings {
+            idle_secs: 30.0,
+            look_secs: 20.0,
+            presets: Vec::new(),
+        }
+    }
+}
+
+pub enum Step {
+    // Start the demo audio, or start it over.
+    Play,
+    // Show the next look: a preset slot, or the next mode without any.
+    Show(Option<usize>),
+}
+
+// Plays demo audio through a round of looks while nothing else is loaded, so
+// a kiosk or a first run never sits on an empty screen.
+pub struct Attract {
+    settings: Settings,
+    idle: f32,
+    active: bool,
+    shown: f32,
+    look: usize,
+}
+
+impl Attract {
+    pub fn new(settings: Settings) -> Self {
+        Attract {
+            settings,
+            idle: 0.0,
+            active: false,
+            shown: 0.0,
+            look: 0,
+        }
+    }
+
+    pub fn active(&self) -> bool {
+        self.active
+    }
+
+    // Something real was loaded.
+    pub fn stop(&mut self) {
+        self.active = false;
+        self.idle = 0.0;
+    }
+
+    // `idle` is whether nothing is loaded, `finished` whether the demo has
+    // played to the end.
+    pub fn update(&mut self, idle: bool, finished: bool, delta: f32) -> Option<Step> {
+        if !self.active {
+            self.idle = if idle { self.idle + delta } else { 0.0 };
+            if self.idle < self.settings.idle_secs {
+                return None;
+            }
+            self.active = true;
+            self.shown = 0.0;
+            return Some(Step::Play);
+        }
+        if finished {
+            return Some(Step::Play);
+        }
+        self.shown += delta;
+        if self.shown < self.settings.look_secs.max(1.0) {
+            return None;
+        }
+        self.shown = 0.0;
+        let presets = &self.settings.presets;
+        let slot = if presets.is_empty() {
+            None
+        } else {
+            self.look += 1;
+            Some(presets[(self.look - 1) % presets.len()])
+        };
+        Some(Step::Show(slot))
+    }
+}
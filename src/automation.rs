@@ -0,0 +1,85 @@
+use crate::quantize::Action;
+use serde::{Deserialize, Serialize};
+
+// Playback moving further than this in one update was a seek, whose cues are
+// skipped rather than fired all at once.
+const JUMP: f32 = 1.0;
+
+// Moves `last` on to `now`, returning where playback came from when it played
+// on normally and None when it stood still, went back or jumped.
+pub fn advance(last: &mut f32, now: f32) -> Option<f32> {
+    let from = std::mem::replace(last, now);
+    if now <= from || now - from > JUMP {
+        None
+    } else {
+        Some(from)
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct Cue {
+    // Seconds into the track.
+    pub time: f32,
+    #[serde(flatten)]
+    pub action: Action,
+}
+
+// Visual changes made by hand during a track, recorded against its time and
+// played back whenever it plays again, frame renders included.
+pub struct Automation {
+    cues: Vec<Cue>,
+    recording: Option<Vec<Cue>>,
+    last: f32,
+}
+
+impl Automation {
+    pub fn new() -> Self {
+        Automation {
+            cues: Vec::new(),
+            recording: None,
+            last: -f32::EPSILON,
+        }
+    }
+
+    // A new track's cues; a recording in progress is dropped.
+    pub fn load(&mut self, mut cues: Vec<Cue>) {
+        cues.sort_by(|a, b| a.time.total_cmp(&b.time));
+        self.cues = cues;
+        self.recording = None;
+        self.last = -f32::EPSILON;
+    }
+
+    pub fn recording(&self) -> bool {
+        self.recording.is_some()
+    }
+
+    pub fn start(&mut self) {
+        self.recording = Some(Vec::new());
+    }
+
+    // Ends a recording, which replaces the track's cues.
+    pub fn stop(&mut self) -> Option<Vec<Cue>> {
+        let cues = self.recording.take()?;
+        self.cues = cues.clone();
+        Some(cues)
+    }
+
+    pub fn record(&mut self, time: f32, action: Action) {
+        if let Some(ref mut recording) = self.recording {
+            recording.push(Cue { time, action });
+        }
+    }
+
+    // The cues playback has passed since the last update.
+    pub fn update(&mut self, now: f32) -> Vec<Action> {
+        let last = match advance(&mut self.last, now) {
+            Some(last) if self.recording.is_none() => last,
+            _ => return Vec::new(),
+        };
+        self.cues
+            .iter()
+            .filter(|cue| cue.time > last && cue.time <= now)
+            .map(|cue| cue.action)
+            .collect()
+    }
+}
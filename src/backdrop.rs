@@ -0,0 +1,138 @@
+use crate::safety::Safety;
+use ggez::{
+    error::GameError,
+    graphics::{self, DrawParam, Image},
+    Context, GameResult,
+};
+use std::{
+    io::Read,
+    process::{Child, Command, Stdio},
+    sync::mpsc::{self, Receiver},
+    thread,
+};
+
+// Frames are scaled and cropped to this by ffmpeg, then stretched over the window.
+const WIDTH: usize = 640;
+const HEIGHT: usize = 480;
+// Brightness and saturation at silence, and how far the bass lifts them.
+const DIM: f32 = 0.35;
+const PULSE: f32 = 0.65;
+const GREY: f32 = 0.4;
+const SMOOTHING: f32 = 0.3;
+
+fn input_args(source: &str) -> Vec<String> {
+    let camera = if source == "camera" { Some("") } else { source.strip_prefix("camera:") };
+    let camera = match camera {
+        Some(camera) => camera,
+        None => {
+            // Files loop, and play at their own rate, like a camera would.
+            return ["-re", "-stream_loop", "-1", "-i", source].iter().map(|arg| arg.to_string()).collect();
+        }
+    };
+    let (format, device) = if cfg!(target_os = "windows") {
+        ("dshow", format!("video={}", if camera.is_empty() { "Integrated Camera" } else { camera }))
+    } else if cfg!(target_os = "macos") {
+        ("avfoundation", if camera.is_empty() { "0".to_string() } else { camera.to_string() })
+    } else {
+        ("v4l2", if camera.is_empty() { "/dev/video0".to_string() } else { camera.to_string() })
+    };
+    vec!["-f".into(), format.into(), "-i".into(), device]
+}
+
+// A video file or camera under the visualization, brighter and more colourful
+// with the bass. ffmpeg decodes it, as nothing in-process can.
+pub struct Backdrop {
+    frames: Receiver<Vec<u8>>,
+    // Rendering off-line takes exactly one video frame per rendered frame.
+    offline: bool,
+    frame: Option<Vec<u8>>,
+    image: Option<Image>,
+    level: f32,
+    child: Child,
+}
+
+impl Backdrop {
+    // `frame_rate` is the rate of an off-line render, None when live.
+    pub fn open(source: &str, frame_rate: Option<f32>) -> GameResult<Self> {
+        let mut filter = format!(
+            "scale={0}:{1}:force_original_aspect_ratio=increase,crop={0}:{1}",
+            WIDTH, HEIGHT
+        );
+        let mut args = input_args(source);
+        if let Some(rate) = frame_rate {
+            args.retain(|arg| arg != "-re");
+            filter = format!("fps={},{}", rate, filter);
+        }
+        let mut child = Command::new("ffmpeg")
+            .args(["-loglevel", "error"])
+            .args(&args)
+            .args(["-an", "-vf", &filter, "-pix_fmt", "rgba", "-f", "rawvideo", "-"])
+            .stdin(Stdio::null())
+            .stdout(Stdio::piped())
+            .spawn()
+            .map_err(|err| GameError::ResourceLoadError(format!("Cannot run ffmpeg: {}", err)))?;
+        let mut stdout = child
+            .stdout
+            .take()
+            .ok_or_else(|| GameError::ResourceLoadError("ffmpeg gave no output".into()))?;
+
+        let (sender, frames) = mpsc::sync_channel(2);
+        thread::spawn(move || loop {
+            let mut frame = vec![0; WIDTH * HEIGHT * 4];
+            if stdout.read_exact(&mut frame).is_err() || sender.send(frame).is_err() {
+                break;
+            }
+        });
+        Ok(Backdrop {
+            frames,
+            offline: frame_rate.is_some(),
+            frame: None,
+            image: None,
+            level: 0.0,
+            child,
+        })
+    }
+
+    // `level` is the bass, 0 to 1.
+    pub fn update(&mut self, ctx: &mut Context, level: f32, safety: &Safety, delta: f32) -> GameResult {
+        let newest = if self.offline { self.frames.recv().ok() } else { self.frames.try_iter().last() };
+        if newest.is_some() {
+            self.frame = newest;
+        }
+        self.level = safety.follow(self.level, level.clamp(0.0, 1.0), SMOOTHING, delta);
+
+        let frame = match self.frame {
+            Some(ref frame) => frame,
+            None => return Ok(()),
+        };
+        let brightness = DIM + PULSE * self.level;
+        let saturation = GREY + (1.0 - GREY) * self.level;
+        let mut pixels = frame.clone();
+        for pixel in pixels.chunks_exact_mut(4) {
+            let [red, green, blue] = [pixel[0], pixel[1], pixel[2]].map(f32::from);
+            let luma = 0.299 * red + 0.587 * green + 0.114 * blue;
+            for channel in &mut pixel[..3] {
+                let value = luma + (f32::from(*channel) - luma) * saturation;
+                *channel = (value * brightness).clamp(0.0, 255.0) as u8;
+            }
+        }
+        self.image = Some(Image::from_rgba8(ctx, WIDTH as u16, HEIGHT as u16, &pixels)?);
+        Ok(())
+    }
+
+    pub fn draw(&self, ctx: &mut Context, (width, height): (f32, f32)) -> GameResult {
+        match self.image {
+            Some(ref image) => {
+                let scale = [width / WIDTH as f32, height / HEIGHT as f32];
+                graphics::draw(ctx, image, DrawParam::default().scale(scale))
+            }
+            None => Ok(()),
+        }
+    }
+}
+
+impl Drop for Backdrop {
+    fn drop(&mut self) {
+        let _ = self.child.kill();
+    }
+}
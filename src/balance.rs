@@ -0,0 +1,109 @@
+use crate::{export, overlay::{self, Anchor}, DirectionalSource};
+use ggez::{
+    graphics::{self, DrawParam},
+    Context, GameResult,
+};
+use std::path;
+
+const BINS: usize = 64;
+const PANEL_WIDTH: f32 = 256.0;
+const PANEL_HEIGHT: f32 = 96.0;
+const IMAGE_WIDTH: usize = 512;
+const IMAGE_HEIGHT: usize = 192;
+
+// Energy-weighted histogram of directions over the whole track: a stereo
+// balance fingerprint that shows where the mix lives on average.
+pub struct Balance {
+    pub visible: bool,
+    bins: [f32; BINS],
+}
+
+impl Balance {
+    pub fn new() -> Self {
+        Balance {
+            visible: false,
+            bins: [0.0; BINS],
+        }
+    }
+
+    pub fn clear(&mut self) {
+        self.bins = [0.0; BINS];
+    }
+
+    pub fn update(&mut self, directions: &[DirectionalSource]) {
+        for source in directions.iter().skip(1) {
+            let idx = (((source.dir + 1.0) / 2.0 * BINS as f32) as usize).min(BINS - 1);
+            self.bins[idx] += source.amp * source.amp;
+        }
+    }
+
+    fn normalized(&self) -> impl Iterator<Item = f32> + '_ {
+        let peak = self.bins.iter().fold(0.0f32, |acc, &bin| acc.max(bin)).max(1e-9);
+        self.bins.iter().map(move |bin| bin / peak)
+    }
+
+    pub fn draw(&self, ctx: &mut Context, width: f32, height: f32) -> GameResult {
+        if !self.visible {
+            return Ok(());
+        }
+
+        let (x, y) = (width - PANEL_WIDTH - 16.0, height / 2.0 - PANEL_HEIGHT / 2.0);
+        let mut builder = graphics::MeshBuilder::new();
+        builder.rectangle(
+            graphics::DrawMode::fill(),
+            graphics::Rect::new(x - 8.0, y - 24.0, PANEL_WIDTH + 16.0, PANEL_HEIGHT + 32.0),
+            graphics::Color::from_rgba(20, 20, 20, 220),
+        );
+        let column = PANEL_WIDTH / BINS as f32;
+        for (idx, value) in self.normalized().enumerate() {
+            let bar = value * PANEL_HEIGHT;
+            if bar < 0.5 {
+                continue;
+            }
+            builder.rectangle(
+                graphics::DrawMode::fill(),
+                graphics::Rect::new(x + idx as f32 * column, y + PANEL_HEIGHT - bar, column, bar),
+                graphics::Color::from_rgba(128, 192, 255, 220),
+            );
+        }
+        builder.line(
+            &[[x + PANEL_WIDTH / 2.0, y], [x + PANEL_WIDTH / 2.0, y + PANEL_HEIGHT]],
+            1.0,
+            graphics::Color::from_rgba(255, 255, 255, 64),
+        )?;
+        let mesh = builder.build(ctx)?;
+        graphics::draw(ctx, &mesh, DrawParam::default())?;
+
+        overlay::label(
+            ctx,
+            "L        balance        R",
+            14.0,
+            Anchor::TopLeft,
+            [x, y - 18.0],
+            graphics::Color::from_rgb(160, 160, 160),
+        )
+    }
+
+    pub fn write_png<P>(&self, path: P) -> GameResult
+    where
+        P: AsRef<path::Path>,
+    {
+        let mut rgba = vec![0u8; IMAGE_WIDTH * IMAGE_HEIGHT * 4];
+        let values: Vec<f32> = self.normalized().collect();
+        for x in 0..IMAGE_WIDTH {
+            let bar = (values[x * BINS / IMAGE_WIDTH] * IMAGE_HEIGHT as f32) as usize;
+            for y in 0..IMAGE_HEIGHT {
+                let pixel = &mut rgba[(y * IMAGE_WIDTH + x) * 4..][..4];
+                let color = if IMAGE_HEIGHT - y <= bar {
+                    [128, 192, 255, 255]
+                } else if x == IMAGE_WIDTH / 2 {
+                    [64, 64, 64, 255]
+                } else {
+                    [0, 0, 0, 255]
+                };
+                pixel.copy_from_slice(&color);
+            }
+        }
+        export::write_png(path, IMAGE_WIDTH as u32, IMAGE_HEIGHT as u32, &rgba)
+    }
+}
@@ -0,0 +1,82 @@
+use crate::DirectionalSource;
+use serde::{Deserialize, Serialize};
+
+const MEL_BANDS: usize = 40;
+const LOWEST: f32 = 20.0;
+
+// How FFT bins are grouped into stage bars; `bins` draws every bin.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Scale {
+    #[default]
+    Bins,
+    Mel,
+    Bark,
+    Erb,
+}
+
+impl Scale {
+    fn to_scale(self, hz: f32) -> f32 {
+        match self {
+            Scale::Bins => hz,
+            Scale::Mel => 2595.0 * (1.0 + hz / 700.0).log10(),
+            // Traunmüller's approximation of the critical-band rate.
+            Scale::Bark => 26.81 * hz / (1960.0 + hz) - 0.53,
+            Scale::Erb => 21.4 * (1.0 + 0.004_37 * hz).log10(),
+        }
+    }
+
+    fn to_hz(self, value: f32) -> f32 {
+        match self {
+            Scale::Bins => value,
+            Scale::Mel => 700.0 * (10.0f32.powf(value / 2595.0) - 1.0),
+            Scale::Bark => 1960.0 * (value + 0.53) / (26.28 - value),
+            Scale::Erb => (10.0f32.powf(value / 21.4) - 1.0) / 0.004_37,
+        }
+    }
+
+    // Band edges in Hz: one critical band (Bark) or one ERB per band, and a
+    // fixed count of equally spaced mel bands.
+    fn edges(self, nyquist: f32) -> Vec<f32> {
+        let (low, high) = (self.to_scale(LOWEST), self.to_scale(nyquist));
+        let step = match self {
+            Scale::Mel => (high - low) / MEL_BANDS as f32,
+            _ => 1.0,
+        };
+        let count = ((high - low) / step).floor() as usize;
+        (0..=count).map(|idx| self.to_hz(low + idx as f32 * step)).collect()
+    }
+
+    // Merges bins into bands, each placed at its amplitude-weighted direction
+    // and as loud as its loudest bin. Returns the band centers with the merged
+    // sources; bands narrower than a bin borrow the nearest bin.
+    pub fn aggregate(self, directions: &[DirectionalSource], bin_hz: f32) -> Vec<(f32, DirectionalSource)> {
+        let edges = self.edges(directions.len() as f32 * bin_hz);
+        edges
+            .windows(2)
+            .filter_map(|edge| {
+                let center = (edge[0] * edge[1]).sqrt();
+                let first = (edge[0] / bin_hz).ceil() as usize;
+                let last = ((edge[1] / bin_hz).ceil() as usize).min(directions.len());
+                let sources = if first < last {
+                    &directions[first..last]
+                } else {
+                    let idx = ((center / bin_hz).round() as usize).min(directions.len().checked_sub(1)?);
+                    &directions[idx..=idx]
+                };
+
+                let energy: f32 = sources.iter().map(|source| source.amp).sum();
+                let amp = sources.iter().fold(0.0f32, |acc, source| acc.max(source.amp));
+                let weighted = |value: fn(&DirectionalSource) -> f32| {
+                    if energy > 0.0 {
+                        sources.iter().map(|source| value(source) * source.amp).sum::<f32>() / energy
+                    } else {
+                        0.0
+                    }
+                };
+                let (dir, confidence) = (weighted(|source| source.dir), weighted(|source| source.confidence));
+                Some((center, DirectionalSource { dir, amp, confidence }))
+            })
+            .collect()
+    }
+}
@@ -0,0 +1,42 @@
+use std::collections::VecDeque;
+
+const HISTORY: usize = 43;
+const MIN_INTERVAL: f32 = 0.25;
+
+pub struct BeatDetector {
+    history: VecDeque<f32>,
+    since_beat: f32,
+}
+
+impl BeatDetector {
+    pub fn new() -> Self {
+        BeatDetector {
+            history: VecDeque::with_capacity(HISTORY),
+            since_beat: 0.0,
+        }
+    }
+
+    pub fn process(&mut self, energy: f32, delta: f32) -> bool {
+        self.since_beat += delta;
+
+        let average = if self.history.is_empty() {
+            0.0
+        } else {
+            self.history.iter().sum::<f32>() / self.history.len() as f32
+        };
+
+        if self.history.len() == HISTORY {
+            self.history.pop_front();
+        }
+        self.history.push_back(energy);
+
+        let beat = self.history.len() == HISTORY
+            && energy > average * 1.4
+            && energy > 0.05
+            && self.since_beat >= MIN_INTERVAL;
+        if beat {
+            self.since_beat = 0.0;
+        }
+        beat
+    }
+}
@@ -0,0 +1,96 @@
+use rustfft::{num_complex::Complex, num_traits::Zero, FFTplanner};
+use std::f32::consts::PI;
+
+const FFT_SIZE: usize = 1024;
+const HOP: usize = FFT_SIZE / 2;
+const EXAGGERATION: f32 = 2.0;
+const HEAD_RADIUS: f32 = 0.0875;
+const SPEED_OF_SOUND: f32 = 343.0;
+
+// Spherical head model (Brown & Duda) standing in for a measured HRTF set.
+fn hrtf(azimuth: f32, ear: f32, freq: f32) -> Complex<f32> {
+    let incidence = (azimuth - ear).abs().min(2.0 * PI - (azimuth - ear).abs());
+    let w = 2.0 * PI * freq;
+    let w0 = SPEED_OF_SOUND / HEAD_RADIUS;
+
+    let alpha = 1.05 + 0.95 * (incidence * 180.0 / 150.0).min(PI).cos();
+    let shadow = Complex::new(1.0, alpha * w / (2.0 * w0)) / Complex::new(1.0, w / (2.0 * w0));
+
+    let delay = if incidence < PI / 2.0 {
+        -HEAD_RADIUS / SPEED_OF_SOUND * incidence.cos()
+    } else {
+        HEAD_RADIUS / SPEED_OF_SOUND * (incidence - PI / 2.0)
+    } + HEAD_RADIUS / SPEED_OF_SOUND;
+
+    shadow * Complex::from_polar(&1.0, &(-w * delay))
+}
+
+pub fn render(left: &[f32], right: &[f32], sample_rate: u32) -> (Vec<f32>, Vec<f32>) {
+    let len = left.len().min(right.len());
+    let fft = FFTplanner::new(false).plan_fft(FFT_SIZE);
+    let ifft = FFTplanner::new(true).plan_fft(FFT_SIZE);
+
+    // Square-root Hann on both ends sums to one at 50% overlap.
+    let window: Vec<f32> = (0..FFT_SIZE)
+        .map(|idx| (PI * idx as f32 / FFT_SIZE as f32).sin())
+        .collect();
+
+    let mut out_left = vec![0.0; len + FFT_SIZE];
+    let mut out_right = vec![0.0; len + FFT_SIZE];
+    let mut left_fft = vec![Complex::zero(); FFT_SIZE];
+    let mut right_fft = vec![Complex::zero(); FFT_SIZE];
+    let mut left_out = vec![Complex::zero(); FFT_SIZE];
+    let mut right_out = vec![Complex::zero(); FFT_SIZE];
+    let bin_hz = sample_rate as f32 / FFT_SIZE as f32;
+
+    let mut offset = 0;
+    while offset < len {
+        let frame = |wave: &[f32]| -> Vec<Complex<f32>> {
+            window
+                .iter()
+                .enumerate()
+                .map(|(idx, &gain)| Complex::new(wave.get(offset + idx).map_or(0.0, |&amp| amp * gain), 0.0))
+                .collect()
+        };
+        fft.process(&mut frame(left), &mut left_fft);
+        fft.process(&mut frame(right), &mut right_fft);
+
+        for idx in 0..=FFT_SIZE / 2 {
+            let (l, r) = (left_fft[idx], right_fft[idx]);
+            let amp = l.norm().max(r.norm());
+            let dir = if amp > 0.0 {
+                ((r.norm() - l.norm()) / amp * EXAGGERATION).clamp(-1.0, 1.0)
+            } else {
+                0.0
+            };
+
+            // Negative azimuth is to the left, matching the stage.
+            let azimuth = dir * PI / 2.0;
+            let source = (l + r) * 0.5;
+            let freq = idx as f32 * bin_hz;
+            left_out[idx] = source * hrtf(azimuth, -PI / 2.0, freq);
+            right_out[idx] = source * hrtf(azimuth, PI / 2.0, freq);
+
+            if idx > 0 && idx < FFT_SIZE / 2 {
+                left_out[FFT_SIZE - idx] = left_out[idx].conj();
+                right_out[FFT_SIZE - idx] = right_out[idx].conj();
+            }
+        }
+        left_out[FFT_SIZE / 2].im = 0.0;
+        right_out[FFT_SIZE / 2].im = 0.0;
+
+        ifft.process(&mut left_out, &mut left_fft);
+        ifft.process(&mut right_out, &mut right_fft);
+
+        for (idx, &gain) in window.iter().enumerate() {
+            out_left[offset + idx] += left_fft[idx].re * gain / FFT_SIZE as f32;
+            out_right[offset + idx] += right_fft[idx].re * gain / FFT_SIZE as f32;
+        }
+
+        offset += HOP;
+    }
+
+    out_left.truncate(len);
+    out_right.truncate(len);
+    (out_left, out_right)
+}
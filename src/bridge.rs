@@ -0,0 +1,100 @@
+pub use server::listen;
+
+// The bridge server, left out of builds without the network feature.
+#[cfg(feature = "network")]
+mod server {
+    use crate::{dsp::Resampler, live::LiveInput};
+    use ggez::{error::GameError, GameResult};
+    use std::{
+        io::{self, Read},
+        net::{TcpListener, TcpStream},
+        sync::mpsc::{self, Sender},
+        thread,
+    };
+    use stereo_visualizer::bridge::MAGIC;
+
+    // Every sender is resampled to this, so the display keeps one rate.
+    const RATE: u32 = 48_000;
+
+    fn header(stream: &mut TcpStream) -> io::Result<Option<(u32, usize)>> {
+        let mut header = [0u8; 12];
+        stream.read_exact(&mut header)?;
+        if &header[..4] != MAGIC {
+            return Ok(None);
+        }
+        let rate = u32::from_le_bytes([header[4], header[5], header[6], header[7]]);
+        let channels = u32::from_le_bytes([header[8], header[9], header[10], header[11]]) as usize;
+        Ok(if rate == 0 || channels == 0 { None } else { Some((rate, channels)) })
+    }
+
+    // Passes one sender's audio on until it disconnects. Errs only once the
+    // visualizer has stopped listening.
+    fn serve(mut stream: TcpStream, sender: &Sender<Vec<f32>>) -> Result<(), ()> {
+        let (rate, channels) = match header(&mut stream) {
+            Ok(Some(format)) => format,
+            _ => return Ok(()),
+        };
+        let mut resamplers = if rate == RATE {
+            None
+        } else {
+            Some((Resampler::new(rate, RATE), Resampler::new(rate, RATE)))
+        };
+
+        let frame = channels * 4;
+        // Mono is sent to both sides; beyond two channels only the first pair counts.
+        let second = if channels > 1 { 4 } else { 0 };
+        let mut buffer = [0u8; 16_384];
+        let mut pending = Vec::new();
+        loop {
+            let len = match stream.read(&mut buffer) {
+                Ok(0) | Err(_) => return Ok(()),
+                Ok(len) => len,
+            };
+            pending.extend_from_slice(&buffer[..len]);
+            let whole = pending.len() / frame * frame;
+            let sample = |bytes: &[u8]| f32::from_le_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]);
+            let (mut left, mut right): (Vec<f32>, Vec<f32>) = pending[..whole]
+                .chunks_exact(frame)
+                .map(|frame| (sample(&frame[..4]), sample(&frame[second..])))
+                .unzip();
+            pending.drain(..whole);
+
+            if let Some((ref mut left_resampler, ref mut right_resampler)) = resamplers {
+                left = left_resampler.process(&left);
+                right = right_resampler.process(&right);
+            }
+            let block = left.iter().zip(&right).flat_map(|(&left, &right)| [left, right]).collect();
+            sender.send(block).map_err(|_| ())?;
+        }
+    }
+
+    // Listens on `addr` for audio streamed from a DAW by the sender in the C
+    // library, taking one connection at a time.
+    pub fn listen(addr: &str) -> GameResult<LiveInput> {
+        let listener = TcpListener::bind(addr)
+            .map_err(|err| GameError::AudioError(format!("Cannot listen on {}: {}", addr, err)))?;
+
+        let (sender, receiver) = mpsc::channel();
+        thread::spawn(move || {
+            for stream in listener.incoming().flatten() {
+                let _ = stream.set_nodelay(true);
+                if serve(stream, &sender).is_err() {
+                    break;
+                }
+            }
+        });
+        Ok(LiveInput::new(receiver, RATE, format!("Bridge {}", addr), ()))
+    }
+}
+
+#[cfg(not(feature = "network"))]
+mod server {
+    use crate::live::LiveInput;
+    use ggez::{error::GameError, GameResult};
+
+    pub fn listen(_addr: &str) -> GameResult<LiveInput> {
+        Err(GameError::ConfigError(
+            "This build has no bridge support; rebuild with `--features network`".into(),
+        ))
+    }
+}
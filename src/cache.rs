@@ -0,0 +1,58 @@
+use crate::automation::Cue;
+use ggez::{filesystem, Context, GameResult};
+use serde::{Deserialize, Serialize};
+use std::{
+    fs,
+    path::{Path, PathBuf},
+};
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Bookmark {
+    pub name: String,
+    pub time: f32,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Analysis {
+    pub file: PathBuf,
+    pub bookmarks: Vec<Bookmark>,
+    pub automation: Vec<Cue>,
+}
+
+// 64-bit FNV-1a, written out so cache names stay the same across Rust
+// releases, which std's hasher does not promise.
+fn fnv1a(bytes: &[u8]) -> u64 {
+    bytes.iter().fold(0xcbf2_9ce4_8422_2325, |hash, &byte| {
+        (hash ^ u64::from(byte)).wrapping_mul(0x0100_0000_01b3)
+    })
+}
+
+impl Analysis {
+    fn path(ctx: &Context, file: &Path) -> PathBuf {
+        let file = fs::canonicalize(file).unwrap_or_else(|_| file.to_path_buf());
+        let hash = fnv1a(file.to_string_lossy().as_bytes());
+        filesystem::user_data_dir(ctx)
+            .join("cache")
+            .join(format!("{:016x}.toml", hash))
+    }
+
+    pub fn load(ctx: &Context, file: &Path) -> Self {
+        fs::read_to_string(Self::path(ctx, file))
+            .ok()
+            .and_then(|text| toml::from_str(&text).ok())
+            .unwrap_or_else(|| Analysis {
+                file: file.to_path_buf(),
+                ..Analysis::default()
+            })
+    }
+
+    pub fn save(&self, ctx: &Context) -> GameResult {
+        let path = Self::path(ctx, &self.file);
+        if let Some(dir) = path.parent() {
+            fs::create_dir_all(dir)?;
+        }
+        fs::write(path, toml::to_string(self)?)?;
+        Ok(())
+    }
+}
@@ -0,0 +1,57 @@
+use crate::overlay::{self, Anchor};
+use ggez::{
+    graphics,
+    Context, GameResult,
+};
+
+pub const BEAT: f32 = 0.5;
+const WARMUP: usize = 2;
+const TAPS: usize = 16;
+
+pub struct Calibration {
+    taps: Vec<f32>,
+    result: Option<f32>,
+}
+
+impl Calibration {
+    pub fn new() -> Self {
+        Calibration {
+            taps: Vec::new(),
+            result: None,
+        }
+    }
+
+    pub fn tap(&mut self, time: f32) -> Option<f32> {
+        if self.result.is_some() {
+            return None;
+        }
+
+        self.taps.push((time + BEAT / 2.0) % BEAT - BEAT / 2.0);
+        if self.taps.len() < WARMUP + TAPS {
+            return None;
+        }
+
+        let mut offsets = self.taps[WARMUP..].to_vec();
+        offsets.sort_by(|a, b| a.total_cmp(b));
+        let median = offsets[offsets.len() / 2];
+        self.result = Some(median);
+        self.result
+    }
+
+    pub fn draw(&self, ctx: &mut Context, width: f32, height: f32) -> GameResult {
+        let message = match self.result {
+            Some(latency) => format!(
+                "Latency: {:.0} ms (saved)\nPress Escape to quit.",
+                latency * 1000.0
+            ),
+            None => format!(
+                "Latency calibration\nPress Space to start the clicks, then tap T on every click you hear.\nTaps: {} / {}",
+                self.taps.len().saturating_sub(WARMUP),
+                TAPS
+            ),
+        };
+
+        let text = overlay::wrapped(message, 24.0, width - 64.0, graphics::Align::Center);
+        overlay::draw(ctx, &text, Anchor::Top, [width / 2.0, height / 5.0], graphics::WHITE)
+    }
+}
@@ -0,0 +1,108 @@
+use crate::{
+    overlay::{self, Anchor},
+    DirectionalSource,
+};
+use ggez::{
+    graphics,
+    Context, GameResult,
+};
+
+const REFRESH: f32 = 0.5;
+
+struct Band {
+    name: [&'static str; 3],
+    low: f32,
+    high: f32,
+}
+
+const BANDS: [Band; 3] = [
+    Band { name: ["soft bass", "bass", "heavy bass"], low: 20.0, high: 250.0 },
+    Band { name: ["soft mids", "mids", "strong mids"], low: 250.0, high: 4000.0 },
+    Band { name: ["soft hats", "hats", "bright hats"], low: 4000.0, high: 20000.0 },
+];
+
+pub struct Captions {
+    pub enabled: bool,
+    lines: Vec<String>,
+    elapsed: f32,
+}
+
+impl Captions {
+    pub fn new() -> Self {
+        Captions {
+            enabled: false,
+            lines: Vec::new(),
+            elapsed: REFRESH,
+        }
+    }
+
+    pub fn update(&mut self, directions: &[DirectionalSource], bin_hz: f32, rms: f32, delta: f32) {
+        self.elapsed += delta;
+        if !self.enabled || self.elapsed < REFRESH {
+            return;
+        }
+        self.elapsed = 0.0;
+        self.lines.clear();
+
+        for band in BANDS.iter() {
+            let low = ((band.low / bin_hz).ceil() as usize).max(1);
+            let high = ((band.high / bin_hz).floor() as usize).min(directions.len());
+            if low >= high {
+                continue;
+            }
+
+            let sources = &directions[low..high];
+            let peak = sources.iter().fold(0.0f32, |acc, source| acc.max(source.amp)) * 0.08;
+            let energy: f32 = sources.iter().map(|source| source.amp).sum();
+            if peak < 0.05 || energy <= 0.0 {
+                continue;
+            }
+
+            let dir = sources.iter().map(|source| source.dir * source.amp).sum::<f32>() / energy;
+            let side = if dir < -0.25 {
+                "left"
+            } else if dir > 0.25 {
+                "right"
+            } else {
+                "center"
+            };
+            let name = if peak > 0.6 {
+                band.name[2]
+            } else if peak > 0.25 {
+                band.name[1]
+            } else {
+                band.name[0]
+            };
+            self.lines.push(format!("{} {}", name, side));
+        }
+
+        let db = 20.0 * rms.max(1e-5).log10();
+        let loudness = if db > -12.0 {
+            "loud"
+        } else if db > -24.0 {
+            "moderate"
+        } else if db > -48.0 {
+            "quiet"
+        } else {
+            "silence"
+        };
+        self.lines.push(format!("{} ({:.0} dBFS)", loudness, db));
+    }
+
+    pub fn draw(&self, ctx: &mut Context, width: f32, height: f32) -> GameResult {
+        if !self.enabled || self.lines.is_empty() {
+            return Ok(());
+        }
+
+        let size = (height / 20.0).max(24.0);
+        let text = overlay::text(self.lines.join("\n"), size);
+        overlay::panel(
+            ctx,
+            &text,
+            Anchor::Bottom,
+            [width / 2.0, height - size],
+            graphics::Color::from_rgb(255, 255, 160),
+            (width, height),
+        )
+    }
+}
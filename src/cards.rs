@@ -0,0 +1,135 @@
+use crate::{
+    export,
+    overlay::{self, Anchor},
+    tags::Tags,
+};
+use ggez::{
+    graphics::{self, DrawParam, Image},
+    Context, GameResult,
+};
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+
+// The `[cards]` table in the config.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Settings {
+    pub intro_secs: f32,
+    pub outro_secs: f32,
+    // Each card fades over this long into and out of the picture.
+    pub fade_secs: f32,
+    // Under the title on the outro, e.g. "Out now".
+    pub outro_text: String,
+    // Looked up next to each track when relative, e.g. "cover.jpg".
+    pub artwork: Option<PathBuf>,
+}
+
+impl Default for Settings {
+    fn default() -> Self {
+        Settings {
+            intro_secs: 3.0,
+            outro_secs: 3.0,
+            fade_secs: 0.5,
+            outro_text: String::new(),
+            artwork: None,
+        }
+    }
+}
+
+// Title cards around each track of a frame render.
+pub struct Cards {
+    settings: Settings,
+    title: String,
+    artist: Option<String>,
+    artwork: Option<Image>,
+}
+
+impl Cards {
+    pub fn new(settings: Settings) -> Self {
+        Cards {
+            settings,
+            title: String::new(),
+            artist: None,
+            artwork: None,
+        }
+    }
+
+    pub fn intro_secs(&self) -> f32 {
+        self.settings.intro_secs.max(0.0)
+    }
+
+    pub fn outro_secs(&self) -> f32 {
+        self.settings.outro_secs.max(0.0)
+    }
+
+    pub fn load(&mut self, ctx: &mut Context, path: &Path, tags: &Tags) {
+        let stem = path.file_stem().map(|stem| stem.to_string_lossy().into_owned());
+        self.title = tags.get("TITLE").map(str::to_string).or(stem).unwrap_or_default();
+        self.artist = tags.get("ARTIST").map(str::to_string);
+        self.artwork = None;
+        if let Some(ref artwork) = self.settings.artwork {
+            let artwork = path.parent().map_or(artwork.clone(), |dir| dir.join(artwork));
+            if artwork.exists() {
+                match export::read_image(ctx, &artwork) {
+                    Ok(image) => self.artwork = Some(image),
+                    Err(err) => println!("No artwork: {}", err),
+                }
+            }
+        }
+    }
+
+    // `time` is seconds into the render of a track lasting `duration`.
+    fn alpha(&self, time: f32, duration: f32) -> f32 {
+        let fade = self.settings.fade_secs.max(f32::EPSILON);
+        let intro = self.intro_secs();
+        let outro = intro + duration;
+        if time < intro {
+            // Fades in from the transparent background too, for compositing.
+            (time / fade).min(1.0)
+        } else if time < outro {
+            let left = if intro > 0.0 { 1.0 - (time - intro) / fade } else { 0.0 };
+            let arriving = if self.outro_secs() > 0.0 { 1.0 - (outro - time) / fade } else { 0.0 };
+            left.max(arriving).max(0.0)
+        } else {
+            ((outro + self.outro_secs() - time) / fade).clamp(0.0, 1.0)
+        }
+    }
+
+    pub fn draw(&self, ctx: &mut Context, time: f32, duration: f32, (width, height): (f32, f32)) -> GameResult {
+        let alpha = self.alpha(time, duration);
+        if alpha <= 0.0 {
+            return Ok(());
+        }
+        let rect = graphics::Rect::new(0.0, 0.0, width, height);
+        let shade = graphics::Color::new(0.0, 0.0, 0.0, alpha);
+        let backdrop = graphics::Mesh::new_rectangle(ctx, graphics::DrawMode::fill(), rect, shade)?;
+        graphics::draw(ctx, &backdrop, DrawParam::default())?;
+
+        let mut y = height * 0.5 - 40.0;
+        if let Some(ref artwork) = self.artwork {
+            let side = height * 0.4;
+            let scale = side / f32::from(artwork.width().max(artwork.height()));
+            let (art_width, art_height) = (f32::from(artwork.width()) * scale, f32::from(artwork.height()) * scale);
+            let param = DrawParam::default()
+                .dest([(width - art_width) / 2.0, height * 0.45 - art_height])
+                .scale([scale, scale])
+                .color(graphics::Color::new(1.0, 1.0, 1.0, alpha));
+            graphics::draw(ctx, artwork, param)?;
+            y = height * 0.5;
+        }
+
+        let color = graphics::Color::new(1.0, 1.0, 1.0, alpha);
+        let title = overlay::text(self.title.as_str(), 48.0);
+        overlay::draw(ctx, &title, Anchor::Top, [width / 2.0, y], color)?;
+        y += overlay::size(ctx, &title).1 + 12.0;
+        if let Some(ref artist) = self.artist {
+            let artist = overlay::text(artist.as_str(), 28.0);
+            overlay::draw(ctx, &artist, Anchor::Top, [width / 2.0, y], color)?;
+            y += overlay::size(ctx, &artist).1 + 12.0;
+        }
+        if time >= self.intro_secs() + duration && !self.settings.outro_text.is_empty() {
+            overlay::label(ctx, self.settings.outro_text.as_str(), 24.0, Anchor::Top, [width / 2.0, y], color)?;
+        }
+        Ok(())
+    }
+}
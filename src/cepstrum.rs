@@ -0,0 +1,137 @@
+use crate::{overlay::{self, Anchor}, view::AXIS_HEIGHT};
+use ggez::{
+    graphics::{self, DrawParam},
+    Context, GameResult,
+};
+use rustfft::{num_complex::Complex, FFTplanner, FFT};
+use std::sync::Arc;
+
+// Pitch range searched for periodicity; a 1024-point window cannot hold
+// periods much below 90 Hz.
+const LOW_HZ: f32 = 90.0;
+const HIGH_HZ: f32 = 1000.0;
+pub const NOTES: [&str; 12] = ["C", "C#", "D", "D#", "E", "F", "F#", "G", "G#", "A", "A#", "B"];
+
+fn note_name(hz: f32) -> String {
+    let key = (69.0 + 12.0 * (hz / 440.0).log2()).round() as i32;
+    format!("{}{}", NOTES[key.rem_euclid(12) as usize], key.div_euclid(12) - 1)
+}
+
+// The real cepstrum of the mid channel: the inverse transform of the log
+// spectrum, where a harmonic series collapses into a peak at its period.
+pub struct Cepstrum {
+    ifft: Arc<dyn FFT<f32>>,
+    values: Vec<f32>,
+    sample_rate: u32,
+}
+
+impl Cepstrum {
+    pub fn new(fft_size: usize) -> Self {
+        Cepstrum {
+            ifft: FFTplanner::new(true).plan_fft(fft_size),
+            values: vec![0.0; fft_size / 2],
+            sample_rate: 1,
+        }
+    }
+
+    pub fn update(&mut self, left_fft: &[Complex<f32>], right_fft: &[Complex<f32>], sample_rate: u32) {
+        let size = left_fft.len();
+        let mut input: Vec<_> = left_fft
+            .iter()
+            .zip(right_fft)
+            .map(|(l, r)| Complex::new(((l + r).norm() + 1e-6).ln(), 0.0))
+            .collect();
+        let mut output = vec![Complex::new(0.0, 0.0); size];
+        self.ifft.process(&mut input, &mut output);
+
+        for (value, out) in self.values.iter_mut().zip(&output) {
+            let amp = (out.re / size as f32).max(0.0);
+            *value += (amp - *value) * 0.5;
+        }
+        self.sample_rate = sample_rate;
+    }
+
+    fn at(&self, hz: f32) -> f32 {
+        let quefrency = self.sample_rate as f32 / hz;
+        let idx = quefrency as usize;
+        let frac = quefrency - idx as f32;
+        match (self.values.get(idx), self.values.get(idx + 1)) {
+            (Some(&a), Some(&b)) => a + (b - a) * frac,
+            _ => 0.0,
+        }
+    }
+
+    // The strongest period in range, as a pitch, if it stands out at all.
+    pub fn peak(&self) -> Option<(f32, f32)> {
+        let first = (self.sample_rate as f32 / HIGH_HZ).ceil() as usize;
+        let last = ((self.sample_rate as f32 / LOW_HZ) as usize).min(self.values.len());
+        let slice = self.values.get(first..last)?;
+        let (idx, &value) = slice.iter().enumerate().max_by(|a, b| a.1.total_cmp(b.1))?;
+        let mean = slice.iter().sum::<f32>() / slice.len() as f32;
+        if value > mean * 3.0 && value > 0.01 {
+            Some((self.sample_rate as f32 / (first + idx) as f32, value))
+        } else {
+            None
+        }
+    }
+
+    pub fn draw(&self, ctx: &mut Context, width: f32, height: f32) -> GameResult {
+        let area = height - AXIS_HEIGHT;
+        let baseline = area * 0.8;
+        let peak = self.peak();
+        let scale = peak.map_or(0.05, |(_, value)| value).max(0.05);
+        let x_at = |hz: f32| (hz / LOW_HZ).ln() / (HIGH_HZ / LOW_HZ).ln() * width;
+
+        let steps = 256;
+        let step = width / steps as f32;
+        for idx in 0..steps {
+            let hz = LOW_HZ * (HIGH_HZ / LOW_HZ).powf((idx as f32 + 0.5) / steps as f32);
+            let bar = (self.at(hz) / scale).min(1.0) * area * 0.6;
+            if bar < 1.0 {
+                continue;
+            }
+            let rect = graphics::Rect::new(idx as f32 * step, baseline - bar, step.max(1.0), bar);
+            let mesh = graphics::Mesh::new_rectangle(
+                ctx,
+                graphics::DrawMode::fill(),
+                rect,
+                graphics::Color::from_rgba((idx * 255 / steps) as u8, 128, 192, 200),
+            )?;
+            graphics::draw(ctx, &mesh, DrawParam::default())?;
+        }
+
+        for &hz in [110.0, 220.0, 440.0, 880.0].iter() {
+            let x = x_at(hz);
+            overlay::label(
+                ctx,
+                format!("{:.0}", hz),
+                14.0,
+                Anchor::TopLeft,
+                [x + 2.0, baseline + 4.0],
+                graphics::Color::from_rgb(120, 120, 120),
+            )?;
+        }
+
+        if let Some((hz, _)) = peak {
+            let x = x_at(hz);
+            let line = graphics::Mesh::new_line(
+                ctx,
+                &[[x, area * 0.1], [x, baseline]],
+                1.0,
+                graphics::Color::from_rgba(255, 255, 255, 128),
+            )?;
+            graphics::draw(ctx, &line, DrawParam::default())?;
+
+            overlay::label(
+                ctx,
+                format!("f0 {:.1} Hz  {}", hz, note_name(hz)),
+                18.0,
+                Anchor::TopLeft,
+                [(x + 6.0).min(width - 160.0), area * 0.1],
+                graphics::WHITE,
+            )?;
+        }
+
+        Ok(())
+    }
+}
@@ -0,0 +1,266 @@
+use crate::{ambisonic, emit, generator::Signal, pcm};
+use ggez::{conf::NumSamples, error::GameError, GameResult};
+use std::{env, ffi::OsString, path::PathBuf};
+
+pub struct Options {
+    pub register: bool,
+    // Set on the copy that draws the analysis window.
+    pub secondary: bool,
+    // Runs under a supervisor that restarts it, and set on the copy it runs.
+    pub install: bool,
+    pub supervised: bool,
+    pub calibrate: bool,
+    pub jack: bool,
+    pub pipewire: bool,
+    pub list_devices: bool,
+    pub stdin_pcm: Option<pcm::Format>,
+    pub udp: Option<String>,
+    pub rtp: Option<String>,
+    pub pcm_format: Option<pcm::Format>,
+    pub snapcast: Option<String>,
+    pub bridge: Option<String>,
+    pub synth: bool,
+    pub timeline: Option<PathBuf>,
+    pub snapcast_play: bool,
+    pub deck: Option<String>,
+    pub metrics: Option<String>,
+    pub device: Option<String>,
+    pub input_channels: Option<(usize, usize)>,
+    pub compare_device: Option<String>,
+    pub generate: Option<Signal>,
+    pub ambisonic: Option<ambisonic::Format>,
+    pub soundfont: Option<PathBuf>,
+    pub watch: Option<PathBuf>,
+    pub spectrogram: Option<PathBuf>,
+    pub frames: Option<String>,
+    pub frame_rate: f32,
+    pub draft: bool,
+    pub frame_data: bool,
+    pub timecode: bool,
+    pub ltc: Option<String>,
+    pub backdrop: Option<String>,
+    pub emit: Option<emit::Format>,
+    pub emit_to: Option<PathBuf>,
+    pub session: Option<PathBuf>,
+    pub overlay: bool,
+    pub opacity: f32,
+    pub msaa: NumSamples,
+    pub background: Option<[u8; 3]>,
+    pub no_bass: bool,
+    pub shader: Option<PathBuf>,
+    pub preset: Option<PathBuf>,
+    pub surprise: Option<u32>,
+    pub files: Vec<PathBuf>,
+}
+
+pub fn parse() -> GameResult<Options> {
+    // macOS passes a process serial number when launched from Finder.
+    let mut args = env::args_os()
+        .skip(1)
+        .filter(|arg| !arg.to_string_lossy().starts_with("-psn_"));
+
+    let mut options = Options {
+        register: false,
+        secondary: false,
+        install: false,
+        supervised: false,
+        calibrate: false,
+        jack: false,
+        pipewire: false,
+        list_devices: false,
+        stdin_pcm: None,
+        udp: None,
+        rtp: None,
+        pcm_format: None,
+        snapcast: None,
+        bridge: None,
+        synth: false,
+        timeline: None,
+        snapcast_play: false,
+        deck: None,
+        metrics: None,
+        device: None,
+        input_channels: None,
+        compare_device: None,
+        generate: None,
+        ambisonic: None,
+        soundfont: None,
+        watch: None,
+        spectrogram: None,
+        frames: None,
+        frame_rate: 30.0,
+        draft: false,
+        frame_data: false,
+        timecode: false,
+        ltc: None,
+        backdrop: None,
+        emit: None,
+        emit_to: None,
+        session: None,
+        overlay: false,
+        opacity: 1.0,
+        msaa: NumSamples::One,
+        background: None,
+        no_bass: false,
+        shader: None,
+        preset: None,
+        surprise: None,
+        files: Vec::new(),
+    };
+
+    while let Some(arg) = args.next() {
+        match arg.to_str() {
+            Some("--register") => options.register = true,
+            Some("--secondary") => options.secondary = true,
+            Some("--install") => options.install = true,
+            Some("--supervised") => options.supervised = true,
+            Some("--calibrate") => options.calibrate = true,
+            Some("--jack") => options.jack = true,
+            Some("--pipewire") => options.pipewire = true,
+            Some("--list-devices") => options.list_devices = true,
+            Some("--stdin-pcm") => options.stdin_pcm = Some(pcm::Format::parse(&value(&mut args, "--stdin-pcm")?)?),
+            Some("--udp") => options.udp = Some(value(&mut args, "--udp")?),
+            Some("--rtp") => options.rtp = Some(value(&mut args, "--rtp")?),
+            Some("--pcm-format") => {
+                options.pcm_format = Some(pcm::Format::parse(&value(&mut args, "--pcm-format")?)?)
+            }
+            Some("--snapcast") => options.snapcast = Some(value(&mut args, "--snapcast")?),
+            Some("--snapcast-play") => options.snapcast_play = true,
+            Some("--synth") => options.synth = true,
+            Some("--timeline") => options.timeline = Some(PathBuf::from(value(&mut args, "--timeline")?)),
+            Some("--bridge") => options.bridge = Some(value(&mut args, "--bridge")?),
+            Some("--deck") => options.deck = Some(value(&mut args, "--deck")?),
+            Some("--metrics") => options.metrics = Some(value(&mut args, "--metrics")?),
+            Some("--compare-device") => options.compare_device = Some(value(&mut args, "--compare-device")?),
+            Some("--device") => options.device = Some(value(&mut args, "--device")?),
+            Some("--input-channels") => {
+                let pair = value(&mut args, "--input-channels")?;
+                options.input_channels = Some(channel_pair(&pair).ok_or_else(|| {
+                    GameError::ConfigError(format!(
+                        "Invalid channel pair '{}', expected e.g. 3,4",
+                        pair
+                    ))
+                })?);
+            }
+            Some("--generate") => {
+                let name = value(&mut args, "--generate")?;
+                options.generate = Some(Signal::from_name(&name).ok_or_else(|| {
+                    GameError::ConfigError(format!(
+                        "Unknown signal '{}', expected one of: {}",
+                        name,
+                        Signal::names()
+                    ))
+                })?);
+            }
+            Some("--ambisonic") => {
+                let name = value(&mut args, "--ambisonic")?;
+                options.ambisonic = Some(ambisonic::Format::from_name(&name).ok_or_else(|| {
+                    GameError::ConfigError(format!(
+                        "Unknown ambisonic format '{}', expected ambix or fuma",
+                        name
+                    ))
+                })?);
+            }
+            Some("--soundfont") => options.soundfont = Some(PathBuf::from(value(&mut args, "--soundfont")?)),
+            Some("--watch") => options.watch = Some(PathBuf::from(value(&mut args, "--watch")?)),
+            Some("--session") => options.session = Some(PathBuf::from(value(&mut args, "--session")?)),
+            Some("--overlay") => options.overlay = true,
+            Some("--opacity") => {
+                let text = value(&mut args, "--opacity")?;
+                options.opacity = text.parse().map_err(|_| {
+                    GameError::ConfigError(format!("Invalid opacity '{}', expected 0 to 1", text))
+                })?;
+            }
+            Some("--msaa") => {
+                let text = value(&mut args, "--msaa")?;
+                options.msaa = text.parse().ok().and_then(NumSamples::from_u32).ok_or_else(|| {
+                    GameError::ConfigError(format!("Invalid sample count '{}', expected 1, 2, 4, 8 or 16", text))
+                })?;
+            }
+            Some("--background-color") => {
+                let name = value(&mut args, "--background-color")?;
+                options.background = Some(color(&name).ok_or_else(|| {
+                    GameError::ConfigError(format!(
+                        "Unknown color '{}', expected black, green, magenta, blue or #rrggbb",
+                        name
+                    ))
+                })?);
+            }
+            Some("--no-bass") => options.no_bass = true,
+            Some("--preset") => options.preset = Some(PathBuf::from(value(&mut args, "--preset")?)),
+            Some("--surprise") => {
+                let seed = value(&mut args, "--surprise")?;
+                options.surprise = Some(seed.parse().map_err(|_| {
+                    GameError::ConfigError(format!("Invalid seed '{}', expected a number", seed))
+                })?);
+            }
+            Some("--shader") => options.shader = Some(PathBuf::from(value(&mut args, "--shader")?)),
+            Some("--frames") => options.frames = Some(value(&mut args, "--frames")?),
+            Some("--frame-rate") => {
+                let text = value(&mut args, "--frame-rate")?;
+                options.frame_rate = text.parse().map_err(|_| {
+                    GameError::ConfigError(format!("Invalid frame rate '{}', expected frames per second", text))
+                })?;
+            }
+            Some("--draft") => options.draft = true,
+            Some("--frame-data") => options.frame_data = true,
+            Some("--timecode") => options.timecode = true,
+            Some("--ltc") => options.ltc = Some(value(&mut args, "--ltc")?),
+            Some("--emit") => {
+                let name = value(&mut args, "--emit")?;
+                options.emit = Some(emit::Format::from_name(&name).ok_or_else(|| {
+                    GameError::ConfigError(format!("Unknown stream format '{}', expected tsv or csv", name))
+                })?);
+            }
+            Some("--emit-to") => options.emit_to = Some(PathBuf::from(value(&mut args, "--emit-to")?)),
+            Some("--backdrop") => options.backdrop = Some(value(&mut args, "--backdrop")?),
+            Some("--spectrogram") => {
+                options.spectrogram = Some(PathBuf::from(value(&mut args, "--spectrogram")?))
+            }
+            _ => options.files.push(PathBuf::from(arg)),
+        }
+    }
+
+    Ok(options)
+}
+
+fn value<I>(args: &mut I, flag: &str) -> GameResult<String>
+where
+    I: Iterator<Item = OsString>,
+{
+    args.next()
+        .map(|arg| arg.to_string_lossy().into_owned())
+        .ok_or_else(|| GameError::ConfigError(format!("{} needs a value", flag)))
+}
+
+// "3,4" picks channels 3 and 4 as left and right; a single "3" feeds both.
+fn channel_pair(text: &str) -> Option<(usize, usize)> {
+    let mut parts = text.split(',').map(|part| part.trim().parse::<usize>().ok().filter(|&ch| ch >= 1));
+    let left = parts.next()??;
+    let right = match parts.next() {
+        Some(right) => right?,
+        None => left,
+    };
+    match parts.next() {
+        Some(_) => None,
+        None => Some((left, right)),
+    }
+}
+
+// Named key colors or "#rrggbb".
+fn color(text: &str) -> Option<[u8; 3]> {
+    match text.to_ascii_lowercase().as_str() {
+        "black" => Some([0, 0, 0]),
+        "green" => Some([0, 255, 0]),
+        "magenta" => Some([255, 0, 255]),
+        "blue" => Some([0, 0, 255]),
+        hex => {
+            let hex = hex.strip_prefix('#').unwrap_or(hex);
+            if hex.len() != 6 {
+                return None;
+            }
+            let channel = |idx: usize| u8::from_str_radix(hex.get(idx..idx + 2)?, 16).ok();
+            Some([channel(0)?, channel(2)?, channel(4)?])
+        }
+    }
+}
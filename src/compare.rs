@@ -0,0 +1,80 @@
+use crate::{animation::Animated, live::LiveInput, player::Player, DirectionalSource};
+use stereo_visualizer::{direction::Estimator, panlaw::PanLaw};
+use rustfft::{num_complex::Complex, num_traits::Zero, FFTplanner, FFT};
+use std::sync::Arc;
+
+// A second live source (usually a microphone) analysed on its own so it can
+// be stacked under the main stage, e.g. to play along with a backing track.
+pub struct Comparison {
+    pub player: Player,
+    fft: Arc<dyn FFT<f32>>,
+    left_fft: Vec<Complex<f32>>,
+    right_fft: Vec<Complex<f32>>,
+    left_rev: Vec<f32>,
+    right_rev: Vec<f32>,
+    pub directions: Vec<DirectionalSource>,
+    estimator: Estimator,
+    pub animated: Animated,
+    pub name: String,
+}
+
+impl Comparison {
+    pub fn new(input: LiveInput, fft_size: usize, pan_law: PanLaw) -> Self {
+        let name = input.name.clone();
+        let mut player = Player::new();
+        player.load_live(input);
+
+        Comparison {
+            player,
+            fft: FFTplanner::new(false).plan_fft(fft_size),
+            left_fft: vec![Complex::zero(); fft_size],
+            right_fft: vec![Complex::zero(); fft_size],
+            left_rev: vec![0.0; fft_size / 2],
+            right_rev: vec![0.0; fft_size / 2],
+            directions: vec![DirectionalSource::new(); fft_size / 2],
+            estimator: Estimator::new(fft_size / 2, pan_law),
+            animated: Animated::new(fft_size / 2),
+            name,
+        }
+    }
+
+    pub fn bin_hz(&self) -> f32 {
+        self.player.sample_rate() as f32 / self.left_fft.len() as f32
+    }
+
+    pub fn update(&mut self) {
+        self.player.update();
+
+        let size = self.left_fft.len();
+        let offset = self.player.position();
+        if offset + size > self.player.len() {
+            return;
+        }
+
+        let mut input: Vec<_> = self.player.left_wave()[offset..offset + size]
+            .iter()
+            .map(|&amp| Complex::new(amp, 0.0))
+            .collect();
+        self.fft.process(&mut input, &mut self.left_fft);
+
+        let mut input: Vec<_> = self.player.right_wave()[offset..offset + size]
+            .iter()
+            .map(|&amp| Complex::new(amp, 0.0))
+            .collect();
+        self.fft.process(&mut input, &mut self.right_fft);
+
+        let bin_hz = self.bin_hz();
+        for (idx, source) in self.directions.iter_mut().enumerate() {
+            let (amp, confidence) = self.estimator.step(
+                idx,
+                (self.left_fft[idx], self.right_fft[idx]),
+                (self.left_fft[idx].re.abs(), self.right_fft[idx].re.abs()),
+                (&mut self.left_rev[idx], &mut self.right_rev[idx]),
+                &mut source.dir,
+                bin_hz,
+            );
+            source.amp = amp;
+            source.confidence = confidence;
+        }
+    }
+}
@@ -0,0 +1,75 @@
+use crate::{
+    attract, bands, cards, gpio::Output, interaction::Force, layout::Layout, mqtt, orientation, quantize,
+    synth, transition, typography::Ambient, watermark,
+};
+use ggez::{filesystem, Context, GameResult};
+use serde::{Deserialize, Serialize};
+use std::{fs, path::PathBuf};
+use stereo_visualizer::panlaw::PanLaw;
+
+// What the stage does while playback is paused: fade out, or hold the last frame.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Pause {
+    #[default]
+    Decay,
+    Freeze,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Config {
+    pub latency: f32,
+    pub soundfont: Option<PathBuf>,
+    pub library: Vec<PathBuf>,
+    pub agc: bool,
+    pub bands: bands::Scale,
+    pub font: Option<PathBuf>,
+    pub target_fps: Option<f32>,
+    pub pause: Pause,
+    pub ambient: Ambient,
+    pub transition: transition::Style,
+    pub mouse: Force,
+    // Until a preset says otherwise.
+    pub kaleidoscope_segments: Option<usize>,
+    // Analyse at the source's own rate instead of resampling to 48 kHz.
+    pub native_rate: bool,
+    // Short-term loudness levels, in LUFS, whose crossing is announced.
+    pub loudness_thresholds: Vec<f32>,
+    // Which boundary mode switches, presets and strobes wait for.
+    pub quantize: quantize::Grid,
+    // No strobes, and no sudden jumps in full-screen brightness.
+    pub photosensitive_safe: bool,
+    // Last, as TOML needs tables after plain values.
+    pub orientation: orientation::PerMode,
+    pub layouts: Vec<Layout>,
+    pub outputs: Vec<Output>,
+    pub mqtt: Option<mqtt::Settings>,
+    pub panning: PanLaw,
+    pub cards: Option<cards::Settings>,
+    pub watermark: Option<watermark::Settings>,
+    pub attract: Option<attract::Settings>,
+    pub synth: Option<synth::Settings>,
+}
+
+impl Config {
+    fn path(ctx: &Context) -> PathBuf {
+        filesystem::user_config_dir(ctx).join("config.toml")
+    }
+
+    pub fn load(ctx: &Context) -> GameResult<Self> {
+        match fs::read_to_string(Self::path(ctx)) {
+            Ok(text) => Ok(toml::from_str(&text)?),
+            Err(_) => Ok(Config::default()),
+        }
+    }
+
+    pub fn save(&self, ctx: &Context) -> GameResult {
+        let path = Self::path(ctx);
+        if let Some(dir) = path.parent() {
+            fs::create_dir_all(dir)?;
+        }
+        fs::write(path, toml::to_string(self)?)?;
+        Ok(())
+    }
+}
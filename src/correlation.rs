@@ -0,0 +1,89 @@
+use crate::view::{FrequencyView, AXIS_HEIGHT};
+use ggez::{
+    graphics::{self, DrawParam},
+    Context, GameResult,
+};
+use rustfft::num_complex::Complex;
+
+const COLUMNS: usize = 96;
+const STRIP: f32 = 10.0;
+// About a quarter second of history at 60 frames per second.
+const SMOOTHING: f32 = 0.07;
+
+// Per-bin L/R correlation, Re(L R*) / sqrt(|L|^2 |R|^2) averaged over a
+// short history: +1 survives a mono fold-down, -1 cancels in it.
+pub struct Correlation {
+    pub enabled: bool,
+    cross: Vec<f32>,
+    left: Vec<f32>,
+    right: Vec<f32>,
+}
+
+impl Correlation {
+    pub fn new(bins: usize) -> Self {
+        Correlation {
+            enabled: false,
+            cross: vec![0.0; bins],
+            left: vec![0.0; bins],
+            right: vec![0.0; bins],
+        }
+    }
+
+    pub fn update(&mut self, left_fft: &[Complex<f32>], right_fft: &[Complex<f32>]) {
+        for idx in 0..self.cross.len() {
+            let (l, r) = (left_fft[idx], right_fft[idx]);
+            self.cross[idx] += ((l * r.conj()).re - self.cross[idx]) * SMOOTHING;
+            self.left[idx] += (l.norm_sqr() - self.left[idx]) * SMOOTHING;
+            self.right[idx] += (r.norm_sqr() - self.right[idx]) * SMOOTHING;
+        }
+    }
+
+    pub fn draw(&self, ctx: &mut Context, view: &FrequencyView, bin_hz: f32, width: f32, height: f32) -> GameResult {
+        if !self.enabled {
+            return Ok(());
+        }
+
+        let y = height - AXIS_HEIGHT - STRIP - 4.0;
+        let column = width / COLUMNS as f32;
+        let peak = self
+            .left
+            .iter()
+            .zip(&self.right)
+            .fold(0.0f32, |acc, (l, r)| acc.max(l + r))
+            .max(1e-9);
+
+        let mut builder = graphics::MeshBuilder::new();
+        let mut empty = true;
+        for col in 0..COLUMNS {
+            let low = view.freq_at(col as f32 * column, width) / bin_hz;
+            let high = view.freq_at((col + 1) as f32 * column, width) / bin_hz;
+            let first = (low.round() as usize).clamp(1, self.cross.len() - 1);
+            let last = (high.round() as usize).clamp(first + 1, self.cross.len());
+
+            let cross: f32 = self.cross[first..last].iter().sum();
+            let left: f32 = self.left[first..last].iter().sum();
+            let right: f32 = self.right[first..last].iter().sum();
+            let energy = (left + right) / (last - first) as f32;
+            let alpha = ((energy / peak).sqrt() * 255.0) as u8;
+            if alpha < 8 {
+                continue;
+            }
+
+            let value = (cross / (left * right).sqrt().max(1e-12)).clamp(-1.0, 1.0);
+            let color = if value >= 0.0 {
+                graphics::Color::from_rgba(((1.0 - value) * 255.0) as u8, 255, 64, alpha)
+            } else {
+                graphics::Color::from_rgba(255, ((1.0 + value) * 255.0) as u8, 64, alpha)
+            };
+            let rect = graphics::Rect::new(col as f32 * column, y, column + 0.5, STRIP);
+            builder.rectangle(graphics::DrawMode::fill(), rect, color);
+            empty = false;
+        }
+
+        if empty {
+            return Ok(());
+        }
+        let mesh = builder.build(ctx)?;
+        graphics::draw(ctx, &mesh, DrawParam::default())
+    }
+}
@@ -0,0 +1,62 @@
+use crate::playlist::{Chapter, Entry};
+use ggez::{error::GameError, GameResult};
+use std::path::Path;
+
+fn unquote(value: &str) -> String {
+    value.trim().trim_matches('"').to_string()
+}
+
+// INDEX times are mm:ss:ff with 75 frames per second.
+fn parse_time(value: &str) -> Option<f32> {
+    let mut fields = value.trim().split(':').map(|field| field.parse::<f32>().ok());
+    let (minutes, seconds, frames) = (fields.next()??, fields.next()??, fields.next()??);
+    Some(minutes * 60.0 + seconds + frames / 75.0)
+}
+
+pub fn parse(text: &str, dir: &Path) -> GameResult<Vec<Entry>> {
+    let mut entries: Vec<Entry> = Vec::new();
+    let mut title = None;
+    let mut performer = None;
+
+    for line in text.lines() {
+        let line = line.trim();
+        let (command, rest) = line.split_once(' ').unwrap_or((line, ""));
+
+        match command {
+            "FILE" => {
+                // The file type follows the last quote, or the last space when unquoted.
+                let name = match rest.rfind('"') {
+                    Some(end) => rest[..end].trim_start_matches('"'),
+                    None => rest.rsplit_once(' ').map_or(rest, |(name, _)| name),
+                };
+                entries.push(Entry::new(dir.join(name)));
+            }
+            "TRACK" => {
+                title = None;
+                performer = None;
+            }
+            "TITLE" => title = Some(unquote(rest)),
+            "PERFORMER" => performer = Some(unquote(rest)),
+            "INDEX" => {
+                let (number, time) = rest.split_once(' ').unwrap_or((rest, ""));
+                let entry = entries.last_mut().ok_or_else(|| {
+                    GameError::ConfigError("CUE sheet has an INDEX before any FILE".into())
+                })?;
+                if number.trim() == "01" {
+                    let start = parse_time(time).ok_or_else(|| {
+                        GameError::ConfigError(format!("Invalid CUE time '{}'", time))
+                    })?;
+                    let title = match (&performer, &title) {
+                        (Some(performer), Some(title)) => format!("{} - {}", performer, title),
+                        (None, Some(title)) => title.clone(),
+                        _ => format!("Track {}", entry.chapters.len() + 1),
+                    };
+                    entry.chapters.push(Chapter { title, start });
+                }
+            }
+            _ => (),
+        }
+    }
+
+    Ok(entries)
+}
@@ -0,0 +1,341 @@
+use crate::Mode;
+use serde::{Deserialize, Serialize};
+
+pub use server::Server;
+
+// What a key press on the deck asks for, sent as e.g. `{"action": "preset", "slot": 3}`.
+#[derive(Debug, Clone, Copy, PartialEq, Deserialize)]
+#[serde(tag = "action", rename_all = "kebab-case")]
+pub enum Command {
+    PlayPause,
+    Next,
+    Previous,
+    Mode,
+    Preset { slot: usize },
+}
+
+// What the deck is told twice a second, with ready-made key images.
+#[derive(Debug, Clone, Serialize)]
+pub struct Status {
+    pub playing: bool,
+    pub mode: Mode,
+    pub preset: String,
+    pub bpm: Option<f32>,
+    pub time: f32,
+    pub duration: f32,
+}
+
+// The WebSocket server, left out of builds without the network feature.
+#[cfg(feature = "network")]
+mod server {
+    use super::{Command, Status};
+    use ggez::{error::GameError, GameResult};
+    use std::{
+        f32::consts::PI,
+        io::{self, BufRead, BufReader, Read, Write},
+        net::{TcpListener, TcpStream},
+        sync::{
+            mpsc::{self, Receiver, Sender},
+            Arc, Mutex,
+        },
+        thread,
+        time::Duration,
+    };
+
+    const GUID: &str = "258EAFA5-E914-47DA-95CA-C5AB0DC85B11";
+    const STATUS_SECS: f32 = 0.5;
+    // Stream Deck keys are 72 points, 144 pixels on the XL and newer models.
+    const KEY: f32 = 144.0;
+
+    fn sha1(data: &[u8]) -> [u8; 20] {
+        let mut h: [u32; 5] = [0x6745_2301, 0xefcd_ab89, 0x98ba_dcfe, 0x1032_5476, 0xc3d2_e1f0];
+        let mut message = data.to_vec();
+        message.push(0x80);
+        while message.len() % 64 != 56 {
+            message.push(0);
+        }
+        message.extend_from_slice(&((data.len() as u64) * 8).to_be_bytes());
+
+        for chunk in message.chunks(64) {
+            let mut w = [0u32; 80];
+            for (idx, word) in chunk.chunks(4).enumerate() {
+                w[idx] = u32::from_be_bytes([word[0], word[1], word[2], word[3]]);
+            }
+            for idx in 16..80 {
+                w[idx] = (w[idx - 3] ^ w[idx - 8] ^ w[idx - 14] ^ w[idx - 16]).rotate_left(1);
+            }
+            let [mut a, mut b, mut c, mut d, mut e] = h;
+            for (idx, &word) in w.iter().enumerate() {
+                let (f, k) = match idx {
+                    0..=19 => ((b & c) | (!b & d), 0x5a82_7999),
+                    20..=39 => (b ^ c ^ d, 0x6ed9_eba1),
+                    40..=59 => ((b & c) | (b & d) | (c & d), 0x8f1b_bcdc),
+                    _ => (b ^ c ^ d, 0xca62_c1d6),
+                };
+                let next = a.rotate_left(5).wrapping_add(f).wrapping_add(e).wrapping_add(k).wrapping_add(word);
+                e = d;
+                d = c;
+                c = b.rotate_left(30);
+                b = a;
+                a = next;
+            }
+            for (state, value) in h.iter_mut().zip(&[a, b, c, d, e]) {
+                *state = state.wrapping_add(*value);
+            }
+        }
+
+        let mut digest = [0u8; 20];
+        for (bytes, state) in digest.chunks_mut(4).zip(&h) {
+            bytes.copy_from_slice(&state.to_be_bytes());
+        }
+        digest
+    }
+
+    fn base64(data: &[u8]) -> String {
+        const ALPHABET: &[u8] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/";
+        let mut text = String::with_capacity(data.len().div_ceil(3) * 4);
+        for group in data.chunks(3) {
+            let bits = group.iter().enumerate().fold(0u32, |bits, (idx, &byte)| bits | u32::from(byte) << (16 - idx * 8));
+            for idx in 0..4 {
+                if idx <= group.len() {
+                    text.push(ALPHABET[(bits >> (18 - idx * 6) & 63) as usize] as char);
+                } else {
+                    text.push('=');
+                }
+            }
+        }
+        text
+    }
+
+    // Answers the HTTP upgrade request of a new connection.
+    fn handshake(stream: &mut TcpStream) -> io::Result<()> {
+        let mut reader = BufReader::new(stream.try_clone()?);
+        let mut key = None;
+        loop {
+            let mut line = String::new();
+            if reader.read_line(&mut line)? == 0 {
+                return Err(io::ErrorKind::UnexpectedEof.into());
+            }
+            let line = line.trim_end();
+            if line.is_empty() {
+                break;
+            }
+            if let Some((name, value)) = line.split_once(':') {
+                if name.trim().eq_ignore_ascii_case("sec-websocket-key") {
+                    key = Some(value.trim().to_string());
+                }
+            }
+        }
+        let key = key.ok_or_else(|| io::Error::new(io::ErrorKind::InvalidData, "not a WebSocket request"))?;
+        let accept = base64(&sha1(format!("{}{}", key, GUID).as_bytes()));
+        write!(
+            stream,
+            "HTTP/1.1 101 Switching Protocols\r\nUpgrade: websocket\r\nConnection: Upgrade\r\nSec-WebSocket-Accept: {}\r\n\r\n",
+            accept
+        )
+    }
+
+    fn frame(opcode: u8, payload: &[u8]) -> Vec<u8> {
+        let mut frame = vec![0x80 | opcode];
+        match payload.len() {
+            len if len < 126 => frame.push(len as u8),
+            len if len <= 0xffff => {
+                frame.push(126);
+                frame.extend_from_slice(&(len as u16).to_be_bytes());
+            }
+            len => {
+                frame.push(127);
+                frame.extend_from_slice(&(len as u64).to_be_bytes());
+            }
+        }
+        frame.extend_from_slice(payload);
+        frame
+    }
+
+    // One frame from a client, whose frames are always masked.
+    fn read_frame(stream: &mut impl Read) -> io::Result<(u8, Vec<u8>)> {
+        let mut header = [0u8; 2];
+        stream.read_exact(&mut header)?;
+        let len = match header[1] & 0x7f {
+            126 => {
+                let mut len = [0u8; 2];
+                stream.read_exact(&mut len)?;
+                u64::from(u16::from_be_bytes(len))
+            }
+            127 => {
+                let mut len = [0u8; 8];
+                stream.read_exact(&mut len)?;
+                u64::from_be_bytes(len)
+            }
+            len => u64::from(len),
+        };
+        if len > 1 << 16 {
+            return Err(io::Error::new(io::ErrorKind::InvalidData, "frame too long"));
+        }
+        let mut mask = [0u8; 4];
+        if header[1] & 0x80 != 0 {
+            stream.read_exact(&mut mask)?;
+        }
+        let mut payload = vec![0u8; len as usize];
+        stream.read_exact(&mut payload)?;
+        for (idx, byte) in payload.iter_mut().enumerate() {
+            *byte ^= mask[idx % 4];
+        }
+        Ok((header[0] & 0x0f, payload))
+    }
+
+    type Clients = Arc<Mutex<Vec<TcpStream>>>;
+
+    fn serve(mut stream: TcpStream, clients: Clients, commands: Sender<Command>) -> io::Result<()> {
+        handshake(&mut stream)?;
+        let client = stream.try_clone()?;
+        // A stuck client must not hold up the frame that writes to it.
+        client.set_write_timeout(Some(Duration::from_millis(50)))?;
+        clients.lock().unwrap().push(client);
+        loop {
+            let (opcode, payload) = read_frame(&mut stream)?;
+            match opcode {
+                0x1 => match serde_json::from_slice(&payload) {
+                    Ok(command) => {
+                        if commands.send(command).is_err() {
+                            return Ok(());
+                        }
+                    }
+                    Err(err) => println!("Ignoring deck message: {}", err),
+                },
+                // Replies go through the lock so they cannot split a status frame.
+                0x9 => {
+                    let _guard = clients.lock().unwrap();
+                    stream.write_all(&frame(0xa, &payload))?;
+                }
+                0x8 => {
+                    let _ = stream.write_all(&frame(0x8, &[]));
+                    return Ok(());
+                }
+                _ => (),
+            }
+        }
+    }
+
+    fn svg(body: &str) -> String {
+        let svg = format!(
+            "<svg xmlns='http://www.w3.org/2000/svg' width='{0}' height='{0}'><rect width='{0}' height='{0}' fill='black'/>{1}</svg>",
+            KEY, body
+        );
+        format!("data:image/svg+xml;base64,{}", base64(svg.as_bytes()))
+    }
+
+    fn bpm_key(bpm: Option<f32>) -> String {
+        let number = bpm.map_or_else(|| "--".to_string(), |bpm| format!("{:.0}", bpm));
+        svg(&format!(
+            "<text x='72' y='84' font-family='sans-serif' font-size='52' font-weight='bold' fill='white' text-anchor='middle'>{}</text>\
+             <text x='72' y='120' font-family='sans-serif' font-size='22' fill='#9cf' text-anchor='middle'>BPM</text>",
+            number
+        ))
+    }
+
+    fn progress_key(time: f32, duration: f32) -> String {
+        let share = if duration > 0.0 { (time / duration).clamp(0.0, 0.9999) } else { 0.0 };
+        let (radius, center) = (56.0, KEY / 2.0);
+        let angle = share * 2.0 * PI - PI / 2.0;
+        let end = (center + radius * angle.cos(), center + radius * angle.sin());
+        let arc = format!(
+            "M {} {} A {r} {r} 0 {} 1 {:.1} {:.1}",
+            center,
+            center - radius,
+            u8::from(share > 0.5),
+            end.0,
+            end.1,
+            r = radius
+        );
+        let secs = time.max(0.0) as u32;
+        svg(&format!(
+            "<circle cx='72' cy='72' r='56' stroke='#333' stroke-width='10' fill='none'/>\
+             <path d='{}' stroke='#6c8' stroke-width='10' fill='none'/>\
+             <text x='72' y='82' font-family='sans-serif' font-size='28' fill='white' text-anchor='middle'>{}:{:02}</text>",
+            arc,
+            secs / 60,
+            secs % 60
+        ))
+    }
+
+    // A WebSocket endpoint for a Stream Deck plugin or any other remote: keys send
+    // commands, and every client gets the status with images for its keys.
+    pub struct Server {
+        clients: Clients,
+        commands: Receiver<Command>,
+        until_status: f32,
+    }
+
+    impl Server {
+        pub fn bind(addr: &str) -> GameResult<Self> {
+            let listener = TcpListener::bind(addr)
+                .map_err(|err| GameError::ConfigError(format!("Cannot listen on {}: {}", addr, err)))?;
+            let clients = Clients::default();
+            let (sender, commands) = mpsc::channel();
+            let accepted = clients.clone();
+            thread::spawn(move || {
+                for stream in listener.incoming().flatten() {
+                    let (clients, sender) = (accepted.clone(), sender.clone());
+                    thread::spawn(move || {
+                        if let Err(err) = serve(stream, clients, sender) {
+                            println!("Deck connection closed: {}", err);
+                        }
+                    });
+                }
+            });
+            println!("Listening for deck connections on ws://{}", addr);
+            Ok(Server {
+                clients,
+                commands,
+                until_status: 0.0,
+            })
+        }
+
+        pub fn commands(&self) -> Vec<Command> {
+            self.commands.try_iter().collect()
+        }
+
+        pub fn update(&mut self, status: &Status, delta: f32) {
+            self.until_status -= delta;
+            if self.until_status > 0.0 {
+                return;
+            }
+            self.until_status = STATUS_SECS;
+
+            let mut message = match serde_json::to_value(status) {
+                Ok(message) => message,
+                Err(_) => return,
+            };
+            message["images"] = serde_json::json!({
+                "bpm": bpm_key(status.bpm),
+                "progress": progress_key(status.time, status.duration),
+            });
+            let frame = frame(0x1, message.to_string().as_bytes());
+            // Clients that went away drop out on the first failed write.
+            self.clients.lock().unwrap().retain_mut(|client| client.write_all(&frame).is_ok());
+        }
+    }
+}
+
+#[cfg(not(feature = "network"))]
+mod server {
+    use super::{Command, Status};
+    use ggez::{error::GameError, GameResult};
+
+    pub struct Server;
+
+    impl Server {
+        pub fn bind(_addr: &str) -> GameResult<Self> {
+            Err(GameError::ConfigError(
+                "This build has no deck support; rebuild with `--features network`".into(),
+            ))
+        }
+
+        pub fn commands(&self) -> Vec<Command> {
+            Vec::new()
+        }
+
+        pub fn update(&mut self, _status: &Status, _delta: f32) {}
+    }
+}
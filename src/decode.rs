@@ -0,0 +1,80 @@
+use ggez::{error::GameError, GameResult};
+use std::{env, fs, path::Path, process::Command};
+
+// No pure-Rust Opus, WavPack or video demuxer is available, so these go
+// through an installed command-line decoder into a temporary WAV file.
+const EXTERNAL: &[(&str, &[&str])] = &[
+    ("opus", &["ffmpeg", "opusdec"]),
+    ("wv", &["ffmpeg", "wvunpack"]),
+    ("mp4", &["ffmpeg"]),
+    ("m4a", &["ffmpeg"]),
+    ("m4b", &["ffmpeg"]),
+    ("mkv", &["ffmpeg"]),
+    ("webm", &["ffmpeg"]),
+    ("mov", &["ffmpeg"]),
+];
+
+pub fn is_url(path: &Path) -> bool {
+    let path = path.to_string_lossy();
+    path.starts_with("http://") || path.starts_with("https://")
+}
+
+pub fn needs_external(path: &Path) -> bool {
+    is_url(path) || extension(path).is_some_and(|ext| EXTERNAL.iter().any(|(name, _)| *name == ext))
+}
+
+fn extension(path: &Path) -> Option<String> {
+    path.extension()
+        .and_then(|ext| ext.to_str())
+        .map(|ext| ext.to_ascii_lowercase())
+}
+
+fn command(tool: &str, input: &Path, output: &Path) -> Command {
+    let mut command = Command::new(tool);
+    match tool {
+        // Opus always decodes at 48 kHz, which ffmpeg and opusdec keep.
+        "ffmpeg" => command
+            .args(["-y", "-loglevel", "error", "-i"])
+            .arg(input)
+            .args(["-vn", "-map", "0:a:0", "-acodec", "pcm_s16le", "-f", "wav"])
+            .arg(output),
+        "opusdec" => command.args(["--quiet", "--force-wav"]).arg(input).arg(output),
+        _ => command.args(["-q", "-y"]).arg(input).arg("-o").arg(output),
+    };
+    command
+}
+
+pub fn to_wav(path: &Path) -> GameResult<Vec<u8>> {
+    let ext = extension(path).unwrap_or_default();
+    let tools = if is_url(path) {
+        &["ffmpeg"]
+    } else {
+        EXTERNAL
+            .iter()
+            .find(|(name, _)| *name == ext)
+            .map(|(_, tools)| *tools)
+            .unwrap_or(&[])
+    };
+    let output = env::temp_dir().join(format!("stereo-visualizer-{}.wav", std::process::id()));
+
+    for tool in tools {
+        let status = match command(tool, path, &output).status() {
+            Ok(status) => status,
+            Err(_) => continue,
+        };
+        if !status.success() {
+            let _ = fs::remove_file(&output);
+            return Err(GameError::AudioError(format!("{} failed to decode {}", tool, path.display())));
+        }
+
+        let bytes = fs::read(&output);
+        let _ = fs::remove_file(&output);
+        return Ok(bytes?);
+    }
+
+    Err(GameError::AudioError(format!(
+        "Decoding {} needs one of: {}",
+        path.display(),
+        tools.join(", ")
+    )))
+}
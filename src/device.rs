@@ -0,0 +1,135 @@
+use crate::live::LiveInput;
+use cpal::{
+    traits::{DeviceTrait, EventLoopTrait, HostTrait},
+    StreamData, UnknownTypeInputBuffer,
+};
+use ggez::{error::GameError, GameResult};
+use std::{sync::mpsc, thread};
+
+// An input device by name and channel pair, as asked for on the command line.
+pub type Selection = (Option<String>, Option<(usize, usize)>);
+
+// ASIO drivers only show up through their own host, which cpal exposes when
+// built with its `asio` feature; prefer it over the platform default (WASAPI,
+// ALSA, CoreAudio) whenever it is there.
+fn host() -> GameResult<cpal::Host> {
+    match cpal::available_hosts()
+        .into_iter()
+        .find(|id| format!("{:?}", id).eq_ignore_ascii_case("asio"))
+    {
+        Some(id) => cpal::host_from_id(id).map_err(|err| GameError::AudioError(err.to_string())),
+        None => Ok(cpal::default_host()),
+    }
+}
+
+pub fn list() -> GameResult {
+    let host = host()?;
+    let devices = host
+        .input_devices()
+        .map_err(|err| GameError::AudioError(err.to_string()))?;
+
+    println!("Input devices ({:?}):", host.id());
+    for device in devices {
+        let name = device.name().unwrap_or_else(|_| "?".into());
+        match device.default_input_format() {
+            Ok(format) => println!("  {} ({} ch, {} Hz)", name, format.channels, format.sample_rate.0),
+            Err(_) => println!("  {}", name),
+        }
+    }
+    Ok(())
+}
+
+// The default input device and every input device's name.
+pub fn names() -> (Option<String>, Vec<String>) {
+    let host = match host() {
+        Ok(host) => host,
+        Err(_) => return (None, Vec::new()),
+    };
+    let default = host.default_input_device().and_then(|device| device.name().ok());
+    let all = host
+        .input_devices()
+        .map(|devices| devices.filter_map(|device| device.name().ok()).collect())
+        .unwrap_or_default();
+    (default, all)
+}
+
+fn find(host: &cpal::Host, name: Option<&str>) -> GameResult<cpal::Device> {
+    let device = match name {
+        Some(name) => {
+            let wanted = name.to_lowercase();
+            host.input_devices()
+                .map_err(|err| GameError::AudioError(err.to_string()))?
+                .find(|device| device.name().is_ok_and(|found| found.to_lowercase().contains(&wanted)))
+        }
+        None => host.default_input_device(),
+    };
+    device.ok_or_else(|| match name {
+        Some(name) => GameError::AudioError(format!("No input device matches '{}'", name)),
+        None => GameError::AudioError("No default input device".into()),
+    })
+}
+
+// Captures `channels` (1-based left/right, both defaulting to the first two)
+// from an input device; a mono device feeds both sides.
+pub fn open(name: Option<&str>, channels: Option<(usize, usize)>) -> GameResult<LiveInput> {
+    let host = host()?;
+    let device = find(&host, name)?;
+    let device_name = device.name().unwrap_or_else(|_| "input".into());
+    let format = device
+        .default_input_format()
+        .map_err(|err| GameError::AudioError(err.to_string()))?;
+
+    let count = format.channels as usize;
+    let (left, right) = match channels {
+        Some((left, right)) => (left - 1, right - 1),
+        None => (0, 1.min(count.saturating_sub(1))),
+    };
+    if left >= count || right >= count {
+        return Err(GameError::AudioError(format!(
+            "{} has only {} input channels",
+            device_name, count
+        )));
+    }
+
+    let event_loop = host.event_loop();
+    let stream = event_loop
+        .build_input_stream(&device, &format)
+        .map_err(|err| GameError::AudioError(err.to_string()))?;
+    event_loop
+        .play_stream(stream)
+        .map_err(|err| GameError::AudioError(err.to_string()))?;
+
+    let (sender, receiver) = mpsc::channel();
+    thread::spawn(move || {
+        event_loop.run(move |_, data| {
+            let pick = |frame: &[f32]| [frame[left], frame[right]];
+            let block: Vec<f32> = match data {
+                Ok(StreamData::Input { buffer: UnknownTypeInputBuffer::F32(buffer) }) => {
+                    buffer.chunks_exact(count).flat_map(pick).collect()
+                }
+                Ok(StreamData::Input { buffer: UnknownTypeInputBuffer::I16(buffer) }) => buffer
+                    .chunks_exact(count)
+                    .flat_map(|frame| [frame[left] as f32 / 32768.0, frame[right] as f32 / 32768.0])
+                    .collect(),
+                Ok(StreamData::Input { buffer: UnknownTypeInputBuffer::U16(buffer) }) => buffer
+                    .chunks_exact(count)
+                    .flat_map(|frame| {
+                        [
+                            (frame[left] as f32 - 32768.0) / 32768.0,
+                            (frame[right] as f32 - 32768.0) / 32768.0,
+                        ]
+                    })
+                    .collect(),
+                _ => return,
+            };
+            let _ = sender.send(block);
+        })
+    });
+
+    Ok(LiveInput::new(
+        receiver,
+        format.sample_rate.0,
+        format!("{} [{}+{}]", device_name, left + 1, right + 1),
+        (),
+    ))
+}
@@ -0,0 +1,95 @@
+use crate::panlaw::{Calibration, PanLaw};
+use rustfft::num_complex::Complex;
+
+// Share of the way the spectra move towards each new frame.
+const SMOOTHING: f32 = 0.3;
+// Share of the way the shown levels move towards each new frame.
+const EASE: f32 = 0.9;
+// Level at which a bin is as likely signal as noise, in FFT magnitude.
+const NOISE_FLOOR: f32 = 1.0;
+
+#[derive(Debug, Clone, Copy, Default)]
+struct Spectra {
+    cross: Complex<f32>,
+    left: f32,
+    right: f32,
+}
+
+// How far each bin's direction can be trusted: a source panned between the
+// speakers makes both channels coherent, while noise, reverb and mixed
+// sources decorrelate them, and near the floor the level difference is noise.
+pub struct Estimator {
+    spectra: Vec<Spectra>,
+    pan_law: Calibration,
+}
+
+impl Estimator {
+    pub fn new(bins: usize, pan_law: PanLaw) -> Self {
+        Estimator {
+            spectra: vec![Spectra::default(); bins],
+            pan_law: Calibration::new(pan_law),
+        }
+    }
+
+    // One frame of bin `idx`, for the visualizer and the C library alike:
+    // eases `eased` towards `levels`, the bin's magnitudes after any EQ, and
+    // moves `dir` to where they point through the pan law, as far as that can
+    // be trusted. Returns the level and the confidence.
+    pub fn step(
+        &mut self,
+        idx: usize,
+        (left, right): (Complex<f32>, Complex<f32>),
+        levels: (f32, f32),
+        eased: (&mut f32, &mut f32),
+        dir: &mut f32,
+        bin_hz: f32,
+    ) -> (f32, f32) {
+        *eased.0 += (levels.0 - *eased.0) * EASE;
+        *eased.1 += (levels.1 - *eased.1) * EASE;
+
+        let amp = eased.0.max(*eased.1);
+        let confidence = self.confidence(idx, left, right, amp);
+        let target = raw(*eased.0, *eased.1);
+        let target = self.pan_law.apply(idx, target, self.spectra.len(), bin_hz);
+        *dir = steer(*dir, target, confidence);
+        (amp, confidence)
+    }
+
+    // Confidence in 0..1: the magnitude-squared coherence of the two channels,
+    // or for a bin mostly in one channel how one-sided it is, as coherence
+    // means nothing against silence; taken down where `amp` nears the floor.
+    fn confidence(&mut self, idx: usize, left: Complex<f32>, right: Complex<f32>, amp: f32) -> f32 {
+        let spectra = match self.spectra.get_mut(idx) {
+            Some(spectra) => spectra,
+            None => return 0.0,
+        };
+        spectra.cross += (left * right.conj() - spectra.cross) * SMOOTHING;
+        spectra.left += (left.norm_sqr() - spectra.left) * SMOOTHING;
+        spectra.right += (right.norm_sqr() - spectra.right) * SMOOTHING;
+
+        let (power, total) = (spectra.left * spectra.right, spectra.left + spectra.right);
+        if total <= 0.0 {
+            return 0.0;
+        }
+        let coherence = if power > 0.0 { (spectra.cross.norm_sqr() / power).min(1.0) } else { 0.0 };
+        let one_sided = (spectra.left - spectra.right).abs() / total;
+        let level = amp * amp / (amp * amp + NOISE_FLOOR * NOISE_FLOOR);
+        (coherence + (1.0 - coherence) * one_sided) * level
+    }
+}
+
+// The level difference as a direction from -1 (left) to 1 (right).
+fn raw(left: f32, right: f32) -> f32 {
+    let amp = left.max(right);
+    if amp > 0.0 {
+        (right - left) / amp
+    } else {
+        0.0
+    }
+}
+
+// Moves towards `target` only as far as it is trusted, so bins near the floor
+// hold still instead of jittering from side to side.
+fn steer(current: f32, target: f32, confidence: f32) -> f32 {
+    current + (target - current) * confidence.clamp(0.0, 1.0)
+}
@@ -0,0 +1,138 @@
+use std::f32::consts::PI;
+
+#[derive(Debug, Clone, Copy)]
+pub struct Biquad {
+    b0: f32,
+    b1: f32,
+    b2: f32,
+    a1: f32,
+    a2: f32,
+    x1: f32,
+    x2: f32,
+    y1: f32,
+    y2: f32,
+}
+
+impl Biquad {
+    pub fn new(b0: f32, b1: f32, b2: f32, a1: f32, a2: f32) -> Self {
+        Biquad {
+            b0,
+            b1,
+            b2,
+            a1,
+            a2,
+            x1: 0.0,
+            x2: 0.0,
+            y1: 0.0,
+            y2: 0.0,
+        }
+    }
+
+    pub fn peaking(freq: f32, gain: f32, q: f32, sample_rate: f32) -> Self {
+        let a = 10.0f32.powf(gain / 40.0);
+        let w0 = 2.0 * PI * freq / sample_rate;
+        let alpha = w0.sin() / (2.0 * q);
+        let cos = w0.cos();
+
+        let a0 = 1.0 + alpha / a;
+        Biquad::new(
+            (1.0 + alpha * a) / a0,
+            -2.0 * cos / a0,
+            (1.0 - alpha * a) / a0,
+            -2.0 * cos / a0,
+            (1.0 - alpha / a) / a0,
+        )
+    }
+
+    pub fn process(&mut self, x: f32) -> f32 {
+        let y = self.b0 * x + self.b1 * self.x1 + self.b2 * self.x2 - self.a1 * self.y1 - self.a2 * self.y2;
+        self.x2 = self.x1;
+        self.x1 = x;
+        self.y2 = self.y1;
+        self.y1 = y;
+        y
+    }
+}
+
+// Zero crossings of the sinc on either side, and kernels tabulated per
+// fraction of an input sample, linearly interpolated in between.
+const HALF_TAPS: usize = 16;
+const PHASES: usize = 256;
+
+// Band-limited resampling with a Blackman-windowed sinc. It runs on blocks as
+// they come, so live inputs can go through it as well as whole tracks.
+#[derive(Debug, Clone)]
+pub struct Resampler {
+    // Input samples per output sample.
+    step: f64,
+    table: Vec<[f32; HALF_TAPS * 2]>,
+    history: Vec<f32>,
+    // Where the next output falls in `history`.
+    time: f64,
+}
+
+impl Resampler {
+    pub fn new(from: u32, to: u32) -> Self {
+        // Below the lower Nyquist frequency, with a little room for the transition.
+        let cutoff = (to as f32 / from as f32).min(1.0) * 0.95;
+        let table = (0..=PHASES)
+            .map(|phase| {
+                let frac = phase as f32 / PHASES as f32;
+                let mut kernel = [0.0; HALF_TAPS * 2];
+                for (idx, tap) in kernel.iter_mut().enumerate() {
+                    // Distance from the output point to input sample `idx`.
+                    let x = idx as f32 - (HALF_TAPS - 1) as f32 - frac;
+                    let u = x / HALF_TAPS as f32;
+                    if u.abs() >= 1.0 {
+                        continue;
+                    }
+                    let sinc = if x == 0.0 { 1.0 } else { (PI * cutoff * x).sin() / (PI * cutoff * x) };
+                    let window = 0.42 + 0.5 * (PI * u).cos() + 0.08 * (2.0 * PI * u).cos();
+                    *tap = cutoff * sinc * window;
+                }
+                kernel
+            })
+            .collect();
+        Resampler {
+            step: f64::from(from) / f64::from(to),
+            table,
+            // Silence before the first sample, so output starts right at it.
+            history: vec![0.0; HALF_TAPS - 1],
+            time: (HALF_TAPS - 1) as f64,
+        }
+    }
+
+    pub fn process(&mut self, input: &[f32]) -> Vec<f32> {
+        self.history.extend_from_slice(input);
+        let mut output = Vec::with_capacity((input.len() as f64 / self.step) as usize + 1);
+        while (self.time as usize) + HALF_TAPS < self.history.len() {
+            let whole = self.time as usize;
+            let position = (self.time - whole as f64) as f32 * PHASES as f32;
+            // The fraction can round up to a whole sample in f32.
+            let phase = (position as usize).min(PHASES - 1);
+            let blend = position - phase as f32;
+            let (before, after) = (&self.table[phase], &self.table[phase + 1]);
+            let window = &self.history[whole + 1 - HALF_TAPS..=whole + HALF_TAPS];
+            let mut sum = 0.0;
+            for ((&sample, &a), &b) in window.iter().zip(before).zip(after) {
+                sum += sample * (a + (b - a) * blend);
+            }
+            output.push(sum);
+            self.time += self.step;
+        }
+
+        let consumed = (self.time as usize).saturating_sub(HALF_TAPS - 1);
+        self.history.drain(..consumed.min(self.history.len()));
+        self.time -= consumed as f64;
+        output
+    }
+}
+
+// A whole wave at another rate, as long as the original in seconds.
+pub fn resample(wave: &[f32], from: u32, to: u32) -> Vec<f32> {
+    let mut resampler = Resampler::new(from, to);
+    let mut output = resampler.process(wave);
+    output.extend(resampler.process(&[0.0; HALF_TAPS]));
+    output.truncate((wave.len() as f64 * f64::from(to) / f64::from(from)).round() as usize);
+    output
+}
@@ -0,0 +1,226 @@
+use crate::{dsp::Biquad, loudness, overlay::{self, Anchor}};
+use ggez::{
+    graphics::{self, DrawParam},
+    Context, GameResult,
+};
+use std::collections::VecDeque;
+
+const BLOCK_SECS: f32 = 0.1;
+// Short-term loudness spans 3 s, integrated gating uses 400 ms blocks.
+const SHORT_TERM: usize = 30;
+const MOMENTARY: usize = 4;
+const HISTORY: usize = 200;
+const FLOOR_LUFS: f32 = -60.0;
+// A block is flat-topped when this many samples sit in runs of at least
+// RUN samples pinned near its peak, the tell of a hard limiter or clipper.
+const RUN: usize = 3;
+const FLAT_SAMPLES: usize = 24;
+const GRAPH_HEIGHT: f32 = 120.0;
+
+fn to_lufs(power: f64) -> f32 {
+    -0.691 + 10.0 * (power.max(1e-12) as f32).log10()
+}
+
+// Scrolling short-term loudness with the integrated value of everything
+// heard so far, measured incrementally as playback advances.
+pub struct Dynamics {
+    pub visible: bool,
+    filters: Option<([Biquad; 2], [Biquad; 2])>,
+    next: usize,
+    sum: f64,
+    count: usize,
+    block_peak: f32,
+    block: Vec<f32>,
+    blocks: Vec<f64>,
+    history: VecDeque<(f32, bool)>,
+}
+
+impl Dynamics {
+    pub fn new() -> Self {
+        Dynamics {
+            visible: false,
+            filters: None,
+            next: 0,
+            sum: 0.0,
+            count: 0,
+            block_peak: 0.0,
+            block: Vec::new(),
+            blocks: Vec::new(),
+            history: VecDeque::with_capacity(HISTORY),
+        }
+    }
+
+    pub fn clear(&mut self) {
+        *self = Dynamics {
+            visible: self.visible,
+            ..Dynamics::new()
+        };
+    }
+
+    // `start` is the absolute index of the first sample in the waves, which
+    // moves when a live buffer is trimmed.
+    pub fn update(&mut self, left: &[f32], right: &[f32], start: usize, position: usize, sample_rate: u32) {
+        let rate = sample_rate as usize;
+        let position = position + start;
+        if self.next < start || position < self.next || position > self.next + rate {
+            // Seeked: start measuring again from here.
+            self.clear();
+            self.next = position;
+        }
+
+        let rate_hz = sample_rate as f32;
+        let mut filters = self
+            .filters
+            .take()
+            .unwrap_or_else(|| (loudness::k_weighting(rate_hz), loudness::k_weighting(rate_hz)));
+        let block_len = (rate as f32 * BLOCK_SECS) as usize;
+        let end = (position - start).min(left.len()).min(right.len());
+
+        for idx in self.next - start..end {
+            let (l, r) = (left[idx], right[idx]);
+            let fl = filters.0.iter_mut().fold(l, |amp, filter| filter.process(amp));
+            let fr = filters.1.iter_mut().fold(r, |amp, filter| filter.process(amp));
+            self.sum += (fl * fl + fr * fr) as f64;
+            self.count += 1;
+
+            let peak = l.abs().max(r.abs());
+            self.block_peak = self.block_peak.max(peak);
+            self.block.push(peak);
+
+            if self.count >= block_len.max(1) {
+                self.finish_block();
+            }
+        }
+        self.next = end + start;
+        self.filters = Some(filters);
+    }
+
+    fn finish_block(&mut self) {
+        let power = self.sum / self.count as f64;
+        self.blocks.push(power);
+        self.sum = 0.0;
+        self.count = 0;
+
+        let threshold = self.block_peak * 0.999;
+        let mut flat = 0;
+        let mut run = 0;
+        for &peak in &self.block {
+            if self.block_peak > 0.5 && peak >= threshold {
+                run += 1;
+            } else {
+                if run >= RUN {
+                    flat += run;
+                }
+                run = 0;
+            }
+        }
+        if run >= RUN {
+            flat += run;
+        }
+        self.block.clear();
+        self.block_peak = 0.0;
+
+        let recent = &self.blocks[self.blocks.len().saturating_sub(SHORT_TERM)..];
+        let short_term = to_lufs(recent.iter().sum::<f64>() / recent.len() as f64);
+        if self.history.len() == HISTORY {
+            self.history.pop_front();
+        }
+        self.history.push_back((short_term, flat >= FLAT_SAMPLES));
+    }
+
+    pub fn short_term(&self) -> Option<f32> {
+        self.history.back().map(|&(lufs, _)| lufs)
+    }
+
+    // BS.1770 gating over overlapping 400 ms blocks built from the 100 ms ones.
+    fn integrated(&self) -> Option<f32> {
+        let windows: Vec<f64> = self
+            .blocks
+            .windows(MOMENTARY)
+            .map(|window| window.iter().sum::<f64>() / MOMENTARY as f64)
+            .collect();
+        let gated_mean = |threshold: f32| {
+            let gated: Vec<f64> = windows.iter().copied().filter(|&power| to_lufs(power) > threshold).collect();
+            if gated.is_empty() {
+                None
+            } else {
+                Some(gated.iter().sum::<f64>() / gated.len() as f64)
+            }
+        };
+        let relative = to_lufs(gated_mean(-70.0)?) - 10.0;
+        gated_mean(relative.max(-70.0)).map(to_lufs)
+    }
+
+    pub fn draw(&self, ctx: &mut Context, width: f32) -> GameResult {
+        if !self.visible {
+            return Ok(());
+        }
+
+        let (top, left) = (48.0, 16.0);
+        let graph_width = width - left * 2.0;
+        let y_at = |lufs: f32| top + (lufs / FLOOR_LUFS).clamp(0.0, 1.0) * GRAPH_HEIGHT;
+        let step = graph_width / (HISTORY - 1) as f32;
+        let x_at = |idx: usize| left + graph_width - (self.history.len() - 1 - idx) as f32 * step;
+
+        let mut builder = graphics::MeshBuilder::new();
+        builder.rectangle(
+            graphics::DrawMode::fill(),
+            graphics::Rect::new(left, top, graph_width, GRAPH_HEIGHT),
+            graphics::Color::from_rgba(20, 20, 20, 200),
+        );
+        for lufs in [-12.0, -24.0, -36.0, -48.0].iter() {
+            builder.line(
+                &[[left, y_at(*lufs)], [left + graph_width, y_at(*lufs)]],
+                1.0,
+                graphics::Color::from_rgba(255, 255, 255, 24),
+            )?;
+        }
+
+        for (idx, &(_, flat)) in self.history.iter().enumerate() {
+            if flat {
+                let x = x_at(idx);
+                builder.rectangle(
+                    graphics::DrawMode::fill(),
+                    graphics::Rect::new(x - 1.0, top, 2.0, 8.0),
+                    graphics::Color::from_rgb(255, 64, 64),
+                );
+            }
+        }
+
+        let points: Vec<[f32; 2]> = self
+            .history
+            .iter()
+            .enumerate()
+            .map(|(idx, &(lufs, _))| [x_at(idx), y_at(lufs)])
+            .collect();
+        if points.len() >= 2 {
+            builder.polyline(graphics::DrawMode::stroke(1.5), &points, graphics::Color::from_rgb(128, 192, 255))?;
+        }
+
+        let integrated = self.integrated();
+        if let Some(lufs) = integrated {
+            builder.line(
+                &[[left, y_at(lufs)], [left + graph_width, y_at(lufs)]],
+                1.0,
+                graphics::Color::from_rgba(255, 220, 96, 160),
+            )?;
+        }
+
+        let mesh = builder.build(ctx)?;
+        graphics::draw(ctx, &mesh, DrawParam::default())?;
+
+        let short_term = self.short_term().unwrap_or(f32::NEG_INFINITY);
+        let label = match integrated {
+            Some(lufs) => format!("short-term {:.1} LUFS   integrated {:.1} LUFS", short_term, lufs),
+            None => format!("short-term {:.1} LUFS", short_term),
+        };
+        overlay::label(
+            ctx,
+            label,
+            14.0,
+            Anchor::TopLeft,
+            [left + 4.0, top + GRAPH_HEIGHT + 4.0],
+            graphics::Color::from_rgb(160, 160, 160),
+        )
+    }
+}
@@ -0,0 +1,119 @@
+use std::{
+    fs::OpenOptions,
+    io::{self, BufWriter, Write},
+    path::PathBuf,
+    sync::mpsc::{self, Sender},
+    thread,
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Format {
+    Tsv,
+    Csv,
+}
+
+impl Format {
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name {
+            "tsv" => Some(Format::Tsv),
+            "csv" => Some(Format::Csv),
+            _ => None,
+        }
+    }
+
+    fn separator(self) -> &'static str {
+        match self {
+            Format::Tsv => "\t",
+            Format::Csv => ",",
+        }
+    }
+}
+
+// The stream takes over stdout, and everything else printed goes to stderr
+// instead so it cannot end up in the middle of the data.
+#[cfg(unix)]
+fn take_stdout() -> Box<dyn Write + Send> {
+    use std::{fs::File, os::unix::io::FromRawFd};
+
+    let _ = io::stdout().flush();
+    unsafe {
+        let fd = libc::dup(1);
+        if fd >= 0 && libc::dup2(2, 1) >= 0 {
+            return Box::new(File::from_raw_fd(fd));
+        }
+    }
+    Box::new(io::stdout())
+}
+
+#[cfg(not(unix))]
+fn take_stdout() -> Box<dyn Write + Send> {
+    Box::new(io::stdout())
+}
+
+// What one analysed frame contributes to the stream.
+pub struct Row<'a> {
+    pub time: f32,
+    pub lufs: Option<f32>,
+    // Band centers in Hz with their level and direction.
+    pub bands: &'a [(f32, f32, f32)],
+}
+
+// Streams the analysis a line per frame, for reading live from Python or R.
+// The writing happens on its own thread, so a slow reader or a named pipe
+// nobody has opened yet never holds up drawing.
+pub struct Emitter {
+    format: Format,
+    lines: Sender<String>,
+    header: bool,
+}
+
+impl Emitter {
+    // To stdout, or to `path` (which may be a named pipe).
+    pub fn open(format: Format, path: Option<PathBuf>) -> Self {
+        let (lines, received) = mpsc::channel::<String>();
+        let stdout = if path.is_none() { Some(take_stdout()) } else { None };
+        thread::spawn(move || {
+            let out: Box<dyn Write> = match (path, stdout) {
+                // Opening a named pipe waits for a reader, hence here.
+                (Some(path), _) => match OpenOptions::new().write(true).create(true).truncate(true).open(&path) {
+                    Ok(file) => Box::new(file),
+                    Err(err) => {
+                        eprintln!("Cannot write to {}: {}", path.display(), err);
+                        return;
+                    }
+                },
+                (None, Some(stdout)) => stdout,
+                (None, None) => return,
+            };
+            let mut out = BufWriter::new(out);
+            for line in received {
+                if writeln!(out, "{}", line).and_then(|_| out.flush()).is_err() {
+                    break;
+                }
+            }
+        });
+        Emitter { format, lines, header: false }
+    }
+
+    pub fn emit(&mut self, row: &Row) {
+        let separator = self.format.separator();
+        if !self.header {
+            self.header = true;
+            let mut columns = vec!["timestamp".to_string(), "time".to_string(), "lufs".to_string()];
+            columns.extend(row.bands.iter().map(|(hz, _, _)| format!("energy_{:.0}", hz)));
+            columns.extend(row.bands.iter().map(|(hz, _, _)| format!("pan_{:.0}", hz)));
+            let _ = self.lines.send(columns.join(separator));
+        }
+
+        let timestamp = SystemTime::now().duration_since(UNIX_EPOCH).map_or(0.0, |time| time.as_secs_f64());
+        let mut fields = vec![
+            format!("{:.3}", timestamp),
+            format!("{:.3}", row.time),
+            row.lufs.map_or(String::new(), |lufs| format!("{:.2}", lufs)),
+        ];
+        fields.extend(row.bands.iter().map(|(_, amp, _)| format!("{:.4}", amp)));
+        fields.extend(row.bands.iter().map(|(_, _, dir)| format!("{:.4}", dir)));
+        let _ = self.lines.send(fields.join(separator));
+    }
+}
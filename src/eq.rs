@@ -0,0 +1,162 @@
+use crate::{dsp::Biquad, overlay::{self, Anchor}};
+use ggez::{
+    graphics::{self, DrawParam},
+    Context, GameResult,
+};
+
+const BANDS: [f32; 10] = [
+    31.0, 63.0, 125.0, 250.0, 500.0, 1000.0, 2000.0, 4000.0, 8000.0, 16000.0,
+];
+const MAX_GAIN: f32 = 12.0;
+const LOW: f32 = 20.0;
+const HIGH: f32 = 20000.0;
+
+pub struct Equalizer {
+    gains: [f32; BANDS.len()],
+    pub editing: bool,
+    pub playback: bool,
+    dragging: bool,
+    changed: bool,
+}
+
+impl Equalizer {
+    pub fn new() -> Self {
+        Equalizer {
+            gains: [0.0; BANDS.len()],
+            editing: false,
+            playback: false,
+            dragging: false,
+            changed: false,
+        }
+    }
+
+    pub fn gain_db(&self, freq: f32) -> f32 {
+        let freq = freq.max(BANDS[0]).min(BANDS[BANDS.len() - 1]);
+        let idx = BANDS
+            .iter()
+            .rposition(|&band| band <= freq)
+            .unwrap_or(0)
+            .min(BANDS.len() - 2);
+
+        let t = (freq / BANDS[idx]).ln() / (BANDS[idx + 1] / BANDS[idx]).ln();
+        self.gains[idx] + (self.gains[idx + 1] - self.gains[idx]) * t
+    }
+
+    pub fn gains(&self) -> &[f32] {
+        &self.gains
+    }
+
+    pub fn set_gains(&mut self, gains: &[f32]) {
+        for (gain, &saved) in self.gains.iter_mut().zip(gains) {
+            *gain = saved.clamp(-MAX_GAIN, MAX_GAIN);
+        }
+    }
+
+    pub fn gain(&self, freq: f32) -> f32 {
+        10.0f32.powf(self.gain_db(freq) / 20.0)
+    }
+
+    pub fn filters(&self, sample_rate: u32) -> Vec<Biquad> {
+        BANDS
+            .iter()
+            .zip(self.gains.iter())
+            .filter(|(&freq, &gain)| gain.abs() > 0.1 && freq < sample_rate as f32 / 2.0)
+            .map(|(&freq, &gain)| Biquad::peaking(freq, gain, 1.41, sample_rate as f32))
+            .collect()
+    }
+
+    fn x_at(freq: f32, width: f32) -> f32 {
+        (freq / LOW).ln() / (HIGH / LOW).ln() * width
+    }
+
+    fn y_at(gain: f32, height: f32) -> f32 {
+        height / 2.0 - gain / MAX_GAIN * height / 4.0
+    }
+
+    pub fn begin_edit(&mut self, x: f32, y: f32, width: f32, height: f32) {
+        self.dragging = true;
+        self.edit_to(x, y, width, height);
+    }
+
+    pub fn edit_to(&mut self, x: f32, y: f32, width: f32, height: f32) {
+        if !self.dragging {
+            return;
+        }
+
+        let idx = BANDS
+            .iter()
+            .enumerate()
+            .min_by(|(_, a), (_, b)| {
+                (Self::x_at(**a, width) - x)
+                    .abs()
+                    .total_cmp(&(Self::x_at(**b, width) - x).abs())
+            })
+            .map(|(idx, _)| idx)
+            .unwrap_or(0);
+
+        let gain = ((height / 2.0 - y) / (height / 4.0) * MAX_GAIN).clamp(-MAX_GAIN, MAX_GAIN);
+        if (self.gains[idx] - gain).abs() > f32::EPSILON {
+            self.gains[idx] = gain;
+            self.changed = true;
+        }
+    }
+
+    pub fn end_edit(&mut self) -> bool {
+        self.dragging = false;
+        let changed = self.changed;
+        self.changed = false;
+        changed
+    }
+
+    pub fn draw(&self, ctx: &mut Context, width: f32, height: f32) -> GameResult {
+        if !self.editing {
+            return Ok(());
+        }
+
+        let zero = Self::y_at(0.0, height);
+        let line = graphics::Mesh::new_line(
+            ctx,
+            &[[0.0, zero], [width, zero]],
+            1.0,
+            graphics::Color::from_rgba(255, 255, 255, 48),
+        )?;
+        graphics::draw(ctx, &line, DrawParam::default())?;
+
+        let points: Vec<_> = (0..=128)
+            .map(|idx| {
+                let x = width * idx as f32 / 128.0;
+                let freq = LOW * (HIGH / LOW).powf(idx as f32 / 128.0);
+                [x, Self::y_at(self.gain_db(freq), height)]
+            })
+            .collect();
+        let curve = graphics::Mesh::new_line(
+            ctx,
+            &points,
+            2.0,
+            graphics::Color::from_rgba(255, 200, 64, 200),
+        )?;
+        graphics::draw(ctx, &curve, DrawParam::default())?;
+
+        for (&freq, &gain) in BANDS.iter().zip(self.gains.iter()) {
+            let handle = graphics::Mesh::new_circle(
+                ctx,
+                graphics::DrawMode::fill(),
+                [Self::x_at(freq, width), Self::y_at(gain, height)],
+                5.0,
+                0.5,
+                graphics::Color::from_rgb(255, 200, 64),
+            )?;
+            graphics::draw(ctx, &handle, DrawParam::default())?;
+        }
+
+        let label = format!("EQ  playback {}", if self.playback { "on" } else { "off" });
+        overlay::label(
+            ctx,
+            label,
+            18.0,
+            Anchor::TopLeft,
+            [16.0, height / 4.0 - 24.0],
+            graphics::Color::from_rgb(255, 200, 64),
+        )
+    }
+}
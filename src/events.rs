@@ -0,0 +1,104 @@
+use crate::{dynamics::Dynamics, tempo::TempoCurve};
+use std::path::PathBuf;
+
+// How often the short-term loudness is announced, and thresholds checked.
+const LOUDNESS_SECS: f32 = 0.25;
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Event {
+    // Strength is the bass level the beat was found at, may exceed 1.
+    Beat { strength: f32 },
+    // The tempo changed at `time` seconds into the track.
+    Section { time: f32, bpm: Option<f32> },
+    Loudness { lufs: f32 },
+    // The short-term loudness crossed one of the configured thresholds.
+    Threshold { lufs: f32, rising: bool },
+    Track { path: PathBuf },
+}
+
+pub trait Subscriber {
+    fn event(&mut self, event: &Event);
+}
+
+// Analysis publishes here once, and outputs subscribe instead of each
+// reading the state they care about every frame.
+#[derive(Default)]
+pub struct Bus {
+    queue: Vec<Event>,
+    subscribers: Vec<Box<dyn Subscriber>>,
+}
+
+impl Bus {
+    pub fn subscribe(&mut self, subscriber: Box<dyn Subscriber>) {
+        self.subscribers.push(subscriber);
+    }
+
+    pub fn publish(&mut self, event: Event) {
+        self.queue.push(event);
+    }
+
+    // Hands what was published since the last call to every subscriber, and
+    // back to the caller for what the main state handles itself.
+    pub fn dispatch(&mut self) -> Vec<Event> {
+        let events = std::mem::take(&mut self.queue);
+        for subscriber in &mut self.subscribers {
+            for event in &events {
+                subscriber.event(event);
+            }
+        }
+        events
+    }
+}
+
+// Turns the running analysis into section, loudness and threshold events.
+pub struct Detector {
+    thresholds: Vec<f32>,
+    until_loudness: f32,
+    lufs: Option<f32>,
+    section: Option<f32>,
+}
+
+impl Detector {
+    pub fn new(thresholds: &[f32]) -> Self {
+        Detector {
+            thresholds: thresholds.to_vec(),
+            until_loudness: 0.0,
+            lufs: None,
+            section: None,
+        }
+    }
+
+    // For a new track, whose sections and loudness start over.
+    pub fn clear(&mut self) {
+        self.lufs = None;
+        self.section = None;
+    }
+
+    pub fn update(&mut self, bus: &mut Bus, dynamics: &Dynamics, tempo: &TempoCurve, delta: f32) {
+        self.until_loudness -= delta;
+        if self.until_loudness > 0.0 {
+            return;
+        }
+        self.until_loudness = LOUDNESS_SECS;
+
+        if let Some(lufs) = dynamics.short_term() {
+            bus.publish(Event::Loudness { lufs });
+            if let Some(last) = self.lufs {
+                for &threshold in &self.thresholds {
+                    if (last < threshold) != (lufs < threshold) {
+                        bus.publish(Event::Threshold { lufs: threshold, rising: lufs >= threshold });
+                    }
+                }
+            }
+            self.lufs = Some(lufs);
+        }
+        // Finding sections walks the whole tempo curve, so it rides along here.
+        let latest = tempo.changes().last().copied();
+        if latest > self.section {
+            self.section = latest;
+            if let Some(time) = latest {
+                bus.publish(Event::Section { time, bpm: tempo.bpm() });
+            }
+        }
+    }
+}
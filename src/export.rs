@@ -0,0 +1,38 @@
+use ggez::{error::GameError, graphics, Context, GameResult};
+use std::{fs::File, io::BufWriter, path};
+
+pub fn write_png<P>(path: P, width: u32, height: u32, rgba: &[u8]) -> GameResult
+where
+    P: AsRef<path::Path>,
+{
+    let file = File::create(path)?;
+    image::png::PNGEncoder::new(BufWriter::new(file))
+        .encode(rgba, width, height, image::ColorType::RGBA(8))
+        .map_err(|err| GameError::RenderError(err.to_string()))
+}
+
+// A PNG or JPEG file as a texture.
+pub fn read_image(ctx: &mut Context, path: &path::Path) -> GameResult<graphics::Image> {
+    let image = image::open(path)
+        .map_err(|err| GameError::ResourceLoadError(format!("{}: {}", path.display(), err)))?
+        .to_rgba();
+    let (width, height) = image.dimensions();
+    graphics::Image::from_rgba8(ctx, width as u16, height as u16, &image.into_raw())
+}
+
+pub fn screenshot<P>(ctx: &mut Context, path: P) -> GameResult
+where
+    P: AsRef<path::Path>,
+{
+    let image = graphics::screenshot(ctx)?;
+    let (width, height) = (image.width() as usize, image.height() as usize);
+    let pixels = image.to_rgba8(ctx)?;
+
+    // Render targets are read back bottom row first.
+    let mut flipped = Vec::with_capacity(pixels.len());
+    for row in pixels.chunks(width * 4).rev() {
+        flipped.extend_from_slice(row);
+    }
+
+    write_png(path, width as u32, height as u32, &flipped)
+}
@@ -0,0 +1,147 @@
+use crate::{overlay::{self, Anchor}, view::AXIS_HEIGHT};
+use ggez::{
+    graphics::{self, DrawParam},
+    Context, GameResult,
+};
+use rustfft::num_complex::Complex;
+
+const ROLLOFF: f32 = 0.85;
+const GAUGE_WIDTH: f32 = 120.0;
+
+#[derive(Debug, Clone, Copy, Default)]
+pub struct Features {
+    pub centroid: f32,
+    pub flatness: f32,
+    pub rolloff: f32,
+    pub zcr: f32,
+}
+
+impl Features {
+    // Computed on the mid (L+R) spectrum and signal of one analysis window.
+    pub fn compute(
+        left_fft: &[Complex<f32>],
+        right_fft: &[Complex<f32>],
+        left: &[f32],
+        right: &[f32],
+        bin_hz: f32,
+    ) -> Self {
+        let mags: Vec<f32> = left_fft
+            .iter()
+            .zip(right_fft)
+            .take(left_fft.len() / 2)
+            .skip(1)
+            .map(|(l, r)| (l.norm() + r.norm()) / 2.0)
+            .collect();
+        let total: f32 = mags.iter().sum();
+        if total <= 1e-9 {
+            return Features::default();
+        }
+
+        let centroid = mags.iter().enumerate().map(|(idx, mag)| (idx + 1) as f32 * mag).sum::<f32>() / total * bin_hz;
+
+        let log_mean = mags.iter().map(|mag| mag.max(1e-9).ln()).sum::<f32>() / mags.len() as f32;
+        let flatness = log_mean.exp() / (total / mags.len() as f32);
+
+        let mut sum = 0.0;
+        let rolloff = mags
+            .iter()
+            .position(|mag| {
+                sum += mag;
+                sum >= total * ROLLOFF
+            })
+            .map_or(0.0, |idx| (idx + 1) as f32 * bin_hz);
+
+        let mid: Vec<f32> = left.iter().zip(right).map(|(l, r)| l + r).collect();
+        let crossings = mid.windows(2).filter(|pair| (pair[0] >= 0.0) != (pair[1] >= 0.0)).count();
+        let zcr = crossings as f32 / mid.len().saturating_sub(1).max(1) as f32;
+
+        Features {
+            centroid,
+            flatness,
+            rolloff,
+            zcr,
+        }
+    }
+}
+
+pub struct Hud {
+    pub enabled: bool,
+    shown: Features,
+}
+
+impl Hud {
+    pub fn new() -> Self {
+        Hud {
+            enabled: false,
+            shown: Features::default(),
+        }
+    }
+
+    pub fn update(&mut self, features: Features) {
+        let ease = |shown: &mut f32, value: f32| *shown += (value - *shown) * 0.2;
+        ease(&mut self.shown.centroid, features.centroid);
+        ease(&mut self.shown.flatness, features.flatness);
+        ease(&mut self.shown.rolloff, features.rolloff);
+        ease(&mut self.shown.zcr, features.zcr);
+    }
+
+    pub fn draw(&self, ctx: &mut Context, height: f32, nyquist: f32) -> GameResult {
+        if !self.enabled {
+            return Ok(());
+        }
+
+        // Frequencies are shown on a log scale from 20 Hz up to Nyquist.
+        let log = |hz: f32| ((hz.max(20.0) / 20.0).ln() / (nyquist.max(40.0) / 20.0).ln()).clamp(0.0, 1.0);
+        let gauges = [
+            ("centroid", format!("{:.0} Hz", self.shown.centroid), log(self.shown.centroid)),
+            ("flatness", format!("{:.3}", self.shown.flatness), self.shown.flatness.clamp(0.0, 1.0)),
+            ("rolloff", format!("{:.0} Hz", self.shown.rolloff), log(self.shown.rolloff)),
+            ("zcr", format!("{:.3}", self.shown.zcr), (self.shown.zcr * 2.0).clamp(0.0, 1.0)),
+        ];
+
+        let top = height - AXIS_HEIGHT - gauges.len() as f32 * 20.0 - 12.0;
+        for (row, (name, value, fraction)) in gauges.iter().enumerate() {
+            let y = top + row as f32 * 20.0;
+            overlay::label(
+                ctx,
+                format!("{:<9}{}", name, value),
+                14.0,
+                Anchor::TopLeft,
+                [16.0, y],
+                graphics::Color::from_rgb(160, 160, 160),
+            )?;
+
+            let track = graphics::Rect::new(160.0, y + 3.0, GAUGE_WIDTH, 8.0);
+            let mesh = graphics::Mesh::new_rectangle(
+                ctx,
+                graphics::DrawMode::stroke(1.0),
+                track,
+                graphics::Color::from_rgba(255, 255, 255, 40),
+            )?;
+            graphics::draw(ctx, &mesh, DrawParam::default())?;
+
+            if *fraction > 0.0 {
+                let fill = graphics::Rect::new(160.0, y + 3.0, GAUGE_WIDTH * fraction, 8.0);
+                let mesh = graphics::Mesh::new_rectangle(
+                    ctx,
+                    graphics::DrawMode::fill(),
+                    fill,
+                    graphics::Color::from_rgba(128, 192, 255, 160),
+                )?;
+                graphics::draw(ctx, &mesh, DrawParam::default())?;
+            }
+        }
+        Ok(())
+    }
+}
+
+pub fn write_csv<P: AsRef<std::path::Path>>(path: P, features: &Features) -> GameResult {
+    std::fs::write(
+        path,
+        format!(
+            "centroid_hz,flatness,rolloff_hz,zcr\n{},{},{},{}\n",
+            features.centroid, features.flatness, features.rolloff, features.zcr
+        ),
+    )?;
+    Ok(())
+}
@@ -0,0 +1,69 @@
+use ggez::{graphics, Context, GameResult};
+
+// Turns the window into a floating overlay: no decorations, always on top,
+// clicks going through to whatever is underneath and a whole-window opacity.
+// The window is created without an alpha channel, so the background only lets
+// the desktop through as far as `opacity` does; key it out for anything more.
+pub fn apply(ctx: &Context, opacity: f32) -> GameResult {
+    let window = graphics::window(ctx);
+    window.set_decorations(false);
+    window.set_always_on_top(true);
+    platform::pass_through(window, opacity.clamp(0.0, 1.0))
+}
+
+#[cfg(target_os = "linux")]
+mod platform {
+    use ggez::{error::GameError, GameResult};
+    use std::{ffi::CString, os::raw::c_ulong, ptr};
+    use winit::{os::unix::WindowExt, Window};
+    use x11_dl::{xfixes, xlib};
+
+    // ShapeInput from the X Shape extension.
+    const SHAPE_INPUT: i32 = 2;
+
+    pub fn pass_through(window: &Window, opacity: f32) -> GameResult {
+        let (display, window) = match (window.get_xlib_display(), window.get_xlib_window()) {
+            (Some(display), Some(window)) => (display as *mut xlib::Display, window),
+            _ => return Err(GameError::WindowError("The overlay mode needs an X11 window".to_string())),
+        };
+        let open_error = |err: x11_dl::error::OpenError| GameError::WindowError(err.to_string());
+        let xlib = xlib::Xlib::open().map_err(open_error)?;
+        let fixes = xfixes::Xlib::open().map_err(open_error)?;
+        let name = CString::new("_NET_WM_WINDOW_OPACITY").expect("Atom name");
+        // Compositors read the opacity as a fraction of 0xffffffff.
+        let value = (opacity as f64 * u32::MAX as f64) as c_ulong;
+
+        unsafe {
+            // An empty input region hands every click to the window below.
+            let region = (fixes.XFixesCreateRegion)(display, ptr::null_mut(), 0);
+            (fixes.XFixesSetWindowShapeRegion)(display, window, SHAPE_INPUT, 0, 0, region);
+            (fixes.XFixesDestroyRegion)(display, region);
+
+            let atom = (xlib.XInternAtom)(display, name.as_ptr(), xlib::False);
+            (xlib.XChangeProperty)(
+                display,
+                window,
+                atom,
+                xlib::XA_CARDINAL,
+                32,
+                xlib::PropModeReplace,
+                &value as *const c_ulong as *const u8,
+                1,
+            );
+            (xlib.XFlush)(display);
+        }
+        Ok(())
+    }
+}
+
+#[cfg(not(target_os = "linux"))]
+mod platform {
+    use ggez::{error::GameError, GameResult};
+    use winit::Window;
+
+    pub fn pass_through(_window: &Window, _opacity: f32) -> GameResult {
+        Err(GameError::WindowError(
+            "Click-through and opacity are only supported on X11 so far".to_string(),
+        ))
+    }
+}
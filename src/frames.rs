@@ -0,0 +1,165 @@
+use crate::export;
+use ggez::{Context, GameResult};
+use std::{
+    fs::{self, File},
+    io::{self, BufWriter, Write},
+    path::{Path, PathBuf},
+};
+
+// A draft analyses and draws this many times fewer frames, in a window this
+// many times smaller each way, at this quality level.
+pub const DRAFT_HOP: f32 = 2.0;
+pub const DRAFT_SCALE: f32 = 2.0;
+pub const DRAFT_QUALITY: u32 = 2;
+
+// The analysis behind each frame of a track, as one JSON array that is
+// closed when the track's render ends.
+struct Sidecar {
+    file: BufWriter<File>,
+    first: bool,
+}
+
+impl Sidecar {
+    fn create(path: &Path) -> GameResult<Self> {
+        let mut file = BufWriter::new(File::create(path)?);
+        file.write_all(b"[")?;
+        Ok(Sidecar { file, first: true })
+    }
+
+    fn write(&mut self, entry: &serde_json::Value) -> GameResult {
+        let separator = if self.first { "\n" } else { ",\n" };
+        self.first = false;
+        write!(self.file, "{}{}", separator, entry)?;
+        Ok(())
+    }
+}
+
+impl Drop for Sidecar {
+    fn drop(&mut self) {
+        let _ = self.file.write_all(b"\n]\n");
+        let _ = self.file.flush();
+    }
+}
+
+// Renders tracks frame by frame at a fixed rate instead of in real time,
+// writing each frame out as a numbered PNG for compositing and each track to
+// a directory of its own.
+pub struct Sequence {
+    // `{name}` and `{index}` stand for the track's file name and number.
+    template: String,
+    rate: f32,
+    dir: PathBuf,
+    frame: u64,
+    track: (usize, usize),
+    name: String,
+    percent: Option<u32>,
+    // Seconds of title card before and after each track.
+    intro: f32,
+    outro: f32,
+    data: bool,
+    sidecar: Option<Sidecar>,
+}
+
+impl Sequence {
+    pub fn new(template: String, rate: f32) -> Self {
+        Sequence {
+            template,
+            rate: rate.max(1.0),
+            dir: PathBuf::new(),
+            frame: 0,
+            track: (0, 0),
+            name: String::new(),
+            percent: None,
+            intro: 0.0,
+            outro: 0.0,
+            data: false,
+            sidecar: None,
+        }
+    }
+
+    pub fn set_cards(&mut self, intro: f32, outro: f32) {
+        self.intro = intro;
+        self.outro = outro;
+    }
+
+    // Writes `frames.json` next to each track's frames.
+    pub fn set_data(&mut self, enabled: bool) {
+        self.data = enabled;
+    }
+
+    // Starts over with track `index` of `count`, from `path`.
+    pub fn start(&mut self, path: &Path, index: usize, count: usize) -> GameResult {
+        let name = path.file_stem().map_or("track".into(), |stem| stem.to_string_lossy().into_owned());
+        let number = (index + 1).to_string();
+        self.dir = if self.template.contains('{') {
+            PathBuf::from(self.template.replace("{name}", &name).replace("{index}", &number))
+        } else if count > 1 {
+            // Several tracks in one directory would overwrite each other.
+            Path::new(&self.template).join(&name)
+        } else {
+            PathBuf::from(&self.template)
+        };
+        fs::create_dir_all(&self.dir)?;
+        // The last track's file is finished before the next one starts.
+        self.sidecar = None;
+        if self.data {
+            self.sidecar = Some(Sidecar::create(&self.dir.join("frames.json"))?);
+        }
+        self.frame = 0;
+        self.track = (index + 1, count);
+        self.name = name;
+        self.percent = None;
+        Ok(())
+    }
+
+    pub fn delta(&self) -> f32 {
+        1.0 / self.rate
+    }
+
+    // Seconds into the render of the frame being drawn, cards included.
+    pub fn time(&self) -> f32 {
+        self.frame as f32 / self.rate
+    }
+
+    // Seconds into the track itself, held at either end over the cards.
+    pub fn track_time(&self, duration: f32) -> f32 {
+        (self.time() - self.intro).clamp(0.0, duration)
+    }
+
+    pub fn finished(&self, duration: f32) -> bool {
+        self.time() >= self.intro + duration + self.outro
+    }
+
+    pub fn records(&self) -> bool {
+        self.sidecar.is_some()
+    }
+
+    // Adds what the frame about to be drawn was made from.
+    pub fn record(&mut self, mut entry: serde_json::Value, duration: f32) -> GameResult {
+        entry["frame"] = self.frame.into();
+        entry["file"] = format!("frame-{:06}.png", self.frame).into();
+        entry["time"] = self.time().into();
+        entry["track_time"] = self.track_time(duration).into();
+        match self.sidecar {
+            Some(ref mut sidecar) => sidecar.write(&entry),
+            None => Ok(()),
+        }
+    }
+
+    pub fn capture(&mut self, ctx: &mut Context, duration: f32) -> GameResult {
+        export::screenshot(ctx, self.dir.join(format!("frame-{:06}.png", self.frame)))?;
+        self.frame += 1;
+
+        let total = self.intro + duration + self.outro;
+        let percent = (self.time() / total.max(f32::EPSILON) * 100.0).min(100.0) as u32;
+        if self.percent != Some(percent) {
+            self.percent = Some(percent);
+            print!("\rRendering {}/{} {}: {}%", self.track.0, self.track.1, self.name, percent);
+            let _ = io::stdout().flush();
+            if percent == 100 {
+                println!(" -> {}", self.dir.display());
+            }
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,180 @@
+use std::f32::consts::PI;
+
+pub const SAMPLE_RATE: u32 = 44100;
+// Sixteen bars at 120 bpm, which loop seamlessly.
+pub const DEMO_SECS: f32 = 32.0;
+// A minor, F, C and G, a bar each, and the bass note under each chord.
+const CHORDS: [[f32; 3]; 4] = [[57.0, 60.0, 64.0], [57.0, 60.0, 65.0], [55.0, 60.0, 64.0], [55.0, 59.0, 62.0]];
+const ROOTS: [f32; 4] = [45.0, 41.0, 48.0, 43.0];
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Signal {
+    Sweep,
+    PinkNoise,
+    PannedTones,
+    Clicks,
+    Demo,
+}
+
+impl Signal {
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name {
+            "sweep" => Some(Signal::Sweep),
+            "pink" => Some(Signal::PinkNoise),
+            "pan" => Some(Signal::PannedTones),
+            "click" => Some(Signal::Clicks),
+            "demo" => Some(Signal::Demo),
+            _ => None,
+        }
+    }
+
+    pub fn names() -> &'static str {
+        "sweep, pink, pan, click, demo"
+    }
+}
+
+fn pan(amp: f32, pos: f32) -> (f32, f32) {
+    let angle = (pos.clamp(-1.0, 1.0) + 1.0) * PI / 4.0;
+    (amp * angle.cos(), amp * angle.sin())
+}
+
+fn note_hz(note: f32) -> f32 {
+    440.0 * 2.0f32.powf((note - 69.0) / 12.0)
+}
+
+// A generated groove with its parts spread across the stage: kick in the
+// middle, hats to the right, claps a little left, bass, an arpeggio sweeping
+// from side to side and a pad drifting the other way.
+fn demo(len: usize, rate: f32) -> Vec<(f32, f32)> {
+    let mut seed = 0x9e37_79b9u32;
+    (0..len)
+        .map(|idx| {
+            seed ^= seed << 13;
+            seed ^= seed >> 17;
+            seed ^= seed << 5;
+            let noise = seed as f32 / u32::MAX as f32 * 2.0 - 1.0;
+
+            let t = idx as f32 / rate;
+            let beats = t * 2.0;
+            let bar = (beats / 4.0) as usize % CHORDS.len();
+            let since = |step: f32| (beats / step).fract() * step * 0.5;
+            let (chord, root) = (CHORDS[bar], ROOTS[bar]);
+
+            // Kick on every beat, its pitch falling from 150 Hz to 50 Hz.
+            let kick_t = since(1.0);
+            let kick_phase = 2.0 * PI * (50.0 * kick_t + 100.0 * 0.03 * (1.0 - (-kick_t / 0.03).exp()));
+            let kick = 0.6 * kick_phase.sin() * (-kick_t / 0.15).exp();
+            let (mut left, mut right) = (kick, kick);
+
+            let offbeat = (beats + 0.5).fract() * 0.5;
+            let (l, r) = pan(0.12 * noise * (-offbeat / 0.025).exp(), 0.6);
+            left += l;
+            right += r;
+            if beats as usize % 2 == 1 {
+                let (l, r) = pan(0.25 * noise * (-kick_t / 0.08).exp(), -0.2);
+                left += l;
+                right += r;
+            }
+
+            let bass_t = since(0.5);
+            let bass_hz = note_hz(root);
+            let bass = (1..=3)
+                .map(|harmonic| (2.0 * PI * bass_hz * harmonic as f32 * t).sin() / harmonic as f32)
+                .sum::<f32>();
+            let (l, r) = pan(0.15 * bass * (-bass_t / 0.2).exp(), -0.3);
+            left += l;
+            right += r;
+
+            let step = (beats * 4.0) as usize;
+            let arp_hz = note_hz(chord[step % 3] + 12.0 * (step / 3 % 2) as f32 + 12.0);
+            let arp_t = since(0.25);
+            let sweep = (2.0 * PI * t / 8.0).sin() * 0.9;
+            let (l, r) = pan(0.1 * (2.0 * PI * arp_hz * t).sin() * (-arp_t / 0.06).exp(), sweep);
+            left += l;
+            right += r;
+
+            for &note in &chord {
+                let (l, r) = pan(0.04 * (2.0 * PI * note_hz(note) * t).sin(), -sweep * 0.7);
+                left += l;
+                right += r;
+            }
+            // Headroom for the parts peaking together.
+            (left * 0.65, right * 0.65)
+        })
+        .collect()
+}
+
+pub fn generate(signal: Signal, sample_rate: u32, secs: f32) -> (Vec<f32>, Vec<f32>) {
+    let len = (secs * sample_rate as f32) as usize;
+    let rate = sample_rate as f32;
+
+    let samples: Vec<(f32, f32)> = match signal {
+        Signal::Sweep => {
+            let (low, high) = (20.0f32, 20000.0f32.min(rate / 2.0));
+            let k = (high / low).ln();
+            (0..len)
+                .map(|idx| {
+                    let t = idx as f32 / rate;
+                    let phase = 2.0 * PI * low * secs / k * ((t / secs * k).exp() - 1.0);
+                    let amp = 0.5 * phase.sin();
+                    (amp, amp)
+                })
+                .collect()
+        }
+        Signal::PinkNoise => {
+            let mut seed = 0x2545_f491u32;
+            let mut b = [0.0f32; 7];
+            (0..len)
+                .map(|_| {
+                    seed ^= seed << 13;
+                    seed ^= seed >> 17;
+                    seed ^= seed << 5;
+                    let white = seed as f32 / u32::MAX as f32 * 2.0 - 1.0;
+
+                    b[0] = 0.99886 * b[0] + white * 0.055_517_9;
+                    b[1] = 0.99332 * b[1] + white * 0.075_075_9;
+                    b[2] = 0.96900 * b[2] + white * 0.153_852;
+                    b[3] = 0.86650 * b[3] + white * 0.310_485_6;
+                    b[4] = 0.55000 * b[4] + white * 0.532_952_2;
+                    b[5] = -0.7616 * b[5] - white * 0.016_898;
+                    let pink = b.iter().sum::<f32>() + white * 0.5362;
+                    b[6] = white * 0.115_926;
+
+                    let amp = pink * 0.05;
+                    (amp, amp)
+                })
+                .collect()
+        }
+        Signal::PannedTones => {
+            let tones = [(200.0, -1.0), (500.0, -0.5), (1000.0, 0.0), (2500.0, 0.5), (6000.0, 1.0)];
+            (0..len)
+                .map(|idx| {
+                    let t = idx as f32 / rate;
+                    tones.iter().fold((0.0, 0.0), |(l, r), &(freq, pos)| {
+                        let (tl, tr) = pan(0.15 * (2.0 * PI * freq * t).sin(), pos);
+                        (l + tl, r + tr)
+                    })
+                })
+                .collect()
+        }
+        Signal::Clicks => {
+            let beat = (rate * 0.5) as usize;
+            let click = (rate * 0.01) as usize;
+            (0..len)
+                .map(|idx| {
+                    let within = idx % beat;
+                    if within >= click {
+                        return (0.0, 0.0);
+                    }
+                    let accent = if (idx / beat).is_multiple_of(4) { 1.0 } else { 0.6 };
+                    let decay = 1.0 - within as f32 / click as f32;
+                    let amp = accent * decay * (2.0 * PI * 2000.0 * within as f32 / rate).sin();
+                    (amp * 0.8, amp * 0.8)
+                })
+                .collect()
+        }
+        Signal::Demo => demo(len, rate),
+    };
+
+    samples.into_iter().unzip()
+}
@@ -0,0 +1,224 @@
+use crate::DirectionalSource;
+use ggez::GameResult;
+use serde::{Deserialize, Serialize};
+
+// How long a beat keeps its outputs on.
+const BEAT_SECS: f32 = 0.08;
+const BAND_EDGES: [f32; 5] = [20.0, 250.0, 2000.0, 6000.0, 20000.0];
+
+// What drives an output.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum Source {
+    Beat,
+    Bass,
+    LowMid,
+    HighMid,
+    Treble,
+}
+
+fn half() -> f32 {
+    0.5
+}
+
+fn period() -> u32 {
+    // 1 kHz, fine for LEDs and small motors.
+    1_000_000
+}
+
+// One `[[outputs]]` table in the config.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "lowercase")]
+pub enum Output {
+    // A GPIO line, switched on while its source is above `threshold`.
+    Pin {
+        pin: u32,
+        source: Source,
+        #[serde(default = "half")]
+        threshold: f32,
+    },
+    // A PWM channel whose duty cycle follows its source.
+    Pwm {
+        chip: u32,
+        channel: u32,
+        source: Source,
+        #[serde(default = "period")]
+        period_ns: u32,
+    },
+}
+
+impl Output {
+    fn source(&self) -> Source {
+        match *self {
+            Output::Pin { source, .. } | Output::Pwm { source, .. } => source,
+        }
+    }
+}
+
+// Band energies and beats in 0..1, for the outputs to pick from.
+pub struct Levels {
+    bands: [f32; 4],
+    beat: f32,
+}
+
+impl Levels {
+    pub fn new() -> Self {
+        Levels { bands: [0.0; 4], beat: 0.0 }
+    }
+
+    pub fn update(&mut self, directions: &[DirectionalSource], bin_hz: f32, gain: f32, beat: bool, delta: f32) {
+        for (band, edge) in self.bands.iter_mut().zip(BAND_EDGES.windows(2)) {
+            let first = ((edge[0] / bin_hz) as usize).max(1);
+            let last = ((edge[1] / bin_hz) as usize).clamp(first + 1, directions.len().max(first + 1));
+            *band = directions.get(first..last).map_or(0.0, |sources| {
+                let amp = sources.iter().map(|source| source.amp).sum::<f32>() / sources.len() as f32;
+                (amp * gain * 0.04).min(1.0)
+            });
+        }
+        self.beat = if beat { BEAT_SECS } else { (self.beat - delta).max(0.0) };
+    }
+
+    fn get(&self, source: Source) -> f32 {
+        match source {
+            Source::Beat => if self.beat > 0.0 { 1.0 } else { 0.0 },
+            Source::Bass => self.bands[0],
+            Source::LowMid => self.bands[1],
+            Source::HighMid => self.bands[2],
+            Source::Treble => self.bands[3],
+        }
+    }
+}
+
+// Drives GPIO lines and PWM channels through the kernel's sysfs interface, as
+// on a Raspberry Pi with `dtoverlay=pwm-2chan` for the PWM pins.
+pub struct Gpio {
+    lines: Vec<(Output, platform::Line, u32)>,
+}
+
+impl Gpio {
+    pub fn open(outputs: &[Output]) -> GameResult<Self> {
+        let lines = outputs
+            .iter()
+            .map(|&output| Ok((output, platform::Line::open(output)?, u32::MAX)))
+            .collect::<GameResult<_>>()?;
+        Ok(Gpio { lines })
+    }
+
+    pub fn update(&mut self, levels: &Levels) {
+        for (output, line, last) in &mut self.lines {
+            let level = levels.get(output.source()).clamp(0.0, 1.0);
+            let value = match *output {
+                Output::Pin { threshold, .. } => u32::from(level > threshold),
+                Output::Pwm { period_ns, .. } => (level * period_ns as f32) as u32,
+            };
+            // Writing the same value again is a wasted syscall per frame.
+            if value != *last && line.write(value).is_ok() {
+                *last = value;
+            }
+        }
+    }
+}
+
+impl Drop for Gpio {
+    fn drop(&mut self) {
+        for (output, line, _) in &mut self.lines {
+            line.release(*output);
+        }
+    }
+}
+
+#[cfg(target_os = "linux")]
+mod platform {
+    use super::Output;
+    use ggez::{error::GameError, GameResult};
+    use std::{
+        fs::{self, File, OpenOptions},
+        io::{self, Seek, SeekFrom, Write},
+        thread,
+        time::Duration,
+    };
+
+    fn write(path: &str, value: impl ToString) -> GameResult {
+        fs::write(path, value.to_string())
+            .map_err(|err| GameError::ConfigError(format!("Cannot write {}: {}", path, err)))
+    }
+
+    // Exporting fails with "busy" when the line is already exported, and udev
+    // takes a moment to hand the new files to the gpio group.
+    fn export(export: &str, ready: &str, number: u32) -> GameResult {
+        let _ = fs::write(export, number.to_string());
+        for _ in 0..20 {
+            if OpenOptions::new().write(true).open(ready).is_ok() {
+                return Ok(());
+            }
+            thread::sleep(Duration::from_millis(50));
+        }
+        Err(GameError::ConfigError(format!("Cannot export {} through {}", number, export)))
+    }
+
+    pub struct Line(File);
+
+    impl Line {
+        pub fn open(output: Output) -> GameResult<Self> {
+            let value = match output {
+                Output::Pin { pin, .. } => {
+                    let dir = format!("/sys/class/gpio/gpio{}", pin);
+                    export("/sys/class/gpio/export", &format!("{}/direction", dir), pin)?;
+                    write(&format!("{}/direction", dir), "out")?;
+                    format!("{}/value", dir)
+                }
+                Output::Pwm { chip, channel, period_ns, .. } => {
+                    let chip = format!("/sys/class/pwm/pwmchip{}", chip);
+                    let dir = format!("{}/pwm{}", chip, channel);
+                    export(&format!("{}/export", chip), &format!("{}/period", dir), channel)?;
+                    write(&format!("{}/duty_cycle", dir), 0)?;
+                    write(&format!("{}/period", dir), period_ns)?;
+                    write(&format!("{}/enable", dir), 1)?;
+                    format!("{}/duty_cycle", dir)
+                }
+            };
+            let file = OpenOptions::new()
+                .write(true)
+                .open(&value)
+                .map_err(|err| GameError::ConfigError(format!("Cannot open {}: {}", value, err)))?;
+            Ok(Line(file))
+        }
+
+        pub fn write(&mut self, value: u32) -> io::Result<()> {
+            self.0.seek(SeekFrom::Start(0))?;
+            self.0.write_all(value.to_string().as_bytes())
+        }
+
+        pub fn release(&mut self, output: Output) {
+            let _ = self.write(0);
+            let _ = match output {
+                Output::Pin { pin, .. } => write("/sys/class/gpio/unexport", pin),
+                Output::Pwm { chip, channel, .. } => {
+                    let _ = write(&format!("/sys/class/pwm/pwmchip{}/pwm{}/enable", chip, channel), 0);
+                    write(&format!("/sys/class/pwm/pwmchip{}/unexport", chip), channel)
+                }
+            };
+        }
+    }
+}
+
+#[cfg(not(target_os = "linux"))]
+mod platform {
+    use super::Output;
+    use ggez::{error::GameError, GameResult};
+    use std::io;
+
+    pub struct Line;
+
+    impl Line {
+        pub fn open(_output: Output) -> GameResult<Self> {
+            Err(GameError::ConfigError("GPIO and PWM outputs need Linux sysfs".to_string()))
+        }
+
+        pub fn write(&mut self, _value: u32) -> io::Result<()> {
+            Ok(())
+        }
+
+        pub fn release(&mut self, _output: Output) {}
+    }
+}
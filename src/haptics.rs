@@ -0,0 +1,76 @@
+use ggez::input::gamepad::gilrs::{
+    ff::{BaseEffect, BaseEffectType, Effect, EffectBuilder, Repeat, Replay, Ticks},
+    Gilrs,
+};
+
+pub struct Haptics {
+    gilrs: Option<Gilrs>,
+    effect: Option<Effect>,
+    pub enabled: bool,
+}
+
+impl Haptics {
+    pub fn new() -> Self {
+        Haptics {
+            gilrs: Gilrs::new().ok(),
+            effect: None,
+            enabled: true,
+        }
+    }
+
+    pub fn update(&mut self) {
+        if let Some(ref mut gilrs) = self.gilrs {
+            while gilrs.next_event().is_some() {}
+        }
+    }
+
+    pub fn pulse(&mut self, strength: f32) {
+        if !self.enabled {
+            return;
+        }
+
+        let gilrs = match self.gilrs {
+            Some(ref mut gilrs) => gilrs,
+            None => return,
+        };
+
+        let ids: Vec<_> = gilrs
+            .gamepads()
+            .filter(|(_, gamepad)| gamepad.is_ff_supported())
+            .map(|(id, _)| id)
+            .collect();
+        if ids.is_empty() {
+            return;
+        }
+
+        let duration = Ticks::from_ms(80);
+        let magnitude = (strength.clamp(0.0, 1.0) * u16::MAX as f32) as u16;
+        let effect = EffectBuilder::new()
+            .add_effect(BaseEffect {
+                kind: BaseEffectType::Strong { magnitude },
+                scheduling: Replay {
+                    play_for: duration,
+                    ..Default::default()
+                },
+                ..Default::default()
+            })
+            .add_effect(BaseEffect {
+                kind: BaseEffectType::Weak { magnitude: magnitude / 2 },
+                scheduling: Replay {
+                    play_for: duration,
+                    ..Default::default()
+                },
+                ..Default::default()
+            })
+            .repeat(Repeat::For(duration))
+            .gamepads(&ids)
+            .finish(gilrs);
+
+        // Dropping an effect stops it, so the last one is kept alive.
+        if let Ok(effect) = effect {
+            if effect.play().is_ok() {
+                self.effect = Some(effect);
+            }
+        }
+    }
+}
@@ -0,0 +1,116 @@
+use crate::device::{self, Selection};
+use ggez::{audio::AudioContext, error::GameError, GameResult};
+use std::{
+    sync::mpsc::{self, Receiver},
+    thread,
+    time::Duration,
+};
+
+// Listing devices can take a while on ALSA, so it runs this often, off the
+// main thread.
+const POLL_SECS: u64 = 2;
+
+#[derive(Debug, Clone, PartialEq)]
+struct Devices {
+    output: Option<String>,
+    input: Option<String>,
+    inputs: Vec<String>,
+}
+
+fn list() -> Devices {
+    let (input, inputs) = device::names();
+    Devices {
+        output: ggez_rodio::default_output_device().map(|device| device.name()),
+        input,
+        inputs,
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Change {
+    // The default output is now this device.
+    Output(String),
+    // The capture should move to this device, None for the default one.
+    Input(Option<String>),
+}
+
+// An output device handed to ggez in place of the one it opened at startup.
+struct Output(ggez_rodio::Device);
+
+impl AudioContext for Output {
+    fn device(&self) -> &ggez_rodio::Device {
+        &self.0
+    }
+}
+
+pub fn default_output() -> GameResult<Box<dyn AudioContext>> {
+    let device = ggez_rodio::default_output_device()
+        .ok_or_else(|| GameError::AudioError("No default output device".into()))?;
+    Ok(Box::new(Output(device)))
+}
+
+// Watches for devices coming and going, so unplugging an interface moves
+// playback and capture to whatever the system picks instead.
+pub struct Monitor {
+    updates: Receiver<Devices>,
+    seen: Option<Devices>,
+    // A named capture device went away and the default stands in for it.
+    fallback: bool,
+}
+
+impl Monitor {
+    pub fn start() -> Self {
+        let (sender, updates) = mpsc::channel();
+        thread::spawn(move || loop {
+            if sender.send(list()).is_err() {
+                break;
+            }
+            thread::sleep(Duration::from_secs(POLL_SECS));
+        });
+        Monitor {
+            updates,
+            seen: None,
+            fallback: false,
+        }
+    }
+
+    // `capture` is the input device asked for, when capturing from one.
+    pub fn poll(&mut self, capture: Option<&Selection>) -> Vec<Change> {
+        let devices = match self.updates.try_iter().last() {
+            Some(devices) => devices,
+            None => return Vec::new(),
+        };
+        let seen = match self.seen.replace(devices.clone()) {
+            Some(seen) => seen,
+            None => return Vec::new(),
+        };
+
+        let mut changes = Vec::new();
+        if devices.output != seen.output {
+            if let Some(ref name) = devices.output {
+                changes.push(Change::Output(name.clone()));
+            }
+        }
+
+        match capture {
+            Some((Some(wanted), _)) => {
+                let wanted_lower = wanted.to_lowercase();
+                let present = devices.inputs.iter().any(|name| name.to_lowercase().contains(&wanted_lower));
+                if !present && !self.fallback && devices.input.is_some() {
+                    self.fallback = true;
+                    changes.push(Change::Input(None));
+                } else if present && self.fallback {
+                    self.fallback = false;
+                    changes.push(Change::Input(Some(wanted.clone())));
+                } else if self.fallback && devices.input != seen.input && devices.input.is_some() {
+                    changes.push(Change::Input(None));
+                }
+            }
+            Some((None, _)) if devices.input != seen.input && devices.input.is_some() => {
+                changes.push(Change::Input(None));
+            }
+            _ => (),
+        }
+        changes
+    }
+}
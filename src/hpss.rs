@@ -0,0 +1,65 @@
+use crate::DirectionalSource;
+use std::collections::VecDeque;
+
+// Median filter lengths, in frames for the harmonic part and in bins for the
+// percussive part.
+const FRAMES: usize = 17;
+const BINS: usize = 17;
+
+fn median(values: &mut [f32]) -> f32 {
+    let mid = values.len() / 2;
+    *values.select_nth_unstable_by(mid, f32::total_cmp).1
+}
+
+// Median-filtering harmonic/percussive separation on the recent spectrogram:
+// steady tones are smooth along time, hits are smooth along frequency.
+pub struct Hpss {
+    pub enabled: bool,
+    history: VecDeque<Vec<f32>>,
+    harmonic: Vec<f32>,
+}
+
+impl Hpss {
+    pub fn new(bins: usize) -> Self {
+        Hpss {
+            enabled: false,
+            history: VecDeque::with_capacity(FRAMES),
+            harmonic: vec![1.0; bins],
+        }
+    }
+
+    pub fn update(&mut self, directions: &[DirectionalSource]) {
+        if !self.enabled {
+            self.history.clear();
+            return;
+        }
+
+        let frame: Vec<f32> = directions.iter().map(|source| source.amp).collect();
+        if self.history.len() == FRAMES {
+            self.history.pop_front();
+        }
+        self.history.push_back(frame);
+        let frame = &self.history[self.history.len() - 1];
+
+        let mut window = Vec::with_capacity(FRAMES.max(BINS));
+        for idx in 0..self.harmonic.len().min(frame.len()) {
+            window.clear();
+            window.extend(self.history.iter().map(|past| past[idx]));
+            let harmonic = median(&mut window);
+
+            window.clear();
+            let first = idx.saturating_sub(BINS / 2);
+            window.extend_from_slice(&frame[first..(idx + BINS / 2 + 1).min(frame.len())]);
+            let percussive = median(&mut window);
+
+            // Wiener-style soft mask.
+            let (h, p) = (harmonic * harmonic, percussive * percussive);
+            self.harmonic[idx] = if h + p > 0.0 { h / (h + p) } else { 1.0 };
+        }
+    }
+
+    // Share of a bin's energy that is harmonic, 0 to 1.
+    pub fn harmonic(&self, idx: usize) -> f32 {
+        self.harmonic.get(idx).copied().unwrap_or(1.0)
+    }
+}
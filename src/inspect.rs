@@ -0,0 +1,47 @@
+use crate::{
+    overlay::{self, Anchor},
+    DirectionalSource,
+};
+use ggez::{
+    graphics,
+    Context, GameResult,
+};
+use std::{
+    fs::File,
+    io::{BufWriter, Write},
+    path,
+};
+
+pub fn write_csv<P>(
+    path: P,
+    bin_hz: f32,
+    left_rev: &[f32],
+    right_rev: &[f32],
+    directions: &[DirectionalSource],
+) -> GameResult
+where
+    P: AsRef<path::Path>,
+{
+    let mut writer = BufWriter::new(File::create(path)?);
+    writeln!(writer, "bin,frequency_hz,left,right,amp,dir,confidence")?;
+    for (idx, source) in directions.iter().enumerate() {
+        writeln!(
+            writer,
+            "{},{},{},{},{},{},{}",
+            idx,
+            idx as f32 * bin_hz,
+            left_rev[idx],
+            right_rev[idx],
+            source.amp,
+            source.dir,
+            source.confidence
+        )?;
+    }
+    writer.flush()?;
+    Ok(())
+}
+
+pub fn draw_readout(ctx: &mut Context, lines: &str, x: f32, y: f32, width: f32, height: f32) -> GameResult {
+    let text = overlay::text(lines, 18.0);
+    overlay::panel(ctx, &text, Anchor::TopLeft, [x + 16.0, y + 16.0], graphics::WHITE, (width, height))
+}
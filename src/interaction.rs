@@ -0,0 +1,102 @@
+use serde::{Deserialize, Serialize};
+
+// How far from the cursor the pull is still felt, in pixels.
+const RADIUS: f32 = 160.0;
+// Acceleration right at the cursor, in pixels per second squared.
+const STRENGTH: f32 = 2400.0;
+const STIFFNESS: f32 = 60.0;
+const DAMPING: f32 = 9.0;
+// With no mouse or touch movement for this long, the field lets go.
+const IDLE_SECS: f32 = 3.0;
+
+// What the cursor does to the bars and stars.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Force {
+    #[default]
+    Attract,
+    Repel,
+    Off,
+}
+
+#[derive(Debug, Clone, Copy, Default)]
+struct Spring {
+    offset: [f32; 2],
+    velocity: [f32; 2],
+}
+
+// Lets the cursor drag the stage bars about, each hung on a damped spring
+// that pulls it back to where the analysis puts it.
+pub struct ForceField {
+    pub force: Force,
+    pub enabled: bool,
+    // Held right button: the other way round.
+    pub reversed: bool,
+    idle: f32,
+    springs: Vec<Spring>,
+}
+
+impl ForceField {
+    pub fn new(force: Force, len: usize) -> Self {
+        ForceField {
+            force,
+            enabled: force != Force::Off,
+            reversed: false,
+            idle: IDLE_SECS,
+            springs: vec![Spring::default(); len],
+        }
+    }
+
+    pub fn toggle(&mut self) -> bool {
+        self.enabled = !self.enabled;
+        self.enabled
+    }
+
+    pub fn moved(&mut self) {
+        self.idle = 0.0;
+    }
+
+    fn active(&self) -> bool {
+        self.enabled && self.idle < IDLE_SECS
+    }
+
+    // Acceleration felt at `pos` with the cursor at `cursor`.
+    pub fn pull(&self, cursor: [f32; 2], pos: [f32; 2]) -> [f32; 2] {
+        if !self.active() {
+            return [0.0, 0.0];
+        }
+        let sign = match (self.force, self.reversed) {
+            (Force::Repel, false) | (Force::Attract, true) => -1.0,
+            _ => 1.0,
+        };
+        let (dx, dy) = (cursor[0] - pos[0], cursor[1] - pos[1]);
+        let distance = dx.hypot(dy).max(1.0);
+        let falloff = RADIUS * RADIUS / (distance * distance + RADIUS * RADIUS);
+        let scale = sign * STRENGTH * falloff / distance;
+        [dx * scale, dy * scale]
+    }
+
+    // Steps every spring, given where each bar rests (None when not drawn)
+    // and the cursor in the same coordinates.
+    pub fn update(&mut self, cursor: [f32; 2], rests: &[Option<[f32; 2]>], delta: f32) {
+        self.idle += delta;
+        let mut springs = std::mem::take(&mut self.springs);
+        springs.resize(rests.len(), Spring::default());
+        for (spring, rest) in springs.iter_mut().zip(rests) {
+            let pull = match rest {
+                Some([x, y]) => self.pull(cursor, [x + spring.offset[0], y + spring.offset[1]]),
+                None => [0.0, 0.0],
+            };
+            for (axis, pull) in pull.iter().enumerate() {
+                let accel = pull - STIFFNESS * spring.offset[axis] - DAMPING * spring.velocity[axis];
+                spring.velocity[axis] += accel * delta;
+                spring.offset[axis] += spring.velocity[axis] * delta;
+            }
+        }
+        self.springs = springs;
+    }
+
+    pub fn offset(&self, idx: usize) -> [f32; 2] {
+        self.springs.get(idx).map_or([0.0, 0.0], |spring| spring.offset)
+    }
+}
@@ -0,0 +1,127 @@
+use crate::live::LiveInput;
+use ggez::{error::GameError, GameResult};
+
+#[cfg(feature = "jack")]
+mod ffi {
+    use std::os::raw::{c_char, c_int, c_ulong, c_void};
+
+    pub const PORT_IS_INPUT: c_ulong = 0x1;
+    pub const NO_START_SERVER: c_int = 0x01;
+    pub const DEFAULT_AUDIO_TYPE: &[u8] = b"32 bit float mono audio\0";
+
+    pub type ProcessCallback = extern "C" fn(u32, *mut c_void) -> c_int;
+
+    #[link(name = "jack")]
+    extern "C" {
+        pub fn jack_client_open(name: *const c_char, options: c_int, status: *mut c_int, ...) -> *mut c_void;
+        pub fn jack_client_close(client: *mut c_void) -> c_int;
+        pub fn jack_get_sample_rate(client: *mut c_void) -> u32;
+        pub fn jack_port_register(
+            client: *mut c_void,
+            name: *const c_char,
+            port_type: *const c_char,
+            flags: c_ulong,
+            buffer_size: c_ulong,
+        ) -> *mut c_void;
+        pub fn jack_port_get_buffer(port: *mut c_void, frames: u32) -> *mut f32;
+        pub fn jack_set_process_callback(client: *mut c_void, callback: ProcessCallback, arg: *mut c_void) -> c_int;
+        pub fn jack_activate(client: *mut c_void) -> c_int;
+    }
+}
+
+#[cfg(feature = "jack")]
+struct Ports {
+    left: *mut std::os::raw::c_void,
+    right: *mut std::os::raw::c_void,
+    sender: std::sync::mpsc::Sender<Vec<f32>>,
+}
+
+#[cfg(feature = "jack")]
+struct Client {
+    client: *mut std::os::raw::c_void,
+    _ports: Box<Ports>,
+}
+
+#[cfg(feature = "jack")]
+impl Drop for Client {
+    fn drop(&mut self) {
+        unsafe {
+            ffi::jack_client_close(self.client);
+        }
+    }
+}
+
+#[cfg(feature = "jack")]
+extern "C" fn process(frames: u32, arg: *mut std::os::raw::c_void) -> std::os::raw::c_int {
+    let ports = unsafe { &*(arg as *const Ports) };
+    let (left, right) = unsafe {
+        (
+            std::slice::from_raw_parts(ffi::jack_port_get_buffer(ports.left, frames), frames as usize),
+            std::slice::from_raw_parts(ffi::jack_port_get_buffer(ports.right, frames), frames as usize),
+        )
+    };
+
+    // Allocating here is not strictly real-time safe, but blocks are small.
+    let block = left.iter().zip(right).flat_map(|(&l, &r)| [l, r]).collect();
+    let _ = ports.sender.send(block);
+    0
+}
+
+// Registers a JACK client with two input ports that can be patched to any
+// application or bus, e.g. with qjackctl or jack_connect.
+#[cfg(feature = "jack")]
+pub fn open(name: &str) -> GameResult<LiveInput> {
+    use std::{ffi::CString, sync::mpsc};
+
+    let client_name = CString::new(name).map_err(|err| GameError::AudioError(err.to_string()))?;
+    let mut status = 0;
+    let client = unsafe { ffi::jack_client_open(client_name.as_ptr(), ffi::NO_START_SERVER, &mut status) };
+    if client.is_null() {
+        return Err(GameError::AudioError(format!(
+            "Cannot connect to the JACK server (status {:#x})",
+            status
+        )));
+    }
+
+    let register = |port: &[u8]| unsafe {
+        ffi::jack_port_register(
+            client,
+            port.as_ptr() as *const _,
+            ffi::DEFAULT_AUDIO_TYPE.as_ptr() as *const _,
+            ffi::PORT_IS_INPUT,
+            0,
+        )
+    };
+    let (left, right) = (register(b"in_left\0"), register(b"in_right\0"));
+    if left.is_null() || right.is_null() {
+        unsafe {
+            ffi::jack_client_close(client);
+        }
+        return Err(GameError::AudioError("Cannot register JACK ports".into()));
+    }
+
+    let (sender, receiver) = mpsc::channel();
+    let mut ports = Box::new(Ports { left, right, sender });
+    let sample_rate = unsafe {
+        ffi::jack_set_process_callback(client, process, &mut *ports as *mut Ports as *mut _);
+        if ffi::jack_activate(client) != 0 {
+            ffi::jack_client_close(client);
+            return Err(GameError::AudioError("Cannot activate the JACK client".into()));
+        }
+        ffi::jack_get_sample_rate(client)
+    };
+
+    Ok(LiveInput::new(
+        receiver,
+        sample_rate,
+        format!("JACK {}", name),
+        Client { client, _ports: ports },
+    ))
+}
+
+#[cfg(not(feature = "jack"))]
+pub fn open(_name: &str) -> GameResult<LiveInput> {
+    Err(GameError::AudioError(
+        "This build has no JACK support; rebuild with `--features jack`".into(),
+    ))
+}
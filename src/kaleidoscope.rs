@@ -0,0 +1,121 @@
+use crate::offscreen;
+use ggez::{
+    conf::NumSamples,
+    graphics::{self, Canvas, DrawParam, Mesh, Vertex},
+    Context, GameResult,
+};
+use serde::{Deserialize, Serialize};
+use std::f32::consts::PI;
+
+// How far the sampled wedge swings back and forth over a beat, in radians.
+const SWAY: f32 = 0.08;
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Settings {
+    pub enabled: bool,
+    pub segments: usize,
+}
+
+impl Default for Settings {
+    fn default() -> Self {
+        Settings {
+            enabled: false,
+            segments: 6,
+        }
+    }
+}
+
+// Mirrors a wedge from the middle of the picture around the center, every
+// other copy flipped, like the mirrors in a kaleidoscope tube.
+pub struct Kaleidoscope {
+    pub settings: Settings,
+    canvas: Option<Canvas>,
+    samples: NumSamples,
+}
+
+impl Kaleidoscope {
+    pub fn new(segments: usize, samples: NumSamples) -> Self {
+        Kaleidoscope {
+            settings: Settings {
+                segments,
+                ..Settings::default()
+            },
+            canvas: None,
+            samples,
+        }
+    }
+
+    pub fn set(&mut self, ctx: &mut Context, settings: Settings) -> GameResult {
+        self.settings = settings;
+        if settings.enabled && self.canvas.is_none() {
+            self.canvas = Some(offscreen::window(ctx, self.samples)?);
+        }
+        Ok(())
+    }
+
+    pub fn rescale(&mut self, ctx: &mut Context) -> GameResult {
+        offscreen::rescale(ctx, &mut self.canvas, self.samples)
+    }
+
+    pub fn toggle(&mut self, ctx: &mut Context) -> GameResult {
+        let enabled = !self.settings.enabled;
+        self.set(ctx, Settings { enabled, ..self.settings })
+    }
+
+    // Mirrored copies only meet up with an even number of segments.
+    fn segments(&self) -> usize {
+        self.settings.segments.max(2).div_ceil(2) * 2
+    }
+
+    // Draws `picture` through the effect, or straight to the screen when off.
+    pub fn draw<F>(
+        &self,
+        ctx: &mut Context,
+        (width, height): (f32, f32),
+        background: graphics::Color,
+        beat_phase: f32,
+        picture: F,
+    ) -> GameResult
+    where
+        F: FnOnce(&mut Context) -> GameResult,
+    {
+        let canvas = match (self.settings.enabled, &self.canvas) {
+            (true, Some(canvas)) => canvas,
+            _ => return picture(ctx),
+        };
+        graphics::set_canvas(ctx, Some(canvas));
+        graphics::clear(ctx, background);
+        let drawn = picture(ctx);
+        graphics::set_canvas(ctx, None);
+        drawn?;
+
+        let wedge = 2.0 * PI / self.segments() as f32;
+        let center = [width / 2.0, height / 2.0];
+        // Far enough that the straight outer edges still reach the corners.
+        let reach = (width.hypot(height) / 2.0) / (wedge / 2.0).cos();
+        let source = width.min(height) / 2.0;
+        let start = -PI / 2.0 - wedge / 2.0 + SWAY * (2.0 * PI * beat_phase).sin();
+
+        let point = |radius: f32, angle: f32| [center[0] + radius * angle.cos(), center[1] + radius * angle.sin()];
+        // Canvas images are stored upside down.
+        let uv = |[x, y]: [f32; 2]| [x / width, 1.0 - y / height];
+        let vertex = |pos: [f32; 2], at: [f32; 2]| Vertex {
+            pos,
+            uv: uv(at),
+            color: [1.0, 1.0, 1.0, 1.0],
+        };
+
+        let mut vertices = Vec::with_capacity(self.segments() * 3);
+        for segment in 0..self.segments() {
+            let (first, second) = if segment % 2 == 0 { (0.0, wedge) } else { (wedge, 0.0) };
+            let angle = segment as f32 * wedge;
+            vertices.push(vertex(center, center));
+            vertices.push(vertex(point(reach, angle), point(source, start + first)));
+            vertices.push(vertex(point(reach, angle + wedge), point(source, start + second)));
+        }
+        let indices: Vec<u32> = (0..vertices.len() as u32).collect();
+        let mesh = Mesh::from_raw(ctx, &vertices, &indices, Some(canvas.image().clone()))?;
+        graphics::draw(ctx, &mesh, DrawParam::default())
+    }
+}
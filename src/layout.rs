@@ -0,0 +1,136 @@
+use crate::{offscreen, Mode};
+use ggez::{
+    conf,
+    graphics::{self, Canvas, DrawParam, Rect},
+    Context, GameResult,
+};
+use serde::{Deserialize, Serialize};
+
+const GAP: f32 = 2.0;
+
+// One mode shown in a part of the window, given as left, top, width and
+// height in fractions of the window.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct Pane {
+    pub mode: Mode,
+    pub rect: [f32; 4],
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Layout {
+    pub name: String,
+    pub panes: Vec<Pane>,
+}
+
+// Used when the config file defines no `[[layouts]]` of its own.
+pub fn builtin() -> Vec<Layout> {
+    let pane = |mode, rect| Pane { mode, rect };
+    vec![
+        Layout {
+            name: "Dashboard".to_string(),
+            panes: vec![
+                pane(Mode::Stage, [0.0, 0.0, 1.0, 0.55]),
+                pane(Mode::Phase, [0.0, 0.55, 0.5, 0.45]),
+                pane(Mode::Tuner, [0.5, 0.55, 0.5, 0.45]),
+            ],
+        },
+        Layout {
+            name: "Side by side".to_string(),
+            panes: vec![pane(Mode::Stage, [0.0, 0.0, 0.5, 1.0]), pane(Mode::Cepstrum, [0.5, 0.0, 0.5, 1.0])],
+        },
+    ]
+}
+
+// Tiles several modes at once, each drawn into its own off-screen target sized
+// to its pane so circles stay round.
+pub struct Compositor {
+    layouts: Vec<Layout>,
+    current: Option<usize>,
+    canvases: Vec<Canvas>,
+    pixels: (f32, f32),
+    samples: conf::NumSamples,
+}
+
+impl Compositor {
+    pub fn new(layouts: Vec<Layout>, samples: conf::NumSamples) -> Self {
+        Compositor {
+            layouts: if layouts.is_empty() { builtin() } else { layouts },
+            current: None,
+            canvases: Vec::new(),
+            pixels: (0.0, 0.0),
+            samples,
+        }
+    }
+
+    pub fn active(&self) -> Option<&Layout> {
+        self.current.map(|idx| &self.layouts[idx])
+    }
+
+    // Steps through the layouts and back to a single mode.
+    pub fn cycle(&mut self) -> Option<&Layout> {
+        self.current = match self.current {
+            None => Some(0),
+            Some(idx) if idx + 1 < self.layouts.len() => Some(idx + 1),
+            Some(_) => None,
+        };
+        self.canvases.clear();
+        self.active()
+    }
+
+    // Makes one target per pane, again whenever the window changes size.
+    pub fn update(&mut self, ctx: &mut Context) -> GameResult {
+        let pixels = graphics::drawable_size(ctx);
+        if !self.canvases.is_empty() && pixels == self.pixels {
+            return Ok(());
+        }
+        let panes = match self.current {
+            Some(idx) => self.layouts[idx].panes.clone(),
+            None => return Ok(()),
+        };
+        self.canvases.clear();
+        for pane in panes {
+            let width = (pane.rect[2] * pixels.0).max(1.0);
+            let height = (pane.rect[3] * pixels.1).max(1.0);
+            self.canvases.push(offscreen::canvas(ctx, (width, height), self.samples)?);
+        }
+        self.pixels = pixels;
+        Ok(())
+    }
+
+    // Draws every pane with `picture`, given the pane's mode and size, then
+    // lays them out on the window.
+    pub fn draw<F>(&self, ctx: &mut Context, (width, height): (f32, f32), mut picture: F) -> GameResult
+    where
+        F: FnMut(&mut Context, Mode, (f32, f32)) -> GameResult,
+    {
+        let layout = match self.active() {
+            Some(layout) => layout,
+            None => return Ok(()),
+        };
+        let screen = graphics::screen_coordinates(ctx);
+        for (pane, canvas) in layout.panes.iter().zip(&self.canvases) {
+            graphics::set_canvas(ctx, Some(canvas));
+            graphics::clear(ctx, [0.0, 0.0, 0.0, 0.0].into());
+            let drawn = picture(ctx, pane.mode, (pane.rect[2] * width, pane.rect[3] * height));
+            graphics::set_canvas(ctx, None);
+            graphics::set_screen_coordinates(ctx, screen)?;
+            drawn?;
+        }
+
+        let border = graphics::Color::from_rgba(255, 255, 255, 48);
+        for (pane, canvas) in layout.panes.iter().zip(&self.canvases) {
+            let image = canvas.image();
+            let rect = Rect::new(pane.rect[0] * width, pane.rect[1] * height, pane.rect[2] * width, pane.rect[3] * height);
+            // Canvas images are stored upside down, hence the negative scale.
+            let param = DrawParam::default()
+                .dest([rect.x, rect.bottom()])
+                .scale([rect.w / f32::from(image.width()), -rect.h / f32::from(image.height())]);
+            graphics::draw(ctx, image, param)?;
+
+            let inset = Rect::new(rect.x + GAP / 2.0, rect.y + GAP / 2.0, rect.w - GAP, rect.h - GAP);
+            let outline = graphics::Mesh::new_rectangle(ctx, graphics::DrawMode::stroke(1.0), inset, border)?;
+            graphics::draw(ctx, &outline, DrawParam::default())?;
+        }
+        Ok(())
+    }
+}
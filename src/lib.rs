@@ -0,0 +1,286 @@
+//! The stereo analysis behind the visualizer as a C library, for game engines
+//! and plugins to embed; `include/stereo_visualizer.h` declares the API.
+
+use rustfft::{num_complex::Complex, num_traits::Zero, FFTplanner, FFT};
+use std::{slice, sync::Arc};
+
+pub mod direction;
+pub mod panlaw;
+
+use direction::Estimator;
+use panlaw::PanLaw;
+
+// Samples kept beyond one window, so a host pushing faster than it polls
+// does not grow the buffers without bound.
+const BACKLOG_WINDOWS: usize = 4;
+
+/// One FFT bin: where it sits between the speakers, how loud it is and how
+/// far the direction can be trusted.
+#[repr(C)]
+#[derive(Debug, Clone, Copy, Default)]
+pub struct SvSource {
+    /// -1 (left) to 1 (right).
+    pub dir: f32,
+    pub amp: f32,
+    /// 0 to 1.
+    pub confidence: f32,
+}
+
+/// The analysis state behind an `sv_analyzer *`.
+pub struct Analyzer {
+    fft: Arc<dyn FFT<f32>>,
+    left: Vec<f32>,
+    right: Vec<f32>,
+    fresh: bool,
+    left_rev: Vec<f32>,
+    right_rev: Vec<f32>,
+    sources: Vec<SvSource>,
+    estimator: Estimator,
+}
+
+impl Analyzer {
+    fn new(fft_size: usize) -> Self {
+        Analyzer {
+            fft: FFTplanner::new(false).plan_fft(fft_size),
+            left: Vec::new(),
+            right: Vec::new(),
+            fresh: false,
+            left_rev: vec![0.0; fft_size / 2],
+            right_rev: vec![0.0; fft_size / 2],
+            sources: vec![SvSource::default(); fft_size / 2],
+            estimator: Estimator::new(fft_size / 2, PanLaw::default()),
+        }
+    }
+
+    fn push(&mut self, interleaved: &[f32]) {
+        for frame in interleaved.chunks_exact(2) {
+            self.left.push(frame[0]);
+            self.right.push(frame[1]);
+        }
+        let limit = self.fft.len() * BACKLOG_WINDOWS;
+        if self.left.len() > limit {
+            let excess = self.left.len() - limit;
+            self.left.drain(..excess);
+            self.right.drain(..excess);
+        }
+        self.fresh |= !interleaved.is_empty();
+    }
+
+    // Analyses the newest window, the same way the visualizer does each frame.
+    fn poll(&mut self) -> bool {
+        let size = self.fft.len();
+        if !self.fresh || self.left.len() < size {
+            return false;
+        }
+        self.fresh = false;
+        let start = self.left.len() - size;
+        let spectrum = |fft: &Arc<dyn FFT<f32>>, wave: &[f32]| {
+            let mut input: Vec<_> = wave.iter().map(|&amp| Complex::new(amp, 0.0)).collect();
+            let mut output = vec![Complex::zero(); size];
+            fft.process(&mut input, &mut output);
+            output
+        };
+        let left_fft = spectrum(&self.fft, &self.left[start..]);
+        let right_fft = spectrum(&self.fft, &self.right[start..]);
+        self.left.drain(..start);
+        self.right.drain(..start);
+
+        for (idx, source) in self.sources.iter_mut().enumerate() {
+            let levels = (left_fft[idx].re.abs(), right_fft[idx].re.abs());
+            // The default pan law is the same at every frequency, so the
+            // sample rate, which the host never tells us, is not needed.
+            let (amp, confidence) = self.estimator.step(
+                idx,
+                (left_fft[idx], right_fft[idx]),
+                levels,
+                (&mut self.left_rev[idx], &mut self.right_rev[idx]),
+                &mut source.dir,
+                0.0,
+            );
+            source.amp = amp;
+            source.confidence = confidence;
+        }
+        true
+    }
+}
+
+/// Starts an analysis over windows of `fft_size` samples, a power of two of
+/// at least 2 such as 1024. Returns null for any other size.
+#[no_mangle]
+pub extern "C" fn sv_init(fft_size: u32) -> *mut Analyzer {
+    let fft_size = fft_size as usize;
+    if fft_size < 2 || !fft_size.is_power_of_two() {
+        return std::ptr::null_mut();
+    }
+    Box::into_raw(Box::new(Analyzer::new(fft_size)))
+}
+
+/// Adds `frames` frames of interleaved stereo samples.
+///
+/// # Safety
+///
+/// `analyzer` must come from `sv_init` and not be freed yet, and `samples`
+/// must point to `frames * 2` floats, or be null when `frames` is 0.
+#[no_mangle]
+pub unsafe extern "C" fn sv_push_samples(analyzer: *mut Analyzer, samples: *const f32, frames: usize) {
+    if analyzer.is_null() || samples.is_null() {
+        return;
+    }
+    (*analyzer).push(slice::from_raw_parts(samples, frames * 2));
+}
+
+/// Analyses what has been pushed since the last poll and writes up to
+/// `capacity` bins, `fft_size / 2` in all, into `out`. Returns how many were
+/// written, or 0 when no new window was ready.
+///
+/// # Safety
+///
+/// `analyzer` must come from `sv_init` and not be freed yet, and `out` must
+/// have room for `capacity` sources.
+#[no_mangle]
+pub unsafe extern "C" fn sv_poll_frame(analyzer: *mut Analyzer, out: *mut SvSource, capacity: usize) -> usize {
+    if analyzer.is_null() || out.is_null() {
+        return 0;
+    }
+    let analyzer = &mut *analyzer;
+    if !analyzer.poll() {
+        return 0;
+    }
+    let count = capacity.min(analyzer.sources.len());
+    slice::from_raw_parts_mut(out, count).copy_from_slice(&analyzer.sources[..count]);
+    count
+}
+
+/// Ends an analysis.
+///
+/// # Safety
+///
+/// `analyzer` must come from `sv_init`, or be null, and is not usable after.
+#[no_mangle]
+pub unsafe extern "C" fn sv_free(analyzer: *mut Analyzer) {
+    if !analyzer.is_null() {
+        drop(Box::from_raw(analyzer));
+    }
+}
+
+// The sender for `--bridge`, left out of builds without the network feature.
+#[cfg(feature = "network")]
+pub mod bridge {
+    use std::{
+        ffi::CStr,
+        io::Write,
+        net::TcpStream,
+        os::raw::c_char,
+        slice,
+        sync::mpsc::{self, Receiver, SyncSender, TryRecvError},
+        thread,
+        time::Duration,
+    };
+
+    // Each connection opens with this, then the sender's sample rate and
+    // channel count as little-endian u32s, then interleaved little-endian f32
+    // samples; `bridge.rs` in the visualizer is the receiving end.
+    pub const MAGIC: &[u8; 4] = b"SVB1";
+    // Blocks queued for the bridge before new ones are dropped, and how often a
+    // bridge looks for the visualizer while it is not running.
+    const QUEUE: usize = 64;
+    const RETRY: Duration = Duration::from_secs(1);
+
+    /// The sending end of `stereo-visualizer --bridge`, behind an `sv_bridge *`.
+    pub struct Bridge {
+        blocks: SyncSender<Vec<u8>>,
+        channels: usize,
+    }
+
+    // Connects, and connects again whenever the visualizer goes away, until the
+    // bridge is freed. Audio arriving while disconnected is dropped.
+    fn run_bridge(addr: String, header: Vec<u8>, blocks: Receiver<Vec<u8>>) {
+        loop {
+            let mut stream = match TcpStream::connect(&addr) {
+                Ok(stream) => stream,
+                Err(_) => {
+                    thread::sleep(RETRY);
+                    loop {
+                        match blocks.try_recv() {
+                            Ok(_) => {}
+                            Err(TryRecvError::Empty) => break,
+                            Err(TryRecvError::Disconnected) => return,
+                        }
+                    }
+                    continue;
+                }
+            };
+            let _ = stream.set_nodelay(true);
+            if stream.write_all(&header).is_err() {
+                continue;
+            }
+            for block in blocks.iter() {
+                if stream.write_all(&block).is_err() {
+                    break;
+                }
+            }
+            // A closed channel ends the loop above for good.
+            if let Err(TryRecvError::Disconnected) = blocks.try_recv() {
+                return;
+            }
+        }
+    }
+
+    /// Starts streaming audio to a visualizer run with `--bridge ADDR`, e.g.
+    /// "127.0.0.1:7878". Returns null for an address that is not valid UTF-8.
+    ///
+    /// # Safety
+    ///
+    /// `addr` must be a null-terminated string.
+    #[no_mangle]
+    pub unsafe extern "C" fn sv_bridge_connect(addr: *const c_char, sample_rate: u32, channels: u32) -> *mut Bridge {
+        if addr.is_null() || sample_rate == 0 || channels == 0 {
+            return std::ptr::null_mut();
+        }
+        let addr = match CStr::from_ptr(addr).to_str() {
+            Ok(addr) => addr.to_string(),
+            Err(_) => return std::ptr::null_mut(),
+        };
+        let mut header = MAGIC.to_vec();
+        header.extend_from_slice(&sample_rate.to_le_bytes());
+        header.extend_from_slice(&channels.to_le_bytes());
+
+        let (blocks, received) = mpsc::sync_channel(QUEUE);
+        thread::spawn(move || run_bridge(addr, header, received));
+        Box::into_raw(Box::new(Bridge {
+            blocks,
+            channels: channels as usize,
+        }))
+    }
+
+    /// Queues `frames` frames of interleaved samples, in the channel count given
+    /// to `sv_bridge_connect`. Never blocks, so it is safe on an audio thread
+    /// apart from the allocation.
+    ///
+    /// # Safety
+    ///
+    /// `bridge` must come from `sv_bridge_connect` and not be freed yet, and
+    /// `samples` must point to `frames * channels` floats.
+    #[no_mangle]
+    pub unsafe extern "C" fn sv_bridge_send(bridge: *mut Bridge, samples: *const f32, frames: usize) {
+        if bridge.is_null() || samples.is_null() {
+            return;
+        }
+        let bridge = &*bridge;
+        let samples = slice::from_raw_parts(samples, frames * bridge.channels);
+        let block = samples.iter().flat_map(|sample| sample.to_le_bytes()).collect();
+        let _ = bridge.blocks.try_send(block);
+    }
+
+    /// Stops streaming and disconnects.
+    ///
+    /// # Safety
+    ///
+    /// `bridge` must come from `sv_bridge_connect`, or be null, and is not usable after.
+    #[no_mangle]
+    pub unsafe extern "C" fn sv_bridge_free(bridge: *mut Bridge) {
+        if !bridge.is_null() {
+            drop(Box::from_raw(bridge));
+        }
+    }
+}
@@ -0,0 +1,274 @@
+use crate::{
+    overlay::{self, Anchor},
+    tags::Tags,
+    watch::is_audio,
+};
+use ggez::{
+    filesystem,
+    graphics,
+    Context, GameResult,
+};
+use serde::{Deserialize, Serialize};
+use std::{
+    collections::HashMap,
+    fs,
+    io::Read,
+    path::{Path, PathBuf},
+    sync::mpsc::{self, Receiver},
+    thread,
+    time::UNIX_EPOCH,
+};
+
+const RESULTS: usize = 12;
+const TAG_BYTES: u64 = 256 * 1024;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Track {
+    pub path: PathBuf,
+    pub title: String,
+    pub artist: String,
+    pub album: String,
+    modified: u64,
+}
+
+impl Track {
+    fn label(&self) -> String {
+        let name = || {
+            self.path
+                .file_stem()
+                .map(|name| name.to_string_lossy().into_owned())
+                .unwrap_or_default()
+        };
+        match (self.artist.is_empty(), self.title.is_empty()) {
+            (false, false) => format!("{} - {}", self.artist, self.title),
+            (true, false) => self.title.clone(),
+            _ => name(),
+        }
+    }
+
+    fn haystack(&self) -> String {
+        format!("{} {} {} {}", self.artist, self.title, self.album, self.path.display()).to_lowercase()
+    }
+}
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+#[serde(default)]
+struct Index {
+    tracks: Vec<Track>,
+}
+
+fn read_track(path: &Path, modified: u64) -> Track {
+    // Tags sit at the start of most formats; MP4 keeps them wherever moov is.
+    let mut bytes = Vec::new();
+    if let Ok(file) = fs::File::open(path) {
+        let limit = if path.extension().is_some_and(|ext| ext.eq_ignore_ascii_case("m4a")) {
+            u64::MAX
+        } else {
+            TAG_BYTES
+        };
+        let _ = file.take(limit).read_to_end(&mut bytes);
+    }
+    let tags = Tags::read(&bytes);
+    let tag = |key| tags.get(key).unwrap_or("").to_string();
+
+    Track {
+        path: path.to_path_buf(),
+        title: tag("TITLE"),
+        artist: tag("ARTIST"),
+        album: tag("ALBUM"),
+        modified,
+    }
+}
+
+fn scan_dir(dir: &Path, cached: &HashMap<PathBuf, Track>, tracks: &mut Vec<Track>) {
+    let entries = match fs::read_dir(dir) {
+        Ok(entries) => entries,
+        Err(_) => return,
+    };
+
+    for entry in entries.filter_map(|entry| entry.ok()) {
+        let path = entry.path();
+        let metadata = match entry.metadata() {
+            Ok(metadata) => metadata,
+            Err(_) => continue,
+        };
+
+        if metadata.is_dir() {
+            scan_dir(&path, cached, tracks);
+        } else if is_audio(&path) {
+            let modified = metadata
+                .modified()
+                .ok()
+                .and_then(|time| time.duration_since(UNIX_EPOCH).ok())
+                .map_or(0, |time| time.as_secs());
+            match cached.get(&path) {
+                Some(track) if track.modified == modified => tracks.push(track.clone()),
+                _ => tracks.push(read_track(&path, modified)),
+            }
+        }
+    }
+}
+
+// Only files whose modification time changed since the last scan are re-read.
+fn scan(index_path: PathBuf, dirs: Vec<PathBuf>) -> Vec<Track> {
+    let cached: HashMap<PathBuf, Track> = fs::read_to_string(&index_path)
+        .ok()
+        .and_then(|text| toml::from_str::<Index>(&text).ok())
+        .map(|index| index.tracks.into_iter().map(|track| (track.path.clone(), track)).collect())
+        .unwrap_or_default();
+
+    let mut tracks = Vec::new();
+    for dir in &dirs {
+        scan_dir(dir, &cached, &mut tracks);
+    }
+    tracks.sort_by(|a, b| a.path.cmp(&b.path));
+
+    let index = Index { tracks };
+    if let Some(dir) = index_path.parent() {
+        let _ = fs::create_dir_all(dir);
+    }
+    if let Ok(text) = toml::to_string(&index) {
+        let _ = fs::write(&index_path, text);
+    }
+    index.tracks
+}
+
+// Scores a subsequence match, favouring consecutive characters and early hits.
+fn fuzzy_score(query: &str, haystack: &str) -> Option<i32> {
+    let mut score = 0;
+    let mut chars = haystack.char_indices();
+    let mut last = None;
+
+    for needle in query.chars().filter(|c| !c.is_whitespace()) {
+        let (idx, _) = chars.by_ref().find(|&(_, c)| c == needle)?;
+        score += match last {
+            Some(last) if idx == last + needle.len_utf8() => 8,
+            _ => 1,
+        };
+        if idx == 0 {
+            score += 4;
+        }
+        last = Some(idx);
+    }
+    Some(score * 100 - haystack.len().min(99) as i32)
+}
+
+pub struct Library {
+    tracks: Vec<Track>,
+    scanning: Option<Receiver<Vec<Track>>>,
+    query: Option<String>,
+    results: Vec<usize>,
+    selected: usize,
+}
+
+impl Library {
+    pub fn new() -> Self {
+        Library {
+            tracks: Vec::new(),
+            scanning: None,
+            query: None,
+            results: Vec::new(),
+            selected: 0,
+        }
+    }
+
+    pub fn scan(&mut self, ctx: &Context, dirs: &[PathBuf]) {
+        if dirs.is_empty() {
+            return;
+        }
+
+        let index_path = filesystem::user_data_dir(ctx).join("library.toml");
+        let dirs = dirs.to_vec();
+        let (sender, receiver) = mpsc::channel();
+        thread::spawn(move || {
+            let _ = sender.send(scan(index_path, dirs));
+        });
+        self.scanning = Some(receiver);
+    }
+
+    pub fn update(&mut self) {
+        let tracks = self.scanning.as_ref().and_then(|receiver| receiver.try_recv().ok());
+        if let Some(tracks) = tracks {
+            println!("Library: {} tracks", tracks.len());
+            self.tracks = tracks;
+            self.scanning = None;
+            self.search();
+        }
+    }
+
+    pub fn searching(&self) -> bool {
+        self.query.is_some()
+    }
+
+    pub fn open(&mut self) {
+        self.query = Some(String::new());
+        self.search();
+    }
+
+    pub fn close(&mut self) {
+        self.query = None;
+    }
+
+    pub fn push(&mut self, character: char) {
+        if let Some(ref mut query) = self.query {
+            query.extend(character.to_lowercase());
+        }
+        self.search();
+    }
+
+    pub fn pop(&mut self) {
+        if let Some(ref mut query) = self.query {
+            query.pop();
+        }
+        self.search();
+    }
+
+    pub fn select_by(&mut self, delta: i32) {
+        if !self.results.is_empty() {
+            let len = self.results.len() as i32;
+            self.selected = (self.selected as i32 + delta).rem_euclid(len) as usize;
+        }
+    }
+
+    pub fn selected(&self) -> Option<&Path> {
+        self.results
+            .get(self.selected)
+            .map(|&idx| self.tracks[idx].path.as_path())
+    }
+
+    fn search(&mut self) {
+        let query = self.query.clone().unwrap_or_default();
+        let mut scored: Vec<(i32, usize)> = self
+            .tracks
+            .iter()
+            .enumerate()
+            .filter_map(|(idx, track)| fuzzy_score(&query, &track.haystack()).map(|score| (score, idx)))
+            .collect();
+        scored.sort_by(|a, b| b.0.cmp(&a.0).then(a.1.cmp(&b.1)));
+
+        self.results = scored.into_iter().take(RESULTS).map(|(_, idx)| idx).collect();
+        self.selected = 0;
+    }
+
+    pub fn draw(&self, ctx: &mut Context, width: f32, height: f32) -> GameResult {
+        let query = match self.query {
+            Some(ref query) => query,
+            None => return Ok(()),
+        };
+
+        let mut lines = vec![format!("Search: {}_", query)];
+        if self.scanning.is_some() {
+            lines.push("  scanning...".to_string());
+        } else if self.results.is_empty() {
+            lines.push("  no matches".to_string());
+        }
+        for (idx, &track) in self.results.iter().enumerate() {
+            let marker = if idx == self.selected { ">" } else { " " };
+            lines.push(format!("{} {}", marker, self.tracks[track].label()));
+        }
+        lines.push("Enter to enqueue, Escape to close".to_string());
+
+        let text = overlay::wrapped(lines.join("\n"), 20.0, width * 0.6, graphics::Align::Left);
+        overlay::panel(ctx, &text, Anchor::Top, [width / 2.0, height / 6.0], graphics::WHITE, (width, height))
+    }
+}
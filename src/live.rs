@@ -0,0 +1,21 @@
+use std::{any::Any, sync::mpsc::Receiver};
+
+// Live sources hand over interleaved stereo blocks through a channel; the
+// handle keeps whatever owns the capture (a client, a thread) alive.
+pub struct LiveInput {
+    pub receiver: Receiver<Vec<f32>>,
+    pub sample_rate: u32,
+    pub name: String,
+    _handle: Box<dyn Any>,
+}
+
+impl LiveInput {
+    pub fn new<H: Any>(receiver: Receiver<Vec<f32>>, sample_rate: u32, name: String, handle: H) -> Self {
+        LiveInput {
+            receiver,
+            sample_rate,
+            name,
+            _handle: Box::new(handle),
+        }
+    }
+}
@@ -0,0 +1,111 @@
+use crate::{dsp::Biquad, tags::Tags};
+use std::f32::consts::PI;
+
+pub const REFERENCE: f32 = -18.0;
+
+pub fn k_weighting(sample_rate: f32) -> [Biquad; 2] {
+    let k = (PI * 1_681.974_5 / sample_rate).tan();
+    let q = 0.707_175_24;
+    let vh = 10.0f32.powf(3.999_844 / 20.0);
+    let vb = vh.powf(0.499_666_78);
+    let a0 = 1.0 + k / q + k * k;
+    let shelf = Biquad::new(
+        (vh + vb * k / q + k * k) / a0,
+        2.0 * (k * k - vh) / a0,
+        (vh - vb * k / q + k * k) / a0,
+        2.0 * (k * k - 1.0) / a0,
+        (1.0 - k / q + k * k) / a0,
+    );
+
+    let k = (PI * 38.135_47 / sample_rate).tan();
+    let q = 0.500_327;
+    let a0 = 1.0 + k / q + k * k;
+    let high_pass = Biquad::new(
+        1.0,
+        -2.0,
+        1.0,
+        2.0 * (k * k - 1.0) / a0,
+        (1.0 - k / q + k * k) / a0,
+    );
+
+    [shelf, high_pass]
+}
+
+pub fn integrated(left: &[f32], right: &[f32], sample_rate: u32) -> Option<f32> {
+    let block = (sample_rate as f32 * 0.4) as usize;
+    let hop = block / 4;
+    if hop == 0 || left.len() < block {
+        return None;
+    }
+
+    let mut left_filters = k_weighting(sample_rate as f32);
+    let mut right_filters = k_weighting(sample_rate as f32);
+    let power: Vec<f32> = left
+        .iter()
+        .zip(right)
+        .map(|(&l, &r)| {
+            let l = left_filters.iter_mut().fold(l, |amp, filter| filter.process(amp));
+            let r = right_filters.iter_mut().fold(r, |amp, filter| filter.process(amp));
+            l * l + r * r
+        })
+        .collect();
+
+    let mut sums = Vec::with_capacity(power.len() / hop);
+    let mut sum: f64 = power[..block].iter().map(|&p| p as f64).sum();
+    let mut start = 0;
+    loop {
+        sums.push((sum / block as f64) as f32);
+        if start + hop + block > power.len() {
+            break;
+        }
+        sum -= power[start..start + hop].iter().map(|&p| p as f64).sum::<f64>();
+        sum += power[start + block..start + block + hop].iter().map(|&p| p as f64).sum::<f64>();
+        start += hop;
+    }
+
+    let to_lufs = |power: f32| -0.691 + 10.0 * power.max(1e-12).log10();
+    let gated_mean = |threshold: f32| {
+        let (total, count) = sums
+            .iter()
+            .filter(|&&power| to_lufs(power) > threshold)
+            .fold((0.0, 0), |(total, count), &power| (total + power, count + 1));
+        if count > 0 {
+            Some(total / count as f32)
+        } else {
+            None
+        }
+    };
+
+    let relative = to_lufs(gated_mean(-70.0)?) - 10.0;
+    gated_mean(relative.max(-70.0)).map(to_lufs)
+}
+
+pub fn replay_gain(tags: &Tags) -> Option<f32> {
+    if let Some(gain) = tags.get("REPLAYGAIN_TRACK_GAIN") {
+        return gain.trim().trim_end_matches("dB").trim().parse().ok();
+    }
+    // Opus gains are Q7.8 relative to -23 LUFS.
+    tags.get("R128_TRACK_GAIN")
+        .and_then(|gain| gain.trim().parse::<i32>().ok())
+        .map(|gain| gain as f32 / 256.0 + (REFERENCE + 23.0))
+}
+
+pub fn normalization(tags: &Tags, left: &[f32], right: &[f32], sample_rate: u32) -> f32 {
+    let gain = replay_gain(tags).or_else(|| {
+        integrated(left, right, sample_rate).map(|loudness| REFERENCE - loudness)
+    });
+    let gain = match gain {
+        Some(gain) => 10.0f32.powf(gain / 20.0),
+        None => return 1.0,
+    };
+
+    let peak = left
+        .iter()
+        .chain(right)
+        .fold(0.0f32, |acc, amp| acc.max(amp.abs()));
+    if peak > 0.0 {
+        gain.min(1.0 / peak)
+    } else {
+        gain
+    }
+}
@@ -1,20 +1,174 @@
 use ggez::{
-    audio::{self, SoundSource},
-    conf::{WindowSetup, WindowMode},
+    conf::{NumSamples, WindowSetup, WindowMode},
     error::GameError,
     event, graphics,
-    input::keyboard,
+    input::{keyboard, mouse},
     graphics::DrawParam,
-    Context, GameResult,
+    timer, Context, GameResult,
 };
-use rodio::Source;
 use rustfft::{num_complex::Complex, num_traits::Zero, FFTplanner, FFT};
-use std::{fs::File, i16, io::BufReader, path, sync::Arc, env};
+use serde::{Deserialize, Serialize};
+use std::{
+    fs, path,
+    sync::{mpsc::Receiver, Arc},
+    time::{Instant, SystemTime, UNIX_EPOCH},
+};
+use stereo_visualizer::direction;
+
+mod agc;
+mod ambisonic;
+mod animation;
+mod attract;
+mod automation;
+mod backdrop;
+mod balance;
+mod bands;
+mod beat;
+mod binaural;
+mod bridge;
+mod cache;
+mod calibration;
+mod captions;
+mod cards;
+mod cepstrum;
+mod cli;
+mod compare;
+mod config;
+mod correlation;
+mod cue;
+mod deck;
+mod decode;
+mod device;
+mod dsp;
+mod dynamics;
+mod emit;
+mod eq;
+mod events;
+mod export;
+mod features;
+mod floating;
+mod frames;
+mod generator;
+mod gpio;
+mod haptics;
+mod hotplug;
+mod hpss;
+mod inspect;
+mod interaction;
+mod jack;
+mod kaleidoscope;
+mod layout;
+mod library;
+mod live;
+mod loudness;
+mod metrics;
+mod metronome;
+mod midi;
+mod mqtt;
+mod net;
+mod noise;
+mod offscreen;
+mod orientation;
+mod overlay;
+mod pan;
+mod pcm;
+mod phase;
+mod pipewire;
+mod player;
+mod playlist;
+mod playlist_file;
+mod preset;
+mod quality;
+mod quantize;
+mod register;
+mod replay;
+mod safety;
+mod secondary;
+mod session;
+mod shader;
+mod snapcast;
+mod soundfont;
+mod spectrogram;
+mod starfield;
+mod strobe;
+mod surround;
+mod synth;
+mod tags;
+mod tempo;
+mod timecode;
+mod timeline;
+mod touch;
+mod tracker;
+mod transition;
+mod tuner;
+mod typography;
+mod view;
+mod watch;
+mod watchdog;
+mod watermark;
+
+use agc::Agc;
+use ambisonic::Ambisonic;
+use animation::Animated;
+use balance::Balance;
+use beat::BeatDetector;
+use cache::{Analysis, Bookmark};
+use calibration::Calibration;
+use captions::Captions;
+use cepstrum::Cepstrum;
+use compare::Comparison;
+use config::{Config, Pause};
+use correlation::Correlation;
+use dynamics::Dynamics;
+use eq::Equalizer;
+use events::Event;
+use features::{Features, Hud};
+use gpio::Gpio;
+use haptics::Haptics;
+use hpss::Hpss;
+use interaction::ForceField;
+use kaleidoscope::Kaleidoscope;
+use layout::Compositor;
+use library::Library;
+use metrics::Metrics;
+use metronome::Metronome;
+use midi::PianoRoll;
+use noise::NoiseGate;
+use orientation::Orientation;
+use overlay::{Anchor, Toast};
+use pan::PanTracker;
+use phase::PhaseView;
+use player::Player;
+use preset::Preset;
+use playlist::Playlist;
+use quality::Quality;
+use quantize::{Action, Scheduler};
+use replay::ReplayBuffer;
+use safety::Safety;
+use session::Session;
+use shader::ShaderMode;
+use soundfont::SoundFont;
+use starfield::Starfield;
+use strobe::Strobe;
+use surround::Surround;
+use tempo::TempoCurve;
+use touch::{Gesture, GestureHandler};
+use tracker::TrackerView;
+use transition::Transition;
+use tuner::Tuner;
+use typography::{Ambient, Typography};
+use view::FrequencyView;
+use watchdog::Watchdog;
+
+// Time constant of the fade when playback pauses.
+const PAUSE_DECAY_SECS: f32 = 0.25;
 
 #[derive(Debug, Clone, Copy)]
 struct DirectionalSource {
     dir: f32,
     amp: f32,
+    // How far `dir` can be trusted, 0 to 1.
+    confidence: f32,
 }
 
 impl DirectionalSource {
@@ -22,27 +176,123 @@ impl DirectionalSource {
         DirectionalSource {
             dir: 0.0,
             amp: 0.0,
+            confidence: 0.0,
         }
     }
 }
 
+#[derive(Debug, Clone, Copy, PartialEq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+enum Mode {
+    #[default]
+    Stage,
+    Ring,
+    Tracker,
+    Cepstrum,
+    Phase,
+    Tuner,
+    Typography,
+    Shader,
+}
+
 struct MainState {
     canvas_width: f32,
     canvas_height: f32,
-    sound: Option<audio::Source>,
-    sample_rate: u32,
-    left_wave: Vec<f32>,
-    right_wave: Vec<f32>,
+    config: Config,
+    player: Player,
     fft: Arc<dyn FFT<f32>>,
     left_fft: Vec<Complex<f32>>,
     right_fft: Vec<Complex<f32>>,
     left_rev: Vec<f32>,
     right_rev: Vec<f32>,
     directions: Vec<DirectionalSource>,
+    animated: Animated,
+    beat: BeatDetector,
+    haptics: Haptics,
+    captions: Captions,
+    view: FrequencyView,
+    frozen: bool,
+    export_requested: bool,
+    replay: ReplayBuffer,
+    eq: Equalizer,
+    calibration: Option<Calibration>,
+    mode: Mode,
+    surround: Option<Surround>,
+    ambisonic: Option<Ambisonic>,
+    tracker: Option<TrackerView>,
+    piano_roll: Option<PianoRoll>,
+    soundfont: Option<path::PathBuf>,
+    playlist: Playlist,
+    was_playing: bool,
+    watch: Option<Receiver<path::PathBuf>>,
+    bookmark_name: Option<String>,
+    library: Library,
+    picker: pipewire::Picker,
+    comparison: Option<Comparison>,
+    noise: NoiseGate,
+    agc: Agc,
+    hpss: Hpss,
+    features: Features,
+    hud: Hud,
+    cepstrum: Cepstrum,
+    phase: PhaseView,
+    correlation: Correlation,
+    pans: PanTracker,
+    balance: Balance,
+    dynamics: Dynamics,
+    tempo: TempoCurve,
+    metronome: Metronome,
+    scheduler: Scheduler,
+    strobe: Strobe,
+    attract: Option<attract::Attract>,
+    synth: Option<synth::Control>,
+    automation: automation::Automation,
+    timeline: Option<timeline::Timeline>,
+    safety: Safety,
+    tuner: Tuner,
+    session_path: path::PathBuf,
+    toast: Toast,
+    quality: Quality,
+    kaleidoscope: Kaleidoscope,
+    starfield: Starfield,
+    transition: Transition,
+    layout: Compositor,
+    secondary: Option<secondary::Link>,
+    force: ForceField,
+    gpio: Option<Gpio>,
+    bus: events::Bus,
+    detector: events::Detector,
+    deck: Option<deck::Server>,
+    frames: Option<frames::Sequence>,
+    cards: Option<cards::Cards>,
+    watermark: Option<watermark::Watermark>,
+    backdrop: Option<backdrop::Backdrop>,
+    emitter: Option<emit::Emitter>,
+    // The frame rate timecode is burned in at, when it is.
+    timecode: Option<u32>,
+    ltc: Option<timecode::Chase>,
+    metrics: Metrics,
+    watchdog: Option<Watchdog>,
+    hotplug: hotplug::Monitor,
+    // The input device asked for, when capturing from one.
+    capture: Option<device::Selection>,
+    debug: bool,
+    estimator: direction::Estimator,
+    levels: gpio::Levels,
+    stage: preset::Stage,
+    preset_name: String,
+    typography: Typography,
+    shader: Option<ShaderMode>,
+    skip_char: bool,
+    show_bass: bool,
+    background: graphics::Color,
+    scrub: f32,
+    scrub_time: f32,
 }
 
 impl MainState {
-    fn new(width: f32, height: f32) -> GameResult<Self> {
+    // `samples` is the window's multisampling, matched by the off-screen targets.
+    fn new(width: f32, height: f32, config: Config, samples: NumSamples) -> GameResult<Self> {
         let fft_size = 1024;
 
         let mut left_fft = Vec::with_capacity(fft_size);
@@ -60,19 +310,112 @@ impl MainState {
         let mut directions = Vec::with_capacity(fft_size / 2);
         directions.resize(directions.capacity(), DirectionalSource::new());
 
+        let agc = Agc::new(config.agc);
+        let quality = Quality::new(config.target_fps.unwrap_or(60.0));
+        let detector = events::Detector::new(&config.loudness_thresholds);
+        let metrics = Metrics::new(config.target_fps.unwrap_or(60.0));
+        let kaleidoscope = Kaleidoscope::new(config.kaleidoscope_segments.unwrap_or(6), samples);
+        let transition = Transition::new(config.transition, Mode::default(), samples);
+        let layout = Compositor::new(config.layouts.clone(), samples);
+        let force = ForceField::new(config.mouse, fft_size / 2);
+        let estimator = direction::Estimator::new(fft_size / 2, config.panning.clone());
+        let mut player = Player::new();
+        player.set_analysis_rate(if config.native_rate { None } else { Some(player::ANALYSIS_RATE) });
+        let scheduler = Scheduler::new(config.quantize);
+        let safety = Safety::new(config.photosensitive_safe);
+        let attract = config.attract.clone().map(attract::Attract::new);
+
         Ok(MainState {
             canvas_width: width,
             canvas_height: height,
-            sound: None,
-            sample_rate: 0,
-            left_wave: Vec::new(),
-            right_wave: Vec::new(),
+            config,
+            player,
             fft: FFTplanner::new(false).plan_fft(fft_size),
             left_fft,
             right_fft,
             left_rev,
             right_rev,
+            animated: Animated::new(directions.len()),
             directions,
+            beat: BeatDetector::new(),
+            haptics: Haptics::new(),
+            captions: Captions::new(),
+            view: FrequencyView::new(20.0, 20000.0),
+            frozen: false,
+            export_requested: false,
+            replay: ReplayBuffer::new(),
+            eq: Equalizer::new(),
+            calibration: None,
+            mode: Mode::Stage,
+            surround: None,
+            ambisonic: None,
+            tracker: None,
+            piano_roll: None,
+            soundfont: None,
+            playlist: Playlist::new(Vec::new())?,
+            was_playing: false,
+            watch: None,
+            bookmark_name: None,
+            library: Library::new(),
+            picker: pipewire::Picker::new(),
+            comparison: None,
+            noise: NoiseGate::new(fft_size / 2),
+            agc,
+            hpss: Hpss::new(fft_size / 2),
+            features: Features::default(),
+            hud: Hud::new(),
+            cepstrum: Cepstrum::new(fft_size),
+            phase: PhaseView::new(fft_size / 2),
+            correlation: Correlation::new(fft_size / 2),
+            pans: PanTracker::new(),
+            balance: Balance::new(),
+            dynamics: Dynamics::new(),
+            tempo: TempoCurve::new(),
+            metronome: Metronome::new(),
+            scheduler,
+            strobe: Strobe::new(),
+            attract,
+            synth: None,
+            automation: automation::Automation::new(),
+            timeline: None,
+            safety,
+            tuner: Tuner::new(),
+            session_path: path::PathBuf::from("session.toml"),
+            toast: Toast::new(),
+            quality,
+            kaleidoscope,
+            starfield: Starfield::new(),
+            transition,
+            layout,
+            secondary: None,
+            force,
+            gpio: None,
+            bus: events::Bus::default(),
+            detector,
+            deck: None,
+            frames: None,
+            cards: None,
+            watermark: None,
+            backdrop: None,
+            emitter: None,
+            timecode: None,
+            ltc: None,
+            metrics,
+            watchdog: None,
+            hotplug: hotplug::Monitor::start(),
+            capture: None,
+            debug: false,
+            estimator,
+            levels: gpio::Levels::new(),
+            stage: preset::Stage::default(),
+            preset_name: String::new(),
+            typography: Typography::new(samples),
+            shader: None,
+            skip_char: false,
+            show_bass: true,
+            background: graphics::BLACK,
+            scrub: 0.0,
+            scrub_time: 0.0,
         })
     }
 
@@ -80,120 +423,771 @@ impl MainState {
     where
         P: AsRef<path::Path>,
     {
-        self.left_wave.clear();
-        self.right_wave.clear();
-        self.sound = None;
-
-        let mut sound = audio::Source::new(ctx, path::Path::new("/").join(&path))?;
-        sound.set_volume(0.4);
-        self.sound = Some(sound);
-
-        let source = File::open(path)
-            .map_err(|err| err.to_string())
-            .and_then(|file| {
-                rodio::Decoder::new(BufReader::new(file))
-                    .map_err(|err| err.to_string())
-            });
-
-        match source {
-            Ok(source) if source.channels() == 2 => {
-                self.sample_rate = source.sample_rate();
-                dbg!(self.sample_rate);
-
-                let samples: Vec<_> = source.collect();
-                self.left_wave = samples
-                    .iter()
-                    .enumerate()
-                    .filter_map(|(idx, &amp)| if idx % 2 == 0 { Some(amp) } else { None })
-                    .map(|amp| amp as f32 / i16::MAX as f32)
-                    .collect();
-                self.right_wave = samples
-                    .iter()
-                    .enumerate()
-                    .filter_map(|(idx, &amp)| if idx % 2 != 0 { Some(amp) } else { None })
-                    .map(|amp| amp as f32 / i16::MAX as f32)
-                    .collect();
+        if tracker::is_module(path.as_ref()) {
+            return self.load_module(path, ctx);
+        }
+        if midi::is_midi(path.as_ref()) {
+            return self.load_midi(path, ctx);
+        }
+        self.piano_roll = None;
 
-                dbg!(self.left_wave.len());
-                dbg!(self.right_wave.len());
+        self.player.load(path, ctx)?;
+        self.reset_view();
+        self.ambisonic = None;
+        self.tracker = None;
 
-                Ok(())
+        let channels = self.player.channels().len();
+        if channels > 2 {
+            self.surround = Some(Surround::new(channels));
+            self.mode = Mode::Ring;
+        } else {
+            self.surround = None;
+            self.mode = Mode::Stage;
+        }
+
+        Ok(())
+    }
+
+    fn play_entry(&mut self, idx: usize, ctx: &mut Context, autoplay: bool) -> GameResult {
+        let path = match self.playlist.select(idx) {
+            Some(entry) => entry.path.clone(),
+            None => return Ok(()),
+        };
+
+        if let Err(err) = self.load_sound(&path, ctx) {
+            self.metrics.decode_errors += 1;
+            return Err(err);
+        }
+        if let Some(ref mut attract) = self.attract {
+            attract.stop();
+        }
+        self.synth = None;
+        self.pans.clear();
+        self.balance.clear();
+        self.dynamics.clear();
+        self.tempo.clear();
+        self.detector.clear();
+        self.bus.publish(Event::Track { path: path.clone() });
+        self.playlist.set_chapters(playlist::chapters_from_tags(self.player.tags()));
+        let analysis = Analysis::load(ctx, &path);
+        self.playlist.set_bookmarks(analysis.bookmarks);
+        self.automation.load(analysis.automation);
+        if let Some(ref mut timeline) = self.timeline {
+            timeline.rewind();
+        }
+        if autoplay {
+            self.player.toggle(ctx)?;
+        }
+        self.was_playing = autoplay;
+
+        Ok(())
+    }
+
+    fn enqueue(&mut self, ctx: &mut Context, path: path::PathBuf) {
+        println!("Enqueued {}", path.display());
+        self.playlist.push(path);
+        let demo = self.attract.as_ref().is_some_and(|attract| attract.active());
+        if self.player.len() == 0 || demo {
+            if let Err(err) = self.play_entry(self.playlist.len() - 1, ctx, true) {
+                println!("{}", err);
             }
-            Ok(_) => Err(GameError::AudioError("Channels must be stereo".into())),
-            Err(err) => Err(GameError::FilesystemError(err)),
         }
     }
 
-    fn toggle_sound(&mut self) {
-        if let Some(ref mut sound) = self.sound {
-            if sound.playing() {
-                sound.pause();
-            } else if sound.stopped() {
-                sound.play().expect("Play stopped sound");
-            } else {
-                sound.resume();
+    // Plays on into the next entry that loads when a track ends, so one
+    // missing or broken file does not end the session.
+    fn advance(&mut self, ctx: &mut Context) {
+        let mut idx = self.playlist.index() + 1;
+        while idx < self.playlist.len() {
+            match self.play_entry(idx, ctx, true) {
+                Ok(()) => return,
+                Err(err) => self.notify(format!("Skipping {}: {}", idx + 1, err)),
             }
+            idx += 1;
         }
+        self.was_playing = false;
     }
-}
 
-impl event::EventHandler for MainState {
-    fn update(&mut self, _ctx: &mut Context) -> GameResult {
-        if let Some(ref sound) = self.sound {
-            if sound.playing() {
-                let time = sound.elapsed().as_secs_f32();
-                let offset = (time * self.sample_rate as f32).floor() as usize;
-
-                if offset + self.left_fft.len() <= self.left_wave.len()
-                    && offset + self.right_fft.len() <= self.right_wave.len()
-                {
-                    let mut left_input: Vec<_> = (&self.left_wave
-                        [offset..offset + self.left_fft.len()])
-                        .into_iter()
-                        .map(|&amp| Complex::new(amp, 0.0))
-                        .collect();
-                    self.fft.process(left_input.as_mut_slice(), self.left_fft.as_mut_slice());
+    fn skip_entry(&mut self, ctx: &mut Context, forward: bool) -> GameResult {
+        let idx = self.playlist.index();
+        let idx = if forward { idx + 1 } else { idx.wrapping_sub(1) };
+        if idx < self.playlist.len() {
+            let playing = self.player.playing();
+            self.play_entry(idx, ctx, playing)?;
+        }
+        Ok(())
+    }
 
-                    let mut right_input: Vec<_> = (&self.right_wave
-                        [offset..offset + self.right_fft.len()])
-                        .into_iter()
-                        .map(|&amp| Complex::new(amp, 0.0))
-                        .collect();
-                    self.fft.process(right_input.as_mut_slice(), self.right_fft.as_mut_slice());
+    fn skip_chapter(&mut self, ctx: &mut Context, forward: bool) -> GameResult {
+        let time = self.player.time();
+        let target = if forward {
+            self.playlist.next_chapter(time)
+        } else {
+            self.playlist.previous_chapter(time)
+        };
+        match target {
+            Some(target) => {
+                let offset = (target * self.player.sample_rate() as f32) as usize;
+                self.player.seek(ctx, offset)
+            }
+            None => Ok(()),
+        }
+    }
+
+    fn save_bookmark(&mut self, ctx: &mut Context, name: String) -> GameResult {
+        let path = match self.playlist.current() {
+            Some(entry) => entry.path.clone(),
+            None => return Ok(()),
+        };
+
+        let name = if name.trim().is_empty() {
+            format!("Bookmark {}", self.playlist.bookmarks().len() + 1)
+        } else {
+            name.trim().to_string()
+        };
+        self.playlist.add_bookmark(Bookmark {
+            name,
+            time: self.player.time(),
+        });
+
+        let mut analysis = Analysis::load(ctx, &path);
+        analysis.bookmarks = self.playlist.bookmarks().to_vec();
+        analysis.save(ctx)
+    }
+
+    fn skip_bookmark(&mut self, ctx: &mut Context, forward: bool) -> GameResult {
+        let time = self.player.time();
+        let target = if forward {
+            self.playlist.next_bookmark(time)
+        } else {
+            self.playlist.previous_bookmark(time)
+        };
+        match target {
+            Some(target) => {
+                let offset = (target * self.player.sample_rate() as f32) as usize;
+                self.player.seek(ctx, offset)
+            }
+            None => Ok(()),
+        }
+    }
+
+    // Synthesized sound is made at the analysis rate rather than resampled to it.
+    fn render_rate(&self) -> u32 {
+        self.player.analysis_rate().unwrap_or(generator::SAMPLE_RATE)
+    }
+
+    fn load_module<P>(&mut self, path: P, ctx: &mut Context) -> GameResult
+    where
+        P: AsRef<path::Path>,
+    {
+        let bytes = fs::read(&path).map_err(|err| {
+            GameError::FilesystemError(format!("{}: {}", path.as_ref().display(), err))
+        })?;
+        let song = tracker::Module::parse(&bytes)?.render(self.render_rate());
+
+        let (left, right) = song.mix();
+        self.player.load_samples(ctx, left, right, song.sample_rate)?;
+        self.reset_view();
+
+        self.tracker = Some(TrackerView::new(song));
+        self.piano_roll = None;
+        self.ambisonic = None;
+        self.surround = None;
+        self.mode = Mode::Tracker;
+
+        Ok(())
+    }
+
+    fn load_midi<P>(&mut self, path: P, ctx: &mut Context) -> GameResult
+    where
+        P: AsRef<path::Path>,
+    {
+        let read = |path: &path::Path| {
+            fs::read(path).map_err(|err| GameError::FilesystemError(format!("{}: {}", path.display(), err)))
+        };
+        let sequence = midi::parse(&read(path.as_ref())?)?;
+        let rate = self.render_rate();
+        let font = match self.soundfont {
+            Some(ref font) => SoundFont::parse(&read(font)?)?,
+            None => SoundFont::builtin(rate),
+        };
 
-                    for idx in 0..self.directions.len() {
-                        let source = &mut self.directions[idx];
+        let (left, right) = midi::render(&sequence, &font, rate);
+        self.player.load_samples(ctx, left, right, rate)?;
+        self.reset_view();
 
-                        let left_amp = self.left_fft[idx].re.abs();
-                        let right_amp = self.right_fft[idx].re.abs();
+        self.piano_roll = Some(PianoRoll::new(sequence.notes));
+        self.tracker = None;
+        self.ambisonic = None;
+        self.surround = None;
+        self.mode = Mode::Stage;
 
-                        self.left_rev[idx] += (left_amp - self.left_rev[idx]) * 0.9;
-                        self.right_rev[idx] += (right_amp - self.right_rev[idx]) * 0.9;
+        Ok(())
+    }
+
+    fn load_ambisonic<P>(&mut self, path: P, format: ambisonic::Format, ctx: &mut Context) -> GameResult
+    where
+        P: AsRef<path::Path>,
+    {
+        self.player.load(path, ctx)?;
+
+        let channels = self.player.take_channels();
+        let ambisonic = Ambisonic::new(channels, format, self.left_fft.len()).ok_or_else(|| {
+            GameError::AudioError("Ambisonic files must have exactly 4 channels".into())
+        })?;
+
+        let (left, right) = ambisonic.decode_stereo();
+        let sample_rate = self.player.sample_rate();
+        self.player.load_samples(ctx, left, right, sample_rate)?;
+        self.reset_view();
+
+        self.ambisonic = Some(ambisonic);
+        self.tracker = None;
+        self.piano_roll = None;
+        self.surround = None;
+        self.mode = Mode::Stage;
+
+        Ok(())
+    }
+
+    fn load_signal(&mut self, signal: generator::Signal, ctx: &mut Context) -> GameResult {
+        let secs = match signal {
+            generator::Signal::Sweep => 20.0,
+            generator::Signal::Demo => generator::DEMO_SECS,
+            _ => 30.0,
+        };
+        let rate = self.render_rate();
+        let (left, right) = generator::generate(signal, rate, secs);
+        self.player.load_samples(ctx, left, right, rate)?;
+        self.reset_view();
+        Ok(())
+    }
+
+    fn reset_view(&mut self) {
+        let bin_hz = self.bin_hz();
+        self.view = FrequencyView::new(32.0 * bin_hz, self.directions.len() as f32 * bin_hz);
+    }
 
-                        source.amp = self.left_rev[idx].max(self.right_rev[idx]);
-                        source.dir = (self.right_rev[idx] - self.left_rev[idx]) / source.amp.max(1.0);
+    // Moves a frame render on to the next track that loads; false after the last.
+    fn next_render(&mut self, ctx: &mut Context) -> GameResult<bool> {
+        let mut idx = self.playlist.index() + 1;
+        while idx < self.playlist.len() {
+            match self.play_entry(idx, ctx, false) {
+                Ok(()) => {
+                    let path = self.playlist.current().map(|entry| entry.path.clone()).unwrap_or_default();
+                    if let Some(ref mut frames) = self.frames {
+                        frames.start(&path, idx, self.playlist.len())?;
+                    }
+                    if let Some(ref mut cards) = self.cards {
+                        cards.load(ctx, &path, self.player.tags());
                     }
+                    return Ok(true);
                 }
+                Err(err) => println!("Skipping {}: {}", idx + 1, err),
             }
+            idx += 1;
         }
+        Ok(false)
+    }
 
-        Ok(())
+    // What a rendered frame shows, for tools that line things up with it.
+    fn frame_data(&self, beat: bool) -> serde_json::Value {
+        let bands: Vec<_> = bands::Scale::Bark
+            .aggregate(&self.directions, self.bin_hz())
+            .into_iter()
+            .map(|(hz, source)| {
+                serde_json::json!({ "hz": hz, "amp": source.amp, "dir": source.dir, "confidence": source.confidence })
+            })
+            .collect();
+        serde_json::json!({
+            "beat": beat,
+            "bass": self.bass(),
+            "bpm": self.tempo.bpm(),
+            "lufs": self.dynamics.short_term(),
+            "centroid": self.features.centroid,
+            "flatness": self.features.flatness,
+            "rolloff": self.features.rolloff,
+            "zcr": self.features.zcr,
+            "bands": bands,
+        })
     }
 
-    fn draw(&mut self, ctx: &mut Context) -> GameResult {
-        graphics::clear(ctx, [0.0, 0.0, 0.0, 1.0].into());
+    // The time a frame stands for, fixed while rendering an image sequence.
+    fn frame_delta(&self, ctx: &Context) -> f32 {
+        match self.frames {
+            Some(ref frames) => frames.delta(),
+            None => timer::delta(ctx).as_secs_f32(),
+        }
+    }
+
+    fn bin_hz(&self) -> f32 {
+        self.player.sample_rate() as f32 / self.left_fft.len() as f32
+    }
+
+    // The stage is split into one lane per source; returns a lane's center and height.
+    fn lane(&self, lane: usize) -> (f32, f32) {
+        let count = if self.comparison.is_some() { 2.0 } else { 1.0 };
+        let height = self.canvas_height / count;
+        (height * (lane as f32 + 0.5), height)
+    }
+
+    // The track title for the typography mode, or the time when asked for or untitled.
+    fn ambient_label(&self) -> String {
+        let title = match self.config.ambient {
+            Ambient::Clock => None,
+            Ambient::Title if self.player.live() => None,
+            Ambient::Title => self.player.tags().get("TITLE").map(str::to_string).or_else(|| {
+                let entry = self.playlist.current()?;
+                Some(entry.path.file_stem()?.to_string_lossy().into_owned())
+            }),
+        };
+        title.unwrap_or_else(typography::clock)
+    }
+
+    // Seconds of audio heard so far, on the analysis' clock.
+    fn playback_time(&self) -> f32 {
+        (self.player.position() + self.player.dropped()) as f32 / self.player.sample_rate() as f32
+    }
 
+    // How far playback is through the current beat, 0 to 1.
+    fn beat_phase(&self) -> f32 {
+        let now = self.playback_time();
+        match self.tempo.beat() {
+            Some((period, beat, _)) => ((now - beat) / period).rem_euclid(1.0),
+            None => 0.0,
+        }
+    }
+
+    fn next_mode(&mut self) {
+        self.mode = match self.mode {
+            Mode::Stage if self.surround.is_some() => Mode::Ring,
+            Mode::Stage if self.tracker.is_some() => Mode::Tracker,
+            Mode::Stage | Mode::Ring | Mode::Tracker => Mode::Cepstrum,
+            Mode::Cepstrum => Mode::Phase,
+            Mode::Phase => Mode::Tuner,
+            Mode::Tuner => Mode::Typography,
+            Mode::Typography if self.shader.is_some() => Mode::Shader,
+            Mode::Typography | Mode::Shader => Mode::Stage,
+        };
+    }
+
+    // Every mode on screen: the current one, or the panes of the layout.
+    fn shown_modes(&self) -> Vec<Mode> {
+        match self.layout.active() {
+            Some(layout) => layout.panes.iter().map(|pane| pane.mode).collect(),
+            None => vec![self.mode],
+        }
+    }
+
+    fn orientation(&self) -> Orientation {
+        self.config.orientation.get(self.mode)
+    }
+
+    fn bar(&self, idx: usize) -> Option<(graphics::Rect, graphics::Color)> {
+        self.bar_in(&self.animated.sources()[idx], idx as f32 * self.bin_hz(), 0)
+    }
+
+    fn bar_in(&self, source: &DirectionalSource, hz: f32, lane: usize) -> Option<(graphics::Rect, graphics::Color)> {
         let padding = 64.0;
 
-        let bass = self.directions.iter()
-            .skip(1)
-            .take(4)
-            .fold(0.0, |acc, source| acc + source.amp * 0.08) / 32.0;
-        if bass > 0.0 {
-            let max_height = 96.0;
+        let amp = source.amp * self.agc.gain() * self.stage.sensitivity;
+        let alpha = (amp * 0.08 * 255.0).min(255.0).floor() as u8;
+
+        if alpha < 8 {
+            return None;
+        }
+
+        let (y, lane_height) = self.lane(lane);
+        let scale = lane_height / self.canvas_height;
+
+        let width = amp * 0.5 * self.stage.width;
+        let height = (self.canvas_height / 5.0 + amp * 8.0) * scale;
+
+        let x = (source.dir + 1.0) / 2.0;
+        let x = padding + x * (self.canvas_width - padding * 2.0);
+
+        let rect = graphics::Rect::new(x - width / 2.0, y - height / 2.0, width, height);
+        Some((rect, self.stage.color(self.view.fraction(hz), alpha)))
+    }
+
+    fn hovered_bin(&self, x: f32, y: f32) -> Option<usize> {
+        self.view
+            .bins(self.bin_hz(), self.directions.len())
+            .filter_map(|idx| self.bar(idx).map(|(rect, _)| (idx, rect)))
+            .filter(|(_, rect)| {
+                x >= rect.left() - 4.0
+                    && x <= rect.right() + 4.0
+                    && y >= rect.top()
+                    && y <= rect.bottom()
+            })
+            .max_by(|(a, _), (b, _)| {
+                self.directions[*a].amp.total_cmp(&self.directions[*b].amp)
+            })
+            .map(|(idx, _)| idx)
+    }
+
+    // Printed for scripts watching the console, and shown briefly on screen.
+    fn notify(&mut self, message: String) {
+        println!("{}", message);
+        self.toast.show(message);
+    }
+
+    // For key and button handlers, which cannot return the error.
+    fn report(&mut self, what: &str, result: GameResult) {
+        if let Err(err) = result {
+            self.notify(format!("{}: {}", what, err));
+        }
+    }
+
+    fn export_frame(&mut self, ctx: &mut Context) -> GameResult {
+        let time = self.player.position() as f32 / self.player.sample_rate() as f32;
+        let name = format!("frame-{:.3}", time);
+
+        inspect::write_csv(
+            format!("{}.csv", name),
+            self.bin_hz(),
+            &self.left_rev,
+            &self.right_rev,
+            &self.directions,
+        )?;
+        features::write_csv(format!("{}-features.csv", name), &self.features)?;
+        pan::write_csv(format!("{}-pans.csv", name), &self.pans.events)?;
+        self.balance.write_png(format!("{}-balance.png", name))?;
+        export::screenshot(ctx, format!("{}.png", name))?;
+
+        self.notify(format!("Exported {}", name));
+        Ok(())
+    }
+
+    fn export_spectrogram(&mut self, path: Option<&path::Path>) -> GameResult {
+        let name = match path {
+            Some(path) => path.to_path_buf(),
+            None => {
+                let stem = self
+                    .playlist
+                    .current()
+                    .and_then(|entry| entry.path.file_stem())
+                    .map_or_else(|| "live".to_string(), |stem| stem.to_string_lossy().into_owned());
+                path::PathBuf::from(format!("spectrogram-{}.png", stem))
+            }
+        };
+        spectrogram::write_png(
+            &name,
+            self.player.left_wave(),
+            self.player.right_wave(),
+            self.player.sample_rate(),
+        )?;
+        self.notify(format!("Exported {}", name.display()));
+        Ok(())
+    }
+
+    fn save_session(&mut self) -> GameResult {
+        let session = Session {
+            files: self.playlist.paths(),
+            current: self.playlist.index(),
+            time: self.player.time(),
+            eq: self.eq.gains().to_vec(),
+            eq_playback: self.eq.playback,
+            mode: self.mode,
+            zoom: Some(self.view.range()),
+            bookmarks: self.playlist.bookmarks().to_vec(),
+        };
+        session.save(&self.session_path)?;
+        self.notify(format!("Saved session to {}", self.session_path.display()));
+        Ok(())
+    }
+
+    fn restore_session(&mut self, session: Session, ctx: &mut Context) -> GameResult {
+        self.playlist = Playlist::new(session.files)?;
+        self.play_entry(session.current, ctx, false)?;
+        if !session.bookmarks.is_empty() {
+            self.playlist.set_bookmarks(session.bookmarks);
+        }
+
+        let offset = (session.time.max(0.0) * self.player.sample_rate() as f32) as usize;
+        self.player.seek(ctx, offset)?;
+
+        self.eq.set_gains(&session.eq);
+        self.eq.playback = session.eq_playback;
+        self.apply_eq(ctx)?;
+
+        if self.available(session.mode) {
+            self.mode = session.mode;
+        }
+        if let Some((low, high)) = session.zoom {
+            self.view.set_range(low, high);
+        }
+        Ok(())
+    }
+
+    // Surround, pattern and shader views only exist for the sources that have them.
+    fn available(&self, mode: Mode) -> bool {
+        match mode {
+            Mode::Ring => self.surround.is_some(),
+            Mode::Tracker => self.tracker.is_some(),
+            Mode::Shader => self.shader.is_some(),
+            _ => true,
+        }
+    }
+
+    fn preset(&self) -> Preset {
+        Preset {
+            name: self.preset_name.clone(),
+            mode: self.mode,
+            stage: self.stage,
+            tuner: self.tuner.settings,
+            typography: self.typography.settings,
+            starfield: self.starfield.settings,
+            kaleidoscope: self.kaleidoscope.settings,
+        }
+    }
+
+    fn apply_preset(&mut self, ctx: &mut Context, preset: Preset) -> GameResult {
+        if self.available(preset.mode) {
+            self.mode = preset.mode;
+        }
+        self.stage = preset.stage;
+        self.tuner.settings = preset.tuner;
+        self.typography.settings = preset.typography;
+        self.starfield.set(preset.starfield);
+        self.kaleidoscope.set(ctx, preset.kaleidoscope)?;
+        self.preset_name = preset.name;
+        Ok(())
+    }
+
+    fn surprise(&mut self, ctx: &mut Context, seed: u32) -> GameResult {
+        self.apply_preset(ctx, Preset::surprise(seed))?;
+        self.notify(format!("Surprise seed {} (Ctrl + number keeps it)", seed));
+        Ok(())
+    }
+
+    // Number keys load presets, with Ctrl they save the current look there.
+    fn preset_key(&mut self, ctx: &mut Context, number: usize, save: bool) -> GameResult {
+        let path = Preset::slot(ctx, number);
+        if save {
+            let mut preset = self.preset();
+            if preset.name.is_empty() {
+                preset.name = format!("Preset {}", number);
+            }
+            preset.save(&path)?;
+            self.preset_name = preset.name;
+            self.notify(format!("Saved {}", path.display()));
+        } else if path.exists() {
+            let preset = Preset::load(&path)?;
+            self.notify(format!("Preset {}: {}", number, preset.name));
+            self.apply_preset(ctx, preset)?;
+        } else {
+            self.notify(format!("No preset {} yet, Ctrl+{} saves one", number, number));
+        }
+        Ok(())
+    }
+
+    // Nothing here may end the session: attract mode runs unattended.
+    fn update_attract(&mut self, ctx: &mut Context, delta: f32) {
+        let attract = match self.attract {
+            Some(ref mut attract) => attract,
+            None => return,
+        };
+        let idle = self.player.len() == 0 && !self.player.live() && !self.picker.open;
+        let finished = !self.player.playing() && self.player.time() + 0.5 >= self.player.duration();
+        match attract.update(idle, finished, delta) {
+            Some(attract::Step::Play) => {
+                let started = self.load_signal(generator::Signal::Demo, ctx).and_then(|()| self.player.toggle(ctx));
+                self.report("Demo", started);
+            }
+            Some(attract::Step::Show(Some(slot))) => {
+                let shown = self.run_action(ctx, Action::Preset(slot));
+                self.report("Preset", shown);
+            }
+            Some(attract::Step::Show(None)) => self.next_mode(),
+            None => (),
+        }
+    }
+
+    // Runs `action` now, or on the next beat or bar while quantizing.
+    fn schedule(&mut self, ctx: &mut Context, action: Action) -> GameResult {
+        if !self.player.playing() {
+            return self.run_action(ctx, action);
+        }
+        match self.scheduler.push(action, &self.tempo, self.playback_time()) {
+            Some(action) => self.run_action(ctx, action),
+            None => {
+                self.toast.show(format!("On the {}", self.scheduler.grid.name()));
+                Ok(())
+            }
+        }
+    }
+
+    fn run_action(&mut self, ctx: &mut Context, action: Action) -> GameResult {
+        match action {
+            Action::NextMode => self.next_mode(),
+            Action::Preset(number) => {
+                // A hand-edited preset that no longer parses should not stop the show.
+                let loaded = self.preset_key(ctx, number, false);
+                self.report("Preset", loaded);
+            }
+            Action::Strobe => {
+                if !self.strobe.start(self.tempo.beat().map(|(period, _, _)| period), &self.safety) {
+                    self.toast.show("No strobe in photosensitive safe mode");
+                }
+            }
+            Action::Kaleidoscope => self.kaleidoscope.toggle(ctx)?,
+            Action::Starfield => self.starfield.settings.enabled = !self.starfield.settings.enabled,
+        }
+        self.automation.record(self.player.time(), action);
+        Ok(())
+    }
+
+    fn update_timeline(&mut self, ctx: &mut Context) -> GameResult {
+        let time = self.player.time();
+        let playing = self.player.playing() && !self.replay.active();
+        let (keys, values) = match self.timeline {
+            Some(ref mut timeline) if playing => (timeline.update(time), timeline.values(time)),
+            Some(ref timeline) => (Vec::new(), timeline.values(time)),
+            None => return Ok(()),
+        };
+        for key in keys {
+            if let Some(slot) = key.preset {
+                self.run_action(ctx, Action::Preset(slot))?;
+            }
+            if let Some(mode) = key.mode.filter(|&mode| self.available(mode)) {
+                self.mode = mode;
+            }
+            if key.strobe {
+                self.run_action(ctx, Action::Strobe)?;
+            }
+        }
+        // Every frame, over whatever a preset just set.
+        if let Some(sensitivity) = values.sensitivity {
+            self.stage.sensitivity = sensitivity;
+        }
+        if let Some(width) = values.width {
+            self.stage.width = width;
+        }
+        if let Some(low) = values.low {
+            self.stage.low = low;
+        }
+        if let Some(high) = values.high {
+            self.stage.high = high;
+        }
+        if let Some(warp) = values.warp {
+            self.starfield.settings.warp = warp;
+        }
+        Ok(())
+    }
+
+    // Starts recording changes against the track's time, or stops and keeps them.
+    fn toggle_automation(&mut self, ctx: &mut Context) -> GameResult {
+        let path = match self.playlist.current() {
+            Some(entry) if !self.player.live() && self.player.len() > 0 => entry.path.clone(),
+            _ => {
+                self.toast.show("Only tracks can be automated");
+                return Ok(());
+            }
+        };
+        match self.automation.stop() {
+            Some(cues) => {
+                self.notify(format!("Recorded {} changes", cues.len()));
+                let mut analysis = Analysis::load(ctx, &path);
+                analysis.automation = cues;
+                analysis.save(ctx)?;
+            }
+            None => {
+                self.automation.start();
+                self.toast.show("Recording changes");
+            }
+        }
+        Ok(())
+    }
+
+    fn deck_command(&mut self, ctx: &mut Context, command: deck::Command) -> GameResult {
+        match command {
+            deck::Command::PlayPause => self.player.toggle(ctx),
+            deck::Command::Next => self.skip_entry(ctx, true),
+            deck::Command::Previous => self.skip_entry(ctx, false),
+            deck::Command::Mode => self.schedule(ctx, Action::NextMode),
+            deck::Command::Preset { slot } => self.schedule(ctx, Action::Preset(slot)),
+        }
+    }
+
+    // How far behind the playing position the analysis window sits, in
+    // seconds. A live input's samples already arrive a capture block late,
+    // so that much of the configured latency is taken as done.
+    fn alignment_latency(&self) -> f32 {
+        let capture = self.player.capture_latency().unwrap_or(0.0);
+        (self.config.latency - capture).max(0.0)
+    }
+
+    // Frame timing and the latencies the picture is lined up with.
+    fn draw_debug(&self, ctx: &mut Context) -> GameResult {
+        let mut lines = vec![
+            format!("{:.0} fps, {:.1} ms of work per frame", timer::fps(ctx), self.quality.work() * 1000.0),
+            format!("{} Hz, configured latency {:.0} ms", self.player.sample_rate(), self.config.latency.max(0.0) * 1000.0),
+        ];
+        if let Some(latency) = self.player.capture_latency() {
+            lines.push(format!("capture blocks of {:.1} ms", latency * 1000.0));
+        }
+        lines.push(format!("analysis held back {:.1} ms", self.alignment_latency() * 1000.0));
+        overlay::label(
+            ctx,
+            lines.join("\n"),
+            14.0,
+            Anchor::TopRight,
+            [self.canvas_width - 16.0, 16.0],
+            graphics::WHITE,
+        )
+    }
+
+    // Incoming LTC when chasing it, otherwise the track's own time.
+    fn draw_timecode(&self, ctx: &mut Context, fps: u32) -> GameResult {
+        let timecode = match self.ltc.as_ref().and_then(timecode::Chase::current) {
+            Some(timecode) => timecode,
+            None => timecode::Timecode::from_secs(self.player.time(), fps),
+        };
+        let text = overlay::text(timecode.to_string(), 28.0);
+        let size = (self.canvas_width, self.canvas_height);
+        overlay::panel(ctx, &text, Anchor::Bottom, [size.0 / 2.0, size.1 - 48.0], graphics::WHITE, size)
+    }
+
+    fn device_change(&mut self, ctx: &mut Context, change: hotplug::Change) -> GameResult {
+        match change {
+            hotplug::Change::Output(name) => {
+                ctx.audio_context = hotplug::default_output()?;
+                // Playing again from here puts the sound on the new device.
+                let position = self.player.position();
+                self.player.seek(ctx, position)?;
+                self.notify(format!("Audio output: {}", name));
+            }
+            hotplug::Change::Input(name) => {
+                let channels = match self.capture {
+                    Some((ref wanted, channels)) if *wanted == name => channels,
+                    _ => None,
+                };
+                self.player.load_live(device::open(name.as_deref(), channels)?);
+                self.reset_view();
+                self.notify(format!("Audio input: {}", name.as_deref().unwrap_or("default device")));
+            }
+        }
+        Ok(())
+    }
+
+    fn apply_eq(&mut self, ctx: &mut Context) -> GameResult {
+        let filters = if self.eq.playback {
+            self.eq.filters(self.player.sample_rate())
+        } else {
+            Vec::new()
+        };
+        self.player.set_filters(ctx, filters)
+    }
+
+    fn draw_stage(&self, ctx: &mut Context) -> GameResult {
+        let bass = self.bass() * self.agc.gain();
+        if self.show_bass && bass > 0.0 {
+            let (y, lane_height) = self.lane(0);
+            let max_height = 96.0 * lane_height / self.canvas_height;
             let height = (bass * max_height).min(max_height);
             let alpha = (height / max_height * 255.0).min(255.0).floor() as u8;
-            let rect = graphics::Rect::new(0.0, (self.canvas_height - height) / 2.0, self.canvas_width, height);
+            let rect = graphics::Rect::new(0.0, y - height / 2.0, self.canvas_width, height);
             let mesh = graphics::Mesh::new_rectangle(
                 ctx,
                 graphics::DrawMode::fill(),
@@ -203,35 +1197,612 @@ impl event::EventHandler for MainState {
             graphics::draw(ctx, &mesh, DrawParam::default())?;
         }
 
-        for idx in 32..self.directions.len() {
-            let source = &self.directions[idx];
+        if self.hpss.enabled && self.config.bands == bands::Scale::Bins {
+            self.draw_hpss(ctx)?;
+        } else {
+            self.draw_sources(ctx, self.animated.sources(), self.bin_hz(), 0)?;
+        }
+
+        if let Some(ref comparison) = self.comparison {
+            self.draw_sources(ctx, comparison.animated.sources(), comparison.bin_hz(), 1)?;
 
-            let alpha = (source.amp * 0.08 * 255.0).min(255.0).floor() as u8;
+            let (y, lane_height) = self.lane(1);
+            let top = y - lane_height / 2.0;
+            let line = graphics::Mesh::new_line(
+                ctx,
+                &[[0.0, top], [self.canvas_width, top]],
+                1.0,
+                graphics::Color::from_rgba(255, 255, 255, 40),
+            )?;
+            graphics::draw(ctx, &line, DrawParam::default())?;
 
-            if alpha < 8 {
-                continue;
+            overlay::label(
+                ctx,
+                comparison.name.as_str(),
+                16.0,
+                Anchor::TopLeft,
+                [16.0, top + 8.0],
+                graphics::Color::from_rgb(160, 160, 160),
+            )?;
+        }
+
+        Ok(())
+    }
+
+    fn draw_sources(&self, ctx: &mut Context, directions: &[DirectionalSource], bin_hz: f32, lane: usize) -> GameResult {
+        let mut draw = |bar: Option<(graphics::Rect, graphics::Color)>| match bar {
+            Some((rect, color)) => {
+                let mesh = graphics::Mesh::new_rectangle(ctx, graphics::DrawMode::fill(), rect, color)?;
+                graphics::draw(ctx, &mesh, DrawParam::default())
             }
+            None => Ok(()),
+        };
 
-            let width = source.amp * 0.5;
-            let height = self.canvas_height / 5.0 + source.amp * 8.0;
+        if self.config.bands == bands::Scale::Bins {
+            // Under load each bar stands for the loudest of its neighbours.
+            let stride = self.quality.stride();
+            for idx in self.view.bins(bin_hz, directions.len()).step_by(stride) {
+                let end = (idx + stride).min(directions.len());
+                let loudest = (idx..end)
+                    .max_by(|&a, &b| directions[a].amp.total_cmp(&directions[b].amp))
+                    .unwrap_or(idx);
+                let bar = self.bar_in(&directions[loudest], loudest as f32 * bin_hz, lane);
+                // Only the main lane hangs on the cursor's springs.
+                let [dx, dy] = if lane == 0 { self.force.offset(loudest) } else { [0.0, 0.0] };
+                draw(bar.map(|(rect, color)| (graphics::Rect::new(rect.x + dx, rect.y + dy, rect.w, rect.h), color)))?;
+            }
+        } else {
+            for (hz, source) in self.config.bands.aggregate(directions, bin_hz) {
+                if self.view.contains(hz) {
+                    draw(self.bar_in(&source, hz, lane))?;
+                }
+            }
+        }
+        Ok(())
+    }
 
-            let x = (source.dir + 1.0) / 2.0;
-            let x = padding + x * (self.canvas_width - padding * 2.0);
+    // Draws one mode into `width` by `height`, except the stage, which lays its
+    // bars out against the whole window.
+    fn draw_mode(&self, ctx: &mut Context, mode: Mode, phase: f32, (width, height): (f32, f32)) -> GameResult {
+        match mode {
+            Mode::Stage => {
+                self.draw_stage(ctx)?;
+                self.pans.draw(ctx, self.canvas_width, self.canvas_height, self.quality.stride())?;
+                let bin_hz = self.bin_hz();
+                self.correlation.draw(ctx, &self.view, bin_hz, self.canvas_width, self.canvas_height)?;
+            }
+            Mode::Ring => {
+                if let Some(ref surround) = self.surround {
+                    surround.draw(ctx, width, height)?;
+                }
+            }
+            Mode::Tracker => {
+                if let Some(ref tracker) = self.tracker {
+                    tracker.draw(ctx, width, height)?;
+                }
+            }
+            Mode::Cepstrum => self.cepstrum.draw(ctx, width, height)?,
+            Mode::Phase => self.phase.draw(ctx, &self.view, width, height)?,
+            Mode::Tuner => self.tuner.draw(ctx, width, height)?,
+            Mode::Shader => {
+                if let Some(ref shader) = self.shader {
+                    let cursor = mouse::position(ctx);
+                    let frame = shader::Frame {
+                        time: self.player.time(),
+                        delta: self.frame_delta(ctx),
+                        beat: phase,
+                        sample_rate: self.player.sample_rate(),
+                        mouse: [cursor.x, cursor.y],
+                        frame: timer::ticks(ctx),
+                    };
+                    shader.draw(ctx, &frame, width, height)?;
+                }
+            }
+            Mode::Typography => {
+                let sources = self.animated.sources();
+                self.typography.draw(ctx, sources, self.bin_hz(), self.agc.gain(), (width, height))?
+            }
+        }
+        Ok(())
+    }
 
-            let y = self.canvas_height / 2.0;
+    // Harmonic content as wide, soft warm ribbons; percussive content as thin
+    // cold spikes on top.
+    fn draw_hpss(&self, ctx: &mut Context) -> GameResult {
+        let bin_hz = self.bin_hz();
+        let sources = self.animated.sources();
+        for idx in self.view.bins(bin_hz, sources.len()) {
+            let source = sources[idx];
+            let harmonic = self.hpss.harmonic(idx);
+            let freq = self.view.color(idx as f32 * bin_hz);
 
-            let freq = (idx as f32 / self.directions.len() as f32 * 255.0).floor() as u8;
+            let soft = DirectionalSource { amp: source.amp * harmonic, ..source };
+            if let Some((mut rect, color)) = self.bar_in(&soft, idx as f32 * bin_hz, 0) {
+                rect.x -= rect.w * 0.5;
+                rect.w *= 2.0;
+                let color = graphics::Color::from_rgba(255, freq, 64, (color.a * 0.6 * 255.0) as u8);
+                let mesh = graphics::Mesh::new_rectangle(ctx, graphics::DrawMode::fill(), rect, color)?;
+                graphics::draw(ctx, &mesh, DrawParam::default())?;
+            }
 
-            let rect = graphics::Rect::new(x - width / 2.0, y - height / 2.0, width, height);
-            let mesh = graphics::Mesh::new_rectangle(
+            let sharp = DirectionalSource { amp: source.amp * (1.0 - harmonic), ..source };
+            if let Some((rect, color)) = self.bar_in(&sharp, idx as f32 * bin_hz, 0) {
+                let (x, y) = (rect.x + rect.w / 2.0, rect.y + rect.h / 2.0);
+                let rect = graphics::Rect::new(x - 1.0, y - rect.h * 0.75, 2.0, rect.h * 1.5);
+                let color = graphics::Color::from_rgba(freq, 255, 255, (color.a * 255.0) as u8);
+                let mesh = graphics::Mesh::new_rectangle(ctx, graphics::DrawMode::fill(), rect, color)?;
+                graphics::draw(ctx, &mesh, DrawParam::default())?;
+            }
+        }
+        Ok(())
+    }
+
+    fn bass(&self) -> f32 {
+        self.directions.iter()
+            .skip(1)
+            .take(4)
+            .fold(0.0, |acc, source| acc + source.amp * 0.08) / 32.0
+    }
+}
+
+impl event::EventHandler for MainState {
+    fn update(&mut self, ctx: &mut Context) -> GameResult {
+        let delta = self.frame_delta(ctx);
+        let finished = self.frames.as_ref().is_some_and(|frames| frames.finished(self.player.duration()));
+        if finished && !self.next_render(ctx)? {
+            event::quit(ctx);
+            return Ok(());
+        }
+        if let Some(ref frames) = self.frames {
+            let position = frames.track_time(self.player.duration()) * self.player.sample_rate() as f32;
+            self.player.set_clock(Some(position as usize));
+        }
+        self.quality.begin();
+        let shown = self.shown_modes();
+        self.toast.update(delta);
+        self.strobe.update(delta);
+        if let Some(ref mut watchdog) = self.watchdog {
+            if watchdog.update(&mut self.player, delta) {
+                self.reset_view();
+            }
+        }
+        let capture = self.capture.as_ref().filter(|_| self.player.live());
+        for change in self.hotplug.poll(capture) {
+            if let Err(err) = self.device_change(ctx, change) {
+                self.notify(format!("Switching devices failed: {}", err));
+            }
+        }
+
+        let commands = self.deck.as_ref().map(deck::Server::commands).unwrap_or_default();
+        for command in commands {
+            // A remote must not be able to end the session.
+            let done = self.deck_command(ctx, command);
+            self.report("Deck", done);
+        }
+        if let Some(ref mut deck) = self.deck {
+            let status = deck::Status {
+                playing: self.player.playing(),
+                mode: self.mode,
+                preset: self.preset_name.clone(),
+                bpm: self.tempo.bpm(),
+                time: self.player.time(),
+                duration: self.player.duration(),
+            };
+            deck.update(&status, delta);
+        }
+
+        self.player.update();
+        self.haptics.update();
+        if let Some(ref mut ltc) = self.ltc {
+            ltc.update();
+        }
+
+        if self.scrub.abs() > 0.2 {
+            self.scrub_time += delta;
+            if self.scrub_time >= 0.25 {
+                self.player.seek_by(ctx, self.scrub * self.scrub_time * 10.0)?;
+                self.scrub_time = 0.0;
+            }
+        }
+
+        if self.replay.active() {
+            if !self.frozen {
+                if let Some(frame) = self.replay.advance(delta) {
+                    self.left_rev.copy_from_slice(&frame.left_rev);
+                    self.right_rev.copy_from_slice(&frame.right_rev);
+                    self.directions.copy_from_slice(&frame.directions);
+                }
+            }
+        } else if self.player.playing() && !self.frozen {
+            let latency = (self.alignment_latency() * self.player.sample_rate() as f32) as usize;
+            let offset = self.player.position().saturating_sub(latency);
+            let left_wave = self.player.left_wave();
+            let right_wave = self.player.right_wave();
+            let started = Instant::now();
+            self.dynamics.update(
+                left_wave,
+                right_wave,
+                self.player.dropped(),
+                self.player.position(),
+                self.player.sample_rate(),
+            );
+
+            if offset + self.left_fft.len() <= left_wave.len()
+                && offset + self.right_fft.len() <= right_wave.len()
+            {
+                let window = offset..offset + self.left_fft.len();
+                let rms = (left_wave[window.clone()]
+                    .iter()
+                    .chain(&right_wave[window])
+                    .map(|amp| amp * amp)
+                    .sum::<f32>()
+                    / (self.left_fft.len() * 2) as f32)
+                    .sqrt();
+
+                if shown.contains(&Mode::Ring) {
+                    if let Some(ref mut surround) = self.surround {
+                        surround.update(self.player.channels(), offset);
+                    }
+                }
+                if let Some(ref mut tracker) = self.tracker {
+                    tracker.update(offset, self.left_fft.len());
+                }
+
+                let mut left_input: Vec<_> = left_wave
+                    [offset..offset + self.left_fft.len()]
+                    .iter()
+                    .map(|&amp| Complex::new(amp, 0.0))
+                    .collect();
+                self.fft.process(left_input.as_mut_slice(), self.left_fft.as_mut_slice());
+
+                let mut right_input: Vec<_> = right_wave
+                    [offset..offset + self.right_fft.len()]
+                    .iter()
+                    .map(|&amp| Complex::new(amp, 0.0))
+                    .collect();
+                self.fft.process(right_input.as_mut_slice(), self.right_fft.as_mut_slice());
+
+                let bin_hz = self.bin_hz();
+                self.features = Features::compute(
+                    &self.left_fft,
+                    &self.right_fft,
+                    &left_wave[offset..offset + self.left_fft.len()],
+                    &right_wave[offset..offset + self.right_fft.len()],
+                    bin_hz,
+                );
+                self.hud.update(self.features);
+                if shown.contains(&Mode::Cepstrum) || shown.contains(&Mode::Tuner) {
+                    self.cepstrum.update(&self.left_fft, &self.right_fft, self.player.sample_rate());
+                }
+                if shown.contains(&Mode::Tuner) {
+                    self.tuner.update(
+                        self.cepstrum.peak().map(|(hz, _)| hz),
+                        &left_wave[offset..offset + self.left_fft.len()],
+                        &right_wave[offset..offset + self.right_fft.len()],
+                        self.player.sample_rate(),
+                        delta,
+                    );
+                }
+                if self.correlation.enabled {
+                    self.correlation.update(&self.left_fft, &self.right_fft);
+                }
+                if shown.contains(&Mode::Phase) {
+                    self.phase.update(&self.left_fft, &self.right_fft, self.player.sample_rate());
+                }
+                if let (true, Some(shader)) = (shown.contains(&Mode::Shader), self.shader.as_mut()) {
+                    let end = offset + self.left_fft.len();
+                    let wave = (&left_wave[..end], &right_wave[..end]);
+                    shader.update(&self.left_fft, &self.right_fft, wave, self.player.sample_rate());
+                }
+
+                let bins = self.directions.len();
+                for idx in 0..bins {
+                    let source = &mut self.directions[idx];
+
+                    let gain = self.eq.gain(idx as f32 * bin_hz);
+                    let (left_amp, right_amp) = self.noise.apply(
+                        idx,
+                        self.left_fft[idx].re.abs() * gain,
+                        self.right_fft[idx].re.abs() * gain,
+                    );
+
+                    let (amp, confidence) = self.estimator.step(
+                        idx,
+                        (self.left_fft[idx], self.right_fft[idx]),
+                        (left_amp, right_amp),
+                        (&mut self.left_rev[idx], &mut self.right_rev[idx]),
+                        &mut source.dir,
+                        bin_hz,
+                    );
+                    source.amp = amp;
+                    source.confidence = confidence;
+                }
+
+                self.noise.end_frame(delta);
+                self.agc.update(&self.directions);
+                self.hpss.update(&self.directions);
+
+                if let Some(ref mut ambisonic) = self.ambisonic {
+                    ambisonic.apply(offset, &mut self.directions);
+                }
+
+                let bass = self.bass();
+                if self.beat.process(bass, delta) {
+                    self.bus.publish(Event::Beat { strength: bass });
+                }
+
+                self.captions.update(&self.directions, bin_hz, rms, delta);
+
+                let time = (offset + self.player.dropped()) as f32 / self.player.sample_rate() as f32;
+                self.pans.update(&self.directions, bin_hz, time);
+                self.balance.update(&self.directions);
+                self.tempo.update(&self.directions, time);
+                self.metronome.update(&self.tempo, self.playback_time(), delta);
+                self.detector.update(&mut self.bus, &self.dynamics, &self.tempo, delta);
+                if let Some(ref mut emitter) = self.emitter {
+                    let bands: Vec<_> = bands::Scale::Bark
+                        .aggregate(&self.directions, bin_hz)
+                        .into_iter()
+                        .map(|(hz, source)| (hz, source.amp, source.dir))
+                        .collect();
+                    emitter.emit(&emit::Row { time, lufs: self.dynamics.short_term(), bands: &bands });
+                }
+                self.replay.record(time, &self.left_rev, &self.right_rev, &self.directions);
+
+                if let Some(ref mut link) = self.secondary {
+                    let size = self.left_fft.len();
+                    let rms = |wave: &[f32]| {
+                        let window = &wave[offset..offset + size];
+                        (window.iter().map(|amp| amp * amp).sum::<f32>() / window.len() as f32).sqrt()
+                    };
+                    let frame = secondary::Frame {
+                        bin_hz,
+                        levels: [rms(left_wave), rms(right_wave)],
+                        left: self.left_rev.clone(),
+                        right: self.right_rev.clone(),
+                    };
+                    if !link.send(&frame) {
+                        self.secondary = None;
+                    }
+                }
+            } else if self.player.live() {
+                self.metrics.underruns += 1;
+            }
+            self.metrics.analysis_secs = started.elapsed().as_secs_f32();
+        } else if !self.frozen && self.config.pause == Pause::Decay {
+            // Paused: no analysis, the picture just fades out.
+            let keep = (-delta / PAUSE_DECAY_SECS).exp();
+            for idx in 0..self.directions.len() {
+                self.left_rev[idx] *= keep;
+                self.right_rev[idx] *= keep;
+                self.directions[idx].amp *= keep;
+            }
+        }
+
+        if self.player.playing() && !self.replay.active() {
+            for action in self.scheduler.update(&self.tempo, self.playback_time()) {
+                self.run_action(ctx, action)?;
+            }
+            for action in self.automation.update(self.player.time()) {
+                self.run_action(ctx, action)?;
+            }
+        }
+        self.update_timeline(ctx)?;
+
+        if let Some(ref mut comparison) = self.comparison {
+            if !self.frozen && !self.replay.active() {
+                comparison.update();
+            }
+            comparison.animated.update(&comparison.directions, delta);
+        }
+        self.animated.update(&self.directions, delta);
+        let bass = self.bass() * self.agc.gain();
+        self.starfield.update(&self.directions, bass, delta);
+        if let Some(ref mut backdrop) = self.backdrop {
+            backdrop.update(ctx, bass, &self.safety, delta)?;
+        }
+        let cursor = mouse::position(ctx);
+        let size = (self.canvas_width, self.canvas_height);
+        let force = &self.force;
+        self.starfield.push(|pos| force.pull([cursor.x, cursor.y], pos), delta, size);
+        let rests: Vec<_> = (0..self.directions.len())
+            .map(|idx| self.bar(idx).map(|(rect, _)| [rect.x + rect.w / 2.0, rect.y + rect.h / 2.0]))
+            .collect();
+        let cursor = self.orientation().unmap([cursor.x, cursor.y], size.0, size.1);
+        self.force.update(cursor, &rests, delta);
+        self.transition.update(ctx, self.mode, delta)?;
+        self.layout.update(ctx)?;
+        if shown.contains(&Mode::Typography) {
+            let label = self.ambient_label();
+            self.typography.update(ctx, label, self.canvas_width, self.canvas_height)?;
+        }
+
+        self.library.update();
+
+        if let Some(ref watch) = self.watch {
+            let found: Vec<_> = watch.try_iter().collect();
+            for path in found {
+                self.enqueue(ctx, path);
+            }
+        }
+
+        self.update_attract(ctx, delta);
+        let playing = self.player.playing();
+        if self.was_playing
+            && !playing
+            && self.player.time() + 0.5 >= self.player.duration()
+            && self.playlist.index() + 1 < self.playlist.len()
+            && self.frames.is_none()
+        {
+            self.advance(ctx);
+        } else {
+            self.was_playing = playing;
+        }
+
+        let events = self.bus.dispatch();
+        let beat = events.iter().find_map(|event| match *event {
+            Event::Beat { strength } => Some(strength),
+            _ => None,
+        });
+        if let Some(strength) = beat {
+            self.haptics.pulse(strength);
+        }
+        if self.frames.as_ref().is_some_and(frames::Sequence::records) {
+            let entry = self.frame_data(beat.is_some());
+            let duration = self.player.duration();
+            if let Some(ref mut frames) = self.frames {
+                frames.record(entry, duration)?;
+            }
+        }
+        let bin_hz = self.bin_hz();
+        if let Some(ref mut gpio) = self.gpio {
+            self.levels.update(&self.directions, bin_hz, self.agc.gain(), beat.is_some(), delta);
+            gpio.update(&self.levels);
+        }
+
+        Ok(())
+    }
+
+    fn draw(&mut self, ctx: &mut Context) -> GameResult {
+        graphics::clear(ctx, self.background);
+        if let Some(ref backdrop) = self.backdrop {
+            backdrop.draw(ctx, (self.canvas_width, self.canvas_height))?;
+        }
+
+        let (width, height) = (self.canvas_width, self.canvas_height);
+        let phase = self.beat_phase();
+        if self.layout.active().is_some() {
+            self.starfield.draw(ctx, width, height)?;
+            self.layout.draw(ctx, (width, height), |ctx, mode, pane| {
+                let size = if mode == Mode::Stage { (width, height) } else { pane };
+                graphics::set_screen_coordinates(ctx, graphics::Rect::new(0.0, 0.0, size.0, size.1))?;
+                let orientation = self.config.orientation.get(mode);
+                orientation.draw(ctx, size.0, size.1, |ctx| self.draw_mode(ctx, mode, phase, size))
+            })?;
+        } else {
+            self.transition.render(ctx, self.background, |ctx, from| {
+                self.starfield.draw(ctx, width, height)?;
+                let orientation = self.config.orientation.get(from);
+                orientation.draw(ctx, width, height, |ctx| self.draw_mode(ctx, from, phase, (width, height)))
+            })?;
+            self.kaleidoscope.draw(ctx, (width, height), self.background, phase, |ctx| {
+                self.starfield.draw(ctx, width, height)?;
+                let orientation = self.orientation();
+                orientation.draw(ctx, width, height, |ctx| self.draw_mode(ctx, self.mode, phase, (width, height)))?;
+                self.transition.draw(ctx, width, height)
+            })?;
+        }
+
+        if let Some(ref piano_roll) = self.piano_roll {
+            let time = self.player.position() as f32 / self.player.sample_rate() as f32 - self.config.latency.max(0.0);
+            piano_roll.draw(ctx, self.canvas_width, self.canvas_height, time)?;
+        }
+        self.eq.draw(ctx, self.canvas_width, self.canvas_height)?;
+        self.view.draw_axis(ctx, self.canvas_width, self.canvas_height)?;
+        if let Some(ref calibration) = self.calibration {
+            calibration.draw(ctx, self.canvas_width, self.canvas_height)?;
+        }
+        self.captions.draw(ctx, self.canvas_width, self.canvas_height)?;
+        if !self.player.live() {
+            self.playlist.draw_progress(ctx, self.canvas_width, self.player.time(), self.player.duration())?;
+            self.tempo.draw(ctx, self.canvas_width, self.player.duration())?;
+        }
+        self.playlist.draw(ctx, self.canvas_width, self.canvas_height, self.player.time())?;
+
+        self.strobe.draw(ctx, self.canvas_width, self.canvas_height)?;
+
+        if let Some(time) = self.replay.time() {
+            overlay::label(
                 ctx,
-                graphics::DrawMode::fill(),
-                rect,
-                graphics::Color::from_rgba(freq, 128, 192, alpha),
+                format!("REPLAY {}x  {:.2}s", replay::SPEED, time),
+                20.0,
+                Anchor::TopLeft,
+                [16.0, 16.0],
+                graphics::Color::from_rgb(255, 96, 96),
             )?;
-            graphics::draw(ctx, &mesh, DrawParam::default())?;
         }
 
+        if self.automation.recording() {
+            let color = graphics::Color::from_rgb(255, 64, 64);
+            overlay::label(ctx, "REC changes", 20.0, Anchor::TopLeft, [16.0, 44.0], color)?;
+        }
+
+        if let Some(ref watermark) = self.watermark {
+            watermark.draw(ctx, (self.canvas_width, self.canvas_height))?;
+        }
+        if self.debug {
+            self.draw_debug(ctx)?;
+        }
+        if let (Some(cards), Some(frames)) = (&self.cards, &self.frames) {
+            let size = (self.canvas_width, self.canvas_height);
+            cards.draw(ctx, frames.time(), self.player.duration(), size)?;
+        }
+        if let Some(fps) = self.timecode {
+            self.draw_timecode(ctx, fps)?;
+        }
+
+        self.balance.draw(ctx, self.canvas_width, self.canvas_height)?;
+        self.dynamics.draw(ctx, self.canvas_width)?;
+        self.metronome.draw(ctx, self.canvas_width, self.canvas_height)?;
+        self.noise.draw(ctx, self.canvas_width)?;
+        self.hud.draw(ctx, self.canvas_height, self.player.sample_rate() as f32 / 2.0)?;
+        self.library.draw(ctx, self.canvas_width, self.canvas_height)?;
+        self.picker.draw(ctx, self.canvas_width, self.canvas_height)?;
+
+        if let Some(ref name) = self.bookmark_name {
+            let prompt = format!("Bookmark name: {}_\nEnter to save, Escape to cancel", name);
+            inspect::draw_readout(
+                ctx,
+                &prompt,
+                self.canvas_width / 2.0 - 160.0,
+                self.canvas_height / 3.0,
+                self.canvas_width,
+                self.canvas_height,
+            )?;
+        }
+
+        if self.export_requested {
+            self.export_requested = false;
+            let exported = self.export_frame(ctx);
+            self.report("Export frame", exported);
+        }
+        self.toast.draw(ctx, self.canvas_width, self.canvas_height)?;
+
+        if self.frozen && self.mode == Mode::Stage && self.config.bands == bands::Scale::Bins {
+            let cursor = mouse::position(ctx);
+            let (width, height) = (self.canvas_width, self.canvas_height);
+            let [x, y] = self.orientation().unmap([cursor.x, cursor.y], width, height);
+            if let Some(idx) = self.hovered_bin(x, y) {
+                let (rect, _) = self.bar(idx).expect("Hovered bar");
+                let mesh = graphics::Mesh::new_rectangle(
+                    ctx,
+                    graphics::DrawMode::stroke(1.0),
+                    rect,
+                    graphics::WHITE,
+                )?;
+                self.orientation()
+                    .draw(ctx, width, height, |ctx| graphics::draw(ctx, &mesh, DrawParam::default()))?;
+
+                let source = &self.directions[idx];
+                let lines = format!(
+                    "bin {} ({:.1} Hz)\nL {:.3}  R {:.3}\namp {:.3}  dir {:+.3}  confidence {:.2}",
+                    idx,
+                    idx as f32 * self.bin_hz(),
+                    self.left_rev[idx],
+                    self.right_rev[idx],
+                    source.amp,
+                    source.dir,
+                    source.confidence,
+                );
+                inspect::draw_readout(ctx, &lines, cursor.x, cursor.y, self.canvas_width, self.canvas_height)?;
+            }
+        }
+
+        self.quality.end(self.frame_delta(ctx));
+        self.metrics.end_frame(timer::fps(ctx), timer::delta(ctx).as_secs_f32());
+        if let Some(ref mut frames) = self.frames {
+            frames.capture(ctx, self.player.duration())?;
+        }
         graphics::present(ctx)?;
         Ok(())
     }
@@ -240,25 +1811,392 @@ impl event::EventHandler for MainState {
         &mut self,
         ctx: &mut Context,
         keycode: keyboard::KeyCode,
-        _keymod: keyboard::KeyMods,
+        keymod: keyboard::KeyMods,
         _repeat: bool,
     ) {
+        if self.picker.open {
+            match keycode {
+                keyboard::KeyCode::Return => {
+                    if let Some(node) = self.picker.chosen().cloned() {
+                        match pipewire::capture(&node) {
+                            Ok(input) => {
+                                self.player.load_live(input);
+                                self.reset_view();
+                                if let Some(ref mut attract) = self.attract {
+                                    attract.stop();
+                                }
+                                self.synth = None;
+                            }
+                            Err(err) => println!("{}", err),
+                        }
+                    }
+                    self.picker.open = false;
+                }
+                keyboard::KeyCode::Escape => self.picker.open = false,
+                keyboard::KeyCode::Up => self.picker.select_by(-1),
+                keyboard::KeyCode::Down => self.picker.select_by(1),
+                _ => (),
+            }
+            return;
+        }
+
+        if self.library.searching() {
+            match keycode {
+                keyboard::KeyCode::Return => {
+                    if let Some(path) = self.library.selected().map(path::Path::to_path_buf) {
+                        self.enqueue(ctx, path);
+                    }
+                }
+                keyboard::KeyCode::Escape => self.library.close(),
+                keyboard::KeyCode::Back => self.library.pop(),
+                keyboard::KeyCode::Up => self.library.select_by(-1),
+                keyboard::KeyCode::Down => self.library.select_by(1),
+                _ => (),
+            }
+            return;
+        }
+
+        if let Some(name) = self.bookmark_name.take() {
+            match keycode {
+                keyboard::KeyCode::Return => {
+                    let saved = self.save_bookmark(ctx, name);
+                    self.report("Save bookmark", saved);
+                }
+                keyboard::KeyCode::Escape => (),
+                keyboard::KeyCode::Back => {
+                    let mut name = name;
+                    name.pop();
+                    self.bookmark_name = Some(name);
+                }
+                _ => self.bookmark_name = Some(name),
+            }
+            return;
+        }
+
         match keycode {
-            keyboard::KeyCode::Space => self.toggle_sound(),
+            keyboard::KeyCode::Space => {
+                let toggled = self.player.toggle(ctx);
+                self.report("Toggle sound", toggled);
+            }
+            keyboard::KeyCode::C => self.captions.enabled = !self.captions.enabled,
+            keyboard::KeyCode::F => self.frozen = !self.frozen,
+            keyboard::KeyCode::E => self.export_requested = true,
+            keyboard::KeyCode::F5 => {
+                let saved = self.save_session();
+                self.report("Save session", saved);
+            }
+            keyboard::KeyCode::F9 => {
+                match Session::load(&self.session_path).and_then(|session| self.restore_session(session, ctx)) {
+                    Ok(()) => self.notify(format!("Restored session from {}", self.session_path.display())),
+                    Err(err) => self.notify(format!("Restore session: {}", err)),
+                }
+            }
+            keyboard::KeyCode::W => {
+                let exported = self.export_spectrogram(None);
+                self.report("Spectrogram export", exported);
+            }
+            keyboard::KeyCode::M => {
+                let scheduled = self.schedule(ctx, Action::NextMode);
+                self.report("Next mode", scheduled);
+            }
+            keyboard::KeyCode::Semicolon => {
+                let scheduled = self.schedule(ctx, Action::Strobe);
+                self.report("Strobe", scheduled);
+            }
+            keyboard::KeyCode::Slash | keyboard::KeyCode::Minus | keyboard::KeyCode::Equals => {
+                if let Some(ref mut synth) = self.synth {
+                    let message = match keycode {
+                        keyboard::KeyCode::Slash => synth.select_next(),
+                        up => synth.nudge(up == keyboard::KeyCode::Equals),
+                    };
+                    self.toast.show(message);
+                }
+            }
+            keyboard::KeyCode::Backslash => {
+                self.scheduler.grid = self.scheduler.grid.cycle();
+                self.toast.show(format!("Quantize: {}", self.scheduler.grid.name()));
+            }
+            keyboard::KeyCode::N => {
+                let normalize = !self.player.normalize();
+                self.player.set_normalize(normalize);
+            }
+            keyboard::KeyCode::Q => self.eq.editing = !self.eq.editing,
+            keyboard::KeyCode::P => {
+                self.eq.playback = !self.eq.playback;
+                let applied = self.apply_eq(ctx);
+                self.report("Apply EQ", applied);
+            }
+            keyboard::KeyCode::H => {
+                let binaural = !self.player.binaural();
+                let toggled = self.player.set_binaural(ctx, binaural);
+                self.report("Toggle binaural playback", toggled);
+            }
+            keyboard::KeyCode::O => {
+                if let Some(ref mut piano_roll) = self.piano_roll {
+                    piano_roll.enabled = !piano_roll.enabled;
+                }
+            }
+            keyboard::KeyCode::T => {
+                let time = self.player.position() as f32 / self.player.sample_rate() as f32;
+                let latency = self.calibration.as_mut().and_then(|calibration| calibration.tap(time));
+                if let Some(latency) = latency {
+                    self.config.latency = latency;
+                    let saved = self.config.save(ctx);
+                    self.report("Save config", saved);
+                }
+            }
+            keyboard::KeyCode::R => {
+                if self.replay.active() {
+                    self.replay.stop();
+                } else {
+                    self.replay.start();
+                }
+            }
+            keyboard::KeyCode::B => {
+                self.bookmark_name = Some(String::new());
+                self.skip_char = true;
+            }
+            keyboard::KeyCode::G => self.noise.toggle(),
+            keyboard::KeyCode::K => self.hpss.enabled = !self.hpss.enabled,
+            keyboard::KeyCode::U => self.hud.enabled = !self.hud.enabled,
+            keyboard::KeyCode::D => self.balance.visible = !self.balance.visible,
+            keyboard::KeyCode::Y => self.dynamics.visible = !self.dynamics.visible,
+            keyboard::KeyCode::X => self.tempo.visible = !self.tempo.visible,
+            keyboard::KeyCode::Z => self.metronome.cycle(),
+            keyboard::KeyCode::F6 => {
+                let scheduled = self.schedule(ctx, Action::Kaleidoscope);
+                self.report("Kaleidoscope canvas", scheduled);
+            }
+            keyboard::KeyCode::F3 => self.debug = !self.debug,
+            keyboard::KeyCode::F4 => {
+                let message = if self.force.toggle() { "Mouse force field on" } else { "Mouse force field off" };
+                self.notify(message.to_string());
+            }
+            keyboard::KeyCode::F12 => {
+                if self.secondary.take().is_some() {
+                    self.notify("Analysis window closed".to_string());
+                } else {
+                    match secondary::Link::spawn() {
+                        Ok(link) => self.secondary = Some(link),
+                        Err(err) => self.notify(format!("{}", err)),
+                    }
+                }
+            }
+            keyboard::KeyCode::F10 => {
+                let message = match self.layout.cycle() {
+                    Some(layout) => format!("Layout: {}", layout.name),
+                    None => "Layout: single mode".to_string(),
+                };
+                self.notify(message);
+            }
+            keyboard::KeyCode::F8 => {
+                let seed = SystemTime::now()
+                    .duration_since(UNIX_EPOCH)
+                    .map(|since| since.subsec_nanos() ^ since.as_secs() as u32)
+                    .unwrap_or(1);
+                let surprised = self.surprise(ctx, seed);
+                self.report("Surprise preset", surprised);
+            }
+            keyboard::KeyCode::F7 => {
+                let scheduled = self.schedule(ctx, Action::Starfield);
+                self.report("Starfield", scheduled);
+            }
+            keyboard::KeyCode::Grave => {
+                let toggled = self.toggle_automation(ctx);
+                self.report("Record automation", toggled);
+            }
+            keyboard::KeyCode::V => self.pans.enabled = !self.pans.enabled,
+            keyboard::KeyCode::J => self.correlation.enabled = !self.correlation.enabled,
+            keyboard::KeyCode::A => {
+                self.agc.enabled = !self.agc.enabled;
+                self.config.agc = self.agc.enabled;
+                let saved = self.config.save(ctx);
+                self.report("Save config", saved);
+            }
+            keyboard::KeyCode::I => self.picker.show(),
+            keyboard::KeyCode::L => {
+                self.library.open();
+                self.skip_char = true;
+            }
+            keyboard::KeyCode::Comma | keyboard::KeyCode::Period => {
+                let skipped = self.skip_bookmark(ctx, keycode == keyboard::KeyCode::Period);
+                self.report("Skip bookmark", skipped);
+            }
+            keyboard::KeyCode::S => {
+                let path = path::Path::new("playlist.m3u");
+                match playlist_file::write_m3u(path, &self.playlist.paths()) {
+                    Ok(()) => self.notify(format!("Saved {}", path.display())),
+                    Err(err) => self.notify(format!("Save playlist: {}", err)),
+                }
+            }
+            keyboard::KeyCode::Tab => self.playlist.visible = !self.playlist.visible,
+            keyboard::KeyCode::PageUp | keyboard::KeyCode::PageDown => {
+                let skipped = self.skip_entry(ctx, keycode == keyboard::KeyCode::PageDown);
+                self.report("Skip entry", skipped);
+            }
+            keyboard::KeyCode::LBracket | keyboard::KeyCode::RBracket => {
+                let skipped = self.skip_chapter(ctx, keycode == keyboard::KeyCode::RBracket);
+                self.report("Skip chapter", skipped);
+            }
             keyboard::KeyCode::Escape => event::quit(ctx),
+            _ => {
+                if let Some(number) = digit(keycode) {
+                    let result = if keymod.contains(keyboard::KeyMods::CTRL) {
+                        self.preset_key(ctx, number, true)
+                    } else {
+                        self.schedule(ctx, Action::Preset(number))
+                    };
+                    self.report("Preset", result);
+                }
+            }
+        }
+    }
+
+    fn text_input_event(&mut self, _ctx: &mut Context, character: char) {
+        // The key that opened the prompt arrives here too.
+        if std::mem::replace(&mut self.skip_char, false) {
+            return;
+        }
+        if character.is_control() {
+            return;
+        }
+        if self.library.searching() {
+            self.library.push(character);
+        } else if let Some(ref mut name) = self.bookmark_name {
+            name.push(character);
+        }
+    }
+
+    fn mouse_button_down_event(&mut self, _ctx: &mut Context, button: mouse::MouseButton, x: f32, y: f32) {
+        self.force.moved();
+        if button == mouse::MouseButton::Right {
+            self.force.reversed = true;
+        }
+        if button == mouse::MouseButton::Left {
+            if self.eq.editing {
+                self.eq.begin_edit(x, y, self.canvas_width, self.canvas_height);
+            } else {
+                self.view.begin_drag(x);
+            }
+        }
+    }
+
+    fn mouse_button_up_event(&mut self, ctx: &mut Context, button: mouse::MouseButton, _x: f32, _y: f32) {
+        if button == mouse::MouseButton::Right {
+            self.force.reversed = false;
+        }
+        if button == mouse::MouseButton::Left {
+            self.view.end_drag();
+            if self.eq.end_edit() && self.eq.playback {
+                let applied = self.apply_eq(ctx);
+                self.report("Apply EQ", applied);
+            }
+        }
+    }
+
+    fn mouse_motion_event(&mut self, _ctx: &mut Context, x: f32, y: f32, _dx: f32, _dy: f32) {
+        self.force.moved();
+        self.view.drag_to(x, self.canvas_width);
+        self.eq.edit_to(x, y, self.canvas_width, self.canvas_height);
+    }
+
+    fn mouse_wheel_event(&mut self, ctx: &mut Context, _x: f32, y: f32) {
+        self.view.zoom(y, mouse::position(ctx).x, self.canvas_width);
+    }
+}
+
+fn digit(keycode: keyboard::KeyCode) -> Option<usize> {
+    use keyboard::KeyCode::*;
+    [Key1, Key2, Key3, Key4, Key5, Key6, Key7, Key8, Key9]
+        .iter()
+        .position(|&key| key == keycode)
+        .map(|idx| idx + 1)
+}
+
+impl GestureHandler for MainState {
+    fn gesture_event(&mut self, ctx: &mut Context, gesture: Gesture) {
+        match gesture {
+            Gesture::Seek(secs) => {
+                let seeked = self.player.seek_by(ctx, secs);
+                self.report("Seek sound", seeked);
+            }
+            Gesture::Volume(change) => {
+                self.player.set_level(self.player.level() + change);
+                self.toast.show(format!("Volume {:.0}%", self.player.level() * 100.0));
+            }
+            Gesture::NextMode => {
+                let scheduled = self.schedule(ctx, Action::NextMode);
+                self.report("Next mode", scheduled);
+            }
+            Gesture::Zoom { steps, x } => self.view.zoom(steps, x, self.canvas_width),
+        }
+    }
+
+    fn button_event(&mut self, ctx: &mut Context, btn: event::Button) {
+        match btn {
+            event::Button::South | event::Button::Start => {
+                let toggled = self.player.toggle(ctx);
+                self.report("Toggle sound", toggled);
+            }
+            event::Button::LeftTrigger | event::Button::RightTrigger => {
+                let secs = if btn == event::Button::LeftTrigger { -10.0 } else { 10.0 };
+                let seeked = self.player.seek_by(ctx, secs);
+                self.report("Seek sound", seeked);
+            }
+            event::Button::North => self.haptics.enabled = !self.haptics.enabled,
+            event::Button::West => self.show_bass = !self.show_bass,
             _ => (),
         }
     }
+
+    fn scale_event(&mut self, ctx: &mut Context, factor: f32) {
+        // Everything is laid out in logical pixels, so only what was
+        // rasterized at the old resolution needs doing again; layout panes
+        // follow the drawable size themselves.
+        overlay::set_scale(factor);
+        self.typography.rescale();
+        let rescaled = self.kaleidoscope.rescale(ctx).and_then(|_| self.transition.rescale(ctx));
+        if let Err(err) = rescaled {
+            self.notify(format!("Rescaling: {}", err));
+        }
+    }
+
+    fn axis_event(&mut self, _ctx: &mut Context, axis: event::Axis, value: f32) {
+        if axis == event::Axis::LeftStickX {
+            self.scrub = value;
+            if value.abs() <= 0.2 {
+                self.scrub_time = 0.0;
+            }
+        }
+    }
 }
 
 fn main() -> GameResult {
-    let args: Vec<String> = env::args().skip(1).collect();
+    let options = cli::parse()?;
+
+    if options.register {
+        return register::register();
+    }
+    if options.list_devices {
+        return device::list();
+    }
+    if options.secondary {
+        return secondary::run();
+    }
+    if options.install {
+        return watchdog::supervise();
+    }
 
-    let width = 1024.0;
-    let height = 768.0;
+    if options.draft && options.frames.is_none() {
+        return Err(GameError::ConfigError("--draft previews a --frames render".into()));
+    }
+    let scale = if options.draft { frames::DRAFT_SCALE } else { 1.0 };
+    let width = 1024.0 / scale;
+    let height = 768.0 / scale;
 
     let win_setup = WindowSetup::default()
-        .title("Stereo Visualizer");
+        .title("Stereo Visualizer")
+        .samples(options.msaa);
     let win_mode = WindowMode::default()
         .dimensions(width, height);
 
@@ -268,16 +2206,191 @@ fn main() -> GameResult {
         .add_resource_path(path::PathBuf::from("."));
     let (ctx, event_loop) = &mut cb.build()?;
 
-    let state = &mut MainState::new(width, height)?;
-    
-    if args.len() == 1 {
-        state.load_sound(&args[0], ctx)?;
+    let config = Config::load(ctx)?;
+    overlay::init(ctx, config.font.as_deref())?;
+    if options.overlay {
+        floating::apply(ctx, options.opacity)?;
+    }
+    let state = &mut MainState::new(width, height, config, options.msaa)?;
+    let mut session = None;
+    if let Some(path) = options.session {
+        if path.exists() {
+            session = Some(Session::load(&path)?);
+        }
+        state.session_path = path;
+    }
+    if let Some([r, g, b]) = options.background {
+        state.background = graphics::Color::from_rgb(r, g, b);
+    }
+    state.show_bass = !options.no_bass;
+    if !state.config.outputs.is_empty() {
+        match Gpio::open(&state.config.outputs) {
+            Ok(gpio) => state.gpio = Some(gpio),
+            Err(err) => state.notify(format!("GPIO outputs: {}", err)),
+        }
+    }
+    if let Some(addr) = options.metrics {
+        if let Err(err) = state.metrics.serve(&addr) {
+            state.notify(format!("Metrics: {}", err));
+        }
     }
-    else {
-        state.load_sound("sound.mp3", ctx)?;
+    if let Some(addr) = options.deck {
+        match deck::Server::bind(&addr) {
+            Ok(server) => state.deck = Some(server),
+            Err(err) => state.notify(format!("Deck: {}", err)),
+        }
+    }
+    if let Some(settings) = state.config.watermark.clone() {
+        match watermark::Watermark::load(ctx, settings) {
+            Ok(watermark) => state.watermark = Some(watermark),
+            Err(err) => state.notify(format!("Watermark: {}", err)),
+        }
+    }
+    if let Some(ref settings) = state.config.mqtt {
+        match mqtt::Publisher::connect(settings) {
+            Ok(publisher) => state.bus.subscribe(Box::new(publisher)),
+            Err(err) => state.notify(format!("MQTT: {}", err)),
+        }
+    }
+    if let Some(path) = options.shader {
+        state.shader = Some(ShaderMode::load(ctx, &path)?);
+        state.mode = Mode::Shader;
+    }
+    if let Some(path) = options.preset {
+        let preset = Preset::load(&path)?;
+        state.apply_preset(ctx, preset)?;
+    }
+    if let Some(seed) = options.surprise {
+        state.surprise(ctx, seed)?;
+    }
+    state.soundfont = options.soundfont.or_else(|| state.config.soundfont.clone());
+    if options.jack {
+        state.player.load_live(jack::open("stereo-visualizer")?);
+        state.reset_view();
+    } else if let Some(format) = options.stdin_pcm {
+        state.player.load_live(pcm::stdin(format));
+        state.reset_view();
+    } else if let Some(addr) = options.udp.as_ref().or(options.rtp.as_ref()) {
+        // Bare UDP defaults to little-endian like --stdin-pcm, RTP to big-endian L16.
+        let rtp = options.rtp.is_some();
+        let default = if rtp { "s16be:48000:2" } else { "s16le:48000:2" };
+        let format = match options.pcm_format {
+            Some(format) => format,
+            None => pcm::Format::parse(default)?,
+        };
+        state.player.load_live(net::listen(addr, format, rtp)?);
+        state.reset_view();
+    } else if let Some(host) = &options.snapcast {
+        state.player.load_live(snapcast::connect(host, options.snapcast_play)?);
+        state.reset_view();
+    } else if let Some(addr) = &options.bridge {
+        state.player.load_live(bridge::listen(addr)?);
+        state.reset_view();
+    } else if options.synth {
+        let (input, control) = synth::start(state.config.synth.clone().unwrap_or_default());
+        state.player.load_live(input);
+        state.reset_view();
+        state.synth = Some(control);
+    } else if options.device.is_some() || options.input_channels.is_some() {
+        state.player.load_live(device::open(options.device.as_deref(), options.input_channels)?);
+        state.reset_view();
+        state.capture = Some((options.device.clone(), options.input_channels));
+        if options.supervised {
+            state.watchdog = Some(Watchdog::start(state.capture.clone()));
+        }
+    } else if options.pipewire {
+        state.picker.show();
+    } else if options.calibrate {
+        state.load_signal(generator::Signal::Clicks, ctx)?;
+        state.calibration = Some(Calibration::new());
+    } else if let Some(session) = session {
+        state.restore_session(session, ctx)?;
+    } else {
+        match options.generate {
+            Some(signal) => state.load_signal(signal, ctx)?,
+            None => {
+                let mut files = options.files;
+                if files.is_empty() && options.watch.is_none() {
+                    files.push(path::PathBuf::from("sound.mp3"));
+                }
+                state.playlist = Playlist::new(files)?;
+                match options.ambisonic {
+                    Some(format) => {
+                        let path = state.playlist.current().map(|entry| entry.path.clone()).unwrap_or_default();
+                        state.load_ambisonic(&path, format, ctx)?
+                    }
+                    None => {
+                        // Left idle for the demo to take over instead.
+                        if let Err(err) = state.play_entry(0, ctx, false) {
+                            if state.attract.is_none() {
+                                return Err(err);
+                            }
+                            println!("{}", err);
+                        }
+                    }
+                }
+            }
+        }
+    }
+
+    if let Some(ref output) = options.spectrogram {
+        return state.export_spectrogram(Some(output));
+    }
+    if let Some(template) = options.frames {
+        let mut sequence = if options.draft {
+            state.quality.pin(frames::DRAFT_QUALITY);
+            frames::Sequence::new(template, options.frame_rate / frames::DRAFT_HOP)
+        } else {
+            state.quality.pin(0);
+            frames::Sequence::new(template, options.frame_rate)
+        };
+        let path = state.playlist.current().map(|entry| entry.path.clone()).unwrap_or_default();
+        sequence.set_data(options.frame_data);
+        sequence.start(&path, state.playlist.index(), state.playlist.len())?;
+        if let Some(settings) = state.config.cards.clone() {
+            let mut cards = cards::Cards::new(settings);
+            cards.load(ctx, &path, state.player.tags());
+            sequence.set_cards(cards.intro_secs(), cards.outro_secs());
+            state.cards = Some(cards);
+        }
+        state.frames = Some(sequence);
+        // Left transparent for the footage underneath, unless a color was asked for.
+        if options.background.is_none() {
+            state.background = graphics::Color::new(0.0, 0.0, 0.0, 0.0);
+        }
+    }
+
+    if let Some(ref path) = options.timeline {
+        state.timeline = Some(timeline::Timeline::load(path)?);
+    }
+    if let Some(format) = options.emit {
+        state.emitter = Some(emit::Emitter::open(format, options.emit_to.clone()));
+    }
+    if let Some(ref source) = options.backdrop {
+        let offline = state.frames.as_ref().map(|frames| 1.0 / frames.delta());
+        state.backdrop = Some(backdrop::Backdrop::open(source, offline)?);
+    }
+    if options.timecode {
+        state.timecode = Some(options.frame_rate.round().max(1.0) as u32);
+    }
+    if let Some(name) = options.ltc {
+        let name = Some(name.as_str()).filter(|&name| name != "default");
+        state.ltc = Some(timecode::Chase::new(device::open(name, None)?));
+    }
+    if let Some(name) = options.compare_device {
+        let name = Some(name.as_str()).filter(|&name| name != "default");
+        state.comparison = Some(Comparison::new(device::open(name, None)?, state.left_fft.len(), state.config.panning.clone()));
+    }
+
+    state.watch = options.watch.map(watch::watch);
+    let library = state.config.library.clone();
+    state.library.scan(ctx, &library);
+
+    if options.supervised && state.watchdog.is_none() {
+        state.watchdog = Some(Watchdog::start(None));
     }
 
     println!("Ready");
 
-    event::run(ctx, event_loop, state)
+    touch::run(ctx, event_loop, state)
 }
@@ -0,0 +1,171 @@
+use ggez::GameResult;
+
+// A frame this much longer than the budget missed at least one refresh.
+const LATE: f32 = 1.5;
+
+// Health figures for long-running installations, counted all the time and
+// served for Prometheus to scrape when asked for.
+pub struct Metrics {
+    pub analysis_secs: f32,
+    pub underruns: u64,
+    pub decode_errors: u64,
+    budget: f32,
+    frames: u64,
+    dropped_frames: u64,
+    endpoint: endpoint::Endpoint,
+}
+
+impl Metrics {
+    pub fn new(target_fps: f32) -> Self {
+        Metrics {
+            analysis_secs: 0.0,
+            underruns: 0,
+            decode_errors: 0,
+            budget: 1.0 / target_fps.max(1.0),
+            frames: 0,
+            dropped_frames: 0,
+            endpoint: endpoint::Endpoint::new(),
+        }
+    }
+
+    pub fn serve(&mut self, addr: &str) -> GameResult {
+        self.endpoint.serve(addr)
+    }
+
+    pub fn end_frame(&mut self, fps: f64, delta: f32) {
+        self.frames += 1;
+        if delta > self.budget * LATE {
+            self.dropped_frames += 1;
+        }
+        self.endpoint.publish(self, fps);
+    }
+}
+
+// The HTTP endpoint, left out of builds without the network feature.
+#[cfg(feature = "network")]
+mod endpoint {
+    use super::Metrics;
+    use ggez::{error::GameError, GameResult};
+    use std::{
+        fmt::Write as _,
+        io::{BufRead, BufReader, Write},
+        net::{TcpListener, TcpStream},
+        sync::{Arc, Mutex},
+        thread,
+    };
+
+    #[derive(Debug, Clone, Copy, Default)]
+    struct Snapshot {
+        fps: f64,
+        analysis_secs: f32,
+        frames: u64,
+        dropped_frames: u64,
+        underruns: u64,
+        decode_errors: u64,
+    }
+
+    impl Snapshot {
+        fn render(&self) -> String {
+            let mut text = String::new();
+            let mut metric = |name: &str, kind: &str, help: &str, value: f64| {
+                let _ = write!(
+                    text,
+                    "# HELP stereo_visualizer_{0} {1}\n# TYPE stereo_visualizer_{0} {2}\nstereo_visualizer_{0} {3}\n",
+                    name, help, kind, value
+                );
+            };
+            metric("fps", "gauge", "Frames drawn per second.", self.fps);
+            metric("analysis_seconds", "gauge", "Time spent analysing the last frame.", f64::from(self.analysis_secs));
+            metric("frames_total", "counter", "Frames drawn.", self.frames as f64);
+            metric("dropped_frames_total", "counter", "Frames that missed the target rate.", self.dropped_frames as f64);
+            metric("underruns_total", "counter", "Frames a live input had too few samples for.", self.underruns as f64);
+            metric("decode_errors_total", "counter", "Files that failed to load.", self.decode_errors as f64);
+            text
+        }
+    }
+
+    fn respond(stream: TcpStream, snapshot: &Mutex<Snapshot>) -> std::io::Result<()> {
+        let mut reader = BufReader::new(stream.try_clone()?);
+        let mut request = String::new();
+        reader.read_line(&mut request)?;
+        // The headers are of no interest, but browsers wait for them to be read.
+        let mut line = String::new();
+        while reader.read_line(&mut line)? > 2 {
+            line.clear();
+        }
+
+        let mut stream = stream;
+        let path = request.split_whitespace().nth(1).unwrap_or("");
+        if path == "/metrics" {
+            let body = snapshot.lock().unwrap().render();
+            write!(
+                stream,
+                "HTTP/1.1 200 OK\r\nContent-Type: text/plain; version=0.0.4\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+                body.len(),
+                body
+            )
+        } else {
+            write!(stream, "HTTP/1.1 404 Not Found\r\nContent-Length: 0\r\nConnection: close\r\n\r\n")
+        }
+    }
+
+    pub struct Endpoint {
+        shared: Option<Arc<Mutex<Snapshot>>>,
+    }
+
+    impl Endpoint {
+        pub fn new() -> Self {
+            Endpoint { shared: None }
+        }
+
+        pub fn serve(&mut self, addr: &str) -> GameResult {
+            let listener = TcpListener::bind(addr)
+                .map_err(|err| GameError::ConfigError(format!("Cannot listen on {}: {}", addr, err)))?;
+            let shared = Arc::new(Mutex::new(Snapshot::default()));
+            let served = shared.clone();
+            thread::spawn(move || {
+                for stream in listener.incoming().flatten() {
+                    let _ = respond(stream, &served);
+                }
+            });
+            println!("Serving metrics on http://{}/metrics", addr);
+            self.shared = Some(shared);
+            Ok(())
+        }
+
+        pub fn publish(&self, metrics: &Metrics, fps: f64) {
+            if let Some(ref shared) = self.shared {
+                *shared.lock().unwrap() = Snapshot {
+                    fps,
+                    analysis_secs: metrics.analysis_secs,
+                    frames: metrics.frames,
+                    dropped_frames: metrics.dropped_frames,
+                    underruns: metrics.underruns,
+                    decode_errors: metrics.decode_errors,
+                };
+            }
+        }
+    }
+}
+
+#[cfg(not(feature = "network"))]
+mod endpoint {
+    use super::Metrics;
+    use ggez::{error::GameError, GameResult};
+
+    pub struct Endpoint;
+
+    impl Endpoint {
+        pub fn new() -> Self {
+            Endpoint
+        }
+
+        pub fn serve(&mut self, _addr: &str) -> GameResult {
+            Err(GameError::ConfigError(
+                "This build has no metrics endpoint; rebuild with `--features network`".into(),
+            ))
+        }
+
+        pub fn publish(&self, _metrics: &Metrics, _fps: f64) {}
+    }
+}
@@ -0,0 +1,123 @@
+use crate::{tempo::TempoCurve, view::AXIS_HEIGHT};
+use ggez::{
+    graphics::{self, DrawParam},
+    Context, GameResult,
+};
+use std::f32::consts::PI;
+
+const CLICK_RATE: u32 = 44100;
+const CLICK_SECS: f32 = 0.03;
+const FLASH_SECS: f32 = 0.15;
+// Beats further behind playback than this are skipped rather than clicked late.
+const LATE: f32 = 0.1;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Click {
+    Off,
+    Flash,
+    Audible,
+}
+
+fn click(hz: f32) -> Vec<f32> {
+    let len = (CLICK_RATE as f32 * CLICK_SECS) as usize;
+    (0..len)
+        .map(|idx| {
+            let t = idx as f32 / CLICK_RATE as f32;
+            (2.0 * PI * hz * t).sin() * (-t / CLICK_SECS * 6.0).exp() * 0.5
+        })
+        .collect()
+}
+
+// A click track that follows the detected beat grid, flashing on every beat
+// and optionally mixing a click in, with an accent on the downbeat.
+pub struct Metronome {
+    pub click: Click,
+    // Kept alive alongside the sink that plays through it.
+    output: Option<rodio::Device>,
+    sink: Option<rodio::Sink>,
+    last_beat: f32,
+    downbeat: bool,
+    beat_in_bar: usize,
+    flash: f32,
+}
+
+impl Metronome {
+    pub fn new() -> Self {
+        Metronome {
+            click: Click::Off,
+            output: None,
+            sink: None,
+            last_beat: f32::NEG_INFINITY,
+            downbeat: false,
+            beat_in_bar: 0,
+            flash: 0.0,
+        }
+    }
+
+    pub fn cycle(&mut self) {
+        self.click = match self.click {
+            Click::Off => Click::Flash,
+            Click::Flash => Click::Audible,
+            Click::Audible => Click::Off,
+        };
+        if self.click == Click::Audible && self.sink.is_none() {
+            self.output = rodio::default_output_device();
+            self.sink = self.output.as_ref().map(rodio::Sink::new);
+            if self.sink.is_none() {
+                println!("No output device for the metronome click");
+            }
+        }
+    }
+
+    // `now` is the playback time, which runs ahead of the analysed audio by
+    // the configured latency; the beat grid is extrapolated to it.
+    pub fn update(&mut self, tempo: &TempoCurve, now: f32, delta: f32) {
+        self.flash = (self.flash - delta / FLASH_SECS).max(0.0);
+        if self.click == Click::Off {
+            return;
+        }
+        let (period, anchor, bar) = match tempo.beat() {
+            Some(beat) => beat,
+            None => return,
+        };
+
+        let beat = anchor + ((now - anchor) / period).floor() * period;
+        if beat <= self.last_beat + period * 0.5 || now - beat > LATE {
+            return;
+        }
+        self.last_beat = beat;
+        self.beat_in_bar = (((beat - bar) / period).round() as i64).rem_euclid(4) as usize;
+        self.downbeat = self.beat_in_bar == 0;
+        self.flash = 1.0;
+
+        if self.click == Click::Audible {
+            if let Some(ref sink) = self.sink {
+                let hz = if self.downbeat { 1760.0 } else { 1320.0 };
+                sink.append(rodio::buffer::SamplesBuffer::new(1, CLICK_RATE, click(hz)));
+            }
+        }
+    }
+
+    pub fn draw(&self, ctx: &mut Context, width: f32, height: f32) -> GameResult {
+        if self.click == Click::Off {
+            return Ok(());
+        }
+
+        let y = height - AXIS_HEIGHT - 40.0;
+        let mut builder = graphics::MeshBuilder::new();
+        for idx in 0..4 {
+            let x = width / 2.0 + (idx as f32 - 1.5) * 24.0;
+            let lit = idx == self.beat_in_bar && self.flash > 0.0;
+            let alpha = if lit { 96 + (self.flash * 159.0) as u8 } else { 48 };
+            let color = if idx == 0 {
+                graphics::Color::from_rgba(255, 96, 64, alpha)
+            } else {
+                graphics::Color::from_rgba(255, 255, 255, alpha)
+            };
+            let radius = if lit { 6.0 + self.flash * 4.0 } else { 6.0 };
+            builder.circle(graphics::DrawMode::fill(), [x, y], radius, 0.5, color);
+        }
+        let mesh = builder.build(ctx)?;
+        graphics::draw(ctx, &mesh, DrawParam::default())
+    }
+}
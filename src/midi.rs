@@ -0,0 +1,380 @@
+use crate::{soundfont::SoundFont, view::AXIS_HEIGHT};
+use ggez::{
+    error::GameError,
+    graphics::{self, DrawParam},
+    Context, GameResult,
+};
+use std::path::Path;
+
+const DRUMS: u8 = 9;
+const TAIL: f32 = 2.0;
+const LOW_KEY: u8 = 21;
+const HIGH_KEY: u8 = 108;
+const PAST: f32 = 1.0;
+const AHEAD: f32 = 4.0;
+
+pub fn is_midi(path: &Path) -> bool {
+    path.extension()
+        .and_then(|ext| ext.to_str())
+        .is_some_and(|ext| ext.eq_ignore_ascii_case("mid") || ext.eq_ignore_ascii_case("midi"))
+}
+
+#[derive(Debug, Clone, Copy)]
+enum Kind {
+    NoteOn(u8, u8),
+    NoteOff(u8),
+    Program(u8),
+    Volume(u8),
+    Pan(u8),
+    Tempo(u32),
+}
+
+#[derive(Debug, Clone, Copy)]
+struct Event {
+    time: f32,
+    channel: u8,
+    kind: Kind,
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct Note {
+    pub start: f32,
+    pub end: f32,
+    pub key: u8,
+    pub channel: u8,
+}
+
+pub struct Sequence {
+    events: Vec<Event>,
+    pub notes: Vec<Note>,
+    pub length: f32,
+}
+
+fn read_var(bytes: &[u8], pos: &mut usize) -> Option<u32> {
+    let mut value = 0u32;
+    for _ in 0..4 {
+        let byte = *bytes.get(*pos)?;
+        *pos += 1;
+        value = (value << 7) | (byte & 0x7f) as u32;
+        if byte & 0x80 == 0 {
+            return Some(value);
+        }
+    }
+    Some(value)
+}
+
+fn read_track(track: &[u8], events: &mut Vec<(u32, Event)>) -> Option<()> {
+    let (mut pos, mut tick, mut status) = (0, 0u32, 0u8);
+
+    while pos < track.len() {
+        tick = tick.saturating_add(read_var(track, &mut pos)?);
+        if *track.get(pos)? & 0x80 != 0 {
+            status = track[pos];
+            pos += 1;
+        }
+
+        let channel = status & 0x0f;
+        let kind = match status {
+            0xff => {
+                let meta = *track.get(pos)?;
+                pos += 1;
+                let len = read_var(track, &mut pos)? as usize;
+                let data = track.get(pos..pos + len)?;
+                pos += len;
+                match meta {
+                    0x2f => return Some(()),
+                    0x51 if len == 3 => {
+                        Kind::Tempo(((data[0] as u32) << 16) | ((data[1] as u32) << 8) | data[2] as u32)
+                    }
+                    _ => continue,
+                }
+            }
+            0xf0 | 0xf7 => {
+                pos += read_var(track, &mut pos)? as usize;
+                continue;
+            }
+            _ => {
+                let data = track.get(pos..)?;
+                match status & 0xf0 {
+                    0x80 => {
+                        pos += 2;
+                        Kind::NoteOff(*data.first()?)
+                    }
+                    0x90 => {
+                        pos += 2;
+                        match (*data.first()?, *data.get(1)?) {
+                            (key, 0) => Kind::NoteOff(key),
+                            (key, vel) => Kind::NoteOn(key, vel),
+                        }
+                    }
+                    0xb0 => {
+                        pos += 2;
+                        match (*data.first()?, *data.get(1)?) {
+                            (7, value) => Kind::Volume(value),
+                            (10, value) => Kind::Pan(value),
+                            _ => continue,
+                        }
+                    }
+                    0xc0 => {
+                        pos += 1;
+                        Kind::Program(*data.first()?)
+                    }
+                    0xd0 => {
+                        pos += 1;
+                        continue;
+                    }
+                    0xa0 | 0xe0 => {
+                        pos += 2;
+                        continue;
+                    }
+                    _ => return None,
+                }
+            }
+        };
+
+        events.push((tick, Event { time: 0.0, channel, kind }));
+    }
+
+    Some(())
+}
+
+pub fn parse(bytes: &[u8]) -> GameResult<Sequence> {
+    let invalid = || GameError::AudioError("Invalid MIDI file".into());
+    if bytes.len() < 14 || &bytes[..4] != b"MThd" {
+        return Err(invalid());
+    }
+
+    let division = u16::from_be_bytes([bytes[12], bytes[13]]) as u32;
+    if division & 0x8000 != 0 {
+        return Err(GameError::AudioError("SMPTE-timed MIDI files are not supported".into()));
+    }
+
+    let mut events = Vec::new();
+    let mut pos = 8 + u32::from_be_bytes([bytes[4], bytes[5], bytes[6], bytes[7]]) as usize;
+    while pos + 8 <= bytes.len() {
+        let len = u32::from_be_bytes([bytes[pos + 4], bytes[pos + 5], bytes[pos + 6], bytes[pos + 7]]) as usize;
+        let body = &bytes[pos + 8..(pos + 8 + len).min(bytes.len())];
+        if &bytes[pos..pos + 4] == b"MTrk" {
+            read_track(body, &mut events).ok_or_else(invalid)?;
+        }
+        pos += 8 + len;
+    }
+
+    // A stable sort keeps same-tick events from different tracks in file order.
+    events.sort_by_key(|(tick, _)| *tick);
+
+    let (mut last_tick, mut last_time, mut tempo) = (0, 0.0f64, 500_000u32);
+    let mut timed = Vec::with_capacity(events.len());
+    for (tick, mut event) in events {
+        last_time += (tick - last_tick) as f64 * tempo as f64 / division.max(1) as f64 / 1e6;
+        last_tick = tick;
+        event.time = last_time as f32;
+        match event.kind {
+            Kind::Tempo(value) => tempo = value.max(1),
+            _ => timed.push(event),
+        }
+    }
+
+    let mut notes: Vec<Note> = Vec::new();
+    for event in &timed {
+        match event.kind {
+            Kind::NoteOn(key, _) => notes.push(Note {
+                start: event.time,
+                end: f32::INFINITY,
+                key,
+                channel: event.channel,
+            }),
+            Kind::NoteOff(key) => {
+                if let Some(note) = notes.iter_mut().rev().find(|note| {
+                    note.key == key && note.channel == event.channel && note.end.is_infinite()
+                }) {
+                    note.end = event.time;
+                }
+            }
+            _ => (),
+        }
+    }
+
+    let length = timed.last().map_or(0.0, |event| event.time);
+    for note in notes.iter_mut().filter(|note| note.end.is_infinite()) {
+        note.end = length;
+    }
+
+    Ok(Sequence {
+        events: timed,
+        notes,
+        length,
+    })
+}
+
+struct Voice {
+    channel: u8,
+    key: u8,
+    sample: usize,
+    pos: f64,
+    step: f64,
+    gain: (f32, f32),
+    looped: bool,
+    level: f32,
+    release_secs: f32,
+    release: Option<f32>,
+}
+
+#[derive(Clone, Copy)]
+struct ChannelState {
+    program: u8,
+    volume: f32,
+    pan: f32,
+}
+
+fn mix(font: &SoundFont, voices: &mut Vec<Voice>, left: &mut [f32], right: &mut [f32]) {
+    for voice in voices.iter_mut() {
+        let header = &font.headers[voice.sample];
+        let loop_len = header.loop_end.saturating_sub(header.loop_start) as f64;
+        let looped = voice.looped && loop_len > 0.0;
+
+        for (left, right) in left.iter_mut().zip(right.iter_mut()) {
+            let mut pos = header.start as f64 + voice.pos;
+            if looped && pos >= header.loop_end as f64 {
+                voice.pos -= loop_len;
+                pos -= loop_len;
+            }
+            if pos >= header.end as f64 || voice.level <= 0.0 {
+                voice.level = 0.0;
+                break;
+            }
+
+            let idx = pos as usize;
+            let frac = (pos - idx as f64) as f32;
+            let next = font.samples.get(idx + 1).copied().unwrap_or(0.0);
+            let amp = font.samples.get(idx).map_or(0.0, |&amp| amp + (next - amp) * frac) * voice.level;
+
+            *left += amp * voice.gain.0;
+            *right += amp * voice.gain.1;
+
+            voice.pos += voice.step;
+            if let Some(rate) = voice.release {
+                voice.level -= rate;
+            }
+        }
+    }
+    voices.retain(|voice| voice.level > 0.0);
+}
+
+pub fn render(sequence: &Sequence, font: &SoundFont, sample_rate: u32) -> (Vec<f32>, Vec<f32>) {
+    let rate = sample_rate as f32;
+    let len = ((sequence.length + TAIL) * rate) as usize;
+    let mut left = vec![0.0; len];
+    let mut right = vec![0.0; len];
+
+    let mut channels = [ChannelState { program: 0, volume: 100.0 / 127.0, pan: 0.0 }; 16];
+    let mut voices: Vec<Voice> = Vec::new();
+    let mut cursor = 0;
+
+    for event in &sequence.events {
+        let until = ((event.time * rate) as usize).min(len);
+        if until > cursor {
+            mix(font, &mut voices, &mut left[cursor..until], &mut right[cursor..until]);
+            cursor = until;
+        }
+
+        let state = &mut channels[event.channel as usize];
+        match event.kind {
+            Kind::NoteOn(key, vel) => {
+                let bank = if event.channel == DRUMS { 128 } else { 0 };
+                for region in font.regions(bank, state.program as u16, key, vel) {
+                    let header = &font.headers[region.sample];
+                    let pitch = 2.0f64.powf((key as f64 - region.root as f64) / 12.0);
+                    let pan = (state.pan + region.pan).clamp(-0.5, 0.5) + 0.5;
+                    let gain = region.gain * state.volume * (vel as f32 / 127.0).powi(2) * 0.5;
+                    voices.push(Voice {
+                        channel: event.channel,
+                        key,
+                        sample: region.sample,
+                        pos: 0.0,
+                        step: pitch * header.sample_rate as f64 / sample_rate as f64,
+                        gain: (gain * (1.0 - pan), gain * pan),
+                        looped: region.looped,
+                        level: 1.0,
+                        release_secs: region.release,
+                        release: None,
+                    });
+                }
+            }
+            Kind::NoteOff(key) => {
+                for voice in voices.iter_mut().filter(|voice| {
+                    voice.channel == event.channel && voice.key == key && voice.release.is_none()
+                }) {
+                    voice.release = Some(1.0 / (voice.release_secs * rate));
+                }
+            }
+            Kind::Program(program) => state.program = program,
+            Kind::Volume(value) => state.volume = value as f32 / 127.0,
+            Kind::Pan(value) => state.pan = value as f32 / 127.0 - 0.5,
+            Kind::Tempo(_) => (),
+        }
+    }
+
+    mix(font, &mut voices, &mut left[cursor..], &mut right[cursor..]);
+
+    let peak = left.iter().chain(&right).fold(0.0f32, |acc, amp| acc.max(amp.abs()));
+    if peak > 0.95 {
+        let gain = 0.95 / peak;
+        left.iter_mut().chain(right.iter_mut()).for_each(|amp| *amp *= gain);
+    }
+
+    (left, right)
+}
+
+pub struct PianoRoll {
+    notes: Vec<Note>,
+    pub enabled: bool,
+}
+
+impl PianoRoll {
+    pub fn new(notes: Vec<Note>) -> Self {
+        PianoRoll {
+            notes,
+            enabled: true,
+        }
+    }
+
+    pub fn draw(&self, ctx: &mut Context, width: f32, height: f32, time: f32) -> GameResult {
+        if !self.enabled {
+            return Ok(());
+        }
+
+        let now = width * PAST / (PAST + AHEAD);
+        let scale = width / (PAST + AHEAD);
+        let area = height - AXIS_HEIGHT;
+        let key_height = area / (HIGH_KEY - LOW_KEY + 1) as f32;
+
+        for note in self.notes.iter().filter(|note| note.end > time - PAST && note.start < time + AHEAD) {
+            if note.key < LOW_KEY || note.key > HIGH_KEY {
+                continue;
+            }
+
+            let x = now + (note.start - time) * scale;
+            let y = (HIGH_KEY - note.key) as f32 * key_height;
+            let w = ((note.end - note.start) * scale).max(2.0);
+            let playing = note.start <= time && note.end > time;
+            let alpha = if playing { 220 } else { 90 };
+
+            let mesh = graphics::Mesh::new_rectangle(
+                ctx,
+                graphics::DrawMode::fill(),
+                graphics::Rect::new(x, y, w, key_height.max(2.0)),
+                graphics::Color::from_rgba(note.channel * 16, 200, 128, alpha),
+            )?;
+            graphics::draw(ctx, &mesh, DrawParam::default())?;
+        }
+
+        let line = graphics::Mesh::new_line(
+            ctx,
+            &[[now, 0.0], [now, area]],
+            1.0,
+            graphics::Color::from_rgba(255, 255, 255, 64),
+        )?;
+        graphics::draw(ctx, &line, DrawParam::default())
+    }
+}
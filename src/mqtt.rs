@@ -0,0 +1,237 @@
+use serde::{Deserialize, Serialize};
+
+pub use client::Publisher;
+
+// The `[mqtt]` table in the config.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Settings {
+    // Host and port, as in "homeassistant.local:1883".
+    pub broker: String,
+    pub client_id: String,
+    pub username: Option<String>,
+    pub password: Option<String>,
+    pub beat_topic: String,
+    pub section_topic: String,
+    pub loudness_topic: String,
+    pub threshold_topic: String,
+    pub track_topic: String,
+    // How often the short-term loudness goes out.
+    pub loudness_secs: f32,
+}
+
+impl Default for Settings {
+    fn default() -> Self {
+        Settings {
+            broker: "localhost:1883".to_string(),
+            client_id: "stereo-visualizer".to_string(),
+            username: None,
+            password: None,
+            beat_topic: "stereo-visualizer/beat".to_string(),
+            section_topic: "stereo-visualizer/section".to_string(),
+            loudness_topic: "stereo-visualizer/loudness".to_string(),
+            threshold_topic: "stereo-visualizer/threshold".to_string(),
+            track_topic: "stereo-visualizer/track".to_string(),
+            loudness_secs: 1.0,
+        }
+    }
+}
+
+// The MQTT client, left out of builds without the network feature.
+#[cfg(feature = "network")]
+mod client {
+    use super::Settings;
+    use crate::events::{Event, Subscriber};
+    use ggez::{error::GameError, GameResult};
+    use std::{
+        io::{self, Write},
+        net::{TcpStream, ToSocketAddrs},
+        sync::mpsc::{self, Receiver, RecvTimeoutError, Sender},
+        thread,
+        time::{Duration, Instant},
+    };
+
+    const KEEP_ALIVE_SECS: u16 = 60;
+    const CONNECT_TIMEOUT: Duration = Duration::from_secs(3);
+    // A broker that went away is tried again no more often than this.
+    const RETRY_SECS: u64 = 5;
+
+    // MQTT 3.1.1 packets, just the few a publisher needs.
+    fn remaining_length(mut len: usize, packet: &mut Vec<u8>) {
+        loop {
+            let byte = (len % 128) as u8;
+            len /= 128;
+            packet.push(if len > 0 { byte | 0x80 } else { byte });
+            if len == 0 {
+                break;
+            }
+        }
+    }
+
+    fn string(text: &str, body: &mut Vec<u8>) {
+        body.extend_from_slice(&(text.len() as u16).to_be_bytes());
+        body.extend_from_slice(text.as_bytes());
+    }
+
+    fn packet(kind: u8, body: &[u8]) -> Vec<u8> {
+        let mut packet = vec![kind];
+        remaining_length(body.len(), &mut packet);
+        packet.extend_from_slice(body);
+        packet
+    }
+
+    fn connect_packet(settings: &Settings) -> Vec<u8> {
+        let mut flags = 0x02; // Clean session.
+        let mut body = Vec::new();
+        string("MQTT", &mut body);
+        body.push(4);
+        if settings.username.is_some() {
+            flags |= 0x80;
+        }
+        if settings.password.is_some() {
+            flags |= 0x40;
+        }
+        body.push(flags);
+        body.extend_from_slice(&KEEP_ALIVE_SECS.to_be_bytes());
+        string(&settings.client_id, &mut body);
+        for field in [&settings.username, &settings.password].iter().filter_map(|field| field.as_ref()) {
+            string(field, &mut body);
+        }
+        packet(0x10, &body)
+    }
+
+    fn publish_packet(topic: &str, payload: &str) -> Vec<u8> {
+        let mut body = Vec::new();
+        string(topic, &mut body);
+        body.extend_from_slice(payload.as_bytes());
+        // At most once: a late beat is no use to anyone.
+        packet(0x30, &body)
+    }
+
+    fn connect(settings: &Settings) -> io::Result<TcpStream> {
+        let addr = settings
+            .broker
+            .to_socket_addrs()?
+            .next()
+            .ok_or_else(|| io::Error::new(io::ErrorKind::NotFound, "no address"))?;
+        let mut stream = TcpStream::connect_timeout(&addr, CONNECT_TIMEOUT)?;
+        stream.set_nodelay(true)?;
+        stream.write_all(&connect_packet(settings))?;
+
+        stream.set_read_timeout(Some(CONNECT_TIMEOUT))?;
+        let mut ack = [0u8; 4];
+        io::Read::read_exact(&mut stream, &mut ack)?;
+        if ack[0] != 0x20 || ack[3] != 0 {
+            return Err(io::Error::new(
+                io::ErrorKind::PermissionDenied,
+                format!("refused with code {}", ack[3]),
+            ));
+        }
+        stream.set_read_timeout(None)?;
+        Ok(stream)
+    }
+
+    // Sends whatever the main loop hands it, pinging the broker when quiet and
+    // reconnecting when it drops, so the frame never waits on the network.
+    fn run(settings: Settings, mut stream: Option<TcpStream>, messages: Receiver<(String, String)>) {
+        let keep_alive = Duration::from_secs(u64::from(KEEP_ALIVE_SECS) / 2);
+        let mut retry = Instant::now();
+        loop {
+            let packet = match messages.recv_timeout(keep_alive) {
+                Ok((topic, payload)) => publish_packet(&topic, &payload),
+                Err(RecvTimeoutError::Timeout) => vec![0xc0, 0x00],
+                Err(RecvTimeoutError::Disconnected) => break,
+            };
+            if stream.is_none() && retry.elapsed().as_secs() >= RETRY_SECS {
+                retry = Instant::now();
+                stream = connect(&settings).ok();
+            }
+            if let Some(ref mut link) = stream {
+                if link.write_all(&packet).is_err() {
+                    stream = None;
+                    retry = Instant::now();
+                }
+            }
+        }
+        if let Some(mut link) = stream {
+            let _ = link.write_all(&[0xe0, 0x00]);
+        }
+    }
+
+    // Publishes beats, section changes and loudness for home automation, such as
+    // Home Assistant flashing lights in time with the music.
+    pub struct Publisher {
+        settings: Settings,
+        sender: Sender<(String, String)>,
+        loudness: Option<Instant>,
+    }
+
+    impl Publisher {
+        pub fn connect(settings: &Settings) -> GameResult<Self> {
+            let stream = connect(settings)
+                .map_err(|err| GameError::ConfigError(format!("Cannot reach {}: {}", settings.broker, err)))?;
+            let (sender, messages) = mpsc::channel();
+            let thread_settings = settings.clone();
+            thread::spawn(move || run(thread_settings, Some(stream), messages));
+            Ok(Publisher {
+                settings: settings.clone(),
+                sender,
+                loudness: None,
+            })
+        }
+
+        fn publish(&self, topic: &str, payload: serde_json::Value) {
+            let _ = self.sender.send((topic.to_string(), payload.to_string()));
+        }
+    }
+
+    impl Subscriber for Publisher {
+        fn event(&mut self, event: &Event) {
+            match *event {
+                Event::Beat { strength } => {
+                    self.publish(&self.settings.beat_topic, serde_json::json!({ "strength": strength.min(1.0) }));
+                }
+                Event::Section { time, bpm } => {
+                    self.publish(&self.settings.section_topic, serde_json::json!({ "time": time, "bpm": bpm }));
+                }
+                Event::Loudness { lufs } => {
+                    let due = self.loudness.is_none_or(|sent| sent.elapsed().as_secs_f32() >= self.settings.loudness_secs);
+                    if due {
+                        self.loudness = Some(Instant::now());
+                        self.publish(&self.settings.loudness_topic, serde_json::json!({ "lufs": lufs }));
+                    }
+                }
+                Event::Threshold { lufs, rising } => {
+                    let payload = serde_json::json!({ "lufs": lufs, "rising": rising });
+                    self.publish(&self.settings.threshold_topic, payload);
+                }
+                Event::Track { ref path } => {
+                    let title = path.file_stem().map(|stem| stem.to_string_lossy().into_owned());
+                    let payload = serde_json::json!({ "path": path.display().to_string(), "title": title });
+                    self.publish(&self.settings.track_topic, payload);
+                }
+            }
+        }
+    }
+}
+
+#[cfg(not(feature = "network"))]
+mod client {
+    use super::Settings;
+    use crate::events::{Event, Subscriber};
+    use ggez::{error::GameError, GameResult};
+
+    pub struct Publisher;
+
+    impl Publisher {
+        pub fn connect(_settings: &Settings) -> GameResult<Self> {
+            Err(GameError::ConfigError(
+                "This build has no MQTT support; rebuild with `--features network`".into(),
+            ))
+        }
+    }
+
+    impl Subscriber for Publisher {
+        fn event(&mut self, _event: &Event) {}
+    }
+}
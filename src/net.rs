@@ -0,0 +1,96 @@
+pub use receiver::listen;
+
+// The UDP and RTP receiver, left out of builds without the network feature.
+#[cfg(feature = "network")]
+mod receiver {
+    use crate::{live::LiveInput, pcm::Format};
+    use ggez::{error::GameError, GameResult};
+    use std::{net::UdpSocket, sync::mpsc, thread};
+
+    // Static L16 payload types, stereo and mono at 44.1 kHz; any other L16 rate
+    // has to use a dynamic type.
+    const L16_TYPES: [u8; 2] = [10, 11];
+    const DYNAMIC_TYPES: std::ops::RangeInclusive<u8> = 96..=127;
+
+    // Packets the RTP listener cannot decode.
+    #[derive(Debug)]
+    enum Rejected {
+        Malformed,
+        // A static payload type other than L16, e.g. 0 for G.711 or 14 for MPEG audio.
+        PayloadType(u8),
+    }
+
+    // Drops the RTP header (CSRCs and extension included) and padding. Payload
+    // types are not negotiated, so the sender has to match `--pcm-format`; L16 is
+    // big-endian, e.g. s16be:44100:2 for the static payload type 10. Dynamic
+    // types are taken as L16 too, as nothing says what they carry.
+    fn rtp_payload(packet: &[u8]) -> Result<&[u8], Rejected> {
+        let first = *packet.first().ok_or(Rejected::Malformed)?;
+        if first >> 6 != 2 {
+            return Err(Rejected::Malformed);
+        }
+        let payload_type = packet.get(1).ok_or(Rejected::Malformed)? & 0x7f;
+        if !L16_TYPES.contains(&payload_type) && !DYNAMIC_TYPES.contains(&payload_type) {
+            return Err(Rejected::PayloadType(payload_type));
+        }
+
+        let mut start = 12 + (first & 0x0f) as usize * 4;
+        if first & 0x10 != 0 {
+            let words = packet.get(start + 2..start + 4).ok_or(Rejected::Malformed)?;
+            start += 4 + u16::from_be_bytes([words[0], words[1]]) as usize * 4;
+        }
+        let mut end = packet.len();
+        if first & 0x20 != 0 {
+            let padding = *packet.last().ok_or(Rejected::Malformed)? as usize;
+            end = end.checked_sub(padding).ok_or(Rejected::Malformed)?;
+        }
+        packet.get(start..end).ok_or(Rejected::Malformed)
+    }
+
+    // Listens on `addr` for PCM datagrams in `format`, either bare or wrapped in
+    // RTP. Lost or reordered packets are not repaired; the display just skips.
+    pub fn listen(addr: &str, format: Format, rtp: bool) -> GameResult<LiveInput> {
+        let socket = UdpSocket::bind(addr)
+            .map_err(|err| GameError::AudioError(format!("Cannot listen on {}: {}", addr, err)))?;
+
+        let (sender, receiver) = mpsc::channel();
+        let addr = addr.to_string();
+        let name = format!("{} {}", if rtp { "RTP" } else { "UDP" }, addr);
+        thread::spawn(move || {
+            let mut buffer = [0u8; 65_536];
+            while let Ok((len, _)) = socket.recv_from(&mut buffer) {
+                let payload = if rtp { rtp_payload(&buffer[..len]) } else { Ok(&buffer[..len]) };
+                match payload {
+                    Ok(payload) => {
+                        if sender.send(format.decode(payload)).is_err() {
+                            break;
+                        }
+                    }
+                    Err(Rejected::PayloadType(payload_type)) => {
+                        // Decoding it as PCM would only show noise.
+                        println!(
+                            "RTP payload type {} is not L16; only uncompressed L16 is supported, so stopped listening on {}",
+                            payload_type, addr
+                        );
+                        break;
+                    }
+                    Err(Rejected::Malformed) => (),
+                }
+            }
+        });
+
+        Ok(LiveInput::new(receiver, format.sample_rate, name, ()))
+    }
+}
+
+#[cfg(not(feature = "network"))]
+mod receiver {
+    use crate::{live::LiveInput, pcm::Format};
+    use ggez::{error::GameError, GameResult};
+
+    pub fn listen(_addr: &str, _format: Format, _rtp: bool) -> GameResult<LiveInput> {
+        Err(GameError::ConfigError(
+            "This build has no UDP or RTP input; rebuild with `--features network`".into(),
+        ))
+    }
+}
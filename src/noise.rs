@@ -0,0 +1,92 @@
+use crate::overlay::{self, Anchor};
+use ggez::{
+    graphics,
+    Context, GameResult,
+};
+
+const LEARN_SECS: f32 = 3.0;
+// The learned floor is raised a little so its own fluctuation stays dark.
+const MARGIN: f32 = 1.5;
+
+// Learns the average spectrum of the room (fans, hum) over a few seconds and
+// then subtracts it from every bin.
+pub struct NoiseGate {
+    left: Vec<f32>,
+    right: Vec<f32>,
+    frames: usize,
+    learning: Option<f32>,
+    active: bool,
+}
+
+impl NoiseGate {
+    pub fn new(bins: usize) -> Self {
+        NoiseGate {
+            left: vec![0.0; bins],
+            right: vec![0.0; bins],
+            frames: 0,
+            learning: None,
+            active: false,
+        }
+    }
+
+    pub fn toggle(&mut self) {
+        if self.active || self.learning.is_some() {
+            self.active = false;
+            self.learning = None;
+        } else {
+            self.left.iter_mut().chain(self.right.iter_mut()).for_each(|amp| *amp = 0.0);
+            self.frames = 0;
+            self.learning = Some(LEARN_SECS);
+        }
+    }
+
+    pub fn apply(&mut self, idx: usize, left: f32, right: f32) -> (f32, f32) {
+        if self.learning.is_some() {
+            self.left[idx] += left;
+            self.right[idx] += right;
+            (left, right)
+        } else if self.active {
+            (
+                (left - self.left[idx] * MARGIN).max(0.0),
+                (right - self.right[idx] * MARGIN).max(0.0),
+            )
+        } else {
+            (left, right)
+        }
+    }
+
+    pub fn end_frame(&mut self, delta: f32) {
+        let remaining = match self.learning {
+            Some(remaining) => remaining - delta,
+            None => return,
+        };
+        self.frames += 1;
+
+        if remaining > 0.0 {
+            self.learning = Some(remaining);
+            return;
+        }
+
+        let frames = self.frames as f32;
+        self.left.iter_mut().chain(self.right.iter_mut()).for_each(|amp| *amp /= frames);
+        self.learning = None;
+        self.active = true;
+    }
+
+    pub fn draw(&self, ctx: &mut Context, width: f32) -> GameResult {
+        let label = match self.learning {
+            Some(remaining) => format!("Learning noise floor... {:.0}s", remaining.ceil()),
+            None if self.active => "NOISE GATE".to_string(),
+            None => return Ok(()),
+        };
+
+        overlay::label(
+            ctx,
+            label,
+            16.0,
+            Anchor::TopRight,
+            [width - 16.0, 16.0],
+            graphics::Color::from_rgb(160, 160, 160),
+        )
+    }
+}
@@ -0,0 +1,43 @@
+use ggez::{
+    conf::NumSamples,
+    graphics::{self, Canvas, FilterMode},
+    Context, GameResult,
+};
+
+// ggez cannot sample a multisampled canvas, so with `--msaa` off-screen
+// targets are supersampled instead: drawn at twice the resolution and
+// filtered down as they are laid on the window.
+fn factor(samples: NumSamples) -> f32 {
+    match samples {
+        NumSamples::Zero | NumSamples::One => 1.0,
+        _ => 2.0,
+    }
+}
+
+// A target of `width` by `height` drawable pixels.
+pub fn canvas(ctx: &mut Context, (width, height): (f32, f32), samples: NumSamples) -> GameResult<Canvas> {
+    let factor = factor(samples);
+    let mut canvas = Canvas::new(
+        ctx,
+        (width * factor).max(1.0) as u16,
+        (height * factor).max(1.0) as u16,
+        NumSamples::One,
+    )?;
+    canvas.set_filter(FilterMode::Linear);
+    Ok(canvas)
+}
+
+// A target covering the whole window.
+pub fn window(ctx: &mut Context, samples: NumSamples) -> GameResult<Canvas> {
+    let pixels = graphics::drawable_size(ctx);
+    canvas(ctx, pixels, samples)
+}
+
+// Makes a window target that is in use again at the window's current
+// resolution, e.g. after a move to a screen with another scale factor.
+pub fn rescale(ctx: &mut Context, target: &mut Option<Canvas>, samples: NumSamples) -> GameResult {
+    if target.is_some() {
+        *target = Some(window(ctx, samples)?);
+    }
+    Ok(())
+}
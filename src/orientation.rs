@@ -0,0 +1,132 @@
+use crate::Mode;
+use ggez::{graphics, mint, Context, GameResult};
+use serde::{Deserialize, Serialize};
+
+// How a mode's picture is laid onto the window: turned so the stereo axis
+// runs top to bottom, flipped left/right for mirrored monitors, and mirrored
+// into a top half and its reflection below.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Orientation {
+    pub rotate: bool,
+    pub flip: bool,
+    pub mirror: bool,
+}
+
+// One orientation per mode, as `[orientation.<mode>]` tables in the config.
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct PerMode {
+    pub stage: Orientation,
+    pub ring: Orientation,
+    pub tracker: Orientation,
+    pub cepstrum: Orientation,
+    pub phase: Orientation,
+    pub tuner: Orientation,
+    pub typography: Orientation,
+    pub shader: Orientation,
+}
+
+impl PerMode {
+    pub fn get(&self, mode: Mode) -> Orientation {
+        match mode {
+            Mode::Stage => self.stage,
+            Mode::Ring => self.ring,
+            Mode::Tracker => self.tracker,
+            Mode::Cepstrum => self.cepstrum,
+            Mode::Phase => self.phase,
+            Mode::Tuner => self.tuner,
+            Mode::Typography => self.typography,
+            Mode::Shader => self.shader,
+        }
+    }
+}
+
+// x' = a x + c y + tx, y' = b x + d y + ty
+#[derive(Clone, Copy)]
+struct Affine {
+    a: f32,
+    b: f32,
+    c: f32,
+    d: f32,
+    tx: f32,
+    ty: f32,
+}
+
+impl Affine {
+    const IDENTITY: Affine = Affine { a: 1.0, b: 0.0, c: 0.0, d: 1.0, tx: 0.0, ty: 0.0 };
+
+    // `next` applied after `self`.
+    fn then(self, next: Affine) -> Affine {
+        Affine {
+            a: next.a * self.a + next.c * self.b,
+            b: next.b * self.a + next.d * self.b,
+            c: next.a * self.c + next.c * self.d,
+            d: next.b * self.c + next.d * self.d,
+            tx: next.a * self.tx + next.c * self.ty + next.tx,
+            ty: next.b * self.tx + next.d * self.ty + next.ty,
+        }
+    }
+
+    fn unmap(self, [x, y]: [f32; 2]) -> [f32; 2] {
+        let det = self.a * self.d - self.b * self.c;
+        let (x, y) = (x - self.tx, y - self.ty);
+        [(self.d * x - self.c * y) / det, (self.a * y - self.b * x) / det]
+    }
+
+    fn matrix(self) -> mint::ColumnMatrix4<f32> {
+        mint::ColumnMatrix4::from([
+            [self.a, self.b, 0.0, 0.0],
+            [self.c, self.d, 0.0, 0.0],
+            [0.0, 0.0, 1.0, 0.0],
+            [self.tx, self.ty, 0.0, 1.0],
+        ])
+    }
+}
+
+impl Orientation {
+    // One transform per copy of the picture: two when mirrored.
+    fn passes(&self, width: f32, height: f32) -> Vec<Affine> {
+        let mut base = Affine::IDENTITY;
+        if self.rotate {
+            // A quarter turn clockwise, stretched back onto the window.
+            base = Affine { a: 0.0, b: height / width, c: -width / height, d: 0.0, tx: width, ty: 0.0 };
+        }
+        if self.flip {
+            base = base.then(Affine { a: -1.0, tx: width, ..Affine::IDENTITY });
+        }
+        if self.mirror {
+            vec![
+                base.then(Affine { d: 0.5, ..Affine::IDENTITY }),
+                base.then(Affine { d: -0.5, ty: height, ..Affine::IDENTITY }),
+            ]
+        } else {
+            vec![base]
+        }
+    }
+
+    pub fn draw<F>(&self, ctx: &mut Context, width: f32, height: f32, mut pass: F) -> GameResult
+    where
+        F: FnMut(&mut Context) -> GameResult,
+    {
+        if *self == Orientation::default() {
+            return pass(ctx);
+        }
+        for affine in self.passes(width, height) {
+            graphics::push_transform(ctx, Some(affine.matrix()));
+            graphics::apply_transformations(ctx)?;
+            let result = pass(ctx);
+            graphics::pop_transform(ctx);
+            graphics::apply_transformations(ctx)?;
+            result?;
+        }
+        Ok(())
+    }
+
+    // Where a window position falls in the untransformed picture.
+    pub fn unmap(&self, point: [f32; 2], width: f32, height: f32) -> [f32; 2] {
+        let passes = self.passes(width, height);
+        let pass = if self.mirror && point[1] > height / 2.0 { passes[1] } else { passes[0] };
+        pass.unmap(point)
+    }
+}
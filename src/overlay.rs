@@ -0,0 +1,206 @@
+use ggez::{
+    graphics::{self, Align, DrawParam, Font, Scale, Text, TextFragment},
+    Context, GameResult,
+};
+use std::{cell::Cell, fs, path::Path};
+
+const TOAST_IN: f32 = 0.15;
+const TOAST_HOLD: f32 = 2.0;
+const TOAST_OUT: f32 = 0.5;
+
+// Shared by every overlay: the font and the ratio of physical to logical
+// pixels, so glyphs are rasterized at the resolution they are shown at.
+#[derive(Clone, Copy)]
+struct Style {
+    font: Option<Font>,
+    dpi: f32,
+}
+
+thread_local! {
+    static STYLE: Cell<Style> = const { Cell::new(Style { font: None, dpi: 1.0 }) };
+}
+
+fn style() -> Style {
+    STYLE.with(Cell::get)
+}
+
+// Loads the configured font, if any, and picks up the display's scale factor.
+pub fn init(ctx: &mut Context, font: Option<&Path>) -> GameResult {
+    let font = match font {
+        Some(path) => Some(Font::new_glyph_font_bytes(ctx, &fs::read(path)?)?),
+        None => None,
+    };
+    let dpi = (graphics::window(ctx).get_hidpi_factor() as f32).max(1.0);
+    STYLE.with(|style| style.set(Style { font, dpi }));
+    Ok(())
+}
+
+// For a window moved to a display with another scale factor.
+pub fn set_scale(factor: f32) {
+    STYLE.with(|style| style.set(Style { dpi: factor.max(1.0), ..style.get() }));
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Anchor {
+    TopLeft,
+    Top,
+    TopRight,
+    Center,
+    Bottom,
+}
+
+impl Anchor {
+    // Where the text's top left corner goes for a box of this size at `pos`.
+    fn place(self, pos: [f32; 2], (width, height): (f32, f32)) -> [f32; 2] {
+        let (x, y) = match self {
+            Anchor::TopLeft => (0.0, 0.0),
+            Anchor::Top => (0.5, 0.0),
+            Anchor::TopRight => (1.0, 0.0),
+            Anchor::Center => (0.5, 0.5),
+            Anchor::Bottom => (0.5, 1.0),
+        };
+        [pos[0] - width * x, pos[1] - height * y]
+    }
+}
+
+// Text of `size` logical pixels.
+pub fn text<S>(label: S, size: f32) -> Text
+where
+    S: Into<String>,
+{
+    let style = style();
+    let mut fragment = TextFragment::new(label.into()).scale(Scale::uniform(size * style.dpi));
+    if let Some(font) = style.font {
+        fragment = fragment.font(font);
+    }
+    Text::new(fragment)
+}
+
+// Text wrapped to `width` logical pixels.
+pub fn wrapped<S>(label: S, size: f32, width: f32, align: Align) -> Text
+where
+    S: Into<String>,
+{
+    let mut text = text(label, size);
+    text.set_bounds([width * style().dpi, f32::INFINITY], align);
+    text
+}
+
+// Logical size of laid out text.
+pub fn size(ctx: &mut Context, text: &Text) -> (f32, f32) {
+    let (width, height) = text.dimensions(ctx);
+    let dpi = style().dpi;
+    (width as f32 / dpi, height as f32 / dpi)
+}
+
+pub fn draw(ctx: &mut Context, text: &Text, anchor: Anchor, pos: [f32; 2], color: graphics::Color) -> GameResult {
+    let dpi = style().dpi;
+    let dest = anchor.place(pos, size(ctx, text));
+    graphics::draw(
+        ctx,
+        text,
+        DrawParam::default().dest(dest).scale([1.0 / dpi, 1.0 / dpi]).color(color),
+    )
+}
+
+pub fn label<S>(
+    ctx: &mut Context,
+    label: S,
+    size: f32,
+    anchor: Anchor,
+    pos: [f32; 2],
+    color: graphics::Color,
+) -> GameResult
+where
+    S: Into<String>,
+{
+    draw(ctx, &text(label, size), anchor, pos, color)
+}
+
+// Text on a dark box, kept inside the window.
+pub fn panel(
+    ctx: &mut Context,
+    text: &Text,
+    anchor: Anchor,
+    pos: [f32; 2],
+    color: graphics::Color,
+    (width, height): (f32, f32),
+) -> GameResult {
+    let margin = 12.0;
+    let (text_width, text_height) = size(ctx, text);
+    let (box_width, box_height) = (text_width + margin * 2.0, text_height + margin * 2.0);
+    let [x, y] = anchor.place(pos, (box_width, box_height));
+    let rect = graphics::Rect::new(
+        x.clamp(0.0, (width - box_width).max(0.0)),
+        y.clamp(0.0, (height - box_height).max(0.0)),
+        box_width,
+        box_height,
+    );
+    let mesh = graphics::Mesh::new_rectangle(
+        ctx,
+        graphics::DrawMode::fill(),
+        rect,
+        graphics::Color::from_rgba(20, 20, 20, 220),
+    )?;
+    graphics::draw(ctx, &mesh, DrawParam::default())?;
+    draw(ctx, text, Anchor::TopLeft, [rect.x + margin, rect.y + margin], color)
+}
+
+// A short message that fades in, holds and fades out at the bottom of the window.
+pub struct Toast {
+    message: String,
+    age: f32,
+}
+
+impl Toast {
+    pub fn new() -> Self {
+        Toast {
+            message: String::new(),
+            age: f32::INFINITY,
+        }
+    }
+
+    pub fn show<S>(&mut self, message: S)
+    where
+        S: Into<String>,
+    {
+        self.message = message.into();
+        self.age = 0.0;
+    }
+
+    pub fn update(&mut self, delta: f32) {
+        self.age += delta;
+    }
+
+    fn alpha(&self) -> f32 {
+        if self.age < TOAST_IN {
+            self.age / TOAST_IN
+        } else {
+            (1.0 - (self.age - TOAST_IN - TOAST_HOLD) / TOAST_OUT).clamp(0.0, 1.0)
+        }
+    }
+
+    pub fn draw(&self, ctx: &mut Context, width: f32, height: f32) -> GameResult {
+        let alpha = self.alpha();
+        if alpha <= 0.0 {
+            return Ok(());
+        }
+        let text = text(self.message.as_str(), 18.0);
+        let (text_width, text_height) = size(ctx, &text);
+        let margin = 10.0;
+        let rect = graphics::Rect::new(
+            (width - text_width) / 2.0 - margin,
+            height * 0.8 - text_height - margin,
+            text_width + margin * 2.0,
+            text_height + margin * 2.0,
+        );
+        let mesh = graphics::Mesh::new_rectangle(
+            ctx,
+            graphics::DrawMode::fill(),
+            rect,
+            graphics::Color::new(0.08, 0.08, 0.08, 0.86 * alpha),
+        )?;
+        graphics::draw(ctx, &mesh, DrawParam::default())?;
+        draw(ctx, &text, Anchor::Bottom, [width / 2.0, height * 0.8], graphics::Color::new(1.0, 1.0, 1.0, alpha))
+    }
+}
@@ -0,0 +1,172 @@
+use crate::DirectionalSource;
+use ggez::{
+    graphics::{self, DrawParam},
+    Context, GameResult,
+};
+use std::{
+    collections::VecDeque,
+    fs::File,
+    io::{BufWriter, Write},
+    path,
+};
+
+const BAND_EDGES: [f32; 9] = [20.0, 80.0, 200.0, 500.0, 1000.0, 2000.0, 4000.0, 8000.0, 20000.0];
+const WINDOW: f32 = 2.0;
+// A sweep has to cover this much of the stage (which spans -1 to 1)...
+const SWEEP: f32 = 0.6;
+// ...and move mostly one way, as a share of the total distance travelled.
+const STEADINESS: f32 = 0.7;
+const FLOOR: f32 = 2.0;
+const TRAIL: f32 = 1.0;
+
+#[derive(Debug, Clone, Copy)]
+pub struct PanEvent {
+    pub time: f32,
+    pub duration: f32,
+    pub low: f32,
+    pub high: f32,
+    pub from: f32,
+    pub to: f32,
+}
+
+struct Band {
+    low: f32,
+    high: f32,
+    history: VecDeque<(f32, f32)>,
+}
+
+// Follows each band's energy-weighted direction and reports sweeps across
+// the stage, keeping a short trail of positions to draw.
+pub struct PanTracker {
+    pub enabled: bool,
+    bands: Vec<Band>,
+    pub events: Vec<PanEvent>,
+}
+
+impl PanTracker {
+    pub fn new() -> Self {
+        PanTracker {
+            enabled: false,
+            bands: BAND_EDGES
+                .windows(2)
+                .map(|edge| Band {
+                    low: edge[0],
+                    high: edge[1],
+                    history: VecDeque::new(),
+                })
+                .collect(),
+            events: Vec::new(),
+        }
+    }
+
+    pub fn clear(&mut self) {
+        self.events.clear();
+        self.bands.iter_mut().for_each(|band| band.history.clear());
+    }
+
+    pub fn update(&mut self, directions: &[DirectionalSource], bin_hz: f32, time: f32) {
+        for band in self.bands.iter_mut() {
+            while band.history.front().is_some_and(|&(at, _)| at < time - WINDOW || at > time) {
+                band.history.pop_front();
+            }
+
+            let first = ((band.low / bin_hz).ceil() as usize).max(1);
+            let last = ((band.high / bin_hz) as usize).min(directions.len());
+            if first >= last {
+                continue;
+            }
+            let sources = &directions[first..last];
+            let energy: f32 = sources.iter().map(|source| source.amp).sum();
+            if energy / ((last - first) as f32) < FLOOR {
+                continue;
+            }
+            let dir = sources.iter().map(|source| source.dir * source.amp).sum::<f32>() / energy;
+            band.history.push_back((time, dir));
+
+            // Compare against the point furthest from where the band is now.
+            let (start, from) = band
+                .history
+                .iter()
+                .copied()
+                .max_by(|a, b| (a.1 - dir).abs().total_cmp(&(b.1 - dir).abs()))
+                .unwrap_or((time, dir));
+            if (dir - from).abs() < SWEEP {
+                continue;
+            }
+            let path: Vec<f32> = band.history.iter().filter(|&&(at, _)| at >= start).map(|&(_, dir)| dir).collect();
+            let travelled: f32 = path.windows(2).map(|pair| (pair[1] - pair[0]).abs()).sum();
+            if (dir - from).abs() < travelled * STEADINESS {
+                continue;
+            }
+
+            let event = PanEvent {
+                time: start,
+                duration: time - start,
+                low: band.low,
+                high: band.high,
+                from,
+                to: dir,
+            };
+            println!(
+                "Pan {:.0}-{:.0} Hz at {:.2}s: {:+.2} -> {:+.2} in {:.2}s",
+                event.low, event.high, event.time, event.from, event.to, event.duration
+            );
+            self.events.push(event);
+            band.history.retain(|&(at, _)| at >= time);
+        }
+    }
+
+    pub fn draw(&self, ctx: &mut Context, width: f32, height: f32, step: usize) -> GameResult {
+        if !self.enabled {
+            return Ok(());
+        }
+
+        let padding = 64.0;
+        let mut builder = graphics::MeshBuilder::new();
+        let mut empty = true;
+        for (idx, band) in self.bands.iter().enumerate() {
+            let now = match band.history.back() {
+                Some(&(now, _)) => now,
+                None => continue,
+            };
+            // Low bands at the bottom, like a mixer's frequency ladder.
+            let y = height * (0.8 - 0.6 * idx as f32 / (self.bands.len() - 1) as f32);
+            let shade = (idx * 255 / self.bands.len()) as u8;
+            for &(at, dir) in band.history.iter().rev().step_by(step).filter(|&&(at, _)| at >= now - TRAIL) {
+                let fade = 1.0 - (now - at) / TRAIL;
+                let x = padding + (dir + 1.0) / 2.0 * (width - padding * 2.0);
+                builder.circle(
+                    graphics::DrawMode::fill(),
+                    [x, y],
+                    2.0 + fade * 3.0,
+                    0.5,
+                    graphics::Color::from_rgba(shade, 220, 255 - shade, (fade * 200.0) as u8),
+                );
+                empty = false;
+            }
+        }
+
+        if empty {
+            return Ok(());
+        }
+        let mesh = builder.build(ctx)?;
+        graphics::draw(ctx, &mesh, DrawParam::default())
+    }
+}
+
+pub fn write_csv<P>(path: P, events: &[PanEvent]) -> GameResult
+where
+    P: AsRef<path::Path>,
+{
+    let mut writer = BufWriter::new(File::create(path)?);
+    writeln!(writer, "time_s,duration_s,low_hz,high_hz,from_dir,to_dir")?;
+    for event in events {
+        writeln!(
+            writer,
+            "{},{},{},{},{},{}",
+            event.time, event.duration, event.low, event.high, event.from, event.to
+        )?;
+    }
+    writer.flush()?;
+    Ok(())
+}
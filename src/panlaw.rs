@@ -0,0 +1,92 @@
+use serde::{Deserialize, Serialize};
+
+// How the monitoring setup turns level differences into direction.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Setup {
+    // Crosstalk between the speakers already narrows the image; levels map
+    // straight to direction.
+    #[default]
+    Speakers,
+    // Without crosstalk a small difference already sounds far to one side,
+    // most of all in the bass where speakers would blur it.
+    Headphones,
+}
+
+// Direction is raised to `exponent` at `hz`: below 1 pushes sources to the
+// sides, above 1 pulls them towards the middle.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct Point {
+    pub hz: f32,
+    pub exponent: f32,
+}
+
+// The `[panning]` table in the config.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct PanLaw {
+    pub setup: Setup,
+    // Replaces the setup's curve when given, interpolated on a log frequency axis.
+    pub curve: Vec<Point>,
+}
+
+impl PanLaw {
+    fn points(&self) -> Vec<Point> {
+        if !self.curve.is_empty() {
+            let mut points = self.curve.clone();
+            points.sort_by(|a, b| a.hz.total_cmp(&b.hz));
+            return points;
+        }
+        let point = |hz, exponent| Point { hz, exponent };
+        match self.setup {
+            Setup::Speakers => vec![point(1000.0, 1.0)],
+            Setup::Headphones => vec![point(150.0, 0.6), point(1500.0, 0.75), point(6000.0, 0.9)],
+        }
+    }
+
+    fn exponent(points: &[Point], hz: f32) -> f32 {
+        let (first, last) = match (points.first(), points.last()) {
+            (Some(first), Some(last)) => (first, last),
+            _ => return 1.0,
+        };
+        if hz <= first.hz {
+            return first.exponent;
+        }
+        if hz >= last.hz {
+            return last.exponent;
+        }
+        let idx = points.partition_point(|point| point.hz <= hz);
+        let (low, high) = (points[idx - 1], points[idx]);
+        let at = (hz / low.hz).ln() / (high.hz / low.hz).ln();
+        low.exponent + (high.exponent - low.exponent) * at
+    }
+}
+
+// The pan law laid out per FFT bin, made again whenever the bins change.
+pub struct Calibration {
+    law: PanLaw,
+    bin_hz: f32,
+    exponents: Vec<f32>,
+}
+
+impl Calibration {
+    pub fn new(law: PanLaw) -> Self {
+        Calibration {
+            law,
+            bin_hz: 0.0,
+            exponents: Vec::new(),
+        }
+    }
+
+    pub fn apply(&mut self, idx: usize, dir: f32, bins: usize, bin_hz: f32) -> f32 {
+        if self.exponents.len() != bins || self.bin_hz != bin_hz {
+            let points = self.law.points();
+            self.exponents = (0..bins).map(|bin| PanLaw::exponent(&points, bin as f32 * bin_hz)).collect();
+            self.bin_hz = bin_hz;
+        }
+        match self.exponents.get(idx) {
+            Some(&exponent) if exponent != 1.0 => dir.signum() * dir.abs().min(1.0).powf(exponent),
+            _ => dir,
+        }
+    }
+}
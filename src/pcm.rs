@@ -0,0 +1,154 @@
+use crate::live::LiveInput;
+use ggez::{error::GameError, GameResult};
+use std::{
+    io::{self, Read},
+    sync::mpsc,
+    thread,
+    time::{Duration, Instant},
+};
+
+// How far a paced reader may run ahead of the wall clock.
+const LEAD: f64 = 0.1;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Encoding {
+    U8,
+    S16Le,
+    S16Be,
+    S24Le,
+    S32Le,
+    F32Le,
+}
+
+impl Encoding {
+    fn from_name(name: &str) -> Option<Self> {
+        match name {
+            "u8" => Some(Encoding::U8),
+            "s16le" => Some(Encoding::S16Le),
+            "s16be" => Some(Encoding::S16Be),
+            "s24le" => Some(Encoding::S24Le),
+            "s32le" => Some(Encoding::S32Le),
+            "f32le" => Some(Encoding::F32Le),
+            _ => None,
+        }
+    }
+
+    fn width(self) -> usize {
+        match self {
+            Encoding::U8 => 1,
+            Encoding::S16Le | Encoding::S16Be => 2,
+            Encoding::S24Le => 3,
+            Encoding::S32Le | Encoding::F32Le => 4,
+        }
+    }
+
+    fn sample(self, bytes: &[u8]) -> f32 {
+        match self {
+            Encoding::U8 => (bytes[0] as f32 - 128.0) / 128.0,
+            Encoding::S16Le => i16::from_le_bytes([bytes[0], bytes[1]]) as f32 / 32768.0,
+            Encoding::S16Be => i16::from_be_bytes([bytes[0], bytes[1]]) as f32 / 32768.0,
+            Encoding::S24Le => i32::from_le_bytes([0, bytes[0], bytes[1], bytes[2]]) as f32 / 2_147_483_648.0,
+            Encoding::S32Le => {
+                i32::from_le_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]) as f32 / 2_147_483_648.0
+            }
+            Encoding::F32Le => f32::from_le_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct Format {
+    pub encoding: Encoding,
+    pub sample_rate: u32,
+    pub channels: usize,
+}
+
+impl Format {
+    // Parses `encoding:rate:channels`, e.g. `s16le:48000:2`.
+    pub fn parse(text: &str) -> GameResult<Self> {
+        let invalid = || {
+            GameError::ConfigError(format!(
+                "Invalid PCM format '{}', expected e.g. s16le:48000:2 (u8, s16le, s16be, s24le, s32le, f32le)",
+                text
+            ))
+        };
+
+        let mut parts = text.split(':');
+        let encoding = parts.next().and_then(Encoding::from_name).ok_or_else(invalid)?;
+        let sample_rate = parts
+            .next()
+            .and_then(|rate| rate.parse().ok())
+            .filter(|&rate| rate > 0)
+            .ok_or_else(invalid)?;
+        let channels = match parts.next() {
+            Some(channels) => channels.parse().ok().filter(|&ch| ch > 0).ok_or_else(invalid)?,
+            None => 2,
+        };
+        if parts.next().is_some() {
+            return Err(invalid());
+        }
+
+        Ok(Format {
+            encoding,
+            sample_rate,
+            channels,
+        })
+    }
+
+    pub fn frame_size(&self) -> usize {
+        self.encoding.width() * self.channels
+    }
+
+    // Turns whole frames into interleaved stereo; mono feeds both sides and
+    // anything past the second channel is dropped.
+    pub fn decode(&self, bytes: &[u8]) -> Vec<f32> {
+        let width = self.encoding.width();
+        bytes
+            .chunks_exact(self.frame_size())
+            .flat_map(|frame| {
+                let left = self.encoding.sample(frame);
+                let right = if self.channels > 1 {
+                    self.encoding.sample(&frame[width..])
+                } else {
+                    left
+                };
+                [left, right]
+            })
+            .collect()
+    }
+}
+
+// Reads raw PCM from stdin. Piped input usually arrives faster than real time
+// (ffmpeg decoding a file), so the reader holds back to the sample clock and
+// lets the pipe push back on the producer.
+pub fn stdin(format: Format) -> LiveInput {
+    let (sender, receiver) = mpsc::channel();
+    thread::spawn(move || {
+        let mut stdin = io::stdin();
+        let frame = format.frame_size();
+        let mut buffer = vec![0u8; frame * 1024];
+        let mut filled = 0;
+        let (start, mut frames) = (Instant::now(), 0u64);
+
+        while let Ok(len) = stdin.read(&mut buffer[filled..]) {
+            if len == 0 {
+                break;
+            }
+            filled += len;
+            let whole = filled - filled % frame;
+            if sender.send(format.decode(&buffer[..whole])).is_err() {
+                break;
+            }
+            buffer.copy_within(whole..filled, 0);
+            filled -= whole;
+
+            frames += (whole / frame) as u64;
+            let ahead = frames as f64 / format.sample_rate as f64 - start.elapsed().as_secs_f64();
+            if ahead > LEAD {
+                thread::sleep(Duration::from_secs_f64(ahead - LEAD));
+            }
+        }
+    });
+
+    LiveInput::new(receiver, format.sample_rate, "stdin".into(), ())
+}
@@ -0,0 +1,125 @@
+use crate::{overlay::{self, Anchor}, view::{FrequencyView, AXIS_HEIGHT}};
+use ggez::{
+    graphics::{self, DrawParam},
+    Context, GameResult,
+};
+use rustfft::num_complex::Complex;
+use std::{collections::VecDeque, f32::consts::PI};
+
+const ROWS: usize = 48;
+const COLUMNS: usize = 160;
+// Group delays beyond this are shown at full color.
+const MAX_DELAY_MS: f32 = 2.0;
+
+fn wrap(phase: f32) -> f32 {
+    (phase + PI).rem_euclid(2.0 * PI) - PI
+}
+
+// Blue for negative, white around zero, red for positive values in -1..1.
+fn diverging(value: f32, alpha: u8) -> graphics::Color {
+    let value = value.clamp(-1.0, 1.0);
+    let fade = (255.0 * (1.0 - value.abs())) as u8;
+    if value >= 0.0 {
+        graphics::Color::from_rgba(255, fade, fade, alpha)
+    } else {
+        graphics::Color::from_rgba(fade, fade, 255, alpha)
+    }
+}
+
+// Inter-channel phase difference per bin as a scrolling waterfall, plus the
+// group delay between the channels (the slope of that phase) as a strip.
+pub struct PhaseView {
+    cross: Vec<Complex<f32>>,
+    history: VecDeque<Vec<(f32, f32)>>,
+    sample_rate: u32,
+}
+
+impl PhaseView {
+    pub fn new(bins: usize) -> Self {
+        PhaseView {
+            cross: vec![Complex::new(0.0, 0.0); bins],
+            history: VecDeque::with_capacity(ROWS),
+            sample_rate: 1,
+        }
+    }
+
+    pub fn update(&mut self, left_fft: &[Complex<f32>], right_fft: &[Complex<f32>], sample_rate: u32) {
+        for ((cross, l), r) in self.cross.iter_mut().zip(left_fft).zip(right_fft) {
+            *cross += (l * r.conj() - *cross) * 0.3;
+        }
+
+        let peak = self.cross.iter().fold(0.0f32, |acc, cross| acc.max(cross.norm())).max(1e-6);
+        let row = self.cross.iter().map(|cross| (cross.arg(), (cross.norm() / peak).sqrt())).collect();
+        if self.history.len() == ROWS {
+            self.history.pop_back();
+        }
+        self.history.push_front(row);
+        self.sample_rate = sample_rate;
+    }
+
+    fn delay_ms(&self, bin: usize) -> f32 {
+        let bin_hz = self.sample_rate as f32 / (self.cross.len() * 2) as f32;
+        let next = (bin + 1).min(self.cross.len() - 1);
+        if next == bin {
+            return 0.0;
+        }
+        let slope = wrap(self.cross[next].arg() - self.cross[bin].arg());
+        -slope / (2.0 * PI * bin_hz) * 1000.0
+    }
+
+    pub fn draw(&self, ctx: &mut Context, view: &FrequencyView, width: f32, height: f32) -> GameResult {
+        let bin_hz = self.sample_rate as f32 / (self.cross.len() * 2) as f32;
+        let area = height - AXIS_HEIGHT;
+        let strip = 32.0;
+        let row_height = (area - strip - 48.0) / ROWS as f32;
+        let column = width / COLUMNS as f32;
+        let bins: Vec<usize> = (0..COLUMNS)
+            .map(|col| {
+                let hz = view.freq_at((col as f32 + 0.5) * column, width);
+                ((hz / bin_hz).round() as usize).clamp(1, self.cross.len() - 1)
+            })
+            .collect();
+
+        let mut builder = graphics::MeshBuilder::new();
+        let mut empty = true;
+        for (row, values) in self.history.iter().enumerate() {
+            for (col, &bin) in bins.iter().enumerate() {
+                let (phase, weight) = values[bin];
+                let alpha = (weight * 255.0) as u8;
+                if alpha < 8 {
+                    continue;
+                }
+                let (x, y) = (col as f32 * column, row as f32 * row_height);
+                let rect = graphics::Rect::new(x, y, column + 0.5, row_height + 0.5);
+                builder.rectangle(graphics::DrawMode::fill(), rect, diverging(phase / PI, alpha));
+                empty = false;
+            }
+        }
+
+        let top = ROWS as f32 * row_height + 24.0;
+        let weight = self.history.front();
+        for (col, &bin) in bins.iter().enumerate() {
+            let alpha = weight.map_or(0, |row| (row[bin].1 * 255.0) as u8);
+            if alpha < 8 {
+                continue;
+            }
+            let rect = graphics::Rect::new(col as f32 * column, top, column + 0.5, strip);
+            builder.rectangle(graphics::DrawMode::fill(), rect, diverging(self.delay_ms(bin) / MAX_DELAY_MS, alpha));
+            empty = false;
+        }
+
+        if !empty {
+            let mesh = builder.build(ctx)?;
+            graphics::draw(ctx, &mesh, DrawParam::default())?;
+        }
+
+        let labels = [
+            ("phase L-R (blue -180, red +180 degrees)", 4.0),
+            ("group delay (blue R late, red L late, +/- 2 ms)", top - 20.0),
+        ];
+        for (label, y) in labels.iter() {
+            overlay::label(ctx, *label, 14.0, Anchor::TopLeft, [8.0, *y], graphics::Color::from_rgb(160, 160, 160))?;
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,183 @@
+use crate::{
+    live::LiveInput,
+    overlay::{self, Anchor},
+};
+use ggez::{
+    error::GameError,
+    graphics,
+    Context, GameResult,
+};
+use std::{
+    io::Read,
+    process::{Child, Command, Stdio},
+    sync::mpsc,
+    thread,
+};
+
+const SAMPLE_RATE: u32 = 48000;
+const CLASSES: &[&str] = &["Audio/Sink", "Audio/Source", "Stream/Output/Audio"];
+
+#[derive(Debug, Clone)]
+pub struct Node {
+    pub id: u64,
+    pub name: String,
+    pub description: String,
+    pub class: String,
+}
+
+// Lists capturable nodes from pw-dump: devices, their monitors and app streams.
+pub fn list() -> GameResult<Vec<Node>> {
+    let output = Command::new("pw-dump")
+        .output()
+        .map_err(|err| GameError::AudioError(format!("Cannot run pw-dump: {}", err)))?;
+    let objects: serde_json::Value = serde_json::from_slice(&output.stdout)
+        .map_err(|err| GameError::AudioError(format!("Invalid pw-dump output: {}", err)))?;
+
+    let nodes = objects
+        .as_array()
+        .map(Vec::as_slice)
+        .unwrap_or(&[])
+        .iter()
+        .filter(|object| object["type"] == "PipeWire:Interface:Node")
+        .filter_map(|object| {
+            let props = &object["info"]["props"];
+            let class = props["media.class"].as_str()?;
+            if !CLASSES.contains(&class) {
+                return None;
+            }
+            let description = ["node.description", "application.name", "media.name", "node.name"]
+                .iter()
+                .find_map(|key| props[*key].as_str())
+                .unwrap_or("")
+                .to_string();
+            Some(Node {
+                id: object["id"].as_u64()?,
+                name: props["node.name"].as_str().unwrap_or("").to_string(),
+                description,
+                class: class.to_string(),
+            })
+        })
+        .collect();
+
+    Ok(nodes)
+}
+
+struct Capture(Child);
+
+impl Drop for Capture {
+    fn drop(&mut self) {
+        let _ = self.0.kill();
+        let _ = self.0.wait();
+    }
+}
+
+pub fn capture(node: &Node) -> GameResult<LiveInput> {
+    let target = if node.name.is_empty() {
+        node.id.to_string()
+    } else {
+        node.name.clone()
+    };
+    let mut command = Command::new("pw-record");
+    command
+        .args(["--target", &target])
+        .args(["--rate", &SAMPLE_RATE.to_string(), "--channels", "2", "--format", "f32", "--raw"]);
+    if node.class == "Audio/Sink" {
+        // Record what the sink plays rather than waiting for nothing on its input.
+        command.args(["-P", "stream.capture.sink=true"]);
+    }
+    let mut child = command
+        .arg("-")
+        .stdout(Stdio::piped())
+        .spawn()
+        .map_err(|err| GameError::AudioError(format!("Cannot run pw-record: {}", err)))?;
+
+    let mut stdout = child
+        .stdout
+        .take()
+        .ok_or_else(|| GameError::AudioError("pw-record has no output".into()))?;
+    let (sender, receiver) = mpsc::channel();
+    thread::spawn(move || {
+        let mut buffer = [0u8; 4096];
+        while let Ok(len) = stdout.read(&mut buffer) {
+            if len == 0 {
+                break;
+            }
+            let block = buffer[..len - len % 4]
+                .chunks_exact(4)
+                .map(|bytes| f32::from_le_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]))
+                .collect();
+            if sender.send(block).is_err() {
+                break;
+            }
+        }
+    });
+
+    Ok(LiveInput::new(
+        receiver,
+        SAMPLE_RATE,
+        format!("PipeWire {}", node.description),
+        Capture(child),
+    ))
+}
+
+pub struct Picker {
+    nodes: Vec<Node>,
+    selected: usize,
+    error: Option<String>,
+    pub open: bool,
+}
+
+impl Picker {
+    pub fn new() -> Self {
+        Picker {
+            nodes: Vec::new(),
+            selected: 0,
+            error: None,
+            open: false,
+        }
+    }
+
+    pub fn show(&mut self) {
+        match list() {
+            Ok(nodes) => {
+                self.nodes = nodes;
+                self.error = None;
+            }
+            Err(err) => self.error = Some(err.to_string()),
+        }
+        self.selected = 0;
+        self.open = true;
+    }
+
+    pub fn select_by(&mut self, delta: i32) {
+        if !self.nodes.is_empty() {
+            let len = self.nodes.len() as i32;
+            self.selected = (self.selected as i32 + delta).rem_euclid(len) as usize;
+        }
+    }
+
+    pub fn chosen(&self) -> Option<&Node> {
+        self.nodes.get(self.selected)
+    }
+
+    pub fn draw(&self, ctx: &mut Context, width: f32, height: f32) -> GameResult {
+        if !self.open {
+            return Ok(());
+        }
+
+        let mut lines = vec!["Capture from:".to_string()];
+        if let Some(ref error) = self.error {
+            lines.push(format!("  {}", error));
+        } else if self.nodes.is_empty() {
+            lines.push("  no PipeWire nodes found".to_string());
+        }
+        for (idx, node) in self.nodes.iter().enumerate() {
+            let marker = if idx == self.selected { ">" } else { " " };
+            lines.push(format!("{} {} ({})", marker, node.description, node.class));
+        }
+        lines.push("Enter to capture, Escape to close".to_string());
+
+        let text = overlay::text(lines.join("\n"), 20.0);
+        overlay::panel(ctx, &text, Anchor::Top, [width / 2.0, height / 6.0], graphics::WHITE, (width, height))
+    }
+}
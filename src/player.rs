@@ -0,0 +1,514 @@
+use ggez::{
+    audio::{self, SoundSource},
+    error::GameError,
+    Context, GameResult,
+};
+use crate::{binaural, decode, dsp::{self, Biquad, Resampler}, live::LiveInput, loudness, surround, tags::Tags};
+use rodio::Source;
+use std::{
+    fs,
+    io::{self, Read},
+    path,
+    time::Duration,
+};
+
+const VOLUME: f32 = 0.4;
+const QUERY_INTERVAL: Duration = Duration::from_millis(10);
+const LIVE_WINDOW: usize = 2048;
+const LIVE_KEEP: usize = 10;
+const LIVE_MAX: usize = 60;
+// Everything is analysed at this rate unless the config asks for the source's own.
+pub const ANALYSIS_RATE: u32 = 48000;
+// How much of a file decoded by ffmpeg is read for its tags; videos can be
+// far too big to read whole.
+const TAG_PREFIX: u64 = 1 << 20;
+
+pub struct Player {
+    sound: Option<audio::Source>,
+    sample_rate: u32,
+    left_wave: Vec<f32>,
+    right_wave: Vec<f32>,
+    channels: Vec<Vec<f32>>,
+    binaural: Option<(Vec<f32>, Vec<f32>)>,
+    tags: Tags,
+    live: Option<LiveInput>,
+    dropped: usize,
+    start: usize,
+    filters: Vec<Biquad>,
+    gain: f32,
+    normalize: bool,
+    // The listener's own volume on top, 0 to 1.
+    level: f32,
+    // Frames per block a live input delivers, which is its buffer size.
+    block: usize,
+    analysis_rate: Option<u32>,
+    // Live blocks on their way to the analysis rate, one per side.
+    resamplers: Option<(Resampler, Resampler)>,
+    // A position set by the caller instead of the sound's, for rendering
+    // without playing.
+    clock: Option<usize>,
+}
+
+// Brings every wave to `to` at once, a thread each.
+fn resample_all(waves: &mut [Vec<f32>], from: u32, to: u32) {
+    std::thread::scope(|scope| {
+        for wave in waves.iter_mut() {
+            scope.spawn(move || *wave = dsp::resample(wave, from, to));
+        }
+    });
+}
+
+impl Player {
+    pub fn new() -> Self {
+        Player {
+            sound: None,
+            sample_rate: 0,
+            left_wave: Vec::new(),
+            right_wave: Vec::new(),
+            channels: Vec::new(),
+            binaural: None,
+            tags: Tags::default(),
+            live: None,
+            dropped: 0,
+            start: 0,
+            filters: Vec::new(),
+            gain: 1.0,
+            normalize: true,
+            level: 1.0,
+            block: 0,
+            analysis_rate: Some(ANALYSIS_RATE),
+            resamplers: None,
+            clock: None,
+        }
+    }
+
+    // None analyses every source at its own rate.
+    pub fn set_analysis_rate(&mut self, rate: Option<u32>) {
+        self.analysis_rate = rate;
+    }
+
+    pub fn analysis_rate(&self) -> Option<u32> {
+        self.analysis_rate
+    }
+
+    // The rate `from` is analysed at, and whether that needs resampling.
+    fn target(&self, from: u32) -> Option<u32> {
+        self.analysis_rate.filter(|&to| to != from && from > 0)
+    }
+
+    pub fn load<P>(&mut self, path: P, ctx: &mut Context) -> GameResult
+    where
+        P: AsRef<path::Path>,
+    {
+        self.left_wave.clear();
+        self.right_wave.clear();
+        self.channels.clear();
+        self.binaural = None;
+        self.tags = Tags::default();
+        self.live = None;
+        self.dropped = 0;
+        self.sound = None;
+        self.start = 0;
+        self.gain = 1.0;
+
+        let (tags, bytes) = if decode::is_url(path.as_ref()) {
+            (Tags::default(), decode::to_wav(path.as_ref())?)
+        } else {
+            let unreadable = |err: io::Error| GameError::FilesystemError(format!("{}: {}", path.as_ref().display(), err));
+            if decode::needs_external(path.as_ref()) {
+                let mut prefix = Vec::new();
+                fs::File::open(&path)
+                    .and_then(|file| file.take(TAG_PREFIX).read_to_end(&mut prefix))
+                    .map_err(unreadable)?;
+                (Tags::read(&prefix), decode::to_wav(path.as_ref())?)
+            } else {
+                let bytes = fs::read(&path).map_err(unreadable)?;
+                (Tags::read(&bytes), bytes)
+            }
+        };
+        let data = audio::SoundData::from(bytes);
+
+        let mut sound = audio::Source::from_data(ctx, data.clone())?;
+        sound.set_query_interval(QUERY_INTERVAL);
+        self.sound = Some(sound);
+
+        let source = rodio::Decoder::new(io::Cursor::new(data))
+            .map_err(|err| err.to_string());
+
+        match source {
+            Ok(source) if (1..=8).contains(&source.channels()) => {
+                let channels = source.channels() as usize;
+                self.sample_rate = source.sample_rate();
+                dbg!(self.sample_rate);
+
+                let samples: Vec<_> = source.collect();
+                let mut waves: Vec<Vec<f32>> = (0..channels)
+                    .map(|channel| {
+                        samples
+                            .iter()
+                            .skip(channel)
+                            .step_by(channels)
+                            .map(|&amp| amp as f32 / i16::MAX as f32)
+                            .collect()
+                    })
+                    .collect();
+                // Playback starts from the original data; seeks render the
+                // resampled waves, which last just as long.
+                if let Some(to) = self.target(self.sample_rate) {
+                    resample_all(&mut waves, self.sample_rate, to);
+                    self.sample_rate = to;
+                }
+
+                if channels == 2 {
+                    self.right_wave = waves.pop().unwrap_or_default();
+                    self.left_wave = waves.pop().unwrap_or_default();
+                } else {
+                    let (left_wave, right_wave) = surround::downmix(&waves);
+                    self.left_wave = left_wave;
+                    self.right_wave = right_wave;
+                    if channels > 2 {
+                        self.channels = waves;
+                    }
+                }
+
+                dbg!(self.left_wave.len());
+                dbg!(self.right_wave.len());
+
+                self.gain = loudness::normalization(&tags, &self.left_wave, &self.right_wave, self.sample_rate);
+                self.update_volume();
+                self.tags = tags;
+
+                Ok(())
+            }
+            Ok(source) => Err(GameError::AudioError(format!(
+                "Unsupported channel count: {}",
+                source.channels()
+            ))),
+            Err(err) => Err(GameError::FilesystemError(err)),
+        }
+    }
+
+    fn volume(&self) -> f32 {
+        let volume = VOLUME * self.level;
+        if self.normalize {
+            volume * self.gain
+        } else {
+            volume
+        }
+    }
+
+    fn update_volume(&mut self) {
+        let volume = self.volume();
+        if let Some(ref mut sound) = self.sound {
+            sound.set_volume(volume);
+        }
+    }
+
+    pub fn level(&self) -> f32 {
+        self.level
+    }
+
+    pub fn set_level(&mut self, level: f32) {
+        self.level = level.clamp(0.0, 1.0);
+        self.update_volume();
+    }
+
+    pub fn normalize(&self) -> bool {
+        self.normalize
+    }
+
+    pub fn set_normalize(&mut self, normalize: bool) {
+        self.normalize = normalize;
+        self.update_volume();
+    }
+
+    pub fn load_samples(
+        &mut self,
+        ctx: &mut Context,
+        left_wave: Vec<f32>,
+        right_wave: Vec<f32>,
+        sample_rate: u32,
+    ) -> GameResult {
+        let mut waves = [left_wave, right_wave];
+        let sample_rate = match self.target(sample_rate) {
+            Some(to) => {
+                resample_all(&mut waves, sample_rate, to);
+                to
+            }
+            None => sample_rate,
+        };
+        let [left_wave, right_wave] = waves;
+        self.left_wave = left_wave;
+        self.right_wave = right_wave;
+        self.channels.clear();
+        self.binaural = None;
+        self.tags = Tags::default();
+        self.live = None;
+        self.dropped = 0;
+        self.sample_rate = sample_rate;
+        self.start = 0;
+        self.gain = 1.0;
+
+        let mut sound = audio::Source::from_data(ctx, self.render(0)?)?;
+        sound.set_query_interval(QUERY_INTERVAL);
+        self.sound = Some(sound);
+        self.update_volume();
+
+        Ok(())
+    }
+
+    pub fn load_live(&mut self, input: LiveInput) {
+        println!("Listening to {}", input.name);
+        self.left_wave.clear();
+        self.right_wave.clear();
+        self.channels.clear();
+        self.binaural = None;
+        self.tags = Tags::default();
+        self.sound = None;
+        self.resamplers = self
+            .target(input.sample_rate)
+            .map(|to| (Resampler::new(input.sample_rate, to), Resampler::new(input.sample_rate, to)));
+        self.sample_rate = self.target(input.sample_rate).unwrap_or(input.sample_rate);
+        self.start = 0;
+        self.dropped = 0;
+        self.gain = 1.0;
+        self.block = 0;
+        self.live = Some(input);
+    }
+
+    pub fn live(&self) -> bool {
+        self.live.is_some()
+    }
+
+    // How long one capture block takes to fill, the delay the input buffer adds.
+    pub fn capture_latency(&self) -> Option<f32> {
+        let input = self.live.as_ref()?;
+        Some(self.block as f32 / input.sample_rate.max(1) as f32).filter(|&secs| secs > 0.0)
+    }
+
+    // Samples trimmed off the front of a live buffer, for continuous timestamps.
+    pub fn dropped(&self) -> usize {
+        self.dropped
+    }
+
+    pub fn update(&mut self) {
+        let input = match self.live {
+            Some(ref input) => input,
+            None => return,
+        };
+
+        for block in input.receiver.try_iter() {
+            self.block = block.len() / 2;
+            match self.resamplers {
+                Some((ref mut left, ref mut right)) => {
+                    let (l, r): (Vec<f32>, Vec<f32>) = block.chunks_exact(2).map(|frame| (frame[0], frame[1])).unzip();
+                    self.left_wave.extend(left.process(&l));
+                    self.right_wave.extend(right.process(&r));
+                }
+                None => {
+                    for frame in block.chunks_exact(2) {
+                        self.left_wave.push(frame[0]);
+                        self.right_wave.push(frame[1]);
+                    }
+                }
+            }
+        }
+
+        let rate = self.sample_rate as usize;
+        if self.left_wave.len() > rate * LIVE_MAX {
+            let drop = self.left_wave.len() - rate * LIVE_KEEP;
+            self.left_wave.drain(..drop);
+            self.right_wave.drain(..drop);
+            self.dropped += drop;
+        }
+    }
+
+    pub fn tags(&self) -> &Tags {
+        &self.tags
+    }
+
+    pub fn sample_rate(&self) -> u32 {
+        self.sample_rate
+    }
+
+    pub fn left_wave(&self) -> &[f32] {
+        &self.left_wave
+    }
+
+    pub fn right_wave(&self) -> &[f32] {
+        &self.right_wave
+    }
+
+    pub fn channels(&self) -> &[Vec<f32>] {
+        &self.channels
+    }
+
+    pub fn take_channels(&mut self) -> Vec<Vec<f32>> {
+        std::mem::take(&mut self.channels)
+    }
+
+    pub fn len(&self) -> usize {
+        self.left_wave.len().min(self.right_wave.len())
+    }
+
+    pub fn time(&self) -> f32 {
+        self.position() as f32 / self.sample_rate.max(1) as f32
+    }
+
+    pub fn duration(&self) -> f32 {
+        self.len() as f32 / self.sample_rate.max(1) as f32
+    }
+
+    pub fn set_clock(&mut self, position: Option<usize>) {
+        self.clock = position;
+    }
+
+    pub fn playing(&self) -> bool {
+        if let Some(clock) = self.clock {
+            return clock < self.len();
+        }
+        self.live.is_some() || self.sound.as_ref().is_some_and(|sound| sound.playing())
+    }
+
+    pub fn position(&self) -> usize {
+        if self.live.is_some() {
+            return self.len().saturating_sub(LIVE_WINDOW);
+        }
+        if let Some(clock) = self.clock {
+            return clock.min(self.len());
+        }
+
+        match self.sound {
+            Some(ref sound) => {
+                let time = sound.elapsed().as_secs_f32();
+                self.start + (time * self.sample_rate as f32).floor() as usize
+            }
+            None => 0,
+        }
+    }
+
+    pub fn toggle(&mut self, ctx: &mut Context) -> GameResult {
+        let sound = match self.sound {
+            Some(ref mut sound) if self.clock.is_none() => sound,
+            _ => return Ok(()),
+        };
+
+        if sound.playing() {
+            sound.pause();
+        } else if !sound.stopped() {
+            sound.resume();
+        } else if self.start == 0 {
+            sound.play()?;
+        } else {
+            self.seek(ctx, 0)?;
+            if let Some(ref sound) = self.sound {
+                sound.resume();
+            }
+        }
+
+        Ok(())
+    }
+
+    pub fn set_filters(&mut self, ctx: &mut Context, filters: Vec<Biquad>) -> GameResult {
+        self.filters = filters;
+        let position = self.position();
+        self.seek(ctx, position)
+    }
+
+    pub fn binaural(&self) -> bool {
+        self.binaural.is_some()
+    }
+
+    pub fn set_binaural(&mut self, ctx: &mut Context, enabled: bool) -> GameResult {
+        self.binaural = if enabled {
+            Some(binaural::render(&self.left_wave, &self.right_wave, self.sample_rate))
+        } else {
+            None
+        };
+        let position = self.position();
+        self.seek(ctx, position)
+    }
+
+    pub fn seek_by(&mut self, ctx: &mut Context, secs: f32) -> GameResult {
+        let delta = (secs.abs() * self.sample_rate as f32) as usize;
+        let position = self.position();
+        let offset = if secs < 0.0 {
+            position.saturating_sub(delta)
+        } else {
+            position + delta
+        };
+        self.seek(ctx, offset)
+    }
+
+    pub fn seek(&mut self, ctx: &mut Context, offset: usize) -> GameResult {
+        if self.sound.is_none() {
+            return Ok(());
+        }
+
+        let offset = offset.min(self.len());
+        let paused = !self.playing();
+
+        let mut sound = audio::Source::from_data(ctx, self.render(offset)?)?;
+        sound.set_query_interval(QUERY_INTERVAL);
+        sound.set_volume(self.volume());
+        sound.play()?;
+        if paused {
+            sound.pause();
+        }
+
+        self.sound = Some(sound);
+        self.start = offset;
+
+        Ok(())
+    }
+
+    fn render(&self, offset: usize) -> GameResult<audio::SoundData> {
+        let mut left_filters = self.filters.clone();
+        let mut right_filters = self.filters.clone();
+        let (left_wave, right_wave) = match self.binaural {
+            Some((ref left, ref right)) => (left, right),
+            None => (&self.left_wave, &self.right_wave),
+        };
+        let samples = left_wave[offset..]
+            .iter()
+            .zip(&right_wave[offset..])
+            .map(|(&left, &right)| {
+                let left = left_filters
+                    .iter_mut()
+                    .fold(left, |amp, filter| filter.process(amp));
+                let right = right_filters
+                    .iter_mut()
+                    .fold(right, |amp, filter| filter.process(amp));
+                (left, right)
+            });
+
+        encode_wav(samples, self.sample_rate).map(audio::SoundData::from)
+    }
+}
+
+pub fn encode_wav<I>(samples: I, sample_rate: u32) -> GameResult<Vec<u8>>
+where
+    I: Iterator<Item = (f32, f32)>,
+{
+    let spec = hound::WavSpec {
+        channels: 2,
+        sample_rate,
+        bits_per_sample: 16,
+        sample_format: hound::SampleFormat::Int,
+    };
+    let mut cursor = io::Cursor::new(Vec::new());
+    {
+        let mut writer = hound::WavWriter::new(&mut cursor, spec)
+            .map_err(|err| GameError::AudioError(err.to_string()))?;
+        for (left, right) in samples {
+            writer
+                .write_sample((left.clamp(-1.0, 1.0) * i16::MAX as f32) as i16)
+                .and_then(|_| writer.write_sample((right.clamp(-1.0, 1.0) * i16::MAX as f32) as i16))
+                .map_err(|err| GameError::AudioError(err.to_string()))?;
+        }
+        writer
+            .finalize()
+            .map_err(|err| GameError::AudioError(err.to_string()))?;
+    }
+    Ok(cursor.into_inner())
+}
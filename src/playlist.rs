@@ -0,0 +1,281 @@
+use crate::{
+    cache::Bookmark,
+    cue,
+    overlay::{self, Anchor},
+    playlist_file,
+    tags::Tags,
+};
+use ggez::{
+    graphics::{self, DrawParam},
+    Context, GameResult,
+};
+use std::{fs, path::PathBuf};
+
+const PROGRESS_HEIGHT: f32 = 4.0;
+const RESTART: f32 = 2.0;
+
+#[derive(Debug, Clone)]
+pub struct Chapter {
+    pub title: String,
+    pub start: f32,
+}
+
+#[derive(Debug, Clone)]
+pub struct Entry {
+    pub path: PathBuf,
+    pub chapters: Vec<Chapter>,
+    pub bookmarks: Vec<Bookmark>,
+}
+
+impl Entry {
+    pub fn new(path: PathBuf) -> Self {
+        Entry {
+            path,
+            chapters: Vec::new(),
+            bookmarks: Vec::new(),
+        }
+    }
+
+    fn name(&self) -> String {
+        self.path
+            .file_name()
+            .map(|name| name.to_string_lossy().into_owned())
+            .unwrap_or_else(|| self.path.display().to_string())
+    }
+}
+
+fn parse_timestamp(value: &str) -> Option<f32> {
+    value
+        .split(':')
+        .try_fold(0.0, |acc, field| field.trim().parse::<f32>().ok().map(|field| acc * 60.0 + field))
+}
+
+pub fn format_time(secs: f32) -> String {
+    let secs = secs.max(0.0) as u32;
+    if secs >= 3600 {
+        format!("{}:{:02}:{:02}", secs / 3600, secs / 60 % 60, secs % 60)
+    } else {
+        format!("{:02}:{:02}", secs / 60, secs % 60)
+    }
+}
+
+// Vorbis comment chapters (CHAPTERxxx / CHAPTERxxxNAME), which tags.rs also
+// fills in from MP4 chapter lists.
+pub fn chapters_from_tags(tags: &Tags) -> Vec<Chapter> {
+    let mut chapters = Vec::new();
+    for idx in 0..1000 {
+        let key = format!("CHAPTER{:03}", idx);
+        match tags.get(&key).and_then(parse_timestamp) {
+            Some(start) => chapters.push(Chapter {
+                title: tags
+                    .get(&format!("{}NAME", key))
+                    .map(str::to_string)
+                    .unwrap_or_else(|| format!("Chapter {}", chapters.len() + 1)),
+                start,
+            }),
+            None if idx > 0 => break,
+            None => (),
+        }
+    }
+    chapters
+}
+
+// Playlists may include other playlists, so nesting is capped.
+fn expand(path: PathBuf, entries: &mut Vec<Entry>, depth: usize) -> GameResult {
+    let is_cue = path
+        .extension()
+        .is_some_and(|ext| ext.eq_ignore_ascii_case("cue"));
+    if is_cue {
+        let text = fs::read_to_string(&path)?;
+        let dir = path.parent().map(PathBuf::from).unwrap_or_default();
+        entries.extend(cue::parse(&text, &dir)?);
+    } else if playlist_file::is_playlist(&path) && depth < 4 {
+        for path in playlist_file::parse(&path)? {
+            expand(path, entries, depth + 1)?;
+        }
+    } else {
+        entries.push(Entry::new(path));
+    }
+    Ok(())
+}
+
+pub struct Playlist {
+    entries: Vec<Entry>,
+    current: usize,
+    pub visible: bool,
+}
+
+impl Playlist {
+    pub fn new(paths: Vec<PathBuf>) -> GameResult<Self> {
+        let mut entries = Vec::new();
+        for path in paths {
+            expand(path, &mut entries, 0)?;
+        }
+
+        Ok(Playlist {
+            entries,
+            current: 0,
+            visible: false,
+        })
+    }
+
+    pub fn paths(&self) -> Vec<PathBuf> {
+        self.entries.iter().map(|entry| entry.path.clone()).collect()
+    }
+
+    pub fn push(&mut self, path: PathBuf) {
+        self.entries.push(Entry::new(path));
+    }
+
+    pub fn current(&self) -> Option<&Entry> {
+        self.entries.get(self.current)
+    }
+
+    pub fn index(&self) -> usize {
+        self.current
+    }
+
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    pub fn select(&mut self, idx: usize) -> Option<&Entry> {
+        if idx < self.entries.len() {
+            self.current = idx;
+        }
+        self.entries.get(idx)
+    }
+
+    // Files without CUE tracks take their chapters from the loaded tags.
+    pub fn set_chapters(&mut self, chapters: Vec<Chapter>) {
+        if let Some(entry) = self.entries.get_mut(self.current) {
+            if entry.chapters.is_empty() {
+                entry.chapters = chapters;
+            }
+        }
+    }
+
+    pub fn set_bookmarks(&mut self, bookmarks: Vec<Bookmark>) {
+        if let Some(entry) = self.entries.get_mut(self.current) {
+            entry.bookmarks = bookmarks;
+        }
+    }
+
+    pub fn bookmarks(&self) -> &[Bookmark] {
+        self.current().map_or(&[], |entry| &entry.bookmarks)
+    }
+
+    pub fn add_bookmark(&mut self, bookmark: Bookmark) {
+        if let Some(entry) = self.entries.get_mut(self.current) {
+            let idx = entry.bookmarks.partition_point(|other| other.time <= bookmark.time);
+            entry.bookmarks.insert(idx, bookmark);
+        }
+    }
+
+    pub fn next_bookmark(&self, time: f32) -> Option<f32> {
+        self.bookmarks()
+            .iter()
+            .find(|bookmark| bookmark.time > time + 0.5)
+            .map(|bookmark| bookmark.time)
+    }
+
+    pub fn previous_bookmark(&self, time: f32) -> Option<f32> {
+        self.bookmarks()
+            .iter()
+            .rev()
+            .find(|bookmark| bookmark.time < time - RESTART)
+            .map(|bookmark| bookmark.time)
+    }
+
+    pub fn chapters(&self) -> &[Chapter] {
+        self.current().map_or(&[], |entry| &entry.chapters)
+    }
+
+    pub fn chapter_at(&self, time: f32) -> Option<usize> {
+        self.chapters().iter().rposition(|chapter| chapter.start <= time)
+    }
+
+    pub fn next_chapter(&self, time: f32) -> Option<f32> {
+        self.chapters()
+            .iter()
+            .find(|chapter| chapter.start > time + 0.5)
+            .map(|chapter| chapter.start)
+    }
+
+    pub fn previous_chapter(&self, time: f32) -> Option<f32> {
+        let idx = self.chapter_at(time)?;
+        let chapters = self.chapters();
+        if time - chapters[idx].start > RESTART || idx == 0 {
+            Some(chapters[idx].start)
+        } else {
+            Some(chapters[idx - 1].start)
+        }
+    }
+
+    pub fn draw_progress(&self, ctx: &mut Context, width: f32, time: f32, length: f32) -> GameResult {
+        if length <= 0.0 {
+            return Ok(());
+        }
+
+        let filled = graphics::Mesh::new_rectangle(
+            ctx,
+            graphics::DrawMode::fill(),
+            graphics::Rect::new(0.0, 0.0, width * (time / length).clamp(0.0, 1.0), PROGRESS_HEIGHT),
+            graphics::Color::from_rgba(255, 255, 255, 96),
+        )?;
+        graphics::draw(ctx, &filled, DrawParam::default())?;
+
+        for chapter in self.chapters().iter().filter(|chapter| chapter.start > 0.0) {
+            let x = width * chapter.start / length;
+            let marker = graphics::Mesh::new_rectangle(
+                ctx,
+                graphics::DrawMode::fill(),
+                graphics::Rect::new(x - 1.0, 0.0, 2.0, PROGRESS_HEIGHT * 2.5),
+                graphics::Color::from_rgb(255, 200, 64),
+            )?;
+            graphics::draw(ctx, &marker, DrawParam::default())?;
+        }
+
+        for bookmark in self.bookmarks() {
+            let x = width * bookmark.time / length;
+            let marker = graphics::Mesh::new_rectangle(
+                ctx,
+                graphics::DrawMode::fill(),
+                graphics::Rect::new(x - 1.0, 0.0, 2.0, PROGRESS_HEIGHT * 2.5),
+                graphics::Color::from_rgb(96, 200, 255),
+            )?;
+            graphics::draw(ctx, &marker, DrawParam::default())?;
+        }
+
+        Ok(())
+    }
+
+    pub fn draw(&self, ctx: &mut Context, width: f32, height: f32, time: f32) -> GameResult {
+        if !self.visible {
+            return Ok(());
+        }
+
+        let mut lines = Vec::new();
+        for (idx, entry) in self.entries.iter().enumerate() {
+            let marker = if idx == self.current { ">" } else { " " };
+            lines.push(format!("{} {}. {}", marker, idx + 1, entry.name()));
+
+            if idx == self.current {
+                let playing = self.chapter_at(time);
+                for (chapter_idx, chapter) in entry.chapters.iter().enumerate() {
+                    let marker = if Some(chapter_idx) == playing { "*" } else { " " };
+                    lines.push(format!("    {} {} {}", marker, format_time(chapter.start), chapter.title));
+                }
+                for bookmark in &entry.bookmarks {
+                    lines.push(format!("    # {} {}", format_time(bookmark.time), bookmark.name));
+                }
+            }
+        }
+        if lines.is_empty() {
+            lines.push("Playlist is empty".to_string());
+        }
+
+        let text = overlay::text(lines.join("\n"), 18.0);
+        overlay::panel(ctx, &text, Anchor::TopRight, [width - 16.0, 24.0], graphics::WHITE, (width, height))
+    }
+}
@@ -0,0 +1,103 @@
+use crate::decode;
+use ggez::{error::GameError, GameResult};
+use std::{
+    fs,
+    io::Write,
+    path::{Path, PathBuf},
+};
+
+pub fn is_playlist(path: &Path) -> bool {
+    path.extension()
+        .and_then(|ext| ext.to_str())
+        .is_some_and(|ext| ["m3u", "m3u8", "pls", "xspf"].contains(&ext.to_ascii_lowercase().as_str()))
+}
+
+fn percent_decode(text: &str) -> String {
+    let bytes = text.as_bytes();
+    let mut decoded = Vec::with_capacity(bytes.len());
+    let mut idx = 0;
+    while idx < bytes.len() {
+        let hex = text.get(idx + 1..idx + 3).and_then(|hex| u8::from_str_radix(hex, 16).ok());
+        match (bytes[idx], hex) {
+            (b'%', Some(byte)) => {
+                decoded.push(byte);
+                idx += 3;
+            }
+            (byte, _) => {
+                decoded.push(byte);
+                idx += 1;
+            }
+        }
+    }
+    String::from_utf8_lossy(&decoded).into_owned()
+}
+
+fn xml_unescape(text: &str) -> String {
+    text.replace("&lt;", "<")
+        .replace("&gt;", ">")
+        .replace("&quot;", "\"")
+        .replace("&apos;", "'")
+        .replace("&amp;", "&")
+}
+
+// Relative entries are relative to the playlist, URLs are kept as they are.
+fn resolve(entry: &str, dir: &Path) -> PathBuf {
+    let entry = entry.trim();
+    if let Some(path) = entry.strip_prefix("file://") {
+        return PathBuf::from(percent_decode(path));
+    }
+    let path = PathBuf::from(entry);
+    if decode::is_url(&path) || path.is_absolute() {
+        path
+    } else {
+        dir.join(path)
+    }
+}
+
+pub fn parse(path: &Path) -> GameResult<Vec<PathBuf>> {
+    let text = fs::read_to_string(path)?;
+    let dir = path.parent().unwrap_or_else(|| Path::new(""));
+    let ext = path
+        .extension()
+        .and_then(|ext| ext.to_str())
+        .map(|ext| ext.to_ascii_lowercase())
+        .unwrap_or_default();
+
+    let entries = match ext.as_str() {
+        "pls" => text
+            .lines()
+            .filter_map(|line| line.trim().split_once('='))
+            .filter(|(key, _)| key.to_ascii_lowercase().starts_with("file"))
+            .map(|(_, value)| resolve(value, dir))
+            .collect(),
+        "xspf" => text
+            .split("<location>")
+            .skip(1)
+            .filter_map(|rest| rest.split_once("</location>"))
+            .map(|(location, _)| resolve(&xml_unescape(location), dir))
+            .collect(),
+        _ => text
+            .lines()
+            .map(|line| line.trim().trim_start_matches('\u{feff}'))
+            .filter(|line| !line.is_empty() && !line.starts_with('#'))
+            .map(|line| resolve(line, dir))
+            .collect(),
+    };
+
+    Ok(entries)
+}
+
+pub fn write_m3u(path: &Path, entries: &[PathBuf]) -> GameResult {
+    let mut file = fs::File::create(path)
+        .map_err(|err| GameError::FilesystemError(format!("{}: {}", path.display(), err)))?;
+    writeln!(file, "#EXTM3U")?;
+    for entry in entries {
+        let entry = if decode::is_url(entry) {
+            entry.clone()
+        } else {
+            fs::canonicalize(entry).unwrap_or_else(|_| entry.clone())
+        };
+        writeln!(file, "{}", entry.display())?;
+    }
+    Ok(())
+}
@@ -0,0 +1,159 @@
+use crate::{kaleidoscope, starfield, tuner, typography, Mode};
+use ggez::{filesystem, graphics, Context, GameResult};
+use serde::{Deserialize, Serialize};
+use std::{
+    fs,
+    path::{Path, PathBuf},
+};
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Stage {
+    // Bar colors at the bottom and top of the frequency view.
+    pub low: [u8; 3],
+    pub high: [u8; 3],
+    // Scales the bars' brightness and height, and their width on its own.
+    pub sensitivity: f32,
+    pub width: f32,
+}
+
+impl Default for Stage {
+    fn default() -> Self {
+        Stage {
+            low: [16, 128, 192],
+            high: [255, 128, 192],
+            sensitivity: 1.0,
+            width: 1.0,
+        }
+    }
+}
+
+impl Stage {
+    pub fn color(&self, fraction: f32, alpha: u8) -> graphics::Color {
+        let mix = |channel: usize| {
+            let (low, high) = (f32::from(self.low[channel]), f32::from(self.high[channel]));
+            (low + (high - low) * fraction.clamp(0.0, 1.0)) as u8
+        };
+        graphics::Color::from_rgba(mix(0), mix(1), mix(2), alpha)
+    }
+}
+
+// The look of every mode and effect, saved as a small TOML file to switch
+// between or pass around.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Preset {
+    pub name: String,
+    pub mode: Mode,
+    pub stage: Stage,
+    pub tuner: tuner::Settings,
+    pub typography: typography::Settings,
+    pub starfield: starfield::Settings,
+    pub kaleidoscope: kaleidoscope::Settings,
+}
+
+// xorshift, 0..1
+struct Rng(u32);
+
+impl Rng {
+    fn next(&mut self) -> f32 {
+        self.0 ^= self.0 << 13;
+        self.0 ^= self.0 >> 17;
+        self.0 ^= self.0 << 5;
+        self.0 as f32 / u32::MAX as f32
+    }
+
+    fn range(&mut self, low: f32, high: f32) -> f32 {
+        low + (high - low) * self.next()
+    }
+
+    fn chance(&mut self, odds: f32) -> bool {
+        self.next() < odds
+    }
+}
+
+// Hue, saturation and value in 0..1 to RGB.
+fn hsv(hue: f32, saturation: f32, value: f32) -> [u8; 3] {
+    let sector = hue.rem_euclid(1.0) * 6.0;
+    let fraction = sector - sector.floor();
+    let (p, q, t) = (
+        value * (1.0 - saturation),
+        value * (1.0 - saturation * fraction),
+        value * (1.0 - saturation * (1.0 - fraction)),
+    );
+    let (r, g, b) = match sector as u32 {
+        0 => (value, t, p),
+        1 => (q, value, p),
+        2 => (p, value, t),
+        3 => (p, q, value),
+        4 => (t, p, value),
+        _ => (value, p, q),
+    };
+    [(r * 255.0) as u8, (g * 255.0) as u8, (b * 255.0) as u8]
+}
+
+impl Preset {
+    // A random but coherent look: the palette spans neighbouring hues, and
+    // the same seed always gives the same preset.
+    pub fn surprise(seed: u32) -> Self {
+        // xorshift never leaves zero.
+        let mut rng = Rng(seed.max(1));
+        for _ in 0..4 {
+            rng.next();
+        }
+
+        let hue = rng.next();
+        let spread = rng.range(0.1, 0.35);
+        let low = hsv(hue, rng.range(0.5, 0.9), rng.range(0.5, 0.8));
+        let high = hsv(hue + spread, rng.range(0.4, 0.9), 1.0);
+        let mode = match rng.next() {
+            roll if roll < 0.6 => Mode::Stage,
+            roll if roll < 0.75 => Mode::Typography,
+            roll if roll < 0.9 => Mode::Phase,
+            _ => Mode::Cepstrum,
+        };
+
+        Preset {
+            name: format!("Surprise {}", seed),
+            mode,
+            stage: Stage {
+                low,
+                high,
+                sensitivity: rng.range(0.7, 1.6),
+                width: rng.range(0.6, 2.0),
+            },
+            tuner: tuner::Settings::default(),
+            typography: typography::Settings {
+                strips: rng.range(48.0, 192.0) as usize,
+                stretch: rng.range(0.6, 2.0),
+                color: high,
+            },
+            starfield: starfield::Settings {
+                enabled: rng.chance(0.5),
+                stars: rng.range(150.0, 800.0) as usize,
+                warp: rng.range(0.6, 2.5),
+            },
+            kaleidoscope: kaleidoscope::Settings {
+                enabled: rng.chance(0.3),
+                segments: 2 * rng.range(2.0, 7.0) as usize,
+            },
+        }
+    }
+
+    pub fn load(path: &Path) -> GameResult<Self> {
+        Ok(toml::from_str(&fs::read_to_string(path)?)?)
+    }
+
+    pub fn save(&self, path: &Path) -> GameResult {
+        if let Some(dir) = path.parent() {
+            fs::create_dir_all(dir)?;
+        }
+        fs::write(path, toml::to_string(self)?)?;
+        Ok(())
+    }
+
+    // Where the preset for a number key lives.
+    pub fn slot(ctx: &Context, number: usize) -> PathBuf {
+        filesystem::user_config_dir(ctx).join("presets").join(format!("{}.toml", number))
+    }
+}
@@ -0,0 +1,83 @@
+use std::time::{Duration, Instant};
+
+// Bins per drawn bar (and trail points per dot) at the lowest quality is 1 << MAX_LEVEL.
+const MAX_LEVEL: u32 = 3;
+const SMOOTHING_SECS: f32 = 0.5;
+// How long the frame time has to stay out of bounds before the level moves.
+const SETTLE_SECS: f32 = 2.0;
+// Work above this share of the frame budget sheds detail, below the lower
+// one gets it back.
+const OVERLOADED: f32 = 0.9;
+const HEADROOM: f32 = 0.4;
+
+// Watches how long update and draw take (without waiting for vsync) and thins
+// out what is drawn while they don't fit in a frame at the target rate.
+pub struct Quality {
+    budget: f32,
+    started: Option<Instant>,
+    work: f32,
+    level: u32,
+    settled: f32,
+    // Rendering off-line, where taking longer costs nothing on screen.
+    pinned: bool,
+}
+
+impl Quality {
+    pub fn new(target_fps: f32) -> Self {
+        let budget = 1.0 / target_fps.max(1.0);
+        Quality {
+            budget,
+            started: None,
+            work: budget * 0.5,
+            level: 0,
+            settled: 0.0,
+            pinned: false,
+        }
+    }
+
+    // Holds `level` from now on, 0 for full detail.
+    pub fn pin(&mut self, level: u32) {
+        self.level = level.min(MAX_LEVEL);
+        self.pinned = true;
+    }
+
+    pub fn begin(&mut self) {
+        self.started = Some(Instant::now());
+    }
+
+    // Called before presenting the frame, after `delta` seconds since the last.
+    pub fn end(&mut self, delta: f32) {
+        let work = match self.started.take() {
+            Some(started) => started.elapsed(),
+            None => Duration::default(),
+        };
+        self.work += (work.as_secs_f32() - self.work) * (delta / SMOOTHING_SECS).min(1.0);
+        if self.pinned {
+            return;
+        }
+
+        let load = self.work / self.budget;
+        let wanted = if load > OVERLOADED && self.level < MAX_LEVEL {
+            self.level + 1
+        } else if load < HEADROOM && self.level > 0 {
+            self.level - 1
+        } else {
+            self.settled = 0.0;
+            return;
+        };
+        self.settled += delta;
+        if self.settled >= SETTLE_SECS {
+            self.level = wanted;
+            self.settled = 0.0;
+        }
+    }
+
+    // Smoothed seconds of work per frame.
+    pub fn work(&self) -> f32 {
+        self.work
+    }
+
+    pub fn stride(&self) -> usize {
+        1 << self.level
+    }
+}
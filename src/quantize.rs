@@ -0,0 +1,93 @@
+use crate::tempo::TempoCurve;
+use serde::{Deserialize, Serialize};
+
+// Pressed this soon after a boundary, an action counts as on it rather than
+// waiting a whole beat or bar for the next.
+const GRACE: f32 = 0.06;
+
+// Which boundary queued actions wait for.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Grid {
+    #[default]
+    Off,
+    Beat,
+    Bar,
+}
+
+impl Grid {
+    pub fn cycle(self) -> Self {
+        match self {
+            Grid::Off => Grid::Beat,
+            Grid::Beat => Grid::Bar,
+            Grid::Bar => Grid::Off,
+        }
+    }
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Grid::Off => "off",
+            Grid::Beat => "next beat",
+            Grid::Bar => "next bar",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case", tag = "action", content = "slot")]
+pub enum Action {
+    NextMode,
+    Preset(usize),
+    Strobe,
+    Kaleidoscope,
+    Starfield,
+}
+
+// Holds visual changes back until the next beat or bar of the detected grid,
+// so actions taken by hand land in time with the music.
+pub struct Scheduler {
+    pub grid: Grid,
+    // Each action with the playback time it was asked for at.
+    queue: Vec<(Action, f32)>,
+}
+
+impl Scheduler {
+    pub fn new(grid: Grid) -> Self {
+        Scheduler { grid, queue: Vec::new() }
+    }
+
+    // The latest boundary at or before `now`, None without a beat grid.
+    fn boundary(&self, tempo: &TempoCurve, now: f32) -> Option<f32> {
+        let (period, beat, bar) = tempo.beat()?;
+        let (anchor, step) = match self.grid {
+            Grid::Off => return None,
+            Grid::Beat => (beat, period),
+            Grid::Bar => (bar, period * 4.0),
+        };
+        Some(anchor + ((now - anchor) / step).floor() * step)
+    }
+
+    // Returns the action right away when there is nothing to wait for.
+    pub fn push(&mut self, action: Action, tempo: &TempoCurve, now: f32) -> Option<Action> {
+        match self.boundary(tempo, now) {
+            Some(boundary) if now - boundary > GRACE => {
+                self.queue.retain(|&(queued, _)| queued != action);
+                self.queue.push((action, now));
+                None
+            }
+            _ => Some(action),
+        }
+    }
+
+    // The actions whose boundary `now` has reached, in the order asked for.
+    pub fn update(&mut self, tempo: &TempoCurve, now: f32) -> Vec<Action> {
+        let boundary = match self.boundary(tempo, now) {
+            Some(boundary) => boundary,
+            // The grid was switched off or lost: nothing left to wait for.
+            None => return self.queue.drain(..).map(|(action, _)| action).collect(),
+        };
+        let (due, waiting) = self.queue.drain(..).partition(|&(_, at)| at < boundary);
+        self.queue = waiting;
+        due.into_iter().map(|(action, _)| action).collect::<Vec<_>>()
+    }
+}
@@ -0,0 +1,137 @@
+use ggez::{error::GameError, GameResult};
+use std::env;
+#[cfg(not(target_os = "macos"))]
+use std::process::Command;
+#[cfg(all(unix, not(target_os = "macos")))]
+use std::path::{Path, PathBuf};
+
+pub const EXTENSIONS: &[&str] = &["mp3", "wav", "flac", "ogg", "opus", "wv"];
+
+#[cfg(all(unix, not(target_os = "macos")))]
+const MIME_TYPES: &[&str] = &[
+    "audio/mpeg",
+    "audio/wav",
+    "audio/x-wav",
+    "audio/flac",
+    "audio/x-flac",
+    "audio/ogg",
+    "audio/x-vorbis+ogg",
+    "audio/x-opus+ogg",
+    "audio/x-wavpack",
+];
+
+#[cfg(all(unix, not(target_os = "macos")))]
+pub fn register() -> GameResult {
+    use std::fs;
+
+    let exe = env::current_exe()?;
+
+    let data_dir = env::var_os("XDG_DATA_HOME")
+        .map(PathBuf::from)
+        .or_else(|| env::var_os("HOME").map(|home| Path::new(&home).join(".local/share")))
+        .ok_or_else(|| GameError::FilesystemError("Cannot find the data directory".into()))?;
+    let app_dir = data_dir.join("applications");
+    fs::create_dir_all(&app_dir)?;
+
+    let entry = format!(
+        "[Desktop Entry]\n\
+         Type=Application\n\
+         Name=Stereo Visualizer\n\
+         Exec={} %f\n\
+         Terminal=false\n\
+         NoDisplay=true\n\
+         Categories=AudioVideo;Audio;\n\
+         MimeType={};\n",
+        desktop_quote(&exe),
+        MIME_TYPES.join(";"),
+    );
+    let entry_path = app_dir.join("stereo-visualizer.desktop");
+    fs::write(&entry_path, entry)?;
+    println!("Wrote {}", entry_path.display());
+
+    run(Command::new("xdg-mime")
+        .arg("default")
+        .arg("stereo-visualizer.desktop")
+        .args(MIME_TYPES))?;
+    // Only refreshes the cache, so a missing tool is not fatal.
+    let _ = Command::new("update-desktop-database").arg(&app_dir).status();
+
+    println!("Registered for {}", EXTENSIONS.join(", "));
+    Ok(())
+}
+
+#[cfg(all(unix, not(target_os = "macos")))]
+fn desktop_quote(path: &Path) -> String {
+    let mut quoted = String::from("\"");
+    for ch in path.to_string_lossy().chars() {
+        if let '"' | '`' | '$' | '\\' = ch {
+            quoted.push('\\');
+        }
+        quoted.push(ch);
+    }
+    quoted.push('"');
+    quoted
+}
+
+#[cfg(windows)]
+pub fn register() -> GameResult {
+    const PROG_ID: &str = "StereoVisualizer.AudioFile";
+
+    let exe = env::current_exe()?;
+    let classes = r"HKCU\Software\Classes";
+
+    let command = format!("\"{}\" \"%1\"", exe.display());
+    run(Command::new("reg").args(&[
+        "add",
+        &format!(r"{}\{}\shell\open\command", classes, PROG_ID),
+        "/ve",
+        "/d",
+        &command,
+        "/f",
+    ]))?;
+    run(Command::new("reg").args(&[
+        "add",
+        &format!(r"{}\{}", classes, PROG_ID),
+        "/v",
+        "FriendlyTypeName",
+        "/d",
+        "Stereo Visualizer audio file",
+        "/f",
+    ]))?;
+
+    for ext in EXTENSIONS {
+        run(Command::new("reg").args(&[
+            "add",
+            &format!(r"{}\.{}\OpenWithProgids", classes, ext),
+            "/v",
+            PROG_ID,
+            "/t",
+            "REG_NONE",
+            "/f",
+        ]))?;
+    }
+
+    println!("Registered for {}", EXTENSIONS.join(", "));
+    Ok(())
+}
+
+#[cfg(target_os = "macos")]
+pub fn register() -> GameResult {
+    let exe = env::current_exe()?;
+    Err(GameError::ConfigError(format!(
+        "{} is not inside an app bundle; file types ({}) are declared by \
+         CFBundleDocumentTypes in the bundle's Info.plist",
+        exe.display(),
+        EXTENSIONS.join(", "),
+    )))
+}
+
+#[cfg(not(target_os = "macos"))]
+fn run(command: &mut Command) -> GameResult {
+    let status = command.status()?;
+    if status.success() {
+        Ok(())
+    } else {
+        Err(GameError::ConfigError(format!("{:?} failed with {}", command, status)))
+    }
+}
@@ -0,0 +1,78 @@
+use crate::DirectionalSource;
+use std::collections::VecDeque;
+
+const LENGTH: f32 = 10.0;
+pub const SPEED: f32 = 0.25;
+
+pub struct Frame {
+    pub time: f32,
+    pub left_rev: Vec<f32>,
+    pub right_rev: Vec<f32>,
+    pub directions: Vec<DirectionalSource>,
+}
+
+pub struct ReplayBuffer {
+    frames: VecDeque<Frame>,
+    cursor: Option<(f32, usize)>,
+}
+
+impl ReplayBuffer {
+    pub fn new() -> Self {
+        ReplayBuffer {
+            frames: VecDeque::new(),
+            cursor: None,
+        }
+    }
+
+    pub fn record(&mut self, time: f32, left_rev: &[f32], right_rev: &[f32], directions: &[DirectionalSource]) {
+        match self.frames.back() {
+            Some(frame) if frame.time == time => return,
+            Some(frame) if frame.time > time => self.frames.clear(),
+            _ => (),
+        }
+
+        while self.frames.front().is_some_and(|frame| frame.time < time - LENGTH) {
+            self.frames.pop_front();
+        }
+
+        self.frames.push_back(Frame {
+            time,
+            left_rev: left_rev.to_vec(),
+            right_rev: right_rev.to_vec(),
+            directions: directions.to_vec(),
+        });
+    }
+
+    pub fn active(&self) -> bool {
+        self.cursor.is_some()
+    }
+
+    pub fn time(&self) -> Option<f32> {
+        self.cursor.map(|(time, _)| time)
+    }
+
+    pub fn start(&mut self) {
+        self.cursor = self.frames.front().map(|frame| (frame.time, 0));
+    }
+
+    pub fn stop(&mut self) {
+        self.cursor = None;
+    }
+
+    pub fn advance(&mut self, delta: f32) -> Option<&Frame> {
+        let (mut time, mut idx) = self.cursor?;
+        time += delta * SPEED;
+
+        while idx + 1 < self.frames.len() && self.frames[idx + 1].time <= time {
+            idx += 1;
+        }
+
+        if idx + 1 >= self.frames.len() {
+            self.cursor = None;
+            return None;
+        }
+
+        self.cursor = Some((time, idx));
+        self.frames.get(idx)
+    }
+}
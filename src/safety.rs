@@ -0,0 +1,40 @@
+// Photosensitive epilepsy guidance (ITU-R BT.1702, Ofcom) allows at most three
+// flashes in any second; slower flashing is no risk whatever its contrast.
+pub const MAX_FLASH_HZ: f32 = 3.0;
+// In safe mode, how far a full-screen brightness may move per second.
+const SAFE_SLEW: f32 = 0.5;
+
+// The limits every flashing or pulsing effect goes through.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct Safety {
+    // Photosensitive safe mode: no strobes, and brightness that only drifts.
+    pub safe: bool,
+}
+
+impl Safety {
+    pub fn new(safe: bool) -> Self {
+        Safety { safe }
+    }
+
+    // Seconds from one flash to the next, never faster than the limit.
+    // None in safe mode, where nothing may flash.
+    pub fn flash_interval(&self, interval: f32) -> Option<f32> {
+        if self.safe {
+            None
+        } else {
+            Some(interval.max(1.0 / MAX_FLASH_HZ))
+        }
+    }
+
+    // Moves a brightness `share` of the way to `target`, in safe mode no
+    // faster than a slow fade.
+    pub fn follow(&self, level: f32, target: f32, share: f32, delta: f32) -> f32 {
+        let step = (target - level) * share;
+        if self.safe {
+            let limit = SAFE_SLEW * delta;
+            level + step.clamp(-limit, limit)
+        } else {
+            level + step
+        }
+    }
+}
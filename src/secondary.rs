@@ -0,0 +1,256 @@
+use crate::{
+    config::Config,
+    overlay::{self, Anchor},
+    spectrogram,
+};
+use ggez::{
+    conf::{WindowMode, WindowSetup},
+    error::GameError,
+    event::{self, EventHandler},
+    graphics::{self, DrawParam, Image},
+    timer, Context, GameResult,
+};
+use std::{
+    collections::VecDeque,
+    env,
+    io::{self, Read, Write},
+    process::{Child, ChildStdin, Command, Stdio},
+    sync::mpsc::{self, Receiver},
+    thread,
+};
+use winit::dpi::LogicalPosition;
+
+const COLUMNS: usize = 600;
+const ROWS: usize = 256;
+const LOW_HZ: f32 = 20.0;
+const HIGH_HZ: f32 = 20000.0;
+const FLOOR_DB: f32 = -90.0;
+// What a full-scale sine reads in one bin of the 1024-point transform.
+const FULL_SCALE: f32 = 512.0;
+const METER_WIDTH: f32 = 96.0;
+const PEAK_HOLD_SECS: f32 = 1.5;
+
+// One analysis frame, as the main window sends it down the pipe.
+pub struct Frame {
+    pub bin_hz: f32,
+    pub levels: [f32; 2],
+    pub left: Vec<f32>,
+    pub right: Vec<f32>,
+}
+
+impl Frame {
+    fn write(&self, out: &mut impl Write) -> io::Result<()> {
+        let mut bytes = Vec::with_capacity(16 + self.left.len() * 8);
+        bytes.extend_from_slice(&(self.left.len() as u32).to_le_bytes());
+        for value in [self.bin_hz, self.levels[0], self.levels[1]].iter().chain(&self.left).chain(&self.right) {
+            bytes.extend_from_slice(&value.to_le_bytes());
+        }
+        out.write_all(&bytes)?;
+        out.flush()
+    }
+
+    fn read(input: &mut impl Read) -> io::Result<Frame> {
+        let mut word = [0u8; 4];
+        input.read_exact(&mut word)?;
+        let bins = u32::from_le_bytes(word) as usize;
+        let mut values = vec![0.0; 3 + bins * 2];
+        for value in values.iter_mut() {
+            input.read_exact(&mut word)?;
+            *value = f32::from_le_bytes(word);
+        }
+        let right = values.split_off(3 + bins);
+        let left = values.split_off(3);
+        Ok(Frame {
+            bin_hz: values[0],
+            levels: [values[1], values[2]],
+            left,
+            right,
+        })
+    }
+}
+
+// The main window's end: a second copy of the program, fed over its stdin.
+pub struct Link {
+    child: Child,
+    stdin: ChildStdin,
+}
+
+impl Link {
+    pub fn spawn() -> GameResult<Self> {
+        let exe = env::current_exe()?;
+        let mut child = Command::new(exe)
+            .arg("--secondary")
+            .stdin(Stdio::piped())
+            .spawn()
+            .map_err(|err| GameError::WindowError(format!("Cannot open the analysis window: {}", err)))?;
+        let stdin = child
+            .stdin
+            .take()
+            .ok_or_else(|| GameError::WindowError("The analysis window has no input".into()))?;
+        Ok(Link { child, stdin })
+    }
+
+    // False once the window has been closed.
+    pub fn send(&mut self, frame: &Frame) -> bool {
+        frame.write(&mut self.stdin).is_ok()
+    }
+}
+
+impl Drop for Link {
+    fn drop(&mut self) {
+        let _ = self.child.kill();
+        let _ = self.child.wait();
+    }
+}
+
+// The second window's end: a scrolling spectrogram with left and right level
+// meters beside it.
+struct Analysis {
+    frames: Receiver<Frame>,
+    columns: VecDeque<[f32; ROWS]>,
+    levels: [f32; 2],
+    peaks: [(f32, f32); 2],
+}
+
+impl Analysis {
+    fn column(frame: &Frame) -> [f32; ROWS] {
+        let mut column = [0.0; ROWS];
+        let bins = frame.left.len().min(frame.right.len());
+        for (row, value) in column.iter_mut().enumerate() {
+            // Row 0 is the top, so the highest frequency.
+            let at = (ROWS - 1 - row) as f32 / ROWS as f32;
+            let hz = LOW_HZ * (HIGH_HZ / LOW_HZ).powf(at);
+            let bin = ((hz / frame.bin_hz.max(1e-3)) as usize).min(bins.saturating_sub(1));
+            let amp = frame.left.get(bin).zip(frame.right.get(bin)).map_or(0.0, |(l, r)| (l + r) / 2.0);
+            let db = 20.0 * (amp / FULL_SCALE).max(1e-9).log10();
+            *value = ((db - FLOOR_DB) / -FLOOR_DB).clamp(0.0, 1.0);
+        }
+        column
+    }
+
+    fn image(&self, ctx: &mut Context) -> GameResult<Image> {
+        let mut rgba = vec![0u8; COLUMNS * ROWS * 4];
+        let start = COLUMNS - self.columns.len();
+        for (x, column) in self.columns.iter().enumerate() {
+            for (y, &value) in column.iter().enumerate() {
+                rgba[(y * COLUMNS + start + x) * 4..][..4].copy_from_slice(&spectrogram::heat(value));
+            }
+        }
+        Image::from_rgba8(ctx, COLUMNS as u16, ROWS as u16, &rgba)
+    }
+}
+
+impl EventHandler for Analysis {
+    fn update(&mut self, ctx: &mut Context) -> GameResult {
+        let delta = timer::delta(ctx).as_secs_f32();
+        loop {
+            match self.frames.try_recv() {
+                Ok(frame) => {
+                    self.columns.push_back(Self::column(&frame));
+                    if self.columns.len() > COLUMNS {
+                        self.columns.pop_front();
+                    }
+                    self.levels = frame.levels;
+                }
+                Err(mpsc::TryRecvError::Empty) => break,
+                // The main window has gone, and this one goes with it.
+                Err(mpsc::TryRecvError::Disconnected) => {
+                    event::quit(ctx);
+                    break;
+                }
+            }
+        }
+
+        for (peak, &level) in self.peaks.iter_mut().zip(&self.levels) {
+            peak.1 += delta;
+            if level >= peak.0 || peak.1 > PEAK_HOLD_SECS {
+                *peak = (level, 0.0);
+            }
+        }
+        Ok(())
+    }
+
+    fn draw(&mut self, ctx: &mut Context) -> GameResult {
+        graphics::clear(ctx, graphics::BLACK);
+        let screen = graphics::screen_coordinates(ctx);
+        let (width, height) = (screen.w - METER_WIDTH, screen.h);
+
+        let image = self.image(ctx)?;
+        let scale = [width / COLUMNS as f32, height / ROWS as f32];
+        graphics::draw(ctx, &image, DrawParam::default().scale(scale))?;
+
+        let fraction = |level: f32| {
+            let db = 20.0 * level.max(1e-9).log10();
+            ((db - FLOOR_DB) / -FLOOR_DB).clamp(0.0, 1.0)
+        };
+        let mut builder = graphics::MeshBuilder::new();
+        let bar = METER_WIDTH / 2.0 - 12.0;
+        for (idx, (&level, &(peak, _))) in self.levels.iter().zip(&self.peaks).enumerate() {
+            let x = width + 8.0 + idx as f32 * (bar + 8.0);
+            let meter = height - 40.0;
+            let top = 8.0 + meter * (1.0 - fraction(level));
+            let rect = graphics::Rect::new(x, top, bar, 8.0 + meter - top);
+            let color = if level > 0.9 {
+                graphics::Color::from_rgb(255, 80, 64)
+            } else {
+                graphics::Color::from_rgb(96, 220, 128)
+            };
+            builder.rectangle(graphics::DrawMode::fill(), rect, color);
+            let held = 8.0 + meter * (1.0 - fraction(peak));
+            builder.line(&[[x, held], [x + bar, held]], 2.0, graphics::WHITE)?;
+        }
+        let mesh = builder.build(ctx)?;
+        graphics::draw(ctx, &mesh, DrawParam::default())?;
+
+        for (idx, name) in ["L", "R"].iter().enumerate() {
+            let x = width + 8.0 + idx as f32 * (bar + 8.0) + bar / 2.0;
+            overlay::label(ctx, *name, 16.0, Anchor::Center, [x, height - 16.0], graphics::WHITE)?;
+        }
+
+        graphics::present(ctx)
+    }
+
+    fn resize_event(&mut self, ctx: &mut Context, width: f32, height: f32) {
+        graphics::set_screen_coordinates(ctx, graphics::Rect::new(0.0, 0.0, width, height))
+            .expect("Resize analysis window");
+    }
+}
+
+// Puts the window on a monitor other than the one it opened on, if any.
+fn move_to_other_monitor(ctx: &Context) {
+    let window = graphics::window(ctx);
+    let current = window.get_current_monitor();
+    if let Some(other) = window.get_available_monitors().find(|monitor| monitor.get_name() != current.get_name()) {
+        let position = other.get_position().to_logical(other.get_hidpi_factor());
+        window.set_position(LogicalPosition::new(position.x + 32.0, position.y + 32.0));
+    }
+}
+
+// Runs the analysis window until its input closes.
+pub fn run() -> GameResult {
+    let (sender, frames) = mpsc::channel();
+    thread::spawn(move || {
+        let mut stdin = io::stdin();
+        while let Ok(frame) = Frame::read(&mut stdin) {
+            if sender.send(frame).is_err() {
+                break;
+            }
+        }
+    });
+
+    let (ctx, event_loop) = &mut ggez::ContextBuilder::new("stereo-visualizer", "neurowhai")
+        .window_setup(WindowSetup::default().title("Stereo Visualizer Analysis"))
+        .window_mode(WindowMode::default().dimensions(800.0, 400.0).resizable(true))
+        .build()?;
+    let config = Config::load(ctx)?;
+    overlay::init(ctx, config.font.as_deref())?;
+    move_to_other_monitor(ctx);
+
+    let state = &mut Analysis {
+        frames,
+        columns: VecDeque::with_capacity(COLUMNS),
+        levels: [0.0; 2],
+        peaks: [(0.0, 0.0); 2],
+    };
+    event::run(ctx, event_loop, state)
+}
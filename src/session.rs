@@ -0,0 +1,34 @@
+use crate::{cache::Bookmark, Mode};
+use ggez::GameResult;
+use serde::{Deserialize, Serialize};
+use std::{
+    fs,
+    path::{Path, PathBuf},
+};
+
+// A snapshot of the analysis workspace: what was loaded, where playback was,
+// and how the view and EQ were set up.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Session {
+    pub files: Vec<PathBuf>,
+    pub current: usize,
+    pub time: f32,
+    pub eq: Vec<f32>,
+    pub eq_playback: bool,
+    pub mode: Mode,
+    pub zoom: Option<(f32, f32)>,
+    // Last, as TOML needs tables after plain values.
+    pub bookmarks: Vec<Bookmark>,
+}
+
+impl Session {
+    pub fn load(path: &Path) -> GameResult<Self> {
+        Ok(toml::from_str(&fs::read_to_string(path)?)?)
+    }
+
+    pub fn save(&self, path: &Path) -> GameResult {
+        fs::write(path, toml::to_string(self)?)?;
+        Ok(())
+    }
+}
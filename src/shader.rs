@@ -0,0 +1,245 @@
+use crate::typography;
+use ggez::{
+    graphics::{self, DrawParam, Image},
+    Context, GameResult,
+};
+use gfx::{gfx_constant_struct_meta, gfx_defines, gfx_impl_struct_meta};
+use rustfft::num_complex::Complex;
+use std::{collections::VecDeque, fs, iter, path::Path};
+
+// Shadertoy's sound input: 512 FFT values over 0..11 kHz, then 512 samples.
+const WIDTH: usize = 512;
+const TOP_HZ: f32 = 11025.0;
+// Web Audio's analyser range, which Shadertoy's FFT row is scaled to.
+const MIN_DB: f32 = -100.0;
+const MAX_DB: f32 = -30.0;
+const SMOOTHING: f32 = 0.8;
+// Older FFT rows kept below the waveform for shaders that ask for them.
+const HISTORY: usize = 62;
+const HISTORY_MARKER: &str = "SPECTRUM_HISTORY";
+const BAND_EDGES: [f32; 5] = [20.0, 250.0, 2000.0, 6000.0, 20000.0];
+
+gfx_defines! {
+    constant Uniforms {
+        resolution: [f32; 3] = "iResolution",
+        time: f32 = "iTime",
+        time_delta: f32 = "iTimeDelta",
+        beat: f32 = "iBeat",
+        sample_rate: f32 = "iSampleRate",
+        frame: i32 = "iFrame",
+        mouse: [f32; 4] = "iMouse",
+        bands: [f32; 4] = "iBands",
+        date: [f32; 4] = "iDate",
+    }
+}
+
+// ggez's own sprite vertex shader.
+const VERTEX: &str = "#version 150 core
+
+in vec2 a_Pos;
+in vec2 a_Uv;
+
+in vec4 a_Src;
+in vec4 a_TCol1;
+in vec4 a_TCol2;
+in vec4 a_TCol3;
+in vec4 a_TCol4;
+in vec4 a_Color;
+
+layout (std140) uniform Globals {
+    mat4 u_MVP;
+};
+
+out vec2 v_Uv;
+out vec4 v_Color;
+
+void main() {
+    v_Uv = a_Uv * a_Src.zw + a_Src.xy;
+    v_Color = a_Color;
+    mat4 instance_transform = mat4(a_TCol1, a_TCol2, a_TCol3, a_TCol4);
+    gl_Position = u_MVP * instance_transform * vec4(a_Pos, 0.0, 1.0);
+}
+";
+
+const PRELUDE: &str = "#version 150 core
+
+uniform sampler2D t_Texture;
+in vec2 v_Uv;
+in vec4 v_Color;
+out vec4 Target0;
+
+layout (std140) uniform Globals {
+    mat4 u_MVP;
+};
+
+layout (std140) uniform Uniforms {
+    vec3 iResolution;
+    float iTime;
+    float iTimeDelta;
+    float iBeat;
+    float iSampleRate;
+    int iFrame;
+    vec4 iMouse;
+    vec4 iBands;
+    vec4 iDate;
+};
+
+#define iChannel0 t_Texture
+#define iChannel1 t_Texture
+#define iChannel2 t_Texture
+#define iChannel3 t_Texture
+";
+
+const EPILOGUE: &str = "
+void main() {
+    vec4 color = vec4(0.0, 0.0, 0.0, 1.0);
+    mainImage(color, gl_FragCoord.xy);
+    Target0 = vec4(color.rgb, 1.0);
+}
+";
+
+pub struct Frame {
+    pub time: f32,
+    pub delta: f32,
+    pub beat: f32,
+    pub sample_rate: u32,
+    pub mouse: [f32; 2],
+    pub frame: usize,
+}
+
+// A Shadertoy-style fragment shader (a `mainImage` function) fed with the
+// analysis: iChannel0 is the usual 512x2 sound texture, iBands holds bass,
+// low-mid, high-mid and treble energy, iBeat the phase within the beat.
+pub struct ShaderMode {
+    shader: graphics::Shader<Uniforms>,
+    rows: usize,
+    spectrum: Vec<f32>,
+    wave: Vec<f32>,
+    history: VecDeque<Vec<f32>>,
+    bands: [f32; 4],
+}
+
+impl ShaderMode {
+    pub fn load(ctx: &mut Context, path: &Path) -> GameResult<Self> {
+        let source = fs::read_to_string(path)?;
+        // A shader that defines SPECTRUM_HISTORY gets older FFT rows under the
+        // first two, at the cost of Shadertoy's row positions.
+        let rows = if source.contains(HISTORY_MARKER) { 2 + HISTORY } else { 2 };
+        let resolution = format!(
+            "const vec3 iChannelResolution[4] = vec3[4](vec3({0}.0, {1}.0, 1.0), vec3({0}.0, {1}.0, 1.0), \
+             vec3({0}.0, {1}.0, 1.0), vec3({0}.0, {1}.0, 1.0));\n",
+            WIDTH, rows
+        );
+        let pixel = format!("{}{}\n#line 1\n{}\n{}", PRELUDE, resolution, source, EPILOGUE);
+        let uniforms = Uniforms {
+            resolution: [0.0; 3],
+            time: 0.0,
+            time_delta: 0.0,
+            beat: 0.0,
+            sample_rate: 0.0,
+            frame: 0,
+            mouse: [0.0; 4],
+            bands: [0.0; 4],
+            date: [0.0; 4],
+        };
+        let shader = graphics::Shader::from_u8(
+            ctx,
+            VERTEX.as_bytes(),
+            pixel.as_bytes(),
+            uniforms,
+            "Uniforms",
+            None,
+        )?;
+
+        Ok(ShaderMode {
+            shader,
+            rows,
+            spectrum: vec![0.0; WIDTH],
+            wave: vec![0.5; WIDTH],
+            history: VecDeque::new(),
+            bands: [0.0; 4],
+        })
+    }
+
+    // Takes the latest transforms and the samples they were made from.
+    pub fn update(&mut self, left: &[Complex<f32>], right: &[Complex<f32>], wave: (&[f32], &[f32]), sample_rate: u32) {
+        let size = left.len().min(right.len());
+        if size < 2 {
+            return;
+        }
+        let bin_hz = sample_rate as f32 / size as f32;
+        let magnitude = |bin: usize| ((left[bin] + right[bin]) * 0.5).norm() * 2.0 / size as f32;
+        let db = |bin: usize| 20.0 * magnitude(bin).max(1e-9).log10();
+
+        for (idx, value) in self.spectrum.iter_mut().enumerate() {
+            let at = (idx as f32 * TOP_HZ / WIDTH as f32 / bin_hz).min((size / 2 - 1) as f32);
+            let low = at as usize;
+            let high = (low + 1).min(size / 2 - 1);
+            let level = db(low) + (db(high) - db(low)) * (at - low as f32);
+            let scaled = ((level - MIN_DB) / (MAX_DB - MIN_DB)).clamp(0.0, 1.0);
+            *value = *value * SMOOTHING + scaled * (1.0 - SMOOTHING);
+        }
+
+        for (band, edge) in self.bands.iter_mut().zip(BAND_EDGES.windows(2)) {
+            let first = ((edge[0] / bin_hz) as usize).max(1);
+            let last = ((edge[1] / bin_hz) as usize).clamp(first + 1, size / 2);
+            let energy = (first..last).map(magnitude).sum::<f32>() / (last - first) as f32;
+            let level = ((20.0 * energy.max(1e-9).log10() - MIN_DB) / (MAX_DB - MIN_DB)).clamp(0.0, 1.0);
+            *band = *band * SMOOTHING + level * (1.0 - SMOOTHING);
+        }
+
+        let (left_wave, right_wave) = wave;
+        let start = left_wave.len().min(right_wave.len()).saturating_sub(WIDTH);
+        for (idx, value) in self.wave.iter_mut().enumerate() {
+            let (l, r) = (left_wave.get(start + idx), right_wave.get(start + idx));
+            let sample = l.zip(r).map_or(0.0, |(l, r)| (l + r) * 0.5);
+            *value = (0.5 + sample * 0.5).clamp(0.0, 1.0);
+        }
+
+        if self.rows > 2 {
+            self.history.push_front(self.spectrum.clone());
+            self.history.truncate(HISTORY);
+        }
+    }
+
+    fn texture(&self, ctx: &mut Context) -> GameResult<Image> {
+        let empty = vec![0.0; WIDTH];
+        let rows = iter::once(&self.spectrum)
+            .chain(iter::once(&self.wave))
+            .chain(self.history.iter())
+            .chain(iter::repeat(&empty))
+            .take(self.rows);
+        let mut rgba = Vec::with_capacity(WIDTH * self.rows * 4);
+        for row in rows {
+            for &value in row {
+                let byte = (value * 255.0) as u8;
+                rgba.extend_from_slice(&[byte, byte, byte, 255]);
+            }
+        }
+        Image::from_rgba8(ctx, WIDTH as u16, self.rows as u16, &rgba)
+    }
+
+    pub fn draw(&self, ctx: &mut Context, frame: &Frame, width: f32, height: f32) -> GameResult {
+        let (pixels_wide, pixels_high) = graphics::drawable_size(ctx);
+        let dpi = pixels_wide / width.max(1.0);
+        let (hours, minutes, seconds) = typography::local_time();
+        let uniforms = Uniforms {
+            resolution: [pixels_wide, pixels_high, 1.0],
+            time: frame.time,
+            time_delta: frame.delta,
+            beat: frame.beat,
+            sample_rate: frame.sample_rate as f32,
+            frame: frame.frame as i32,
+            // Shadertoy counts pixels from the bottom left.
+            mouse: [frame.mouse[0] * dpi, pixels_high - frame.mouse[1] * dpi, 0.0, 0.0],
+            bands: self.bands,
+            date: [0.0, 0.0, 0.0, (hours * 3600 + minutes * 60 + seconds) as f32],
+        };
+
+        let texture = self.texture(ctx)?;
+        let _lock = graphics::use_shader(ctx, &self.shader);
+        self.shader.send(ctx, uniforms)?;
+        let scale = [width / f32::from(texture.width()), height / f32::from(texture.height())];
+        graphics::draw(ctx, &texture, DrawParam::default().scale(scale))
+    }
+}
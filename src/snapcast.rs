@@ -0,0 +1,238 @@
+pub use client::connect;
+
+// The Snapcast client, left out of builds without the network feature.
+#[cfg(feature = "network")]
+mod client {
+    use crate::{
+        live::LiveInput,
+        pcm::{Encoding, Format},
+    };
+    use ggez::{error::GameError, GameResult};
+    use std::{
+        collections::VecDeque,
+        io::{Read, Write},
+        net::TcpStream,
+        sync::mpsc,
+        thread,
+        time::{Duration, Instant},
+    };
+
+    const PORT: u16 = 1704;
+    const HEADER: usize = 26;
+
+    const CODEC_HEADER: u16 = 1;
+    const WIRE_CHUNK: u16 = 2;
+    const SERVER_SETTINGS: u16 = 3;
+    const TIME: u16 = 4;
+    const HELLO: u16 = 5;
+
+    fn le16(bytes: &[u8], pos: usize) -> u16 {
+        u16::from_le_bytes([bytes[pos], bytes[pos + 1]])
+    }
+
+    fn le32(bytes: &[u8], pos: usize) -> u32 {
+        u32::from_le_bytes([bytes[pos], bytes[pos + 1], bytes[pos + 2], bytes[pos + 3]])
+    }
+
+    fn timeval(bytes: &[u8], pos: usize) -> f64 {
+        le32(bytes, pos) as i32 as f64 + le32(bytes, pos + 4) as i32 as f64 / 1e6
+    }
+
+    fn push_timeval(out: &mut Vec<u8>, secs: f64) {
+        out.extend_from_slice(&(secs.floor() as i32).to_le_bytes());
+        out.extend_from_slice(&((secs.fract() * 1e6) as i32).to_le_bytes());
+    }
+
+    fn message(kind: u16, id: u16, now: f64, payload: &[u8]) -> Vec<u8> {
+        let mut out = Vec::with_capacity(HEADER + payload.len());
+        out.extend_from_slice(&kind.to_le_bytes());
+        out.extend_from_slice(&id.to_le_bytes());
+        out.extend_from_slice(&0u16.to_le_bytes());
+        push_timeval(&mut out, now);
+        push_timeval(&mut out, 0.0);
+        out.extend_from_slice(&(payload.len() as u32).to_le_bytes());
+        out.extend_from_slice(payload);
+        out
+    }
+
+    fn string(bytes: &[u8], pos: usize) -> Option<&[u8]> {
+        let len = le32(bytes.get(pos..pos + 4)?, 0) as usize;
+        bytes.get(pos + 4..pos + 4 + len)
+    }
+
+    // Only uncompressed streams can be shown without a decoder for each codec;
+    // the PCM codec header is a plain WAV header.
+    fn codec(payload: &[u8]) -> GameResult<Format> {
+        let name = string(payload, 0).unwrap_or_default();
+        if name != b"pcm" {
+            return Err(GameError::AudioError(format!(
+                "Snapcast stream uses the {} codec; set codec=pcm on the server",
+                String::from_utf8_lossy(name)
+            )));
+        }
+
+        let wav = string(payload, 4 + name.len())
+            .filter(|wav| wav.len() >= 36)
+            .ok_or_else(|| GameError::AudioError("Invalid Snapcast codec header".into()))?;
+        let encoding = match le16(wav, 34) {
+            16 => Encoding::S16Le,
+            24 => Encoding::S24Le,
+            32 => Encoding::S32Le,
+            bits => return Err(GameError::AudioError(format!("Unsupported Snapcast sample size {}", bits))),
+        };
+        Ok(Format {
+            encoding,
+            sample_rate: le32(wav, 24),
+            channels: le16(wav, 22).max(1) as usize,
+        })
+    }
+
+    struct Connection {
+        stream: TcpStream,
+        start: Instant,
+    }
+
+    impl Connection {
+        fn now(&self) -> f64 {
+            self.start.elapsed().as_secs_f64()
+        }
+
+        fn read(&mut self) -> GameResult<(u16, Vec<u8>, Vec<u8>)> {
+            let mut header = [0u8; HEADER];
+            self.stream.read_exact(&mut header)?;
+            let mut payload = vec![0u8; le32(&header, 22) as usize];
+            self.stream.read_exact(&mut payload)?;
+            Ok((le16(&header, 0), header.to_vec(), payload))
+        }
+    }
+
+    struct Chunk {
+        due: f64,
+        samples: Vec<f32>,
+    }
+
+    // Joins a Snapcast server as a client. Chunks are released on the server's
+    // schedule (timestamp plus buffer) using the usual Time round trips, so the
+    // picture lines up with the other rooms; `play` also sends them to the output.
+    pub fn connect(host: &str, play: bool) -> GameResult<LiveInput> {
+        let addr = if host.contains(':') {
+            host.to_string()
+        } else {
+            format!("{}:{}", host, PORT)
+        };
+        let stream = TcpStream::connect(&addr)
+            .map_err(|err| GameError::AudioError(format!("Cannot connect to Snapcast at {}: {}", addr, err)))?;
+        let mut conn = Connection {
+            stream,
+            start: Instant::now(),
+        };
+
+        let hello = serde_json::json!({
+            "Arch": std::env::consts::ARCH,
+            "ClientName": "stereo-visualizer",
+            "HostName": "stereo-visualizer",
+            "ID": format!("stereo-visualizer-{}", std::process::id()),
+            "Instance": 1,
+            "MAC": "00:00:00:00:00:00",
+            "OS": std::env::consts::OS,
+            "SnapStreamProtocolVersion": 2,
+            "Version": env!("CARGO_PKG_VERSION"),
+        })
+        .to_string();
+        let mut payload = (hello.len() as u32).to_le_bytes().to_vec();
+        payload.extend_from_slice(hello.as_bytes());
+        conn.stream.write_all(&message(HELLO, 0, conn.now(), &payload))?;
+
+        // The codec header always precedes the first chunk; server settings that
+        // come before it are kept for the reader.
+        let mut early = VecDeque::new();
+        let format = loop {
+            let (kind, header, payload) = conn.read()?;
+            if kind == CODEC_HEADER {
+                break codec(&payload)?;
+            }
+            early.push_back((kind, header, payload));
+        };
+
+        let mut writer = conn.stream.try_clone()?;
+        let start = conn.start;
+        thread::spawn(move || {
+            for id in 1.. {
+                let now = start.elapsed().as_secs_f64();
+                if writer.write_all(&message(TIME, id, now, &[0; 8])).is_err() {
+                    break;
+                }
+                thread::sleep(Duration::from_secs(1));
+            }
+        });
+
+        let (sender, receiver) = mpsc::channel();
+        thread::spawn(move || {
+            let output = if play { rodio::default_output_device() } else { None };
+            let sink = output.as_ref().map(rodio::Sink::new);
+
+            let (mut buffer_ms, mut diff) = (1000.0, None);
+            let mut pending = VecDeque::new();
+            while let Ok((kind, header, payload)) = early.pop_front().map_or_else(|| conn.read(), Ok) {
+                let now = conn.now();
+                match kind {
+                    TIME if payload.len() >= 8 => {
+                        // Half the difference between both one-way trips is the
+                        // offset from our clock to the server's.
+                        let (c2s, s2c) = (timeval(&payload, 0), now - timeval(&header, 6));
+                        let sample = (c2s - s2c) / 2.0;
+                        diff = Some(diff.map_or(sample, |diff: f64| diff + (sample - diff) * 0.1));
+                    }
+                    SERVER_SETTINGS => {
+                        if let Some(json) = string(&payload, 0) {
+                            let settings: serde_json::Value = serde_json::from_slice(json).unwrap_or_default();
+                            buffer_ms = settings["bufferMs"].as_f64().unwrap_or(buffer_ms)
+                                - settings["latency"].as_f64().unwrap_or(0.0);
+                            if let Some(sink) = &sink {
+                                let muted = settings["muted"].as_bool().unwrap_or(false);
+                                let volume = settings["volume"].as_f64().unwrap_or(100.0) / 100.0;
+                                sink.set_volume(if muted { 0.0 } else { volume as f32 });
+                            }
+                        }
+                    }
+                    WIRE_CHUNK if payload.len() >= 12 => pending.push_back(Chunk {
+                        due: timeval(&payload, 0) + buffer_ms / 1000.0,
+                        samples: format.decode(string(&payload, 8).unwrap_or_default()),
+                    }),
+                    _ => (),
+                }
+
+                let diff = match diff {
+                    Some(diff) => diff,
+                    None => continue,
+                };
+                while let Some(chunk) = pending.pop_front() {
+                    if chunk.due - diff > now {
+                        pending.push_front(chunk);
+                        break;
+                    }
+                    if let Some(sink) = &sink {
+                        sink.append(rodio::buffer::SamplesBuffer::new(2, format.sample_rate, chunk.samples.clone()));
+                    }
+                    if sender.send(chunk.samples).is_err() {
+                        return;
+                    }
+                }
+            }
+        });
+
+        Ok(LiveInput::new(receiver, format.sample_rate, format!("Snapcast {}", addr), ()))
+    }
+}
+
+#[cfg(not(feature = "network"))]
+mod client {
+    use crate::live::LiveInput;
+    use ggez::{error::GameError, GameResult};
+
+    pub fn connect(_host: &str, _play: bool) -> GameResult<LiveInput> {
+        Err(GameError::ConfigError(
+            "This build has no Snapcast support; rebuild with `--features network`".into(),
+        ))
+    }
+}
@@ -0,0 +1,339 @@
+use ggez::{error::GameError, GameResult};
+use std::f32::consts::PI;
+
+const GEN_PAN: u16 = 17;
+const GEN_RELEASE: u16 = 38;
+const GEN_INSTRUMENT: u16 = 41;
+const GEN_KEY_RANGE: u16 = 43;
+const GEN_VEL_RANGE: u16 = 44;
+const GEN_ATTENUATION: u16 = 48;
+const GEN_COARSE_TUNE: u16 = 51;
+const GEN_FINE_TUNE: u16 = 52;
+const GEN_SAMPLE: u16 = 53;
+const GEN_SAMPLE_MODES: u16 = 54;
+const GEN_ROOT_KEY: u16 = 58;
+
+#[derive(Debug, Clone, Default)]
+struct Zone {
+    gens: Vec<(u16, i16)>,
+}
+
+impl Zone {
+    fn get(&self, op: u16) -> Option<i16> {
+        self.gens.iter().find(|(gen, _)| *gen == op).map(|&(_, amount)| amount)
+    }
+
+    fn range(&self, op: u16) -> (u8, u8) {
+        match self.get(op) {
+            Some(amount) => (amount as u16 as u8, (amount as u16 >> 8) as u8),
+            None => (0, 127),
+        }
+    }
+
+    fn contains(&self, key: u8, vel: u8) -> bool {
+        let (key_low, key_high) = self.range(GEN_KEY_RANGE);
+        let (vel_low, vel_high) = self.range(GEN_VEL_RANGE);
+        (key_low..=key_high).contains(&key) && (vel_low..=vel_high).contains(&vel)
+    }
+}
+
+struct Preset {
+    bank: u16,
+    program: u16,
+    global: Zone,
+    zones: Vec<Zone>,
+}
+
+struct Instrument {
+    global: Zone,
+    zones: Vec<Zone>,
+}
+
+pub struct SampleHeader {
+    pub start: usize,
+    pub end: usize,
+    pub loop_start: usize,
+    pub loop_end: usize,
+    pub sample_rate: u32,
+    root: u8,
+    correction: i8,
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct Region {
+    pub sample: usize,
+    pub root: f32,
+    pub gain: f32,
+    pub pan: f32,
+    pub looped: bool,
+    pub release: f32,
+}
+
+pub struct SoundFont {
+    pub samples: Vec<f32>,
+    pub headers: Vec<SampleHeader>,
+    presets: Vec<Preset>,
+    instruments: Vec<Instrument>,
+}
+
+fn le16(bytes: &[u8], pos: usize) -> usize {
+    u16::from_le_bytes([bytes[pos], bytes[pos + 1]]) as usize
+}
+
+fn le32(bytes: &[u8], pos: usize) -> usize {
+    u32::from_le_bytes([bytes[pos], bytes[pos + 1], bytes[pos + 2], bytes[pos + 3]]) as usize
+}
+
+fn chunks(bytes: &[u8]) -> Vec<(&[u8], &[u8])> {
+    let mut found = Vec::new();
+    let mut pos = 0;
+    while pos + 8 <= bytes.len() {
+        let len = le32(bytes, pos + 4);
+        let body = &bytes[pos + 8..(pos + 8 + len).min(bytes.len())];
+        found.push((&bytes[pos..pos + 4], body));
+        pos += 8 + len + (len & 1);
+    }
+    found
+}
+
+fn zones(bags: &[u8], gens: &[u8], first: usize, last: usize) -> Vec<Zone> {
+    (first..last)
+        .filter(|bag| (bag + 1) * 4 + 2 <= bags.len())
+        .map(|bag| {
+            let (from, to) = (le16(bags, bag * 4), le16(bags, bag * 4 + 4));
+            Zone {
+                gens: (from..to.min(gens.len() / 4))
+                    .map(|gen| (le16(gens, gen * 4) as u16, le16(gens, gen * 4 + 2) as u16 as i16))
+                    .collect(),
+            }
+        })
+        .collect()
+}
+
+fn split_global(mut zones: Vec<Zone>, link: u16) -> (Zone, Vec<Zone>) {
+    if zones.first().is_some_and(|zone| zone.get(link).is_none()) {
+        let global = zones.remove(0);
+        (global, zones)
+    } else {
+        (Zone::default(), zones)
+    }
+}
+
+impl SoundFont {
+    pub fn parse(bytes: &[u8]) -> GameResult<Self> {
+        if bytes.len() < 12 || &bytes[..4] != b"RIFF" || &bytes[8..12] != b"sfbk" {
+            return Err(GameError::AudioError("Not a SoundFont 2 file".into()));
+        }
+
+        let (mut smpl, mut pdta) = (None, Vec::new());
+        for (id, body) in chunks(&bytes[12..]) {
+            if id == b"LIST" && body.len() >= 4 {
+                for (sub, data) in chunks(&body[4..]) {
+                    match &body[..4] {
+                        b"sdta" if sub == b"smpl" => smpl = Some(data),
+                        b"pdta" => pdta.push((sub, data)),
+                        _ => (),
+                    }
+                }
+            }
+        }
+        let find = |name: &[u8]| {
+            pdta.iter()
+                .find(|(id, _)| *id == name)
+                .map(|(_, data)| *data)
+                .ok_or_else(|| GameError::AudioError("SoundFont is missing preset data".into()))
+        };
+
+        let samples = smpl
+            .ok_or_else(|| GameError::AudioError("SoundFont has no sample data".into()))?
+            .chunks_exact(2)
+            .map(|pair| i16::from_le_bytes([pair[0], pair[1]]) as f32 / 32768.0)
+            .collect();
+
+        let (phdr, pbag, pgen) = (find(b"phdr")?, find(b"pbag")?, find(b"pgen")?);
+        let (inst, ibag, igen) = (find(b"inst")?, find(b"ibag")?, find(b"igen")?);
+        let shdr = find(b"shdr")?;
+
+        // The last record of each list is a terminator.
+        let preset_count = (phdr.len() / 38).saturating_sub(1);
+        let presets = (0..preset_count)
+            .map(|idx| {
+                let record = &phdr[idx * 38..];
+                let (global, zones) = split_global(
+                    zones(pbag, pgen, le16(record, 24), le16(record, 38 + 24)),
+                    GEN_INSTRUMENT,
+                );
+                Preset {
+                    program: le16(record, 20) as u16,
+                    bank: le16(record, 22) as u16,
+                    global,
+                    zones,
+                }
+            })
+            .collect();
+
+        let inst_count = (inst.len() / 22).saturating_sub(1);
+        let instruments = (0..inst_count)
+            .map(|idx| {
+                let (global, zones) = split_global(
+                    zones(ibag, igen, le16(inst, idx * 22 + 20), le16(inst, idx * 22 + 42)),
+                    GEN_SAMPLE,
+                );
+                Instrument { global, zones }
+            })
+            .collect();
+
+        let header_count = (shdr.len() / 46).saturating_sub(1);
+        let headers = (0..header_count)
+            .map(|idx| {
+                let record = &shdr[idx * 46..];
+                SampleHeader {
+                    start: le32(record, 20),
+                    end: le32(record, 24),
+                    loop_start: le32(record, 28),
+                    loop_end: le32(record, 32),
+                    sample_rate: le32(record, 36) as u32,
+                    root: record[40],
+                    correction: record[41] as i8,
+                }
+            })
+            .collect();
+
+        Ok(SoundFont {
+            samples,
+            headers,
+            presets,
+            instruments,
+        })
+    }
+
+    // A looped organ tone and a noise burst stand in when no SoundFont is given.
+    pub fn builtin(sample_rate: u32) -> Self {
+        let cycle = 128;
+        let mut samples: Vec<f32> = (0..cycle)
+            .map(|idx| {
+                let phase = idx as f32 / cycle as f32 * 2.0 * PI;
+                (phase.sin() + (phase * 2.0).sin() * 0.4 + (phase * 3.0).sin() * 0.2) * 0.5
+            })
+            .collect();
+
+        let mut seed = 0x1234_5678u32;
+        let noise_len = sample_rate as usize / 5;
+        samples.extend((0..noise_len).map(|idx| {
+            seed = seed.wrapping_mul(1_664_525).wrapping_add(1_013_904_223);
+            let noise = (seed >> 8) as f32 / (1 << 24) as f32 * 2.0 - 1.0;
+            noise * (1.0 - idx as f32 / noise_len as f32).powi(3)
+        }));
+
+        // 128 samples per cycle puts the root a little below F4.
+        let freq = sample_rate as f32 / cycle as f32;
+        let key = 69.0 + 12.0 * (freq / 440.0).log2();
+        let headers = vec![
+            SampleHeader {
+                start: 0,
+                end: cycle,
+                loop_start: 0,
+                loop_end: cycle,
+                sample_rate,
+                root: key.round() as u8,
+                correction: ((key - key.round()) * 100.0).round() as i8,
+            },
+            SampleHeader {
+                start: cycle,
+                end: cycle + noise_len,
+                loop_start: cycle,
+                loop_end: cycle,
+                sample_rate,
+                root: 60,
+                correction: 0,
+            },
+        ];
+
+        let zone = |sample: i16, modes: i16| Zone {
+            gens: vec![(GEN_SAMPLE, sample), (GEN_SAMPLE_MODES, modes), (GEN_RELEASE, -2400)],
+        };
+        SoundFont {
+            samples,
+            headers,
+            presets: vec![
+                Preset {
+                    bank: 0,
+                    program: 0,
+                    global: Zone::default(),
+                    zones: vec![Zone { gens: vec![(GEN_INSTRUMENT, 0)] }],
+                },
+                Preset {
+                    bank: 128,
+                    program: 0,
+                    global: Zone::default(),
+                    zones: vec![Zone { gens: vec![(GEN_INSTRUMENT, 1)] }],
+                },
+            ],
+            instruments: vec![
+                Instrument { global: Zone::default(), zones: vec![zone(0, 1)] },
+                Instrument {
+                    global: Zone::default(),
+                    zones: vec![Zone { gens: vec![(GEN_SAMPLE, 1), (GEN_ROOT_KEY, 60)] }],
+                },
+            ],
+        }
+    }
+
+    fn preset(&self, bank: u16, program: u16) -> Option<&Preset> {
+        self.presets
+            .iter()
+            .find(|preset| preset.bank == bank && preset.program == program)
+            .or_else(|| self.presets.iter().find(|preset| preset.bank == bank))
+            .or_else(|| self.presets.first())
+    }
+
+    pub fn regions(&self, bank: u16, program: u16, key: u8, vel: u8) -> Vec<Region> {
+        let preset = match self.preset(bank, program) {
+            Some(preset) => preset,
+            None => return Vec::new(),
+        };
+
+        let mut regions = Vec::new();
+        for preset_zone in preset.zones.iter().filter(|zone| zone.contains(key, vel)) {
+            let instrument = match preset_zone
+                .get(GEN_INSTRUMENT)
+                .and_then(|idx| self.instruments.get(idx as u16 as usize))
+            {
+                Some(instrument) => instrument,
+                None => continue,
+            };
+            let preset_gen = |op| preset_zone.get(op).or_else(|| preset.global.get(op)).unwrap_or(0) as f32;
+
+            for zone in instrument.zones.iter().filter(|zone| zone.contains(key, vel)) {
+                let gen = |op| zone.get(op).or_else(|| instrument.global.get(op));
+                let sample = match gen(GEN_SAMPLE).map(|idx| idx as u16 as usize) {
+                    Some(idx) if idx < self.headers.len() => idx,
+                    _ => continue,
+                };
+                let header = &self.headers[sample];
+
+                let root = match gen(GEN_ROOT_KEY) {
+                    Some(root) if (0..128).contains(&root) => root as f32,
+                    _ => header.root as f32,
+                };
+                let tune = (gen(GEN_COARSE_TUNE).unwrap_or(0) as f32 + preset_gen(GEN_COARSE_TUNE)) * 100.0
+                    + gen(GEN_FINE_TUNE).unwrap_or(0) as f32
+                    + preset_gen(GEN_FINE_TUNE)
+                    + header.correction as f32;
+                let attenuation = gen(GEN_ATTENUATION).unwrap_or(0) as f32 + preset_gen(GEN_ATTENUATION);
+                let pan = (gen(GEN_PAN).unwrap_or(0) as f32 + preset_gen(GEN_PAN)) / 1000.0;
+                let release = 2.0f32.powf(gen(GEN_RELEASE).unwrap_or(-12000) as f32 / 1200.0);
+
+                regions.push(Region {
+                    sample,
+                    root: root - tune / 100.0,
+                    gain: 10.0f32.powf(-attenuation.max(0.0) / 200.0),
+                    pan: pan.clamp(-0.5, 0.5),
+                    looped: gen(GEN_SAMPLE_MODES).unwrap_or(0) & 1 == 1,
+                    release: release.max(0.01),
+                });
+            }
+        }
+        regions
+    }
+}
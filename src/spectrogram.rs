@@ -0,0 +1,242 @@
+use crate::export;
+use ggez::GameResult;
+use rustfft::{num_complex::Complex, FFTplanner};
+use std::{f32::consts::PI, path};
+
+const FFT_SIZE: usize = 4096;
+const COLUMNS: usize = 1800;
+const SPECTRUM_HEIGHT: usize = 600;
+const PAN_HEIGHT: usize = 240;
+const PAN_ROWS: usize = 96;
+const LEFT: usize = 64;
+const RIGHT: usize = 96;
+const TOP: usize = 24;
+const GAP: usize = 32;
+const BOTTOM: usize = 40;
+const LOW_HZ: f32 = 20.0;
+const FLOOR_DB: f32 = -90.0;
+const WIDTH: usize = LEFT + COLUMNS + RIGHT;
+const HEIGHT: usize = TOP + SPECTRUM_HEIGHT + GAP + PAN_HEIGHT + BOTTOM;
+const TEXT: [u8; 4] = [200, 200, 200, 255];
+
+// 3x5 glyphs, one row of three bits per entry, for tick labels.
+const GLYPHS: &[(char, [u8; 5])] = &[
+    ('0', [7, 5, 5, 5, 7]),
+    ('1', [2, 6, 2, 2, 7]),
+    ('2', [7, 1, 7, 4, 7]),
+    ('3', [7, 1, 7, 1, 7]),
+    ('4', [5, 5, 7, 1, 1]),
+    ('5', [7, 4, 7, 1, 7]),
+    ('6', [7, 4, 7, 5, 7]),
+    ('7', [7, 1, 2, 2, 2]),
+    ('8', [7, 5, 7, 5, 7]),
+    ('9', [7, 5, 7, 1, 7]),
+    ('-', [0, 0, 7, 0, 0]),
+    (':', [0, 2, 0, 2, 0]),
+    ('k', [4, 5, 6, 5, 5]),
+    ('d', [1, 1, 7, 5, 7]),
+    ('B', [6, 5, 6, 5, 6]),
+    ('H', [5, 5, 7, 5, 5]),
+    ('Z', [7, 1, 2, 4, 7]),
+    ('L', [4, 4, 4, 4, 7]),
+    ('R', [6, 5, 6, 5, 5]),
+];
+
+// Black through purple and orange to pale yellow for 0..1.
+pub fn heat(value: f32) -> [u8; 4] {
+    const STOPS: [[f32; 3]; 5] = [
+        [0.0, 0.0, 0.0],
+        [80.0, 18.0, 123.0],
+        [182.0, 54.0, 121.0],
+        [251.0, 136.0, 54.0],
+        [252.0, 253.0, 191.0],
+    ];
+    let scaled = value.clamp(0.0, 1.0) * (STOPS.len() - 1) as f32;
+    let idx = (scaled as usize).min(STOPS.len() - 2);
+    let frac = scaled - idx as f32;
+    let mix = |channel: usize| (STOPS[idx][channel] + (STOPS[idx + 1][channel] - STOPS[idx][channel]) * frac) as u8;
+    [mix(0), mix(1), mix(2), 255]
+}
+
+struct Canvas {
+    rgba: Vec<u8>,
+}
+
+impl Canvas {
+    fn set(&mut self, x: usize, y: usize, color: [u8; 4]) {
+        if x < WIDTH && y < HEIGHT {
+            self.rgba[(y * WIDTH + x) * 4..][..4].copy_from_slice(&color);
+        }
+    }
+
+    // Text at twice the glyph size; anchored on its right edge when `right` is set.
+    fn text(&mut self, label: &str, x: usize, y: usize, right: bool) {
+        let scale = 2;
+        let advance = 4 * scale;
+        let start = if right { x.saturating_sub(label.len() * advance) } else { x };
+        for (idx, ch) in label.chars().enumerate() {
+            let glyph = match GLYPHS.iter().find(|(glyph, _)| *glyph == ch) {
+                Some((_, rows)) => rows,
+                None => continue,
+            };
+            for (row, bits) in glyph.iter().enumerate() {
+                for col in 0..3 {
+                    if bits & (4 >> col) == 0 {
+                        continue;
+                    }
+                    for dy in 0..scale {
+                        for dx in 0..scale {
+                            self.set(start + idx * advance + col * scale + dx, y + row * scale + dy, TEXT);
+                        }
+                    }
+                }
+            }
+        }
+    }
+
+    fn tick(&mut self, x: usize, y: usize, horizontal: bool) {
+        for step in 0..6 {
+            if horizontal {
+                self.set(x.saturating_sub(step + 1), y, TEXT);
+            } else {
+                self.set(x, y + step, TEXT);
+            }
+        }
+    }
+}
+
+fn frequency_label(hz: f32) -> String {
+    if hz >= 1000.0 {
+        format!("{}k", hz / 1000.0)
+    } else {
+        format!("{}", hz)
+    }
+}
+
+// Renders the whole track as a log-frequency spectrogram above a pan-ogram
+// (energy by direction over time), with axes and a colorbar.
+pub fn write_png<P>(path: P, left: &[f32], right: &[f32], sample_rate: u32) -> GameResult
+where
+    P: AsRef<path::Path>,
+{
+    let len = left.len().min(right.len());
+    let nyquist = sample_rate as f32 / 2.0;
+    let bin_hz = sample_rate as f32 / FFT_SIZE as f32;
+    let fft = FFTplanner::new(false).plan_fft(FFT_SIZE);
+    let window: Vec<f32> = (0..FFT_SIZE)
+        .map(|idx| 0.5 - 0.5 * (2.0 * PI * idx as f32 / FFT_SIZE as f32).cos())
+        .collect();
+    let transform = |wave: &[f32], start: usize| {
+        let mut input: Vec<_> = (0..FFT_SIZE)
+            .map(|idx| Complex::new(wave.get(start + idx).copied().unwrap_or(0.0) * window[idx], 0.0))
+            .collect();
+        let mut output = vec![Complex::new(0.0, 0.0); FFT_SIZE];
+        fft.process(&mut input, &mut output);
+        output.truncate(FFT_SIZE / 2);
+        output
+    };
+
+    // Each spectrogram row covers a slice of bins on the log axis.
+    let row_hz = |row: f32| LOW_HZ * (nyquist / LOW_HZ).powf(1.0 - row / SPECTRUM_HEIGHT as f32);
+    let row_at = |hz: f32| ((1.0 - (hz / LOW_HZ).ln() / (nyquist / LOW_HZ).ln()) * SPECTRUM_HEIGHT as f32) as usize;
+    let rows: Vec<(usize, usize)> = (0..SPECTRUM_HEIGHT)
+        .map(|row| {
+            let high = ((row_hz(row as f32) / bin_hz) as usize).min(FFT_SIZE / 2 - 1);
+            let low = ((row_hz(row as f32 + 1.0) / bin_hz) as usize).clamp(1, high);
+            (low, high + 1)
+        })
+        .collect();
+
+    let mut canvas = Canvas {
+        rgba: [16, 16, 16, 255].repeat(WIDTH * HEIGHT),
+    };
+    let mut pans = vec![[0.0f32; PAN_ROWS]; COLUMNS];
+    let norm = (FFT_SIZE as f32 / 4.0).powi(2);
+    for (col, pan) in pans.iter_mut().enumerate() {
+        let start = col * len.saturating_sub(FFT_SIZE) / COLUMNS;
+        let (left_fft, right_fft) = (transform(left, start), transform(right, start));
+        let power: Vec<(f32, f32)> = left_fft
+            .iter()
+            .zip(&right_fft)
+            .map(|(l, r)| (l.norm_sqr(), r.norm_sqr()))
+            .collect();
+
+        for (row, &(low, high)) in rows.iter().enumerate() {
+            let mean = power[low..high].iter().map(|(l, r)| (l + r) / 2.0).sum::<f32>() / (high - low) as f32;
+            let db = 10.0 * (mean / norm).max(1e-12).log10();
+            canvas.set(LEFT + col, TOP + row, heat(1.0 - db / FLOOR_DB));
+        }
+
+        for &(l, r) in power.iter().skip(1) {
+            let (l, r) = (l.sqrt(), r.sqrt());
+            if l + r <= 0.0 {
+                continue;
+            }
+            let dir = (r - l) / (r + l);
+            let row = (((dir + 1.0) / 2.0 * PAN_ROWS as f32) as usize).min(PAN_ROWS - 1);
+            pan[row] += l * l + r * r;
+        }
+    }
+
+    // The pan-ogram is scaled to its loudest cell, on a log scale like the spectrum.
+    let pan_top = TOP + SPECTRUM_HEIGHT + GAP;
+    let peak = pans.iter().flatten().fold(0.0f32, |acc, &value| acc.max(value)).max(1e-12);
+    for (col, pan) in pans.iter().enumerate() {
+        for y in 0..PAN_HEIGHT {
+            let value = pan[y * PAN_ROWS / PAN_HEIGHT];
+            let db = 10.0 * (value / peak).max(1e-12).log10();
+            canvas.set(LEFT + col, pan_top + y, heat(1.0 - db / (FLOOR_DB / 2.0)));
+        }
+    }
+
+    // Frequency axis.
+    for &hz in [50.0, 100.0, 200.0, 500.0, 1000.0, 2000.0, 5000.0, 10000.0, 20000.0].iter() {
+        if hz >= nyquist {
+            continue;
+        }
+        let y = TOP + row_at(hz);
+        canvas.tick(LEFT, y, true);
+        canvas.text(&frequency_label(hz), LEFT - 8, y.saturating_sub(5), true);
+    }
+    canvas.text("HZ", LEFT - 8, TOP.saturating_sub(16), true);
+
+    // Direction axis, left channel on top as in the stage's left-to-right reading.
+    for (label, y) in [("L", 0), ("0", PAN_HEIGHT / 2), ("R", PAN_HEIGHT - 1)].iter() {
+        canvas.tick(LEFT, pan_top + y, true);
+        canvas.text(label, LEFT - 8, (pan_top + y).saturating_sub(5), true);
+    }
+
+    // Time axis, with a tick spacing that gives no more than about a dozen labels.
+    let duration = len as f32 / sample_rate.max(1) as f32;
+    let step = [1.0, 5.0, 10.0, 15.0, 30.0, 60.0, 120.0, 300.0, 600.0]
+        .iter()
+        .copied()
+        .find(|step| duration / step <= 12.0)
+        .unwrap_or(1200.0);
+    let axis = pan_top + PAN_HEIGHT;
+    let mut time = 0.0;
+    while time <= duration {
+        let x = LEFT + (time / duration.max(1e-6) * COLUMNS as f32) as usize;
+        canvas.tick(x.min(LEFT + COLUMNS - 1), axis, false);
+        let label = format!("{}:{:02}", time as u32 / 60, time as u32 % 60);
+        canvas.text(&label, x.saturating_sub(label.len() * 4), axis + 10, false);
+        time += step;
+    }
+
+    // Colorbar beside the spectrogram, 0 dB at the top.
+    let bar_x = LEFT + COLUMNS + 24;
+    for y in 0..SPECTRUM_HEIGHT {
+        let color = heat(1.0 - y as f32 / SPECTRUM_HEIGHT as f32);
+        for x in bar_x..bar_x + 16 {
+            canvas.set(x, TOP + y, color);
+        }
+    }
+    for db in (0..=90).step_by(15) {
+        let y = TOP + (db as f32 / -FLOOR_DB * (SPECTRUM_HEIGHT - 1) as f32) as usize;
+        let label = if db == 0 { "0".to_string() } else { format!("-{}", db) };
+        canvas.text(&label, bar_x + 22, y.saturating_sub(5), false);
+    }
+    canvas.text("dB", bar_x, TOP.saturating_sub(16), false);
+
+    export::write_png(path, WIDTH as u32, HEIGHT as u32, &canvas.rgba)
+}
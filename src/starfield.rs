@@ -0,0 +1,137 @@
+use crate::DirectionalSource;
+use ggez::{
+    graphics::{self, DrawParam},
+    Context, GameResult,
+};
+use serde::{Deserialize, Serialize};
+
+// Stars start at depth 1 and are recycled once they pass this close.
+const NEAR: f32 = 0.05;
+// Depth travelled per second with no bass.
+const CRUISE: f32 = 0.08;
+// How far the vanishing point can move to either side, as a share of the width.
+const LEAN: f32 = 0.3;
+const LEAN_SECS: f32 = 0.6;
+// Turns the cursor's pull into a speed.
+const PUSH_SECS: f32 = 0.15;
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Settings {
+    pub enabled: bool,
+    pub stars: usize,
+    // Extra depth travelled per second per unit of bass.
+    pub warp: f32,
+}
+
+impl Default for Settings {
+    fn default() -> Self {
+        Settings {
+            enabled: false,
+            stars: 400,
+            warp: 1.2,
+        }
+    }
+}
+
+// Stars flying towards the viewer faster as the bass grows, their vanishing
+// point pulled towards wherever most of the energy sits.
+pub struct Starfield {
+    pub settings: Settings,
+    stars: Vec<[f32; 3]>,
+    seed: u32,
+    lean: f32,
+}
+
+impl Starfield {
+    pub fn new() -> Self {
+        let mut starfield = Starfield {
+            settings: Settings::default(),
+            stars: Vec::new(),
+            seed: 0x9e37_79b9,
+            lean: 0.0,
+        };
+        starfield.set(Settings::default());
+        starfield
+    }
+
+    pub fn set(&mut self, settings: Settings) {
+        self.settings = settings;
+        self.stars.truncate(settings.stars);
+        while self.stars.len() < settings.stars {
+            let [x, y] = [self.random(), self.random()];
+            let depth = NEAR + (self.random() + 1.0) / 2.0 * (1.0 - NEAR);
+            self.stars.push([x, y, depth]);
+        }
+    }
+
+    // -1..1
+    fn random(&mut self) -> f32 {
+        self.seed ^= self.seed << 13;
+        self.seed ^= self.seed >> 17;
+        self.seed ^= self.seed << 5;
+        self.seed as f32 / u32::MAX as f32 * 2.0 - 1.0
+    }
+
+    pub fn update(&mut self, directions: &[DirectionalSource], bass: f32, delta: f32) {
+        if !self.settings.enabled {
+            return;
+        }
+
+        let energy: f32 = directions.iter().map(|source| source.amp).sum();
+        if energy > 0.0 {
+            let pan = directions.iter().map(|source| source.amp * source.dir).sum::<f32>() / energy;
+            self.lean += (pan.clamp(-1.0, 1.0) - self.lean) * (delta / LEAN_SECS).min(1.0);
+        }
+
+        let travel = (CRUISE + bass.clamp(0.0, 2.0) * self.settings.warp) * delta;
+        for idx in 0..self.stars.len() {
+            self.stars[idx][2] -= travel;
+            let [x, y, depth] = self.stars[idx];
+            if depth < NEAR || (x / depth).abs() > 2.0 || (y / depth).abs() > 2.0 {
+                self.stars[idx] = [self.random(), self.random(), 1.0];
+            }
+        }
+    }
+
+    // Moves the stars on screen by `pull`, an acceleration for a screen
+    // position, as if they had no inertia.
+    pub fn push<F>(&mut self, pull: F, delta: f32, (width, height): (f32, f32))
+    where
+        F: Fn([f32; 2]) -> [f32; 2],
+    {
+        if !self.settings.enabled {
+            return;
+        }
+        let center = [width / 2.0 + self.lean * LEAN * width, height / 2.0];
+        for star in &mut self.stars {
+            let [x, y, depth] = *star;
+            let [ax, ay] = pull([center[0] + x / depth * width / 2.0, center[1] + y / depth * height / 2.0]);
+            star[0] += ax * PUSH_SECS * delta * depth / (width / 2.0);
+            star[1] += ay * PUSH_SECS * delta * depth / (height / 2.0);
+        }
+    }
+
+    pub fn draw(&self, ctx: &mut Context, width: f32, height: f32) -> GameResult {
+        if !self.settings.enabled {
+            return Ok(());
+        }
+
+        let center = [width / 2.0 + self.lean * LEAN * width, height / 2.0];
+        let mut builder = graphics::MeshBuilder::new();
+        for &[x, y, depth] in &self.stars {
+            let pos = [center[0] + x / depth * width / 2.0, center[1] + y / depth * height / 2.0];
+            // Nearer stars are bigger and brighter.
+            let near = 1.0 - depth;
+            builder.circle(
+                graphics::DrawMode::fill(),
+                pos,
+                0.5 + near * 2.5,
+                0.5,
+                graphics::Color::new(0.8, 0.85, 1.0, 0.15 + near * 0.7),
+            );
+        }
+        let mesh = builder.build(ctx)?;
+        graphics::draw(ctx, &mesh, DrawParam::default())
+    }
+}
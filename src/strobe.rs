@@ -0,0 +1,66 @@
+use crate::safety::Safety;
+use ggez::{
+    graphics::{self, DrawParam},
+    Context, GameResult,
+};
+
+// Flashes per beat, unless that is faster than is safe, and beats per burst.
+const RATE: f32 = 4.0;
+const BEATS: f32 = 2.0;
+// Without a tempo to follow.
+const DEFAULT_PERIOD: f32 = 0.5;
+
+// A burst of white flashes over the picture, in time with the beat.
+pub struct Strobe {
+    period: f32,
+    interval: f32,
+    elapsed: Option<f32>,
+}
+
+impl Strobe {
+    pub fn new() -> Self {
+        Strobe {
+            period: DEFAULT_PERIOD,
+            interval: DEFAULT_PERIOD / RATE,
+            elapsed: None,
+        }
+    }
+
+    // `period` is the beat's length in seconds. Returns false when flashing
+    // is not allowed at all.
+    pub fn start(&mut self, period: Option<f32>, safety: &Safety) -> bool {
+        self.period = period.unwrap_or(DEFAULT_PERIOD).max(0.05);
+        match safety.flash_interval(self.period / RATE) {
+            Some(interval) => {
+                self.interval = interval;
+                self.elapsed = Some(0.0);
+                true
+            }
+            None => false,
+        }
+    }
+
+    pub fn update(&mut self, delta: f32) {
+        if let Some(elapsed) = self.elapsed {
+            let elapsed = elapsed + delta;
+            self.elapsed = if elapsed < self.period * BEATS { Some(elapsed) } else { None };
+        }
+    }
+
+    pub fn draw(&self, ctx: &mut Context, width: f32, height: f32) -> GameResult {
+        let elapsed = match self.elapsed {
+            Some(elapsed) => elapsed,
+            None => return Ok(()),
+        };
+        // Each flash is lit for the first half of its slot, fading over the burst.
+        let slot = (elapsed / self.interval).fract();
+        if slot >= 0.5 {
+            return Ok(());
+        }
+        let alpha = 0.85 * (1.0 - elapsed / (self.period * BEATS) * 0.5);
+        let rect = graphics::Rect::new(0.0, 0.0, width, height);
+        let color = graphics::Color::new(1.0, 1.0, 1.0, alpha);
+        let flash = graphics::Mesh::new_rectangle(ctx, graphics::DrawMode::fill(), rect, color)?;
+        graphics::draw(ctx, &flash, DrawParam::default())
+    }
+}
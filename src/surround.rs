@@ -0,0 +1,186 @@
+use crate::overlay::{self, Anchor};
+use ggez::{
+    graphics::{self, DrawParam},
+    Context, GameResult,
+};
+use rustfft::{num_complex::Complex, num_traits::Zero, FFTplanner, FFT};
+use std::{f32::consts::PI, sync::Arc};
+
+const FFT_SIZE: usize = 1024;
+const BANDS: usize = 24;
+const SPREAD: f32 = 14.0;
+
+pub struct Speaker {
+    pub name: &'static str,
+    pub angle: Option<f32>,
+}
+
+pub fn layout(channels: usize) -> Vec<Speaker> {
+    let names: &[&'static str] = match channels {
+        1 => &["M"],
+        2 => &["FL", "FR"],
+        3 => &["FL", "FR", "FC"],
+        4 => &["FL", "FR", "BL", "BR"],
+        5 => &["FL", "FR", "FC", "BL", "BR"],
+        6 => &["FL", "FR", "FC", "LFE", "SL", "SR"],
+        7 => &["FL", "FR", "FC", "LFE", "BC", "SL", "SR"],
+        _ => &["FL", "FR", "FC", "LFE", "BL", "BR", "SL", "SR"],
+    };
+    let side = if channels >= 7 { 90.0 } else { 110.0 };
+
+    names
+        .iter()
+        .take(channels)
+        .map(|&name| Speaker {
+            name,
+            angle: match name {
+                "M" | "FC" => Some(0.0),
+                "FL" => Some(-30.0),
+                "FR" => Some(30.0),
+                "SL" => Some(-side),
+                "SR" => Some(side),
+                "BL" => Some(-145.0),
+                "BR" => Some(145.0),
+                "BC" => Some(180.0),
+                _ => None,
+            },
+        })
+        .collect()
+}
+
+pub fn downmix(channels: &[Vec<f32>]) -> (Vec<f32>, Vec<f32>) {
+    let len = channels.iter().map(|channel| channel.len()).min().unwrap_or(0);
+    let gains: Vec<(f32, f32)> = layout(channels.len())
+        .iter()
+        .map(|speaker| match (speaker.name, speaker.angle) {
+            ("M", _) => (1.0, 1.0),
+            ("FL", _) => (1.0, 0.0),
+            ("FR", _) => (0.0, 1.0),
+            (_, None) => (0.0, 0.0),
+            (_, Some(angle)) if angle < 0.0 => (0.707, 0.0),
+            (_, Some(angle)) if angle > 0.0 && angle < 180.0 => (0.0, 0.707),
+            _ => (0.707, 0.707),
+        })
+        .collect();
+
+    (0..len)
+        .map(|idx| {
+            channels.iter().zip(&gains).fold((0.0, 0.0), |(l, r), (channel, gain)| {
+                (l + channel[idx] * gain.0, r + channel[idx] * gain.1)
+            })
+        })
+        .unzip()
+}
+
+pub struct Surround {
+    speakers: Vec<Speaker>,
+    fft: Arc<dyn FFT<f32>>,
+    bands: Vec<Vec<f32>>,
+}
+
+impl Surround {
+    pub fn new(channels: usize) -> Self {
+        Surround {
+            speakers: layout(channels),
+            fft: FFTplanner::new(false).plan_fft(FFT_SIZE),
+            bands: vec![vec![0.0; BANDS]; channels],
+        }
+    }
+
+    pub fn update(&mut self, channels: &[Vec<f32>], offset: usize) {
+        let mut output = vec![Complex::zero(); FFT_SIZE];
+
+        for (channel, bands) in channels.iter().zip(self.bands.iter_mut()) {
+            if offset + FFT_SIZE > channel.len() {
+                continue;
+            }
+
+            let mut input: Vec<_> = channel[offset..offset + FFT_SIZE]
+                .iter()
+                .map(|&amp| Complex::new(amp, 0.0))
+                .collect();
+            self.fft.process(&mut input, &mut output);
+
+            for (band, rev) in bands.iter_mut().enumerate() {
+                let first = (2.0f32.powf(band as f32 * 9.0 / BANDS as f32) as usize).max(1);
+                let last = (2.0f32.powf((band + 1) as f32 * 9.0 / BANDS as f32) as usize)
+                    .clamp(first + 1, FFT_SIZE / 2);
+                let amp = output[first..last]
+                    .iter()
+                    .fold(0.0f32, |acc, bin| acc.max(bin.re.abs()));
+                *rev += (amp - *rev) * 0.9;
+            }
+        }
+    }
+
+    pub fn draw(&self, ctx: &mut Context, width: f32, height: f32) -> GameResult {
+        let center = [width / 2.0, height / 2.0];
+        let radius = height.min(width) * 0.25;
+
+        let ring = graphics::Mesh::new_circle(
+            ctx,
+            graphics::DrawMode::stroke(1.0),
+            center,
+            radius,
+            0.5,
+            graphics::Color::from_rgba(255, 255, 255, 40),
+        )?;
+        graphics::draw(ctx, &ring, DrawParam::default())?;
+
+        for (speaker, bands) in self.speakers.iter().zip(&self.bands) {
+            let angle = match speaker.angle {
+                Some(angle) => angle,
+                None => {
+                    let energy = bands.iter().take(4).sum::<f32>() * 0.08 / 4.0;
+                    let alpha = (energy * 255.0).min(255.0) as u8;
+                    if alpha >= 8 {
+                        let mesh = graphics::Mesh::new_circle(
+                            ctx,
+                            graphics::DrawMode::fill(),
+                            center,
+                            (energy * radius * 0.3).min(radius * 0.5),
+                            0.5,
+                            graphics::Color::from_rgba(30, 30, 30, alpha),
+                        )?;
+                        graphics::draw(ctx, &mesh, DrawParam::default())?;
+                    }
+                    continue;
+                }
+            };
+
+            for (band, &amp) in bands.iter().enumerate() {
+                let alpha = (amp * 0.08 * 255.0).min(255.0) as u8;
+                if alpha < 8 {
+                    continue;
+                }
+
+                let offset = (band as f32 / (BANDS - 1) as f32 - 0.5) * 2.0 * SPREAD;
+                let theta = (angle + offset - 90.0) * PI / 180.0;
+                let length = (amp * 4.0).min(radius);
+                let inner = [center[0] + theta.cos() * radius, center[1] + theta.sin() * radius];
+                let outer = [
+                    center[0] + theta.cos() * (radius + length),
+                    center[1] + theta.sin() * (radius + length),
+                ];
+
+                let freq = (band as f32 / BANDS as f32 * 255.0) as u8;
+                let line = graphics::Mesh::new_line(
+                    ctx,
+                    &[inner, outer],
+                    3.0,
+                    graphics::Color::from_rgba(freq, 128, 192, alpha),
+                )?;
+                graphics::draw(ctx, &line, DrawParam::default())?;
+            }
+
+            let theta = (angle - 90.0) * PI / 180.0;
+            let pos = [
+                center[0] + theta.cos() * radius * 0.85,
+                center[1] + theta.sin() * radius * 0.85,
+            ];
+            overlay::label(ctx, speaker.name, 18.0, Anchor::Center, pos, graphics::Color::from_rgb(160, 160, 160))?;
+        }
+
+        Ok(())
+    }
+}
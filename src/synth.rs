@@ -0,0 +1,226 @@
+use crate::live::LiveInput;
+use serde::{Deserialize, Serialize};
+use std::{
+    f32::consts::PI,
+    sync::{mpsc, Arc, Mutex},
+    thread,
+    time::{Duration, Instant},
+};
+
+const RATE: u32 = 48_000;
+const BLOCK: usize = 1024;
+// How far generation may run ahead of the wall clock, as the output's buffer.
+const LEAD: f64 = 0.1;
+// Intervals of the chord the voices share, in semitones above the root.
+const CHORD: [f32; 6] = [0.0, 7.0, 12.0, 15.0, 19.0, 26.0];
+// The root moves through these, one step per phrase.
+const PROGRESSION: [f32; 4] = [0.0, -4.0, 3.0, -2.0];
+// Seconds per chord at a drift of 1.
+const PHRASE_SECS: f32 = 16.0;
+
+// The `[synth]` table in the config; all of it can be changed while it plays.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Settings {
+    // MIDI note of the lowest voice.
+    pub root: f32,
+    pub voices: usize,
+    // Modulator frequency over carrier frequency, and the modulation index.
+    pub ratio: f32,
+    pub depth: f32,
+    // Level of the filtered noise wind, 0 to 1.
+    pub noise: f32,
+    // Speed of the slow swells, pans and chord changes.
+    pub drift: f32,
+    pub volume: f32,
+}
+
+impl Default for Settings {
+    fn default() -> Self {
+        Settings {
+            root: 45.0,
+            voices: 4,
+            ratio: 1.5,
+            depth: 1.5,
+            noise: 0.2,
+            drift: 1.0,
+            volume: 0.5,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Param {
+    Root,
+    Voices,
+    Ratio,
+    Depth,
+    Noise,
+    Drift,
+}
+
+const PARAMS: [Param; 6] = [Param::Root, Param::Voices, Param::Ratio, Param::Depth, Param::Noise, Param::Drift];
+
+impl Param {
+    fn describe(self, settings: &Settings) -> String {
+        match self {
+            Param::Root => format!("root note {:.0}", settings.root),
+            Param::Voices => format!("voices {}", settings.voices),
+            Param::Ratio => format!("FM ratio {:.2}", settings.ratio),
+            Param::Depth => format!("FM depth {:.1}", settings.depth),
+            Param::Noise => format!("noise {:.2}", settings.noise),
+            Param::Drift => format!("drift {:.2}", settings.drift),
+        }
+    }
+
+    fn nudge(self, settings: &mut Settings, up: bool) {
+        let sign = if up { 1.0 } else { -1.0 };
+        match self {
+            Param::Root => settings.root = (settings.root + sign).clamp(24.0, 72.0),
+            Param::Voices => settings.voices = (settings.voices as isize + sign as isize).clamp(1, 6) as usize,
+            Param::Ratio => settings.ratio = (settings.ratio + sign * 0.25).clamp(0.25, 8.0),
+            Param::Depth => settings.depth = (settings.depth + sign * 0.25).clamp(0.0, 10.0),
+            Param::Noise => settings.noise = (settings.noise + sign * 0.05).clamp(0.0, 1.0),
+            Param::Drift => settings.drift = (settings.drift * if up { 1.25 } else { 0.8 }).clamp(0.1, 10.0),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, Default)]
+struct Voice {
+    carrier: f32,
+    modulator: f32,
+}
+
+struct Engine {
+    voices: [Voice; 6],
+    time: f32,
+    seed: u32,
+    wind: [f32; 2],
+}
+
+impl Engine {
+    fn new() -> Self {
+        Engine {
+            voices: [Voice::default(); 6],
+            time: 0.0,
+            seed: 0x1234_5678,
+            wind: [0.0; 2],
+        }
+    }
+
+    fn noise(&mut self) -> f32 {
+        self.seed ^= self.seed << 13;
+        self.seed ^= self.seed >> 17;
+        self.seed ^= self.seed << 5;
+        self.seed as f32 / u32::MAX as f32 * 2.0 - 1.0
+    }
+
+    // Slow FM voices swelling and wandering across the stage over a wind of
+    // filtered noise; phases carry on through changes, so tweaks never click.
+    fn render(&mut self, settings: &Settings, frames: usize) -> Vec<f32> {
+        let rate = RATE as f32;
+        let drift = settings.drift.max(0.01);
+        let voices = settings.voices.clamp(1, CHORD.len());
+        let mut block = Vec::with_capacity(frames * 2);
+        for _ in 0..frames {
+            let slow = self.time * drift;
+            let phrase = (slow / PHRASE_SECS) as usize % PROGRESSION.len();
+            let root = settings.root + PROGRESSION[phrase];
+            let (mut left, mut right) = (0.0, 0.0);
+            for (idx, voice) in self.voices.iter_mut().enumerate().take(voices) {
+                let hz = 440.0 * 2.0f32.powf((root + CHORD[idx] - 69.0) / 12.0);
+                // Each voice swells and pans at its own pace.
+                let pace = 1.0 + idx as f32 * 0.37;
+                let swell = 0.5 - 0.5 * (2.0 * PI * slow / (11.0 * pace) + idx as f32).cos();
+                let pan = (2.0 * PI * slow / (7.0 * pace) + idx as f32 * 1.3).sin();
+                let index = settings.depth * (0.3 + 0.7 * swell);
+
+                voice.modulator = (voice.modulator + hz * settings.ratio / rate).fract();
+                voice.carrier = (voice.carrier + hz / rate).fract();
+                let amp = swell * (2.0 * PI * voice.carrier + index * (2.0 * PI * voice.modulator).sin()).sin();
+                let angle = (pan + 1.0) * PI / 4.0;
+                left += amp * angle.cos() / voices as f32;
+                right += amp * angle.sin() / voices as f32;
+            }
+
+            // Two one-pole lowpasses whose cutoff breathes slowly.
+            let cutoff = 200.0 + 1800.0 * (0.5 + 0.5 * (2.0 * PI * slow / 13.0).sin());
+            let share = 1.0 - (-2.0 * PI * cutoff / rate).exp();
+            let (first, second) = (self.noise(), self.noise());
+            self.wind[0] += (first - self.wind[0]) * share;
+            self.wind[1] += (second - self.wind[1]) * share;
+            let noise = settings.noise * 2.0;
+            left += self.wind[0] * noise;
+            right += self.wind[1] * noise;
+
+            block.push(left * settings.volume);
+            block.push(right * settings.volume);
+            self.time += 1.0 / rate;
+        }
+        block
+    }
+}
+
+// Changes the settings of a synth that is playing, one parameter at a time.
+pub struct Control {
+    settings: Arc<Mutex<Settings>>,
+    selected: usize,
+}
+
+impl Control {
+    pub fn select_next(&mut self) -> String {
+        self.selected = (self.selected + 1) % PARAMS.len();
+        self.describe()
+    }
+
+    pub fn nudge(&mut self, up: bool) -> String {
+        if let Ok(mut settings) = self.settings.lock() {
+            PARAMS[self.selected].nudge(&mut settings, up);
+        }
+        self.describe()
+    }
+
+    fn describe(&self) -> String {
+        match self.settings.lock() {
+            Ok(settings) => format!("Synth {}", PARAMS[self.selected].describe(&settings)),
+            Err(_) => "Synth stopped".into(),
+        }
+    }
+}
+
+// Plays the synth and feeds the same audio to the visualizer.
+pub fn start(settings: Settings) -> (LiveInput, Control) {
+    let settings = Arc::new(Mutex::new(settings));
+    let shared = settings.clone();
+    let (sender, receiver) = mpsc::channel();
+    thread::spawn(move || {
+        let output = rodio::default_output_device();
+        let sink = output.as_ref().map(rodio::Sink::new);
+        if sink.is_none() {
+            println!("No output device for the synth; showing it silently");
+        }
+        let mut engine = Engine::new();
+        let (started, mut frames) = (Instant::now(), 0u64);
+        loop {
+            let block = match shared.lock() {
+                Ok(settings) => engine.render(&settings, BLOCK),
+                Err(_) => break,
+            };
+            if let Some(ref sink) = sink {
+                sink.append(rodio::buffer::SamplesBuffer::new(2, RATE, block.clone()));
+            }
+            if sender.send(block).is_err() {
+                break;
+            }
+            frames += BLOCK as u64;
+            let ahead = frames as f64 / f64::from(RATE) - started.elapsed().as_secs_f64();
+            if ahead > LEAD {
+                thread::sleep(Duration::from_secs_f64(ahead - LEAD));
+            }
+        }
+    });
+
+    let control = Control { settings, selected: 0 };
+    (LiveInput::new(receiver, RATE, "Synth".into(), ()), control)
+}
@@ -0,0 +1,215 @@
+#[derive(Debug, Default)]
+pub struct Tags {
+    entries: Vec<(String, String)>,
+}
+
+impl Tags {
+    pub fn read(bytes: &[u8]) -> Self {
+        let mut entries = Vec::new();
+
+        if bytes.starts_with(b"ID3") {
+            read_id3(bytes, &mut entries);
+        } else if bytes.starts_with(b"fLaC") {
+            read_flac(bytes, &mut entries);
+        } else if bytes.starts_with(b"OggS") {
+            read_ogg(bytes, &mut entries);
+        } else if bytes.get(4..8) == Some(b"ftyp") {
+            read_mp4(bytes, &mut entries);
+        }
+
+        Tags { entries }
+    }
+
+    pub fn get(&self, key: &str) -> Option<&str> {
+        self.entries
+            .iter()
+            .find(|(name, _)| name.eq_ignore_ascii_case(key))
+            .map(|(_, value)| value.as_str())
+    }
+}
+
+fn syncsafe(bytes: &[u8]) -> usize {
+    bytes.iter().fold(0, |acc, &byte| (acc << 7) | (byte & 0x7f) as usize)
+}
+
+fn read_id3(bytes: &[u8], entries: &mut Vec<(String, String)>) {
+    if bytes.len() < 10 {
+        return;
+    }
+
+    let version = bytes[3];
+    if version < 3 {
+        return;
+    }
+    let end = (10 + syncsafe(&bytes[6..10])).min(bytes.len());
+    let mut pos = 10;
+
+    while pos + 10 <= end {
+        let id = &bytes[pos..pos + 4];
+        if id[0] == 0 {
+            break;
+        }
+
+        let size = if version >= 4 {
+            syncsafe(&bytes[pos + 4..pos + 8])
+        } else {
+            bytes[pos + 4..pos + 8]
+                .iter()
+                .fold(0, |acc, &byte| (acc << 8) | byte as usize)
+        };
+        let body = &bytes[(pos + 10).min(end)..(pos + 10 + size).min(end)];
+        pos += 10 + size;
+
+        if body.is_empty() {
+            continue;
+        }
+
+        let key = match id {
+            b"TIT2" => "TITLE",
+            b"TPE1" => "ARTIST",
+            b"TALB" => "ALBUM",
+            b"TRCK" => "TRACKNUMBER",
+            b"TXXX" => "",
+            _ => continue,
+        };
+
+        let mut fields = decode_text(body[0], &body[1..]).into_iter();
+        if key.is_empty() {
+            if let (Some(name), Some(value)) = (fields.next(), fields.next()) {
+                entries.push((name.to_uppercase(), value));
+            }
+        } else if let Some(value) = fields.next() {
+            entries.push((key.to_string(), value));
+        }
+    }
+}
+
+fn decode_text(encoding: u8, bytes: &[u8]) -> Vec<String> {
+    match encoding {
+        1 | 2 => {
+            let mut big_endian = encoding == 2;
+            let mut units = Vec::with_capacity(bytes.len() / 2);
+            for pair in bytes.chunks_exact(2) {
+                match [pair[0], pair[1]] {
+                    [0xfe, 0xff] => big_endian = true,
+                    [0xff, 0xfe] => big_endian = false,
+                    pair if big_endian => units.push(u16::from_be_bytes(pair)),
+                    pair => units.push(u16::from_le_bytes(pair)),
+                }
+            }
+            units
+                .split(|&unit| unit == 0)
+                .map(String::from_utf16_lossy)
+                .collect()
+        }
+        0 => bytes
+            .split(|&byte| byte == 0)
+            .map(|field| field.iter().map(|&byte| byte as char).collect())
+            .collect(),
+        _ => bytes
+            .split(|&byte| byte == 0)
+            .map(|field| String::from_utf8_lossy(field).into_owned())
+            .collect(),
+    }
+}
+
+fn read_le32(bytes: &[u8], pos: usize) -> Option<usize> {
+    let field = bytes.get(pos..pos + 4)?;
+    Some(u32::from_le_bytes([field[0], field[1], field[2], field[3]]) as usize)
+}
+
+fn read_vorbis_comment(bytes: &[u8], entries: &mut Vec<(String, String)>) -> Option<()> {
+    let vendor = read_le32(bytes, 0)?;
+    let count = read_le32(bytes, 4 + vendor)?;
+    let mut pos = 8 + vendor;
+
+    for _ in 0..count {
+        let len = read_le32(bytes, pos)?;
+        let comment = bytes.get(pos + 4..pos + 4 + len)?;
+        pos += 4 + len;
+
+        let comment = String::from_utf8_lossy(comment);
+        if let Some(split) = comment.find('=') {
+            entries.push((comment[..split].to_uppercase(), comment[split + 1..].to_string()));
+        }
+    }
+
+    Some(())
+}
+
+fn read_flac(bytes: &[u8], entries: &mut Vec<(String, String)>) {
+    let mut pos = 4;
+
+    while pos + 4 <= bytes.len() {
+        let header = bytes[pos];
+        let len = bytes[pos + 1..pos + 4]
+            .iter()
+            .fold(0, |acc, &byte| (acc << 8) | byte as usize);
+        let block = &bytes[pos + 4..(pos + 4 + len).min(bytes.len())];
+        pos += 4 + len;
+
+        if header & 0x7f == 4 {
+            read_vorbis_comment(block, entries);
+            return;
+        }
+        if header & 0x80 != 0 {
+            return;
+        }
+    }
+}
+
+fn read_ogg(bytes: &[u8], entries: &mut Vec<(String, String)>) {
+    // The comment header sits in the first pages, so a plain scan is enough.
+    let head = &bytes[..bytes.len().min(1 << 16)];
+    for magic in [&b"\x03vorbis"[..], &b"OpusTags"[..]].iter() {
+        if let Some(pos) = head.windows(magic.len()).position(|window| window == *magic) {
+            read_vorbis_comment(&head[pos + magic.len()..], entries);
+            return;
+        }
+    }
+}
+
+fn find_atom<'a>(bytes: &'a [u8], name: &[u8]) -> Option<&'a [u8]> {
+    let mut pos = 0;
+    while pos + 8 <= bytes.len() {
+        let size = u32::from_be_bytes([bytes[pos], bytes[pos + 1], bytes[pos + 2], bytes[pos + 3]]) as usize;
+        let size = if size == 0 { bytes.len() - pos } else { size };
+        if size < 8 {
+            return None;
+        }
+        if &bytes[pos + 4..pos + 8] == name {
+            return bytes.get(pos + 8..(pos + size).min(bytes.len()));
+        }
+        pos += size;
+    }
+    None
+}
+
+// Nero chapter lists (moov/udta/chpl), as used by most M4B audiobooks, are
+// stored as Vorbis-style CHAPTERxxx entries.
+fn read_mp4(bytes: &[u8], entries: &mut Vec<(String, String)>) -> Option<()> {
+    let chpl = find_atom(find_atom(find_atom(bytes, b"moov")?, b"udta")?, b"chpl")?;
+    let mut pos = if *chpl.first()? > 0 { 8 } else { 4 };
+    let count = *chpl.get(pos)?;
+    pos += 1;
+
+    for idx in 0..count as usize {
+        let start = chpl
+            .get(pos..pos + 8)?
+            .iter()
+            .fold(0u64, |acc, &byte| (acc << 8) | byte as u64);
+        let len = *chpl.get(pos + 8)? as usize;
+        let title = String::from_utf8_lossy(chpl.get(pos + 9..pos + 9 + len)?).into_owned();
+        pos += 9 + len;
+
+        let secs = start as f64 / 10_000_000.0;
+        let key = format!("CHAPTER{:03}", idx + 1);
+        entries.push((
+            key.clone(),
+            format!("{:02}:{:02}:{:06.3}", (secs / 3600.0) as u32, (secs / 60.0) as u32 % 60, secs % 60.0),
+        ));
+        entries.push((format!("{}NAME", key), title));
+    }
+
+    Some(())
+}
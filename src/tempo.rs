@@ -0,0 +1,271 @@
+use crate::{overlay::{self, Anchor}, DirectionalSource};
+use ggez::{
+    graphics::{self, DrawParam},
+    Context, GameResult,
+};
+use std::collections::VecDeque;
+
+// The onset envelope is resampled onto a fixed grid so the autocorrelation
+// does not depend on the frame rate.
+const GRID_HZ: f32 = 50.0;
+const WINDOW: f32 = 6.0;
+const STEP: f32 = 0.5;
+const MIN_BPM: f32 = 60.0;
+const MAX_BPM: f32 = 200.0;
+// Octave errors are resolved towards this tempo.
+const PREFERRED_BPM: f32 = 120.0;
+// A change is a jump of this share between the medians before and after,
+// rubato is this much spread around a point without a clear jump.
+const CHANGE: f32 = 0.06;
+const RUBATO: f32 = 0.03;
+const SIDE: usize = 6;
+const STRIP_TOP: f32 = 14.0;
+const STRIP_HEIGHT: f32 = 28.0;
+
+fn median(values: &mut [f32]) -> f32 {
+    values.sort_by(|a, b| a.total_cmp(b));
+    values[values.len() / 2]
+}
+
+// Continuous tempo estimates from the autocorrelation of spectral flux,
+// kept as a curve over the track's time line.
+pub struct TempoCurve {
+    pub visible: bool,
+    previous: Vec<f32>,
+    envelope: VecDeque<f32>,
+    last_time: f32,
+    next_estimate: f32,
+    current: Option<f32>,
+    curve: Vec<(f32, f32)>,
+}
+
+impl TempoCurve {
+    pub fn new() -> Self {
+        TempoCurve {
+            visible: false,
+            previous: Vec::new(),
+            envelope: VecDeque::new(),
+            last_time: 0.0,
+            next_estimate: 0.0,
+            current: None,
+            curve: Vec::new(),
+        }
+    }
+
+    pub fn clear(&mut self) {
+        self.envelope.clear();
+        self.previous.clear();
+        self.current = None;
+        self.curve.clear();
+    }
+
+    pub fn update(&mut self, directions: &[DirectionalSource], time: f32) {
+        let cell = 1.0 / GRID_HZ;
+        if time < self.last_time || time > self.last_time + STEP {
+            // Seeked: the envelope no longer lines up with the music.
+            self.envelope.clear();
+            self.previous.clear();
+            self.current = None;
+            self.next_estimate = time + WINDOW;
+        }
+
+        let flux: f32 = directions
+            .iter()
+            .zip(&self.previous)
+            .skip(1)
+            .map(|(source, previous)| (source.amp - previous).max(0.0))
+            .sum();
+        self.previous = directions.iter().map(|source| source.amp).collect();
+
+        // Fill the grid cells passed since the last frame; skipped cells stay empty.
+        let cells = if self.envelope.is_empty() {
+            1
+        } else {
+            ((time / cell) as i64 - (self.last_time / cell) as i64).max(0) as usize
+        };
+        for idx in 0..cells {
+            self.envelope.push_back(if idx + 1 == cells { flux } else { 0.0 });
+        }
+        while self.envelope.len() > (WINDOW * GRID_HZ) as usize {
+            self.envelope.pop_front();
+        }
+        self.last_time = time;
+
+        if time >= self.next_estimate {
+            self.next_estimate = time + STEP;
+            self.current = self.estimate();
+            if let Some(bpm) = self.current {
+                // The estimate describes the window that ends now.
+                let at = time - WINDOW / 2.0;
+                self.curve.retain(|&(other, _)| (other - at).abs() >= STEP / 2.0);
+                let idx = self.curve.partition_point(|&(other, _)| other < at);
+                self.curve.insert(idx, (at, bpm));
+            }
+        }
+    }
+
+    fn estimate(&self) -> Option<f32> {
+        let envelope: Vec<f32> = self.envelope.iter().copied().collect();
+        let mean = envelope.iter().sum::<f32>() / envelope.len().max(1) as f32;
+        let centered: Vec<f32> = envelope.iter().map(|value| value - mean).collect();
+
+        let min_lag = (GRID_HZ * 60.0 / MAX_BPM) as usize;
+        let max_lag = (GRID_HZ * 60.0 / MIN_BPM).ceil() as usize;
+        if centered.len() < max_lag * 2 {
+            return None;
+        }
+        let weighted = |lag: usize| {
+            let correlation: f32 = centered.iter().zip(&centered[lag..]).map(|(a, b)| a * b).sum();
+            let bpm = GRID_HZ * 60.0 / lag as f32;
+            let octaves = (bpm / PREFERRED_BPM).log2();
+            correlation * (-octaves * octaves * 2.0).exp()
+        };
+        let scores: Vec<f32> = (min_lag - 1..=max_lag + 1).map(weighted).collect();
+        let (best, &score) = scores[1..scores.len() - 1]
+            .iter()
+            .enumerate()
+            .max_by(|a, b| a.1.total_cmp(b.1))?;
+        if score <= 0.0 {
+            return None;
+        }
+
+        // Parabolic interpolation between neighbouring lags for sub-cell precision.
+        let (before, after) = (scores[best], scores[best + 2]);
+        let curvature = before - 2.0 * score + after;
+        let shift = if curvature < 0.0 { 0.5 * (before - after) / curvature } else { 0.0 };
+        let lag = (min_lag + best) as f32 + shift.clamp(-0.5, 0.5);
+        Some(GRID_HZ * 60.0 / lag)
+    }
+
+    // The beat period, the time of a recent beat and of a recent downbeat,
+    // found by laying a comb at the current tempo over the onset envelope.
+    pub fn beat(&self) -> Option<(f32, f32, f32)> {
+        let period = 60.0 / self.current?;
+        let cells = period * GRID_HZ;
+        let comb = |offset: f32, spacing: f32| {
+            let mut at = offset;
+            let mut score = 0.0;
+            while (at as usize) < self.envelope.len() {
+                score += self.envelope[self.envelope.len() - 1 - at as usize];
+                at += spacing;
+            }
+            score
+        };
+
+        let offset = (0..cells as usize)
+            .map(|offset| offset as f32)
+            .max_by(|&a, &b| comb(a, cells).total_cmp(&comb(b, cells)))?;
+        let bar = (0..4)
+            .map(|beat| offset + beat as f32 * cells)
+            .max_by(|&a, &b| comb(a, cells * 4.0).total_cmp(&comb(b, cells * 4.0)))?;
+        Some((period, self.last_time - offset / GRID_HZ, self.last_time - bar / GRID_HZ))
+    }
+
+    pub fn bpm(&self) -> Option<f32> {
+        self.current
+    }
+
+    // Times at which the tempo settled at a different value.
+    pub fn changes(&self) -> Vec<f32> {
+        self.sections().0
+    }
+
+    // Points where the tempo settles at a different value, and points whose
+    // surroundings drift without settling.
+    fn sections(&self) -> (Vec<f32>, Vec<usize>) {
+        let mut changes = Vec::new();
+        let mut rubato = Vec::new();
+        if self.curve.len() < SIDE * 2 {
+            return (changes, rubato);
+        }
+        let tempos: Vec<f32> = self.curve.iter().map(|&(_, bpm)| bpm).collect();
+        for idx in SIDE..tempos.len() - SIDE {
+            let before = median(&mut tempos[idx - SIDE..idx].to_vec());
+            let after = median(&mut tempos[idx..idx + SIDE].to_vec());
+            let jump = (after - before).abs() / before;
+            if jump >= CHANGE {
+                if changes.last().is_none_or(|&last| self.curve[idx].0 - last > WINDOW) {
+                    changes.push(self.curve[idx].0);
+                }
+                continue;
+            }
+
+            let around = &tempos[idx - SIDE / 2..idx + SIDE / 2];
+            let mean = around.iter().sum::<f32>() / around.len() as f32;
+            let spread = around.iter().map(|bpm| (bpm - mean).abs()).sum::<f32>() / around.len() as f32;
+            if spread / mean >= RUBATO {
+                rubato.push(idx);
+            }
+        }
+        (changes, rubato)
+    }
+
+    pub fn draw(&self, ctx: &mut Context, width: f32, length: f32) -> GameResult {
+        if !self.visible || length <= 0.0 {
+            return Ok(());
+        }
+
+        let x_at = |time: f32| width * (time / length).clamp(0.0, 1.0);
+        let y_at = |bpm: f32| {
+            let share = ((bpm - MIN_BPM) / (MAX_BPM - MIN_BPM)).clamp(0.0, 1.0);
+            STRIP_TOP + STRIP_HEIGHT * (1.0 - share)
+        };
+        let (changes, rubato) = self.sections();
+
+        let mut builder = graphics::MeshBuilder::new();
+        builder.rectangle(
+            graphics::DrawMode::fill(),
+            graphics::Rect::new(0.0, STRIP_TOP, width, STRIP_HEIGHT),
+            graphics::Color::from_rgba(20, 20, 20, 160),
+        );
+        for idx in rubato {
+            let (from, to) = (x_at(self.curve[idx].0 - STEP / 2.0), x_at(self.curve[idx].0 + STEP / 2.0));
+            builder.rectangle(
+                graphics::DrawMode::fill(),
+                graphics::Rect::new(from, STRIP_TOP, (to - from).max(1.0), STRIP_HEIGHT),
+                graphics::Color::from_rgba(192, 96, 255, 72),
+            );
+        }
+        for time in changes {
+            let x = x_at(time);
+            builder.rectangle(
+                graphics::DrawMode::fill(),
+                graphics::Rect::new(x - 1.0, STRIP_TOP, 2.0, STRIP_HEIGHT),
+                graphics::Color::from_rgb(255, 160, 64),
+            );
+        }
+
+        // Break the line where the curve has gaps from seeking.
+        let mut points: Vec<[f32; 2]> = Vec::new();
+        let mut last = None;
+        for &(time, bpm) in &self.curve {
+            if last.is_some_and(|last| time - last > STEP * 2.0) {
+                if points.len() >= 2 {
+                    builder.line(&points, 1.5, graphics::Color::from_rgb(128, 255, 160))?;
+                }
+                points.clear();
+            }
+            points.push([x_at(time), y_at(bpm)]);
+            last = Some(time);
+        }
+        if points.len() >= 2 {
+            builder.line(&points, 1.5, graphics::Color::from_rgb(128, 255, 160))?;
+        }
+
+        let mesh = builder.build(ctx)?;
+        graphics::draw(ctx, &mesh, DrawParam::default())?;
+
+        let label = match self.curve.last() {
+            Some(&(_, bpm)) => format!("{:.0} BPM", bpm),
+            None => "tempo: listening".to_string(),
+        };
+        overlay::label(
+            ctx,
+            label,
+            14.0,
+            Anchor::TopLeft,
+            [width - 96.0, STRIP_TOP + 6.0],
+            graphics::Color::from_rgb(160, 160, 160),
+        )
+    }
+}
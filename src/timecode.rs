@@ -0,0 +1,144 @@
+use crate::live::LiveInput;
+use std::{
+    fmt,
+    time::{Duration, Instant},
+};
+
+// Bits in one LTC frame, the last 16 of which are the sync word.
+const FRAME_BITS: u32 = 80;
+// The sync word as it lands in the register, read first bit lowest.
+const SYNC: u128 = 0xbffc;
+// Level a signal has to cross to count as a transition, against hum and noise.
+const HYSTERESIS: f32 = 0.02;
+// Timecode that stops arriving is dropped after this long.
+const STALE: Duration = Duration::from_secs(1);
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct Timecode {
+    pub hours: u32,
+    pub minutes: u32,
+    pub seconds: u32,
+    pub frames: u32,
+}
+
+impl Timecode {
+    // Non-drop-frame timecode at a whole frame rate.
+    pub fn from_secs(secs: f32, fps: u32) -> Self {
+        let fps = fps.max(1);
+        let total = (secs.max(0.0) * fps as f32) as u32;
+        let seconds = total / fps;
+        Timecode {
+            hours: seconds / 3600 % 24,
+            minutes: seconds / 60 % 60,
+            seconds: seconds % 60,
+            frames: total % fps,
+        }
+    }
+
+    fn from_bits(bits: u128) -> Self {
+        let field = |start: u32, len: u32| ((bits >> start) & ((1 << len) - 1)) as u32;
+        Timecode {
+            hours: field(48, 4) + field(56, 2) * 10,
+            minutes: field(32, 4) + field(40, 3) * 10,
+            seconds: field(16, 4) + field(24, 3) * 10,
+            frames: field(0, 4) + field(8, 2) * 10,
+        }
+    }
+}
+
+impl fmt::Display for Timecode {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{:02}:{:02}:{:02}:{:02}", self.hours, self.minutes, self.seconds, self.frames)
+    }
+}
+
+// Reads SMPTE linear timecode: biphase mark code, where every bit starts with
+// a transition and a 1 has another one halfway through.
+struct Decoder {
+    // Samples per bit, learnt from the signal.
+    period: f32,
+    high: bool,
+    since: u32,
+    half: bool,
+    bits: u128,
+}
+
+impl Decoder {
+    fn new(sample_rate: u32) -> Self {
+        Decoder {
+            // Starts between 24 and 30 fps, close enough to lock onto either.
+            period: sample_rate as f32 / (27.0 * FRAME_BITS as f32),
+            high: false,
+            since: 0,
+            half: false,
+            bits: 0,
+        }
+    }
+
+    fn push(&mut self, bit: bool) -> Option<Timecode> {
+        self.bits = (self.bits >> 1) | (u128::from(bit) << (FRAME_BITS - 1));
+        if self.bits >> 64 == SYNC {
+            Some(Timecode::from_bits(self.bits))
+        } else {
+            None
+        }
+    }
+
+    fn process(&mut self, sample: f32) -> Option<Timecode> {
+        self.since += 1;
+        let crossed = if self.high { sample < -HYSTERESIS } else { sample > HYSTERESIS };
+        if !crossed {
+            return None;
+        }
+        self.high = !self.high;
+        let interval = self.since as f32;
+        self.since = 0;
+
+        if interval > self.period * 0.75 {
+            self.period += (interval - self.period) * 0.1;
+            self.half = false;
+            self.push(false)
+        } else {
+            self.period += (interval * 2.0 - self.period) * 0.1;
+            self.half = !self.half;
+            if self.half {
+                None
+            } else {
+                self.push(true)
+            }
+        }
+    }
+}
+
+// Follows the timecode coming in on a capture device's first channel.
+pub struct Chase {
+    input: LiveInput,
+    decoder: Decoder,
+    latest: Option<(Timecode, Instant)>,
+}
+
+impl Chase {
+    pub fn new(input: LiveInput) -> Self {
+        let decoder = Decoder::new(input.sample_rate);
+        Chase {
+            input,
+            decoder,
+            latest: None,
+        }
+    }
+
+    pub fn update(&mut self) {
+        for block in self.input.receiver.try_iter() {
+            // Blocks are interleaved stereo.
+            for &sample in block.iter().step_by(2) {
+                if let Some(timecode) = self.decoder.process(sample) {
+                    self.latest = Some((timecode, Instant::now()));
+                }
+            }
+        }
+    }
+
+    pub fn current(&self) -> Option<Timecode> {
+        self.latest.filter(|(_, at)| at.elapsed() < STALE).map(|(timecode, _)| timecode)
+    }
+}
@@ -0,0 +1,148 @@
+use crate::{automation, Mode};
+use ggez::{error::GameError, GameResult};
+use serde::{Deserialize, Serialize};
+use std::{fs, path::Path};
+
+// How a value moves from the key before to the key that names it.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum Ease {
+    #[default]
+    Linear,
+    // Keeps the old value until the key, then jumps.
+    Hold,
+    In,
+    Out,
+    InOut,
+}
+
+impl Ease {
+    fn apply(self, progress: f32) -> f32 {
+        let t = progress.clamp(0.0, 1.0);
+        match self {
+            Ease::Linear => t,
+            Ease::Hold => 0.0,
+            Ease::In => t * t,
+            Ease::Out => t * (2.0 - t),
+            Ease::InOut => t * t * (3.0 - 2.0 * t),
+        }
+    }
+}
+
+// One point of the timeline. Values are eased into from the last key that
+// set them; the mode, preset and strobe happen as playback reaches the key.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Key {
+    // Seconds into the track.
+    pub time: f32,
+    pub ease: Ease,
+    pub sensitivity: Option<f32>,
+    pub width: Option<f32>,
+    pub low: Option<[u8; 3]>,
+    pub high: Option<[u8; 3]>,
+    pub warp: Option<f32>,
+    pub mode: Option<Mode>,
+    pub preset: Option<usize>,
+    pub strobe: bool,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+struct File {
+    keys: Vec<Key>,
+}
+
+// What the timeline says for a moment, where it says anything.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct Values {
+    pub sensitivity: Option<f32>,
+    pub width: Option<f32>,
+    pub low: Option<[u8; 3]>,
+    pub high: Option<[u8; 3]>,
+    pub warp: Option<f32>,
+}
+
+fn mix(from: f32, to: f32, share: f32) -> f32 {
+    from + (to - from) * share
+}
+
+fn mix_color(from: [u8; 3], to: [u8; 3], share: f32) -> [u8; 3] {
+    let channel = |idx: usize| mix(f32::from(from[idx]), f32::from(to[idx]), share).round() as u8;
+    [channel(0), channel(1), channel(2)]
+}
+
+// Keyframes for a scripted video, from a TOML or JSON file, applied against
+// each track's time while it plays and in frame renders.
+pub struct Timeline {
+    keys: Vec<Key>,
+    last: f32,
+}
+
+impl Timeline {
+    pub fn load(path: &Path) -> GameResult<Self> {
+        let text = fs::read_to_string(path)?;
+        let file: File = if path.extension().is_some_and(|ext| ext.eq_ignore_ascii_case("json")) {
+            serde_json::from_str(&text)
+                .map_err(|err| GameError::ResourceLoadError(format!("{}: {}", path.display(), err)))?
+        } else {
+            toml::from_str(&text)?
+        };
+        let mut keys = file.keys;
+        keys.sort_by(|a, b| a.time.total_cmp(&b.time));
+        Ok(Timeline {
+            keys,
+            last: -f32::EPSILON,
+        })
+    }
+
+    // Back to the start, for a new track.
+    pub fn rewind(&mut self) {
+        self.last = -f32::EPSILON;
+    }
+
+    // The keys around `time` that set a value, with how far between them it is.
+    fn around<T: Copy>(&self, time: f32, get: impl Fn(&Key) -> Option<T>) -> Option<(T, T, f32)> {
+        let mut before: Option<(f32, T)> = None;
+        for key in &self.keys {
+            let value = match get(key) {
+                Some(value) => value,
+                None => continue,
+            };
+            if key.time > time {
+                return Some(match before {
+                    Some((at, from)) => {
+                        let progress = (time - at) / (key.time - at).max(f32::EPSILON);
+                        (from, value, key.ease.apply(progress))
+                    }
+                    None => (value, value, 0.0),
+                });
+            }
+            before = Some((key.time, value));
+        }
+        before.map(|(_, value)| (value, value, 0.0))
+    }
+
+    pub fn values(&self, time: f32) -> Values {
+        let number = |get: fn(&Key) -> Option<f32>| self.around(time, get).map(|(from, to, share)| mix(from, to, share));
+        let color = |get: fn(&Key) -> Option<[u8; 3]>| {
+            self.around(time, get).map(|(from, to, share)| mix_color(from, to, share))
+        };
+        Values {
+            sensitivity: number(|key| key.sensitivity),
+            width: number(|key| key.width),
+            low: color(|key| key.low),
+            high: color(|key| key.high),
+            warp: number(|key| key.warp),
+        }
+    }
+
+    // The keys playback reached since the last update.
+    pub fn update(&mut self, now: f32) -> Vec<Key> {
+        let last = match automation::advance(&mut self.last, now) {
+            Some(last) => last,
+            None => return Vec::new(),
+        };
+        self.keys.iter().filter(|key| key.time > last && key.time <= now).cloned().collect()
+    }
+}
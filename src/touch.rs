@@ -0,0 +1,220 @@
+use ggez::{
+    event::{
+        winit_event::{ElementState, Event, KeyboardInput, MouseScrollDelta, TouchPhase, WindowEvent},
+        Axis, Button, EventHandler, EventsLoop,
+    },
+    graphics,
+    input::{gamepad::gilrs, keyboard, mouse},
+    Context, GameResult,
+};
+use std::time::Instant;
+use winit::dpi::LogicalPosition;
+
+// A swipe across the whole width seeks this far.
+const SWIPE_SECS: f32 = 60.0;
+// How far a finger travels before it counts as a drag rather than a tap.
+const SLOP: f32 = 24.0;
+const TAP_SECS: f32 = 0.3;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Gesture {
+    // Seconds to seek by.
+    Seek(f32),
+    // Change in volume, as a share of the full range.
+    Volume(f32),
+    NextMode,
+    // Zoom steps as the mouse wheel counts them, around `x`.
+    Zoom { steps: f32, x: f32 },
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Drag {
+    Undecided,
+    Horizontal,
+    Vertical,
+}
+
+struct Finger {
+    id: u64,
+    start: [f32; 2],
+    pos: [f32; 2],
+}
+
+// Turns raw touch points into gestures: one finger swipes to seek or drags up
+// and down for volume, two fingers pinch to zoom or tap to change mode.
+pub struct Touches {
+    fingers: Vec<Finger>,
+    drag: Drag,
+    // A second finger touched down during this contact.
+    multi: bool,
+    tap: Option<Instant>,
+    pinch: Option<f32>,
+}
+
+impl Touches {
+    pub fn new() -> Self {
+        Touches {
+            fingers: Vec::new(),
+            drag: Drag::Undecided,
+            multi: false,
+            tap: None,
+            pinch: None,
+        }
+    }
+
+    fn spread(&self) -> Option<(f32, f32)> {
+        match self.fingers.as_slice() {
+            [first, second, ..] => {
+                let distance = (first.pos[0] - second.pos[0]).hypot(first.pos[1] - second.pos[1]);
+                Some((distance.max(1.0), (first.pos[0] + second.pos[0]) / 2.0))
+            }
+            _ => None,
+        }
+    }
+
+    pub fn handle(&mut self, phase: TouchPhase, id: u64, pos: [f32; 2], (width, height): (f32, f32)) -> Option<Gesture> {
+        match phase {
+            TouchPhase::Started => {
+                self.fingers.push(Finger { id, start: pos, pos });
+                if self.fingers.len() == 2 {
+                    self.multi = true;
+                    self.tap = Some(Instant::now());
+                    self.pinch = self.spread().map(|(distance, _)| distance);
+                }
+                None
+            }
+            TouchPhase::Moved => {
+                let finger = self.fingers.iter_mut().find(|finger| finger.id == id)?;
+                let last = finger.pos;
+                finger.pos = pos;
+                let (dx, dy) = (pos[0] - finger.start[0], pos[1] - finger.start[1]);
+                let travelled = dx.hypot(dy);
+
+                if self.multi {
+                    if travelled > SLOP {
+                        self.tap = None;
+                    }
+                    let (distance, x) = self.spread()?;
+                    let previous = self.pinch.replace(distance)?;
+                    // Spreading the fingers narrows the range, like scrolling up.
+                    let steps = (previous / distance).ln() / 0.85f32.ln();
+                    return Some(Gesture::Zoom { steps, x });
+                }
+
+                if self.drag == Drag::Undecided && travelled > SLOP {
+                    self.drag = if dx.abs() > dy.abs() { Drag::Horizontal } else { Drag::Vertical };
+                }
+                match self.drag {
+                    Drag::Vertical => Some(Gesture::Volume((last[1] - pos[1]) / height)),
+                    _ => None,
+                }
+            }
+            TouchPhase::Ended | TouchPhase::Cancelled => {
+                let idx = self.fingers.iter().position(|finger| finger.id == id)?;
+                let finger = self.fingers.remove(idx);
+                let gesture = if self.multi {
+                    let tapped = self.tap.take().is_some_and(|at| at.elapsed().as_secs_f32() < TAP_SECS);
+                    if tapped && phase == TouchPhase::Ended {
+                        Some(Gesture::NextMode)
+                    } else {
+                        None
+                    }
+                } else if self.drag == Drag::Horizontal && phase == TouchPhase::Ended {
+                    Some(Gesture::Seek((finger.pos[0] - finger.start[0]) / width * SWIPE_SECS))
+                } else {
+                    None
+                };
+                if self.fingers.len() < 2 {
+                    self.pinch = None;
+                }
+                if self.fingers.is_empty() {
+                    self.multi = false;
+                    self.drag = Drag::Undecided;
+                }
+                gesture
+            }
+        }
+    }
+}
+
+// What the loop below hands over besides the usual events. Gamepad events come
+// without their id, which only ggez itself can make.
+pub trait GestureHandler {
+    fn gesture_event(&mut self, ctx: &mut Context, gesture: Gesture);
+    fn button_event(&mut self, ctx: &mut Context, button: Button);
+    fn axis_event(&mut self, ctx: &mut Context, axis: Axis, value: f32);
+    // The window moved to a display with another ratio of physical to
+    // logical pixels.
+    fn scale_event(&mut self, ctx: &mut Context, factor: f32);
+}
+
+// ggez's own main loop, plus touch events, which it drops.
+pub fn run<S>(ctx: &mut Context, events_loop: &mut EventsLoop, state: &mut S) -> GameResult
+where
+    S: EventHandler + GestureHandler,
+{
+    let mut touches = Touches::new();
+    while ctx.continuing {
+        ctx.timer_context.tick();
+        events_loop.poll_events(|event| {
+            ctx.process_event(&event);
+            let event = match event {
+                Event::WindowEvent { event, .. } => event,
+                _ => return,
+            };
+            match event {
+                WindowEvent::Resized(size) => state.resize_event(ctx, size.width as f32, size.height as f32),
+                WindowEvent::HiDpiFactorChanged(factor) => state.scale_event(ctx, factor as f32),
+                WindowEvent::CloseRequested if !state.quit_event(ctx) => ggez::event::quit(ctx),
+                WindowEvent::Focused(gained) => state.focus_event(ctx, gained),
+                WindowEvent::ReceivedCharacter(ch) => state.text_input_event(ctx, ch),
+                WindowEvent::KeyboardInput {
+                    input: KeyboardInput { state: pressed, virtual_keycode: Some(keycode), modifiers, .. },
+                    ..
+                } => match pressed {
+                    ElementState::Pressed => {
+                        let repeat = keyboard::is_key_repeated(ctx);
+                        state.key_down_event(ctx, keycode, modifiers.into(), repeat);
+                    }
+                    ElementState::Released => state.key_up_event(ctx, keycode, modifiers.into()),
+                },
+                WindowEvent::MouseWheel { delta, .. } => {
+                    let (x, y) = match delta {
+                        MouseScrollDelta::LineDelta(x, y) => (x, y),
+                        MouseScrollDelta::PixelDelta(LogicalPosition { x, y }) => (x as f32, y as f32),
+                    };
+                    state.mouse_wheel_event(ctx, x, y);
+                }
+                WindowEvent::MouseInput { state: pressed, button, .. } => {
+                    let position = mouse::position(ctx);
+                    match pressed {
+                        ElementState::Pressed => state.mouse_button_down_event(ctx, button, position.x, position.y),
+                        ElementState::Released => state.mouse_button_up_event(ctx, button, position.x, position.y),
+                    }
+                }
+                WindowEvent::CursorMoved { .. } => {
+                    let (position, delta) = (mouse::position(ctx), mouse::delta(ctx));
+                    state.mouse_motion_event(ctx, position.x, position.y, delta.x, delta.y);
+                }
+                WindowEvent::Touch(touch) => {
+                    let screen = graphics::screen_coordinates(ctx);
+                    let pos = [touch.location.x as f32, touch.location.y as f32];
+                    if let Some(gesture) = touches.handle(touch.phase, touch.id, pos, (screen.w, screen.h)) {
+                        state.gesture_event(ctx, gesture);
+                    }
+                }
+                _ => (),
+            }
+        });
+        while let Some(gilrs::Event { event, .. }) = ctx.gamepad_context.next_event() {
+            match event {
+                gilrs::EventType::ButtonPressed(button, _) => state.button_event(ctx, button),
+                gilrs::EventType::AxisChanged(axis, value, _) => state.axis_event(ctx, axis, value),
+                _ => (),
+            }
+        }
+        state.update(ctx)?;
+        state.draw(ctx)?;
+    }
+    Ok(())
+}
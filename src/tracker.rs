@@ -0,0 +1,444 @@
+use crate::overlay::{self, Anchor};
+use ggez::{
+    error::GameError,
+    graphics::{self, DrawParam},
+    Context, GameResult,
+};
+use std::{collections::HashSet, f32::consts::PI, path::Path};
+
+const ROWS: usize = 64;
+const PAULA_CLOCK: f32 = 3_546_894.6;
+const MAX_SECS: usize = 20 * 60;
+const VISIBLE_ROWS: usize = 8;
+
+pub fn is_module(path: &Path) -> bool {
+    path.extension()
+        .and_then(|ext| ext.to_str())
+        .is_some_and(|ext| ext.eq_ignore_ascii_case("mod"))
+}
+
+#[derive(Debug, Clone, Copy, Default)]
+pub struct Note {
+    pub period: u16,
+    pub sample: u8,
+    pub effect: u8,
+    pub param: u8,
+}
+
+impl Note {
+    fn name(&self) -> String {
+        const NAMES: [&str; 12] = ["C-", "C#", "D-", "D#", "E-", "F-", "F#", "G-", "G#", "A-", "A#", "B-"];
+
+        let note = if self.period == 0 {
+            "---".to_string()
+        } else {
+            // Period 428 is C-2 in ProTracker.
+            let semis = (12.0 * (428.0 / self.period as f32).log2()).round() as i32 + 24;
+            format!("{}{}", NAMES[semis.rem_euclid(12) as usize], semis.div_euclid(12))
+        };
+        let sample = if self.sample == 0 {
+            "..".to_string()
+        } else {
+            format!("{:02}", self.sample)
+        };
+        let effect = if self.effect == 0 && self.param == 0 {
+            "...".to_string()
+        } else {
+            format!("{:X}{:02X}", self.effect, self.param)
+        };
+        format!("{} {} {}", note, sample, effect)
+    }
+}
+
+struct Sample {
+    data: Vec<f32>,
+    finetune: i8,
+    volume: u8,
+    loop_start: usize,
+    loop_len: usize,
+}
+
+pub struct Module {
+    channels: usize,
+    samples: Vec<Sample>,
+    orders: Vec<u8>,
+    patterns: Vec<Vec<Note>>,
+}
+
+fn read_be16(bytes: &[u8], pos: usize) -> usize {
+    ((bytes[pos] as usize) << 8) | bytes[pos + 1] as usize
+}
+
+impl Module {
+    pub fn parse(bytes: &[u8]) -> GameResult<Self> {
+        if bytes.starts_with(b"Extended Module: ")
+            || bytes.starts_with(b"IMPM")
+            || bytes.get(44..48) == Some(b"SCRM")
+        {
+            return Err(GameError::AudioError(
+                "XM, IT and S3M modules are not supported, only ProTracker MOD".into(),
+            ));
+        }
+        if bytes.len() < 1084 {
+            return Err(GameError::AudioError("Module is too short".into()));
+        }
+
+        let channels = match &bytes[1080..1084] {
+            b"M.K." | b"M!K!" | b"FLT4" | b"4CHN" => 4,
+            b"6CHN" => 6,
+            b"8CHN" | b"FLT8" | b"OKTA" | b"CD81" => 8,
+            [a, b, b'C', b'H'] if a.is_ascii_digit() && b.is_ascii_digit() => {
+                ((a - b'0') * 10 + (b - b'0')) as usize
+            }
+            _ => return Err(GameError::AudioError("Unknown MOD format".into())),
+        };
+
+        let song_len = (bytes[950] as usize).clamp(1, 128);
+        let orders = bytes[952..952 + song_len].to_vec();
+        let pattern_count = bytes[952..1080].iter().max().map_or(0, |&max| max as usize + 1);
+
+        let mut pos = 1084;
+        let mut patterns = Vec::with_capacity(pattern_count);
+        for _ in 0..pattern_count {
+            let size = ROWS * channels * 4;
+            let data = bytes
+                .get(pos..pos + size)
+                .ok_or_else(|| GameError::AudioError("Truncated pattern data".into()))?;
+            patterns.push(
+                data.chunks_exact(4)
+                    .map(|cell| Note {
+                        period: (((cell[0] & 0x0f) as u16) << 8) | cell[1] as u16,
+                        sample: (cell[0] & 0xf0) | (cell[2] >> 4),
+                        effect: cell[2] & 0x0f,
+                        param: cell[3],
+                    })
+                    .collect(),
+            );
+            pos += size;
+        }
+
+        let mut samples = Vec::with_capacity(31);
+        for idx in 0..31 {
+            let header = 20 + idx * 30;
+            let len = read_be16(bytes, header + 22) * 2;
+            let end = (pos + len).min(bytes.len());
+            let data = bytes[pos.min(end)..end]
+                .iter()
+                .map(|&byte| byte as i8 as f32 / 128.0)
+                .collect();
+            pos += len;
+
+            let loop_start = read_be16(bytes, header + 26) * 2;
+            let loop_len = read_be16(bytes, header + 28) * 2;
+            samples.push(Sample {
+                data,
+                finetune: (((bytes[header + 24] & 0x0f) << 4) as i8) >> 4,
+                volume: bytes[header + 25].min(64),
+                loop_start,
+                loop_len: if loop_len > 2 { loop_len } else { 0 },
+            });
+        }
+
+        Ok(Module {
+            channels,
+            samples,
+            orders,
+            patterns,
+        })
+    }
+
+    pub fn render(&self, sample_rate: u32) -> Song {
+        let rate = sample_rate as usize;
+        let mut state = vec![Channel::default(); self.channels];
+        let mut waves = vec![Vec::new(); self.channels];
+        let mut rows = Vec::new();
+        let (mut speed, mut tempo) = (6, 125);
+        let (mut order, mut row) = (0, 0);
+        let mut visited = HashSet::new();
+        let mut written = 0;
+
+        while order < self.orders.len() && written < rate * MAX_SECS && visited.insert((order, row)) {
+            let pattern = match self.patterns.get(self.orders[order] as usize) {
+                Some(pattern) => pattern,
+                None => break,
+            };
+            let cells = &pattern[row * self.channels..(row + 1) * self.channels];
+            rows.push(Row {
+                time: written as f32 / sample_rate as f32,
+                notes: cells.to_vec(),
+            });
+
+            let (mut jump, mut pattern_break, mut stop) = (None, None, false);
+            for (channel, cell) in state.iter_mut().zip(cells) {
+                channel.trigger(cell, &self.samples);
+                match cell.effect {
+                    0xb => jump = Some(cell.param as usize),
+                    0xd => {
+                        let target = (cell.param >> 4) as usize * 10 + (cell.param & 0x0f) as usize;
+                        pattern_break = Some(target.min(ROWS - 1));
+                    }
+                    0xf if cell.param == 0 => stop = true,
+                    0xf if cell.param < 32 => speed = cell.param as usize,
+                    0xf => tempo = cell.param as usize,
+                    _ => (),
+                }
+            }
+            if stop {
+                break;
+            }
+
+            for tick in 0..speed {
+                let len = rate * 5 / (tempo * 2);
+                for (channel, wave) in state.iter_mut().zip(waves.iter_mut()) {
+                    if tick > 0 {
+                        channel.tick(tick);
+                    }
+                    channel.mix(&self.samples, sample_rate as f32, len, wave);
+                }
+                written += len;
+            }
+
+            (order, row) = match (jump, pattern_break) {
+                (Some(target), row) => (target, row.unwrap_or(0)),
+                (None, Some(row)) => (order + 1, row),
+                (None, None) if row + 1 >= ROWS => (order + 1, 0),
+                (None, None) => (order, row + 1),
+            };
+        }
+
+        Song {
+            sample_rate,
+            channels: waves,
+            rows,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Default)]
+struct Channel {
+    sample: usize,
+    pos: f32,
+    period: f32,
+    output: f32,
+    target: f32,
+    volume: i32,
+    effect: u8,
+    param: u8,
+    porta: u8,
+    vibrato_speed: u8,
+    vibrato_depth: u8,
+    vibrato_pos: u8,
+}
+
+impl Channel {
+    fn trigger(&mut self, cell: &Note, samples: &[Sample]) {
+        if cell.sample > 0 && (cell.sample as usize) <= samples.len() {
+            self.sample = cell.sample as usize;
+            self.volume = samples[self.sample - 1].volume as i32;
+        }
+
+        if cell.period > 0 {
+            let finetune = samples.get(self.sample.wrapping_sub(1)).map_or(0, |sample| sample.finetune);
+            let period = cell.period as f32 * 2.0f32.powf(-finetune as f32 / 96.0);
+            if cell.effect == 0x3 || cell.effect == 0x5 {
+                self.target = period;
+            } else {
+                self.period = period;
+                self.pos = if cell.effect == 0x9 { cell.param as f32 * 256.0 } else { 0.0 };
+                self.vibrato_pos = 0;
+            }
+        }
+
+        self.effect = cell.effect;
+        self.param = cell.param;
+        let (x, y) = (cell.param >> 4, cell.param & 0x0f);
+        match cell.effect {
+            0x3 if cell.param > 0 => self.porta = cell.param,
+            0x4 => {
+                if x > 0 {
+                    self.vibrato_speed = x;
+                }
+                if y > 0 {
+                    self.vibrato_depth = y;
+                }
+            }
+            0xc => self.volume = (cell.param as i32).min(64),
+            0xe => match x {
+                0x1 => self.period -= y as f32,
+                0x2 => self.period += y as f32,
+                0xa => self.volume = (self.volume + y as i32).min(64),
+                0xb => self.volume = (self.volume - y as i32).max(0),
+                0xc if y == 0 => self.volume = 0,
+                _ => (),
+            },
+            _ => (),
+        }
+        self.output = self.period;
+    }
+
+    fn tick(&mut self, tick: usize) {
+        let (x, y) = (self.param >> 4, self.param & 0x0f);
+
+        match self.effect {
+            0x1 => self.period = (self.period - self.param as f32).max(113.0),
+            0x2 => self.period = (self.period + self.param as f32).min(856.0),
+            0x3 | 0x5 if self.target > 0.0 => {
+                let step = self.porta as f32;
+                self.period = if self.period < self.target {
+                    (self.period + step).min(self.target)
+                } else {
+                    (self.period - step).max(self.target)
+                };
+            }
+            0xe if x == 0xc && tick == y as usize => self.volume = 0,
+            _ => (),
+        }
+
+        if matches!(self.effect, 0x5 | 0x6 | 0xa) {
+            self.volume = (self.volume + x as i32 - y as i32).clamp(0, 64);
+        }
+
+        self.output = match self.effect {
+            0x0 if self.param > 0 => {
+                let semis = [0, x, y][tick % 3];
+                self.period / 2.0f32.powf(semis as f32 / 12.0)
+            }
+            0x4 | 0x6 => {
+                self.vibrato_pos = self.vibrato_pos.wrapping_add(self.vibrato_speed) & 63;
+                let sine = (self.vibrato_pos as f32 * 2.0 * PI / 64.0).sin() * 255.0;
+                self.period + sine * self.vibrato_depth as f32 / 128.0
+            }
+            _ => self.period,
+        };
+    }
+
+    fn mix(&mut self, samples: &[Sample], rate: f32, len: usize, wave: &mut Vec<f32>) {
+        let sample = match samples.get(self.sample.wrapping_sub(1)) {
+            Some(sample) if self.output > 0.0 && !sample.data.is_empty() => sample,
+            _ => {
+                wave.resize(wave.len() + len, 0.0);
+                return;
+            }
+        };
+
+        let step = PAULA_CLOCK / self.output / rate;
+        let gain = self.volume as f32 / 64.0;
+        let loop_end = (sample.loop_start + sample.loop_len).min(sample.data.len());
+
+        for _ in 0..len {
+            if sample.loop_len > 0 && self.pos >= loop_end as f32 {
+                self.pos -= sample.loop_len as f32;
+            }
+            let amp = sample.data.get(self.pos as usize).map_or(0.0, |&amp| amp * gain);
+            wave.push(amp);
+            self.pos += step;
+        }
+    }
+}
+
+pub struct Row {
+    pub time: f32,
+    pub notes: Vec<Note>,
+}
+
+pub struct Song {
+    pub sample_rate: u32,
+    pub channels: Vec<Vec<f32>>,
+    pub rows: Vec<Row>,
+}
+
+impl Song {
+    pub fn mix(&self) -> (Vec<f32>, Vec<f32>) {
+        let len = self.channels.iter().map(|channel| channel.len()).min().unwrap_or(0);
+        let gain = 2.0 / self.channels.len().max(2) as f32;
+
+        // Amiga panning: channels go left, right, right, left.
+        let pans: Vec<f32> = (0..self.channels.len())
+            .map(|idx| if idx % 4 == 0 || idx % 4 == 3 { 0.8 } else { 0.2 })
+            .collect();
+
+        (0..len)
+            .map(|idx| {
+                self.channels.iter().zip(&pans).fold((0.0, 0.0), |(l, r), (channel, pan)| {
+                    (l + channel[idx] * pan * gain, r + channel[idx] * (1.0 - pan) * gain)
+                })
+            })
+            .unzip()
+    }
+}
+
+pub struct TrackerView {
+    song: Song,
+    levels: Vec<f32>,
+    row: usize,
+}
+
+impl TrackerView {
+    pub fn new(song: Song) -> Self {
+        TrackerView {
+            levels: vec![0.0; song.channels.len()],
+            row: 0,
+            song,
+        }
+    }
+
+    pub fn update(&mut self, offset: usize, window: usize) {
+        for (channel, level) in self.song.channels.iter().zip(self.levels.iter_mut()) {
+            let end = (offset + window).min(channel.len());
+            let samples = &channel[offset.min(end)..end];
+            let rms = if samples.is_empty() {
+                0.0
+            } else {
+                (samples.iter().map(|amp| amp * amp).sum::<f32>() / samples.len() as f32).sqrt()
+            };
+            *level += (rms - *level) * 0.5;
+        }
+
+        let time = offset as f32 / self.song.sample_rate as f32;
+        self.row = self.song.rows.partition_point(|row| row.time <= time).saturating_sub(1);
+    }
+
+    pub fn draw(&self, ctx: &mut Context, width: f32, height: f32) -> GameResult {
+        let count = self.song.channels.len().max(1);
+        let column = width / count as f32;
+        let line_height = 20.0;
+        let center = height / 2.0 - line_height / 2.0;
+
+        let highlight = graphics::Mesh::new_rectangle(
+            ctx,
+            graphics::DrawMode::fill(),
+            graphics::Rect::new(0.0, center, width, line_height),
+            graphics::Color::from_rgba(255, 255, 255, 24),
+        )?;
+        graphics::draw(ctx, &highlight, DrawParam::default())?;
+
+        for (idx, &level) in self.levels.iter().enumerate() {
+            let x = idx as f32 * column;
+
+            let bar_height = (level * 4.0).min(1.0) * height / 3.0;
+            let freq = (idx as f32 / count as f32 * 255.0) as u8;
+            let bar = graphics::Mesh::new_rectangle(
+                ctx,
+                graphics::DrawMode::fill(),
+                graphics::Rect::new(x + column * 0.1, height - 48.0 - bar_height, column * 0.8, bar_height),
+                graphics::Color::from_rgba(freq, 128, 192, 160),
+            )?;
+            graphics::draw(ctx, &bar, DrawParam::default())?;
+
+            let first = self.row.saturating_sub(VISIBLE_ROWS);
+            let last = (self.row + VISIBLE_ROWS + 1).min(self.song.rows.len());
+            for row in first..last {
+                let note = self.song.rows[row].notes.get(idx).copied().unwrap_or_default();
+                let y = center + (row as f32 - self.row as f32) * line_height + 2.0;
+                let color = if row == self.row {
+                    graphics::WHITE
+                } else {
+                    graphics::Color::from_rgb(110, 110, 110)
+                };
+                overlay::label(ctx, note.name(), 16.0, Anchor::TopLeft, [x + 8.0, y], color)?;
+            }
+        }
+
+        Ok(())
+    }
+}
@@ -0,0 +1,139 @@
+use crate::{offscreen, Mode};
+use ggez::{
+    conf::NumSamples,
+    graphics::{self, Canvas, DrawParam, Mesh, Vertex},
+    Context, GameResult,
+};
+use serde::{Deserialize, Serialize};
+use std::f32::consts::PI;
+
+const DURATION_SECS: f32 = 0.5;
+const IRIS_SEGMENTS: usize = 64;
+
+// How the outgoing mode gives way to the next one.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Style {
+    #[default]
+    Fade,
+    Wipe,
+    Iris,
+    Cut,
+}
+
+// Keeps drawing the previous mode off screen for a moment after a switch and
+// lays it over the new one, less of it every frame.
+pub struct Transition {
+    pub style: Style,
+    shown: Mode,
+    from: Option<Mode>,
+    elapsed: f32,
+    canvas: Option<Canvas>,
+    samples: NumSamples,
+}
+
+impl Transition {
+    pub fn new(style: Style, mode: Mode, samples: NumSamples) -> Self {
+        Transition {
+            style,
+            shown: mode,
+            from: None,
+            elapsed: 0.0,
+            canvas: None,
+            samples,
+        }
+    }
+
+    pub fn update(&mut self, ctx: &mut Context, mode: Mode, delta: f32) -> GameResult {
+        if mode != self.shown {
+            self.from = if self.style == Style::Cut { None } else { Some(self.shown) };
+            self.shown = mode;
+            self.elapsed = 0.0;
+            if self.from.is_some() && self.canvas.is_none() {
+                self.canvas = Some(offscreen::window(ctx, self.samples)?);
+            }
+        } else if self.from.is_some() {
+            self.elapsed += delta;
+            if self.elapsed >= DURATION_SECS {
+                self.from = None;
+            }
+        }
+        Ok(())
+    }
+
+    pub fn rescale(&mut self, ctx: &mut Context) -> GameResult {
+        offscreen::rescale(ctx, &mut self.canvas, self.samples)
+    }
+
+    // Draws the outgoing mode with `picture` into the off-screen target.
+    pub fn render<F>(&self, ctx: &mut Context, background: graphics::Color, picture: F) -> GameResult
+    where
+        F: FnOnce(&mut Context, Mode) -> GameResult,
+    {
+        let (from, canvas) = match (self.from, &self.canvas) {
+            (Some(from), Some(canvas)) => (from, canvas),
+            _ => return Ok(()),
+        };
+        graphics::set_canvas(ctx, Some(canvas));
+        graphics::clear(ctx, background);
+        let drawn = picture(ctx, from);
+        graphics::set_canvas(ctx, None);
+        drawn
+    }
+
+    // Lays what is left of the outgoing mode over the new one.
+    pub fn draw(&self, ctx: &mut Context, width: f32, height: f32) -> GameResult {
+        let image = match (self.from, &self.canvas) {
+            (Some(_), Some(canvas)) => canvas.image(),
+            _ => return Ok(()),
+        };
+        let linear = (self.elapsed / DURATION_SECS).clamp(0.0, 1.0);
+        let progress = linear * linear * (3.0 - 2.0 * linear);
+        // Canvas images are stored upside down, hence the negative scale.
+        let scale = [width / f32::from(image.width()), -height / f32::from(image.height())];
+
+        match self.style {
+            Style::Fade => {
+                let param = DrawParam::default()
+                    .dest([0.0, height])
+                    .scale(scale)
+                    .color(graphics::Color::new(1.0, 1.0, 1.0, 1.0 - progress));
+                graphics::draw(ctx, image, param)
+            }
+            Style::Wipe => {
+                let param = DrawParam::default()
+                    .src(graphics::Rect::new(progress, 0.0, 1.0 - progress, 1.0))
+                    .dest([progress * width, height])
+                    .scale(scale);
+                graphics::draw(ctx, image, param)
+            }
+            Style::Iris => {
+                // A ring around a growing hole the new mode shows through.
+                let center = [width / 2.0, height / 2.0];
+                let reach = width.hypot(height) / 2.0 / (PI / IRIS_SEGMENTS as f32).cos();
+                let inner = progress * reach;
+                let vertex = |radius: f32, angle: f32| {
+                    let pos = [center[0] + radius * angle.cos(), center[1] + radius * angle.sin()];
+                    Vertex {
+                        pos,
+                        uv: [pos[0] / width, 1.0 - pos[1] / height],
+                        color: [1.0, 1.0, 1.0, 1.0],
+                    }
+                };
+                let mut vertices = Vec::with_capacity(IRIS_SEGMENTS * 2);
+                let mut indices = Vec::with_capacity(IRIS_SEGMENTS * 6);
+                for segment in 0..IRIS_SEGMENTS {
+                    let angle = segment as f32 / IRIS_SEGMENTS as f32 * 2.0 * PI;
+                    vertices.push(vertex(inner, angle));
+                    vertices.push(vertex(reach, angle));
+                    let [a, b] = [segment as u32 * 2, segment as u32 * 2 + 1];
+                    let [c, d] = [(a + 2) % (IRIS_SEGMENTS as u32 * 2), (b + 2) % (IRIS_SEGMENTS as u32 * 2)];
+                    indices.extend_from_slice(&[a, b, d, a, d, c]);
+                }
+                let mesh = Mesh::from_raw(ctx, &vertices, &indices, Some(image.clone()))?;
+                graphics::draw(ctx, &mesh, DrawParam::default())
+            }
+            Style::Cut => Ok(()),
+        }
+    }
+}
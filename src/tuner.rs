@@ -0,0 +1,151 @@
+use crate::{
+    cepstrum::NOTES,
+    overlay::{self, Anchor},
+    view::AXIS_HEIGHT,
+};
+use ggez::{
+    graphics::{self, DrawParam},
+    Context, GameResult,
+};
+use serde::{Deserialize, Serialize};
+use std::f32::consts::PI;
+
+// The needle spans +/- 50 cents, beyond that the next note is closer.
+const RANGE_CENTS: f32 = 50.0;
+// How long the needle holds after the note fades.
+const HOLD_SECS: f32 = 0.5;
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Settings {
+    // How close counts as in tune, and the time constant of the needle.
+    pub in_tune_cents: f32,
+    pub smoothing_secs: f32,
+}
+
+impl Default for Settings {
+    fn default() -> Self {
+        Settings {
+            in_tune_cents: 3.0,
+            smoothing_secs: 0.08,
+        }
+    }
+}
+
+// Nearest equal-tempered note to a frequency and the offset from it in cents.
+fn nearest(hz: f32) -> (String, f32) {
+    let key = 69.0 + 12.0 * (hz / 440.0).log2();
+    let rounded = key.round() as i32;
+    let name = format!("{}{}", NOTES[rounded.rem_euclid(12) as usize], rounded.div_euclid(12) - 1);
+    (name, (key - rounded as f32) * 100.0)
+}
+
+// Refines a coarse period by autocorrelation of the mid signal around it,
+// interpolating between lags for the sub-sample precision cents need.
+fn refine(left: &[f32], right: &[f32], period: f32) -> Option<f32> {
+    let mid: Vec<f32> = left.iter().zip(right).map(|(l, r)| (l + r) * 0.5).collect();
+    let correlation = |lag: usize| -> f32 {
+        if lag >= mid.len() {
+            return 0.0;
+        }
+        mid.iter().zip(&mid[lag..]).map(|(a, b)| a * b).sum::<f32>() / (mid.len() - lag) as f32
+    };
+
+    let low = ((period * 0.9) as usize).max(2);
+    let high = (period * 1.1).ceil() as usize;
+    let best = (low..=high).max_by(|&a, &b| correlation(a).total_cmp(&correlation(b)))?;
+    let (before, at, after) = (correlation(best - 1), correlation(best), correlation(best + 1));
+    let curvature = before - 2.0 * at + after;
+    let shift = if curvature < 0.0 { 0.5 * (before - after) / curvature } else { 0.0 };
+    Some(best as f32 + shift.clamp(-0.5, 0.5))
+}
+
+// A needle tuner on top of the cepstrum pitch tracker.
+pub struct Tuner {
+    pub settings: Settings,
+    hz: Option<f32>,
+    cents: f32,
+    held: f32,
+}
+
+impl Tuner {
+    pub fn new() -> Self {
+        Tuner {
+            settings: Settings::default(),
+            hz: None,
+            cents: 0.0,
+            held: 0.0,
+        }
+    }
+
+    pub fn update(&mut self, pitch: Option<f32>, left: &[f32], right: &[f32], sample_rate: u32, delta: f32) {
+        let hz = pitch
+            .and_then(|hz| refine(left, right, sample_rate as f32 / hz))
+            .map(|period| sample_rate as f32 / period);
+        let hz = match hz {
+            Some(hz) => hz,
+            None => {
+                self.held += delta;
+                if self.held > HOLD_SECS {
+                    self.hz = None;
+                }
+                return;
+            }
+        };
+        self.held = 0.0;
+
+        let (_, cents) = nearest(hz);
+        // Jump straight to a new note instead of sweeping the needle across.
+        let same_note = self.hz.is_some_and(|old| (12.0 * (hz / old).log2()).abs() < 0.5);
+        if same_note {
+            let blend = (delta / self.settings.smoothing_secs.max(1e-3)).min(1.0);
+            self.cents += (cents - self.cents) * blend;
+            self.hz = self.hz.map(|old| old + (hz - old) * blend);
+        } else {
+            self.cents = cents;
+            self.hz = Some(hz);
+        }
+    }
+
+    pub fn draw(&self, ctx: &mut Context, width: f32, height: f32) -> GameResult {
+        let area = height - AXIS_HEIGHT;
+        let center = [width / 2.0, area * 0.75];
+        let radius = (width * 0.4).min(area * 0.6);
+        let at = |cents: f32, length: f32| {
+            let angle = -PI / 2.0 + (cents / RANGE_CENTS).clamp(-1.0, 1.0) * PI / 3.0;
+            [center[0] + angle.cos() * length, center[1] + angle.sin() * length]
+        };
+
+        let mut builder = graphics::MeshBuilder::new();
+        for cents in (-50..=50).step_by(10) {
+            let long = cents % 50 == 0;
+            let inner = if long { radius * 0.85 } else { radius * 0.92 };
+            let color = if cents == 0 {
+                graphics::Color::from_rgb(96, 255, 128)
+            } else {
+                graphics::Color::from_rgba(255, 255, 255, 96)
+            };
+            builder.line(&[at(cents as f32, inner), at(cents as f32, radius)], 2.0, color)?;
+        }
+
+        let (label, needle) = match self.hz {
+            Some(hz) => {
+                let (name, _) = nearest(hz);
+                let color = if self.cents.abs() <= self.settings.in_tune_cents {
+                    graphics::Color::from_rgb(96, 255, 128)
+                } else {
+                    graphics::Color::from_rgb(255, 160, 64)
+                };
+                builder.line(&[center, at(self.cents, radius * 0.95)], 3.0, color)?;
+                (format!("{}  {:+.0} cents  {:.1} Hz", name, self.cents, hz), color)
+            }
+            None => ("play a note".to_string(), graphics::Color::from_rgb(120, 120, 120)),
+        };
+        builder.circle(graphics::DrawMode::fill(), center, 6.0, 0.5, needle);
+
+        let mesh = builder.build(ctx)?;
+        graphics::draw(ctx, &mesh, DrawParam::default())?;
+
+        overlay::label(ctx, label, 28.0, Anchor::Top, [center[0], center[1] + 24.0], needle)
+    }
+}
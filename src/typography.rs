@@ -0,0 +1,159 @@
+use crate::{
+    offscreen,
+    overlay::{self, Anchor},
+    DirectionalSource,
+};
+use ggez::{
+    conf::NumSamples,
+    graphics::{self, Canvas, DrawParam},
+    Context, GameResult,
+};
+use serde::{Deserialize, Serialize};
+
+const SIZE: f32 = 180.0;
+const LOW_HZ: f32 = 40.0;
+const HIGH_HZ: f32 = 12000.0;
+
+// What the typography mode spells out.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Ambient {
+    #[default]
+    Clock,
+    Title,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Settings {
+    pub strips: usize,
+    // How much taller the loudest strip of a letter can get.
+    pub stretch: f32,
+    pub color: [u8; 3],
+}
+
+impl Default for Settings {
+    fn default() -> Self {
+        Settings {
+            strips: 128,
+            stretch: 1.2,
+            color: [255, 255, 192],
+        }
+    }
+}
+
+// Local wall-clock hours, minutes and seconds.
+#[cfg(unix)]
+pub fn local_time() -> (u32, u32, u32) {
+    let mut tm: libc::tm = unsafe { std::mem::zeroed() };
+    unsafe {
+        let now = libc::time(std::ptr::null_mut());
+        libc::localtime_r(&now, &mut tm);
+    }
+    (tm.tm_hour as u32, tm.tm_min as u32, tm.tm_sec as u32)
+}
+
+// Without a portable local time zone this falls back to UTC.
+#[cfg(not(unix))]
+pub fn local_time() -> (u32, u32, u32) {
+    let secs = std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .map(|since| since.as_secs())
+        .unwrap_or(0);
+    ((secs / 3600 % 24) as u32, (secs / 60 % 60) as u32, (secs % 60) as u32)
+}
+
+pub fn clock() -> String {
+    let (hours, minutes, _) = local_time();
+    format!("{:02}:{:02}", hours, minutes)
+}
+
+// One big line of text, cut into vertical strips that stretch with the
+// spectrum from bass on the left to treble on the right.
+pub struct Typography {
+    pub settings: Settings,
+    canvas: Option<Canvas>,
+    samples: NumSamples,
+    label: String,
+    band: f32,
+}
+
+impl Typography {
+    pub fn new(samples: NumSamples) -> Self {
+        Typography {
+            settings: Settings::default(),
+            canvas: None,
+            samples,
+            label: String::new(),
+            band: 0.0,
+        }
+    }
+
+    // The text is laid out again at the window's current resolution on the
+    // next update.
+    pub fn rescale(&mut self) {
+        self.canvas = None;
+    }
+
+    // Lays the text out off screen whenever it changes.
+    pub fn update(&mut self, ctx: &mut Context, label: String, width: f32, height: f32) -> GameResult {
+        if self.canvas.is_some() && label == self.label {
+            return Ok(());
+        }
+        if self.canvas.is_none() {
+            self.canvas = Some(offscreen::window(ctx, self.samples)?);
+        }
+
+        let mut text = overlay::text(label.as_str(), SIZE);
+        let (text_width, _) = overlay::size(ctx, &text);
+        if text_width > width * 0.9 {
+            text = overlay::text(label.as_str(), SIZE * width * 0.9 / text_width);
+        }
+        self.band = overlay::size(ctx, &text).1 * 1.2;
+
+        graphics::set_canvas(ctx, self.canvas.as_ref());
+        graphics::clear(ctx, [0.0, 0.0, 0.0, 0.0].into());
+        overlay::draw(ctx, &text, Anchor::Center, [width / 2.0, height / 2.0], graphics::WHITE)?;
+        graphics::set_canvas(ctx, None);
+        self.label = label;
+        Ok(())
+    }
+
+    pub fn draw(
+        &self,
+        ctx: &mut Context,
+        sources: &[DirectionalSource],
+        bin_hz: f32,
+        gain: f32,
+        (width, height): (f32, f32),
+    ) -> GameResult {
+        let image = match self.canvas {
+            Some(ref canvas) => canvas.image(),
+            None => return Ok(()),
+        };
+        let scale = [width / f32::from(image.width()), height / f32::from(image.height())];
+        let strips = self.settings.strips.max(1);
+        let strip = 1.0 / strips as f32;
+        let band = self.band / height;
+        let [red, green, blue] = self.settings.color;
+
+        for idx in 0..strips {
+            let hz = |at: f32| LOW_HZ * (HIGH_HZ / LOW_HZ).powf(at * strip);
+            let first = ((hz(idx as f32) / bin_hz) as usize).max(1);
+            let last = ((hz(idx as f32 + 1.0) / bin_hz) as usize + 1).clamp(first + 1, sources.len().max(first + 1));
+            let amp = sources.get(first..last).map_or(0.0, |sources| {
+                sources.iter().map(|source| source.amp).sum::<f32>() / sources.len() as f32
+            });
+            let stretch = 1.0 + (amp * gain * 0.04).min(1.0) * self.settings.stretch;
+
+            // Canvas images are stored upside down, hence the negative scale.
+            let param = DrawParam::default()
+                .src(graphics::Rect::new(idx as f32 * strip, 0.5 - band / 2.0, strip, band))
+                .dest([idx as f32 * strip * width, (height + self.band * stretch) / 2.0])
+                .scale([scale[0], -scale[1] * stretch])
+                .color(graphics::Color::from_rgb(red, green - (usize::from(green) * idx / strips / 2) as u8, blue));
+            graphics::draw(ctx, image, param)?;
+        }
+        Ok(())
+    }
+}
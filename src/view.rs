@@ -0,0 +1,155 @@
+use crate::overlay::{self, Anchor};
+use ggez::{
+    graphics::{self, DrawParam},
+    Context, GameResult,
+};
+use std::ops::Range;
+
+pub const AXIS_HEIGHT: f32 = 28.0;
+
+pub struct FrequencyView {
+    low: f32,
+    high: f32,
+    min: f32,
+    max: f32,
+    drag: Option<f32>,
+}
+
+impl FrequencyView {
+    pub fn new(low: f32, high: f32) -> Self {
+        FrequencyView {
+            low,
+            high,
+            min: 20.0f32.min(low),
+            max: high,
+            drag: None,
+        }
+    }
+
+    pub fn bins(&self, bin_hz: f32, len: usize) -> Range<usize> {
+        let first = (self.low / bin_hz).ceil() as usize;
+        let last = ((self.high / bin_hz).floor() as usize + 1).min(len);
+        first.min(last)..last
+    }
+
+    pub fn contains(&self, freq: f32) -> bool {
+        freq >= self.low && freq <= self.high
+    }
+
+    // Where a frequency sits in the view, 0 at the low edge and 1 at the high one.
+    pub fn fraction(&self, freq: f32) -> f32 {
+        ((freq - self.low) / (self.high - self.low)).clamp(0.0, 1.0)
+    }
+
+    pub fn color(&self, freq: f32) -> u8 {
+        (16.0 + self.fraction(freq) * 239.0).floor() as u8
+    }
+
+    pub fn freq_at(&self, x: f32, width: f32) -> f32 {
+        let (low, high) = (self.low.ln(), self.high.ln());
+        (low + (x / width).clamp(0.0, 1.0) * (high - low)).exp()
+    }
+
+    fn x_at(&self, freq: f32, width: f32) -> f32 {
+        let (low, high) = (self.low.ln(), self.high.ln());
+        (freq.ln() - low) / (high - low) * width
+    }
+
+    pub fn zoom(&mut self, steps: f32, x: f32, width: f32) {
+        let anchor = self.freq_at(x, width).ln();
+        let factor = 0.85f32.powf(steps);
+
+        let mut low = anchor + (self.low.ln() - anchor) * factor;
+        let mut high = anchor + (self.high.ln() - anchor) * factor;
+        if high - low < 1.5f32.ln() {
+            return;
+        }
+
+        let (min, max) = (self.min.ln(), self.max.ln());
+        if high - low > max - min {
+            low = min;
+            high = max;
+        } else if low < min {
+            high += min - low;
+            low = min;
+        } else if high > max {
+            low -= high - max;
+            high = max;
+        }
+
+        self.low = low.exp();
+        self.high = high.exp();
+    }
+
+    pub fn range(&self) -> (f32, f32) {
+        (self.low, self.high)
+    }
+
+    // Restores a saved range, kept inside the limits of the current source.
+    pub fn set_range(&mut self, low: f32, high: f32) {
+        if low < high {
+            self.low = low.max(self.min);
+            self.high = high.min(self.max).max(self.low * 1.5);
+        }
+    }
+
+    pub fn pan(&mut self, dx: f32, width: f32) {
+        let (low, high) = (self.low.ln(), self.high.ln());
+        let (min, max) = (self.min.ln(), self.max.ln());
+        let shift = (-dx / width * (high - low))
+            .max(min - low)
+            .min(max - high);
+        self.low = (low + shift).exp();
+        self.high = (high + shift).exp();
+    }
+
+    pub fn begin_drag(&mut self, x: f32) {
+        self.drag = Some(x);
+    }
+
+    pub fn end_drag(&mut self) {
+        self.drag = None;
+    }
+
+    pub fn drag_to(&mut self, x: f32, width: f32) {
+        if let Some(from) = self.drag {
+            self.pan(x - from, width);
+            self.drag = Some(x);
+        }
+    }
+
+    pub fn draw_axis(&self, ctx: &mut Context, width: f32, height: f32) -> GameResult {
+        let y = height - AXIS_HEIGHT;
+
+        let steps = 64;
+        let step = width / steps as f32;
+        for idx in 0..steps {
+            let freq = self.freq_at((idx as f32 + 0.5) * step, width);
+            let rect = graphics::Rect::new(idx as f32 * step, y, step + 1.0, 4.0);
+            let mesh = graphics::Mesh::new_rectangle(
+                ctx,
+                graphics::DrawMode::fill(),
+                rect,
+                graphics::Color::from_rgba(self.color(freq), 128, 192, 192),
+            )?;
+            graphics::draw(ctx, &mesh, DrawParam::default())?;
+        }
+
+        for idx in 0..=4 {
+            let freq = self.freq_at(width * idx as f32 / 4.0, width);
+            let label = if freq >= 1000.0 {
+                format!("{:.1}k", freq / 1000.0)
+            } else {
+                format!("{:.0}", freq)
+            };
+            let text = overlay::text(label, 16.0);
+            let (text_width, _) = overlay::size(ctx, &text);
+
+            // Labels slide from left- to right-aligned across the axis.
+            let x = self.x_at(freq, width) - text_width * idx as f32 / 4.0;
+            overlay::draw(ctx, &text, Anchor::TopLeft, [x, y + 6.0], graphics::Color::from_rgb(160, 160, 160))?;
+        }
+
+        Ok(())
+    }
+}
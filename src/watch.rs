@@ -0,0 +1,63 @@
+use crate::{decode, midi, register, tracker};
+use std::{
+    collections::HashMap,
+    fs,
+    path::{Path, PathBuf},
+    sync::mpsc::{self, Receiver},
+    thread,
+    time::Duration,
+};
+
+const INTERVAL: Duration = Duration::from_secs(1);
+
+pub fn is_audio(path: &Path) -> bool {
+    let known = path
+        .extension()
+        .and_then(|ext| ext.to_str())
+        .is_some_and(|ext| register::EXTENSIONS.iter().any(|known| known.eq_ignore_ascii_case(ext)));
+    known || decode::needs_external(path) || tracker::is_module(path) || midi::is_midi(path)
+}
+
+fn scan(dir: &Path) -> HashMap<PathBuf, u64> {
+    fs::read_dir(dir)
+        .map(|entries| {
+            entries
+                .filter_map(|entry| entry.ok())
+                .filter(|entry| is_audio(&entry.path()))
+                .filter_map(|entry| Some((entry.path(), entry.metadata().ok()?.len())))
+                .collect()
+        })
+        .unwrap_or_default()
+}
+
+// Polls the folder and reports each new file once its size stops changing,
+// so files still being downloaded or recorded are not picked up half-written.
+pub fn watch(dir: PathBuf) -> Receiver<PathBuf> {
+    let (sender, receiver) = mpsc::channel();
+
+    thread::spawn(move || {
+        let mut known = scan(&dir);
+        let mut pending: HashMap<PathBuf, u64> = HashMap::new();
+
+        loop {
+            thread::sleep(INTERVAL);
+
+            for (path, size) in scan(&dir) {
+                if known.contains_key(&path) {
+                    continue;
+                }
+                if pending.get(&path) == Some(&size) && size > 0 {
+                    pending.remove(&path);
+                    known.insert(path.clone(), size);
+                    if sender.send(path).is_err() {
+                        return;
+                    }
+                } else {
+                    pending.insert(path, size);
+                }
+            }
+        }
+    });
+
+    receiver
+}
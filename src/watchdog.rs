@@ -0,0 +1,140 @@
+use crate::{device, player::Player};
+use ggez::{error::GameError, GameResult};
+use std::{
+    env,
+    process::{self, Command},
+    sync::{
+        atomic::{AtomicU64, Ordering},
+        Arc,
+    },
+    thread,
+    time::{Duration, Instant, SystemTime, UNIX_EPOCH},
+};
+
+// A frame that has not finished after this long is taken as a hung renderer.
+const STALL_SECS: u64 = 15;
+// A live device that delivers nothing for this long has gone away.
+const DEVICE_LOST_SECS: f32 = 3.0;
+const REOPEN_SECS: f32 = 2.0;
+// The exit code a stalled copy leaves with, so the supervisor can say why.
+const STALLED: i32 = 86;
+// A copy that ran at least this long starts again right away, quicker
+// failures back off up to a minute.
+const HEALTHY_SECS: u64 = 60;
+const MAX_BACKOFF_SECS: u64 = 60;
+
+pub fn log(message: &str) {
+    let secs = SystemTime::now().duration_since(UNIX_EPOCH).map_or(0, |time| time.as_secs());
+    eprintln!("[{}] watchdog: {}", secs, message);
+}
+
+// Reruns this program with the same arguments until it quits on purpose,
+// after crashes, startup errors and stalls alike.
+pub fn supervise() -> GameResult {
+    let exe = env::current_exe()?;
+    let args: Vec<_> = env::args_os().skip(1).filter(|arg| arg != "--install").collect();
+    let mut backoff = 1;
+    loop {
+        let started = Instant::now();
+        let status = Command::new(&exe)
+            .args(&args)
+            .arg("--supervised")
+            .status()
+            .map_err(|err| GameError::ConfigError(format!("Cannot start the visualizer: {}", err)))?;
+        if status.success() {
+            return Ok(());
+        }
+        match status.code() {
+            Some(STALLED) => log("rendering stalled, restarting"),
+            Some(code) => log(&format!("exited with code {}, restarting", code)),
+            None => log("killed by a signal, restarting"),
+        }
+
+        if started.elapsed().as_secs() >= HEALTHY_SECS {
+            backoff = 1;
+        } else {
+            thread::sleep(Duration::from_secs(backoff));
+            backoff = (backoff * 2).min(MAX_BACKOFF_SECS);
+        }
+    }
+}
+
+// The supervised copy's half: a thread that ends the process when frames stop
+// coming, and reopening of a capture device that disappears.
+pub struct Watchdog {
+    frames: Arc<AtomicU64>,
+    device: Option<device::Selection>,
+    received: usize,
+    silent: f32,
+    retry: f32,
+}
+
+impl Watchdog {
+    pub fn start(device: Option<device::Selection>) -> Self {
+        let frames = Arc::new(AtomicU64::new(0));
+        let watched = frames.clone();
+        thread::spawn(move || {
+            let mut last = watched.load(Ordering::Relaxed);
+            loop {
+                thread::sleep(Duration::from_secs(STALL_SECS));
+                let now = watched.load(Ordering::Relaxed);
+                if now == last {
+                    log(&format!("no frame for {} s", STALL_SECS));
+                    process::exit(STALLED);
+                }
+                last = now;
+            }
+        });
+        Watchdog {
+            frames,
+            device,
+            received: 0,
+            silent: 0.0,
+            retry: 0.0,
+        }
+    }
+
+    // True when the capture device was opened again.
+    pub fn update(&mut self, player: &mut Player, delta: f32) -> bool {
+        self.frames.fetch_add(1, Ordering::Relaxed);
+        let (name, channels) = match self.device {
+            Some((ref name, channels)) => (name.as_deref(), channels),
+            None => return false,
+        };
+        if !player.live() {
+            return false;
+        }
+
+        // Even silence arrives as samples, so none at all means no device.
+        let received = player.left_wave().len() + player.dropped();
+        if received != self.received {
+            self.received = received;
+            self.silent = 0.0;
+            return false;
+        }
+        self.silent += delta;
+        if self.silent < DEVICE_LOST_SECS {
+            return false;
+        }
+
+        self.retry -= delta;
+        if self.retry > 0.0 {
+            return false;
+        }
+        self.retry = REOPEN_SECS;
+        log(&format!("no input for {:.0} s, reopening the device", self.silent));
+        match device::open(name, channels) {
+            Ok(input) => {
+                player.load_live(input);
+                self.received = 0;
+                self.silent = 0.0;
+                log("device reopened");
+                true
+            }
+            Err(err) => {
+                log(&format!("cannot reopen the device: {}", err));
+                false
+            }
+        }
+    }
+}
@@ -0,0 +1,70 @@
+use crate::export;
+use ggez::{
+    graphics::{self, DrawParam, Image},
+    Context, GameResult,
+};
+use serde::{Deserialize, Serialize};
+use std::path::PathBuf;
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum Corner {
+    TopLeft,
+    TopRight,
+    BottomLeft,
+    #[default]
+    BottomRight,
+}
+
+// The `[watermark]` table in the config.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Settings {
+    pub path: PathBuf,
+    pub corner: Corner,
+    pub opacity: f32,
+    // As a share of the window's width.
+    pub width: f32,
+    pub margin: f32,
+}
+
+impl Default for Settings {
+    fn default() -> Self {
+        Settings {
+            path: PathBuf::new(),
+            corner: Corner::default(),
+            opacity: 0.8,
+            width: 0.15,
+            margin: 16.0,
+        }
+    }
+}
+
+// A logo over the picture, so it ends up in screenshots and renders too.
+pub struct Watermark {
+    settings: Settings,
+    image: Image,
+}
+
+impl Watermark {
+    pub fn load(ctx: &mut Context, settings: Settings) -> GameResult<Self> {
+        let image = export::read_image(ctx, &settings.path)?;
+        Ok(Watermark { settings, image })
+    }
+
+    pub fn draw(&self, ctx: &mut Context, (width, height): (f32, f32)) -> GameResult {
+        let scale = width * self.settings.width.clamp(0.0, 1.0) / f32::from(self.image.width().max(1));
+        let (logo_width, logo_height) = (f32::from(self.image.width()) * scale, f32::from(self.image.height()) * scale);
+        let margin = self.settings.margin;
+        let (left, top) = (margin, margin);
+        let (right, bottom) = (width - margin - logo_width, height - margin - logo_height);
+        let dest = match self.settings.corner {
+            Corner::TopLeft => [left, top],
+            Corner::TopRight => [right, top],
+            Corner::BottomLeft => [left, bottom],
+            Corner::BottomRight => [right, bottom],
+        };
+        let color = graphics::Color::new(1.0, 1.0, 1.0, self.settings.opacity.clamp(0.0, 1.0));
+        graphics::draw(ctx, &self.image, DrawParam::default().dest(dest).scale([scale, scale]).color(color))
+    }
+}